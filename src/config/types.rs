@@ -28,11 +28,74 @@ pub struct RouterConfig {
     /// Intra-node data parallel size (number of DP replicas per worker URL). When > 1, the router will create multiple worker instances per URL, one for each DP rank.
     #[serde(default = "default_intra_node_data_parallel_size")]
     pub intra_node_data_parallel_size: usize,
+    /// Per-worker DP size discovery (overrides intra_node_data_parallel_size when enabled)
+    #[serde(default)]
+    pub dp_discovery: DpDiscoveryConfig,
+    /// LoRA adapter-aware routing and dynamic adapter loading
+    #[serde(default)]
+    pub lora: LoraConfig,
+    /// Topology-aware prefill/decode pair selection
+    #[serde(default)]
+    pub pd_pairing: PdPairingConfig,
+    /// Recovery from decode failures in PD mode
+    #[serde(default)]
+    pub decode_failover: DecodeFailoverConfig,
+    /// PD capacity controller (prefill/decode role flips)
+    #[serde(default)]
+    pub pd_capacity: PdCapacityConfig,
+    /// Skip the prefill stage for short or already-cached prompts
+    #[serde(default)]
+    pub conditional_disaggregation: ConditionalDisaggregationConfig,
+    /// Long-prompt prefill placement for vLLM PD mode
+    #[serde(default)]
+    pub chunked_prefill: ChunkedPrefillConfig,
+    /// KV transfer protocol used to coordinate prefill and decode
+    #[serde(default)]
+    pub kv_transfer_protocol: KvTransferProtocolKind,
+    /// Transport vLLM instances register through in discovery mode
+    #[serde(default)]
+    pub vllm_discovery_transport: VllmDiscoveryTransport,
+    /// Path to a YAML or JSON file with per API key, tenant and model rate limits
+    #[serde(default)]
+    pub rate_limit_config: Option<String>,
+    /// Path to a YAML or JSON file of content policy filters applied to chat
+    /// and completion requests
+    #[serde(default)]
+    pub content_policy_config: Option<String>,
+    /// Sampling parameter defaults and clamps keyed by model name; `*`
+    /// applies to models without an entry of their own
+    #[serde(default)]
+    pub model_params: HashMap<String, ModelParamsConfig>,
+    /// Priority classes and tenant fairness for the request queue
+    #[serde(default)]
+    pub admission: AdmissionConfig,
     /// The api key used for the authorization with the worker
     pub api_key: Option<String>,
     /// API key validation URLs (if set, incoming requests must validate against them)
     #[serde(default)]
     pub api_key_validation_urls: Vec<String>,
+    /// YAML or JSON file of hashed API keys with scopes, allowed models and
+    /// rate-limit classes; reloaded when it changes
+    #[serde(default)]
+    pub api_keys_file: Option<String>,
+    /// Local JWT validation of bearer tokens (optional)
+    #[serde(default)]
+    pub jwt: Option<JwtConfig>,
+    /// Cache of remote API key validation results
+    #[serde(default)]
+    pub api_key_cache: ApiKeyCacheConfig,
+    /// Listener and authentication for the worker management endpoints
+    #[serde(default)]
+    pub admin: AdminConfig,
+    /// TLS termination on the router's listeners (None = plain HTTP)
+    #[serde(default)]
+    pub tls: Option<TlsConfig>,
+    /// CA bundle and client certificate for worker connections
+    #[serde(default)]
+    pub upstream_tls: UpstreamTlsConfig,
+    /// Audit trail of requests and admin actions
+    #[serde(default)]
+    pub audit: AuditConfig,
     /// Service discovery configuration (optional)
     pub discovery: Option<DiscoveryConfig>,
     /// Metrics configuration (optional)
@@ -287,6 +350,55 @@ pub struct DiscoveryConfig {
     pub decode_selector: HashMap<String, String>,
     /// Bootstrap port annotation key
     pub bootstrap_port_annotation: String,
+    /// Discovery backend
+    #[serde(default)]
+    pub backend: DiscoveryBackendKind,
+    /// Worker file watched by the file backend
+    #[serde(default)]
+    pub file_path: Option<String>,
+    /// Names polled by the DNS backend
+    ///
+    /// `_service._proto.domain` names are SRV lookups; anything else is an
+    /// A/AAAA lookup of `[prefill=|decode=]host[:port]`.
+    #[serde(default)]
+    pub dns_names: Vec<String>,
+    /// Services and port names followed by the EndpointSlice backend
+    #[serde(default)]
+    pub endpoint_slices: EndpointSliceConfig,
+}
+
+/// EndpointSlice discovery configuration
+///
+/// Workers are the ready endpoints of the named Services. Ports are looked up
+/// by name, so one pod can expose its HTTP, bootstrap and ZMQ ports together.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct EndpointSliceConfig {
+    /// Service whose endpoints are regular workers
+    pub service: Option<String>,
+    /// Service whose endpoints are prefill workers in PD mode
+    pub prefill_service: Option<String>,
+    /// Service whose endpoints are decode workers in PD mode
+    pub decode_service: Option<String>,
+    /// Name of the worker HTTP port
+    pub http_port_name: String,
+    /// Name of the prefill bootstrap port
+    pub bootstrap_port_name: String,
+    /// Name of the ZMQ port used for P2P NCCL transfers
+    pub zmq_port_name: String,
+}
+
+impl Default for EndpointSliceConfig {
+    fn default() -> Self {
+        Self {
+            service: None,
+            prefill_service: None,
+            decode_service: None,
+            http_port_name: "http".to_string(),
+            bootstrap_port_name: "bootstrap".to_string(),
+            zmq_port_name: "zmq".to_string(),
+        }
+    }
 }
 
 impl Default for DiscoveryConfig {
@@ -300,6 +412,10 @@ impl Default for DiscoveryConfig {
             prefill_selector: HashMap::new(),
             decode_selector: HashMap::new(),
             bootstrap_port_annotation: "vllm.ai/bootstrap-port".to_string(),
+            backend: DiscoveryBackendKind::Kubernetes,
+            file_path: None,
+            dns_names: Vec::new(),
+            endpoint_slices: EndpointSliceConfig::default(),
         }
     }
 }
@@ -364,6 +480,765 @@ impl Default for HealthCheckConfig {
     }
 }
 
+/// Data-parallel size discovery configuration
+///
+/// When enabled, each worker is queried for its `data_parallel_size` and expanded
+/// into that many DP-aware workers, instead of using the global
+/// `intra_node_data_parallel_size` for every worker.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DpDiscoveryConfig {
+    /// Query workers for their DP size
+    #[serde(default)]
+    pub enabled: bool,
+    /// Endpoint reporting `data_parallel_size`
+    #[serde(default = "default_dp_discovery_endpoint")]
+    pub endpoint: String,
+    /// Interval between DP size re-checks in seconds (0 = only discover at registration)
+    #[serde(default = "default_dp_discovery_refresh_interval_secs")]
+    pub refresh_interval_secs: u64,
+}
+
+fn default_dp_discovery_endpoint() -> String {
+    "/get_server_info".to_string()
+}
+
+fn default_dp_discovery_refresh_interval_secs() -> u64 {
+    30
+}
+
+impl Default for DpDiscoveryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            endpoint: default_dp_discovery_endpoint(),
+            refresh_interval_secs: default_dp_discovery_refresh_interval_secs(),
+        }
+    }
+}
+
+/// LoRA adapter routing configuration
+///
+/// When enabled, requests naming an adapter in `lora_path` are routed to a
+/// worker that already has it loaded. If none has, the adapter is loaded on the
+/// selected worker, evicting the least recently used adapter once the worker
+/// holds `max_adapters_per_worker` adapters.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LoraConfig {
+    /// Enable adapter-aware routing and dynamic loading
+    #[serde(default)]
+    pub enabled: bool,
+    /// Maximum number of adapters loaded on a worker at once
+    #[serde(default = "default_lora_max_adapters_per_worker")]
+    pub max_adapters_per_worker: usize,
+    /// Timeout for load/unload calls in seconds
+    #[serde(default = "default_lora_load_timeout_secs")]
+    pub load_timeout_secs: u64,
+}
+
+fn default_lora_max_adapters_per_worker() -> usize {
+    8
+}
+
+fn default_lora_load_timeout_secs() -> u64 {
+    60
+}
+
+impl Default for LoraConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_adapters_per_worker: default_lora_max_adapters_per_worker(),
+            load_timeout_secs: default_lora_load_timeout_secs(),
+        }
+    }
+}
+
+/// Topology-aware PD pair selection
+///
+/// When enabled, prefill and decode workers are chosen together, scoring each
+/// pair by `load_weight * (prefill load + decode load) + transfer cost`, where
+/// the transfer cost depends on how close the two workers are.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PdPairingConfig {
+    /// Select prefill/decode pairs jointly instead of independently
+    #[serde(default)]
+    pub enabled: bool,
+    /// Weight of in-flight requests relative to transfer cost
+    #[serde(default = "default_pd_pairing_load_weight")]
+    pub load_weight: f64,
+    /// KV-transfer cost per locality level
+    #[serde(default)]
+    pub transfer_cost: TransferCostConfig,
+    /// Placement of each worker URL as "zone[/rack[/node]]"
+    #[serde(default)]
+    pub worker_topology: HashMap<String, String>,
+}
+
+fn default_pd_pairing_load_weight() -> f64 {
+    1.0
+}
+
+impl Default for PdPairingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            load_weight: default_pd_pairing_load_weight(),
+            transfer_cost: TransferCostConfig::default(),
+            worker_topology: HashMap::new(),
+        }
+    }
+}
+
+/// KV-transfer cost between a prefill and a decode worker, in units of in-flight requests
+///
+/// Pairs whose locality cannot be determined (missing labels) use `same_zone`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TransferCostConfig {
+    pub same_node: f64,
+    pub same_rack: f64,
+    pub same_zone: f64,
+    pub cross_zone: f64,
+}
+
+impl Default for TransferCostConfig {
+    fn default() -> Self {
+        Self {
+            same_node: 0.0,
+            same_rack: 1.0,
+            same_zone: 4.0,
+            cross_zone: 16.0,
+        }
+    }
+}
+
+/// Decode failover for PD routing
+///
+/// When the decode stage fails (connection error, 5xx, or a stream that ends
+/// before its first chunk), the request is re-run on a different
+/// prefill/decode pair. If every attempt fails, the request can be served by a
+/// regular pool of co-located workers instead.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DecodeFailoverConfig {
+    /// Re-run requests whose decode stage failed
+    #[serde(default)]
+    pub enabled: bool,
+    /// Maximum prefill/decode pairs tried per request, including the first
+    #[serde(default = "default_decode_failover_max_attempts")]
+    pub max_attempts: u32,
+    /// Regular (non-disaggregated) workers used once all pairs have failed
+    #[serde(default)]
+    pub fallback_worker_urls: Vec<String>,
+}
+
+fn default_decode_failover_max_attempts() -> u32 {
+    2
+}
+
+impl Default for DecodeFailoverConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_attempts: default_decode_failover_max_attempts(),
+            fallback_worker_urls: Vec::new(),
+        }
+    }
+}
+
+/// Conditional disaggregation configuration
+///
+/// Short prompts gain nothing from a separate prefill stage, so requests below
+/// `min_prefill_tokens` skip it and go straight to a decode worker (or to a
+/// pool of mixed workers). Requests whose prompt is already mostly cached on a
+/// decode worker can skip remote prefill the same way.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ConditionalDisaggregationConfig {
+    /// Skip the prefill stage for requests that do not benefit from it
+    #[serde(default)]
+    pub enabled: bool,
+    /// Prompts with fewer tokens than this skip the prefill stage
+    #[serde(default = "default_min_prefill_tokens")]
+    pub min_prefill_tokens: usize,
+    /// Skip remote prefill when a decode worker's prefix cache matches at least
+    /// this fraction of the request (requires a cache-aware decode policy)
+    #[serde(default)]
+    pub prefix_cache_threshold: Option<f64>,
+    /// Regular workers that serve short prompts instead of the decode workers
+    #[serde(default)]
+    pub mixed_worker_urls: Vec<String>,
+}
+
+fn default_min_prefill_tokens() -> usize {
+    256
+}
+
+impl Default for ConditionalDisaggregationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_prefill_tokens: default_min_prefill_tokens(),
+            prefix_cache_threshold: None,
+            mixed_worker_urls: Vec::new(),
+        }
+    }
+}
+
+/// Long-prompt prefill placement for vLLM PD mode
+///
+/// Prompts of at least `min_prompt_tokens` go to the least loaded prefill
+/// worker with enough free KV cache, based on the KV usage each prefill worker
+/// exports. With `context_parallel`, they are instead split by token count into
+/// chunks prefilled concurrently on several workers, which requires a KV
+/// connector that understands the chunk ranges.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ChunkedPrefillConfig {
+    /// Place long prompts by prefill KV capacity
+    #[serde(default)]
+    pub enabled: bool,
+    /// Prompts with at least this many tokens are placed by KV capacity
+    #[serde(default = "default_long_prompt_tokens")]
+    pub min_prompt_tokens: usize,
+    /// Split long prompts across prefill workers (experimental)
+    #[serde(default)]
+    pub context_parallel: bool,
+    /// Target tokens per context-parallel chunk
+    #[serde(default = "default_prefill_chunk_tokens")]
+    pub chunk_tokens: usize,
+    /// Maximum prefill workers a prompt is split across
+    #[serde(default = "default_max_prefill_chunks")]
+    pub max_chunks: usize,
+    /// Seconds between KV capacity scrapes of the prefill workers
+    #[serde(default = "default_kv_metrics_interval_secs")]
+    pub metrics_interval_secs: u64,
+    /// Prometheus endpoint on the prefill workers
+    #[serde(default = "default_kv_metrics_path")]
+    pub metrics_path: String,
+}
+
+fn default_long_prompt_tokens() -> usize {
+    32768
+}
+
+fn default_prefill_chunk_tokens() -> usize {
+    32768
+}
+
+fn default_max_prefill_chunks() -> usize {
+    4
+}
+
+fn default_kv_metrics_interval_secs() -> u64 {
+    10
+}
+
+fn default_kv_metrics_path() -> String {
+    "/metrics".to_string()
+}
+
+impl Default for ChunkedPrefillConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_prompt_tokens: default_long_prompt_tokens(),
+            context_parallel: false,
+            chunk_tokens: default_prefill_chunk_tokens(),
+            max_chunks: default_max_prefill_chunks(),
+            metrics_interval_secs: default_kv_metrics_interval_secs(),
+            metrics_path: default_kv_metrics_path(),
+        }
+    }
+}
+
+/// KV transfer protocol coordinating the prefill and decode stages
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum KvTransferProtocolKind {
+    /// Bootstrap rooms in PD mode; NIXL params with a P2P NCCL request id in vLLM PD mode
+    #[default]
+    Auto,
+    /// Bootstrap host/port/room sent with both stages at once
+    Bootstrap,
+    /// `kv_transfer_params` handed from prefill to decode
+    Nixl,
+    /// ZMQ addresses encoded in the request id, prefill before decode
+    P2pNccl,
+}
+
+impl KvTransferProtocolKind {
+    /// Whether requests carry the prefill worker's bootstrap port
+    pub fn uses_bootstrap(self, vllm_pd_mode: bool) -> bool {
+        match self {
+            KvTransferProtocolKind::Auto => !vllm_pd_mode,
+            KvTransferProtocolKind::Bootstrap => true,
+            KvTransferProtocolKind::Nixl | KvTransferProtocolKind::P2pNccl => false,
+        }
+    }
+}
+
+/// Transport vLLM instances use to register with the router
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum VllmDiscoveryTransport {
+    /// MessagePack registrations on a ZMQ ROUTER socket
+    #[default]
+    Zmq,
+    /// JSON registrations via `POST /register`
+    Http,
+}
+
+/// Source of service discovery events
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DiscoveryBackendKind {
+    /// Watch Kubernetes pods matching the configured selectors
+    #[default]
+    Kubernetes,
+    /// Watch the EndpointSlices of the configured Kubernetes Services
+    EndpointSlices,
+    /// Watch a JSON or YAML worker file
+    File,
+    /// Poll DNS A/AAAA and SRV records
+    Dns,
+}
+
+/// PD capacity controller configuration
+///
+/// Compares how long requests wait for prefill against decode inter-token
+/// latency and recommends moving a worker from the less loaded role to the
+/// more loaded one. With an actuator path configured, the flip can also be
+/// performed by calling that endpoint on the worker.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PdCapacityConfig {
+    /// Collect prefill/decode timings and evaluate role flips
+    #[serde(default)]
+    pub enabled: bool,
+    /// Seconds between evaluations
+    #[serde(default = "default_pd_capacity_interval_secs")]
+    pub evaluation_interval_secs: u64,
+    /// Seconds of samples considered by an evaluation
+    #[serde(default = "default_pd_capacity_window_secs")]
+    pub window_secs: u64,
+    /// Minimum samples per role before recommending a flip
+    #[serde(default = "default_pd_capacity_min_samples")]
+    pub min_samples: usize,
+    /// Target prefill time (queueing plus compute) in milliseconds
+    #[serde(default = "default_pd_prefill_time_target_ms")]
+    pub prefill_time_target_ms: f64,
+    /// Target decode inter-token latency in milliseconds
+    #[serde(default = "default_pd_decode_itl_target_ms")]
+    pub decode_itl_target_ms: f64,
+    /// How much more one role must be over its target than the other
+    #[serde(default = "default_pd_capacity_imbalance_ratio")]
+    pub imbalance_ratio: f64,
+    /// Workers that must remain in each role after a flip
+    #[serde(default = "default_pd_capacity_min_workers_per_role")]
+    pub min_workers_per_role: usize,
+    /// Minimum seconds between two flips
+    #[serde(default = "default_pd_capacity_cooldown_secs")]
+    pub cooldown_secs: u64,
+    /// Perform recommended flips automatically instead of only reporting them
+    #[serde(default)]
+    pub auto_apply: bool,
+    /// Worker endpoint called to switch roles, e.g. `/restart`
+    #[serde(default)]
+    pub actuator_path: Option<String>,
+    /// Bootstrap port of decode workers flipped to prefill, when neither an
+    /// earlier prefill registration nor a `bootstrap_port` label provides one
+    #[serde(default)]
+    pub bootstrap_port: Option<u16>,
+}
+
+fn default_pd_capacity_interval_secs() -> u64 {
+    30
+}
+
+fn default_pd_capacity_window_secs() -> u64 {
+    300
+}
+
+fn default_pd_capacity_min_samples() -> usize {
+    50
+}
+
+fn default_pd_prefill_time_target_ms() -> f64 {
+    1000.0
+}
+
+fn default_pd_decode_itl_target_ms() -> f64 {
+    50.0
+}
+
+fn default_pd_capacity_imbalance_ratio() -> f64 {
+    1.5
+}
+
+fn default_pd_capacity_min_workers_per_role() -> usize {
+    1
+}
+
+fn default_pd_capacity_cooldown_secs() -> u64 {
+    600
+}
+
+impl Default for PdCapacityConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            evaluation_interval_secs: default_pd_capacity_interval_secs(),
+            window_secs: default_pd_capacity_window_secs(),
+            min_samples: default_pd_capacity_min_samples(),
+            prefill_time_target_ms: default_pd_prefill_time_target_ms(),
+            decode_itl_target_ms: default_pd_decode_itl_target_ms(),
+            imbalance_ratio: default_pd_capacity_imbalance_ratio(),
+            min_workers_per_role: default_pd_capacity_min_workers_per_role(),
+            cooldown_secs: default_pd_capacity_cooldown_secs(),
+            auto_apply: false,
+            actuator_path: None,
+            bootstrap_port: None,
+        }
+    }
+}
+
+/// Admission queue configuration
+///
+/// Requests that cannot start immediately wait in the queue of their priority
+/// class. Classes are listed highest priority first and served strictly in
+/// that order; within a class, tenants (`x-tenant-id`) share the queue by
+/// weighted fair queuing. Without classes, a single class uses `queue_size`
+/// and `queue_timeout_secs`.
+///
+/// A request's class comes from its API key mapping, then the priority header,
+/// then the longest matching endpoint prefix, then `default_class`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct AdmissionConfig {
+    /// Priority classes, highest priority first
+    #[serde(default)]
+    pub classes: Vec<PriorityClassConfig>,
+    /// Class of requests no mapping applies to (defaults to the lowest class)
+    #[serde(default)]
+    pub default_class: Option<String>,
+    /// Header naming the requested class (e.g. `x-priority: batch`)
+    #[serde(default)]
+    pub priority_header: Option<String>,
+    /// Class per API key
+    #[serde(default)]
+    pub api_key_classes: HashMap<String, String>,
+    /// Class per request path prefix (e.g. `/v1/embeddings`)
+    #[serde(default)]
+    pub endpoint_classes: HashMap<String, String>,
+    /// Fair-share weight per tenant; unlisted tenants have weight 1
+    #[serde(default)]
+    pub tenant_weights: HashMap<String, f64>,
+}
+
+/// A priority class of the admission queue
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PriorityClassConfig {
+    pub name: String,
+    /// Queued requests of this class (None = router `queue_size`)
+    #[serde(default)]
+    pub queue_size: Option<usize>,
+    /// Seconds a request of this class may wait (None = router `queue_timeout_secs`)
+    #[serde(default)]
+    pub queue_timeout_secs: Option<u64>,
+}
+
+/// JWT bearer-token validation
+///
+/// Tokens that parse as JWTs are verified locally against the JWKS instead of
+/// the API key validation URLs. Exactly one of `jwks_file` and `jwks_url` must
+/// be set; the key set is reloaded every `jwks_refresh_secs`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct JwtConfig {
+    /// Path to a JWKS JSON file
+    #[serde(default)]
+    pub jwks_file: Option<String>,
+    /// URL serving the JWKS (e.g. an OIDC provider's `jwks_uri`)
+    #[serde(default)]
+    pub jwks_url: Option<String>,
+    #[serde(default = "default_jwks_refresh_secs")]
+    pub jwks_refresh_secs: u64,
+    /// Required `iss` claim
+    #[serde(default)]
+    pub issuer: Option<String>,
+    /// Accepted `aud` values (empty = audience not checked)
+    #[serde(default)]
+    pub audience: Vec<String>,
+    /// Claim holding the tenant used for rate limits, dot-separated for
+    /// nested claims (e.g. `org.id`); overrides the `x-tenant-id` header
+    #[serde(default)]
+    pub tenant_claim: Option<String>,
+    /// Claim listing the token's scopes or roles, dot-separated for nested
+    /// claims (e.g. `scope` or `realm_access.roles`); only tokens whose claim
+    /// contains `admin_value` may use the admin endpoints
+    #[serde(default)]
+    pub admin_claim: Option<String>,
+    /// Scope or role granting admin access
+    #[serde(default = "default_jwt_admin_value")]
+    pub admin_value: String,
+    /// Clock skew allowed for `exp` and `nbf`
+    #[serde(default = "default_jwt_leeway_secs")]
+    pub leeway_secs: u64,
+}
+
+impl Default for JwtConfig {
+    fn default() -> Self {
+        Self {
+            jwks_file: None,
+            jwks_url: None,
+            jwks_refresh_secs: default_jwks_refresh_secs(),
+            issuer: None,
+            audience: vec![],
+            tenant_claim: None,
+            admin_claim: None,
+            admin_value: default_jwt_admin_value(),
+            leeway_secs: default_jwt_leeway_secs(),
+        }
+    }
+}
+
+fn default_jwt_admin_value() -> String {
+    "admin".to_string()
+}
+
+fn default_jwks_refresh_secs() -> u64 {
+    300
+}
+
+fn default_jwt_leeway_secs() -> u64 {
+    60
+}
+
+/// Cache of remote API key validation results
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ApiKeyCacheConfig {
+    /// Seconds a validation result is reused
+    pub ttl_secs: u64,
+    /// Maximum number of cached keys; least recently used are evicted
+    pub max_entries: usize,
+}
+
+impl Default for ApiKeyCacheConfig {
+    fn default() -> Self {
+        Self {
+            ttl_secs: 300,
+            max_entries: 10000,
+        }
+    }
+}
+
+/// Worker management (admin) endpoints
+///
+/// By default the admin routes share the public listener. Setting `port`
+/// moves them to a separate listener on `host:port` and removes them from the
+/// public one. When `api_key` is set, admin requests must present it as a
+/// bearer token instead of an admin-scoped inference key.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AdminConfig {
+    /// Admin listener host address
+    #[serde(default = "default_admin_host")]
+    pub host: String,
+    /// Admin listener port (None = serve admin routes on the public listener)
+    #[serde(default)]
+    pub port: Option<u16>,
+    /// Bearer token required on admin routes
+    #[serde(default)]
+    pub api_key: Option<String>,
+}
+
+impl Default for AdminConfig {
+    fn default() -> Self {
+        Self {
+            host: default_admin_host(),
+            port: None,
+            api_key: None,
+        }
+    }
+}
+
+impl AdminConfig {
+    /// Whether admin routes are served on their own listener
+    pub fn is_separate(&self) -> bool {
+        self.port.is_some()
+    }
+
+    /// Whether the admin listener only accepts local connections
+    pub fn is_loopback(&self) -> bool {
+        self.host.eq_ignore_ascii_case("localhost")
+            || self
+                .host
+                .trim_matches(|c| c == '[' || c == ']')
+                .parse::<std::net::IpAddr>()
+                .is_ok_and(|ip| ip.is_loopback())
+    }
+}
+
+fn default_admin_host() -> String {
+    "127.0.0.1".to_string()
+}
+
+/// Audit trail of requests and admin actions
+///
+/// Records are written as JSON lines to `file`, which is rotated once it
+/// reaches `max_file_size_mb`, and optionally streamed to a Unix socket or
+/// posted to an HTTP endpoint. Auditing is off unless a destination is set.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AuditConfig {
+    /// JSONL file; rotated files get a `.1`, `.2`, ... suffix
+    #[serde(default)]
+    pub file: Option<String>,
+    /// Size at which the file is rotated
+    #[serde(default = "default_audit_max_file_size_mb")]
+    pub max_file_size_mb: u64,
+    /// Rotated files kept besides the active one
+    #[serde(default = "default_audit_max_files")]
+    pub max_files: usize,
+    /// Unix socket records are written to, one JSON object per line
+    #[serde(default)]
+    pub unix_socket: Option<String>,
+    /// URL batches of records are POSTed to as newline-delimited JSON
+    #[serde(default)]
+    pub http_sink: Option<String>,
+    /// How prompts appear in request records
+    #[serde(default)]
+    pub prompts: AuditRedaction,
+    /// How model outputs appear in request records
+    #[serde(default)]
+    pub outputs: AuditRedaction,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            file: None,
+            max_file_size_mb: default_audit_max_file_size_mb(),
+            max_files: default_audit_max_files(),
+            unix_socket: None,
+            http_sink: None,
+            prompts: AuditRedaction::default(),
+            outputs: AuditRedaction::default(),
+        }
+    }
+}
+
+impl AuditConfig {
+    /// Whether any audit destination is configured
+    pub fn is_enabled(&self) -> bool {
+        self.file.is_some() || self.unix_socket.is_some() || self.http_sink.is_some()
+    }
+}
+
+fn default_audit_max_file_size_mb() -> u64 {
+    100
+}
+
+fn default_audit_max_files() -> usize {
+    10
+}
+
+/// Treatment of prompt and output text in audit records
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditRedaction {
+    /// Leave the text out
+    #[default]
+    Redact,
+    /// Record the SHA-256 digest of the text
+    Hash,
+    /// Record the text as is
+    Full,
+}
+
+/// TLS termination on the router's listeners
+///
+/// The certificate and key files are reloaded when they change. With
+/// `client_ca_file`, clients must present a certificate signed by one of its
+/// CAs unless `client_auth_optional` is set.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct TlsConfig {
+    /// PEM certificate chain, leaf first
+    pub cert_file: String,
+    /// PEM private key (PKCS#8, PKCS#1 or SEC1)
+    pub key_file: String,
+    /// PEM bundle of CAs trusted for client certificates (enables mTLS)
+    #[serde(default)]
+    pub client_ca_file: Option<String>,
+    /// Accept clients without a certificate when `client_ca_file` is set
+    #[serde(default)]
+    pub client_auth_optional: bool,
+}
+
+/// TLS settings for connections to workers
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct UpstreamTlsConfig {
+    /// PEM bundle of CAs trusted in addition to the system roots
+    #[serde(default)]
+    pub ca_file: Option<String>,
+    /// PEM client certificate presented to workers
+    #[serde(default)]
+    pub client_cert_file: Option<String>,
+    /// PKCS#8 PEM key of the client certificate
+    #[serde(default)]
+    pub client_key_file: Option<String>,
+}
+
+/// Sampling parameter defaults and clamps of one model
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ModelParamsConfig {
+    /// Values filled in when the request leaves the parameter unset
+    #[serde(default)]
+    pub defaults: ModelParamDefaults,
+    /// Ranges request values are clamped to
+    #[serde(default)]
+    pub clamps: ModelParamClamps,
+}
+
+/// Default sampling parameters of a model
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ModelParamDefaults {
+    #[serde(default)]
+    pub temperature: Option<f32>,
+    #[serde(default)]
+    pub top_p: Option<f32>,
+    #[serde(default)]
+    pub top_k: Option<i32>,
+    #[serde(default)]
+    pub max_tokens: Option<u32>,
+    #[serde(default)]
+    pub stop: Option<Vec<String>>,
+}
+
+/// Allowed ranges of sampling parameters of a model
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ModelParamClamps {
+    #[serde(default)]
+    pub temperature: Option<ParamRange<f32>>,
+    #[serde(default)]
+    pub top_p: Option<ParamRange<f32>>,
+    /// A top_k of -1 (disabled) counts as unbounded and is clamped to `max`
+    #[serde(default)]
+    pub top_k: Option<ParamRange<i32>>,
+    #[serde(default)]
+    pub max_tokens: Option<ParamRange<u32>>,
+}
+
+/// Inclusive range; either bound may be omitted
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct ParamRange<T> {
+    #[serde(default)]
+    pub min: Option<T>,
+    #[serde(default)]
+    pub max: Option<T>,
+}
+
+impl<T> Default for ParamRange<T> {
+    fn default() -> Self {
+        Self {
+            min: None,
+            max: None,
+        }
+    }
+}
+
 /// Circuit breaker configuration for worker reliability
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CircuitBreakerConfig {
@@ -420,8 +1295,28 @@ impl Default for RouterConfig {
             worker_startup_timeout_secs: 600,
             worker_startup_check_interval_secs: 30,
             intra_node_data_parallel_size: 1,
+            dp_discovery: DpDiscoveryConfig::default(),
+            lora: LoraConfig::default(),
+            pd_pairing: PdPairingConfig::default(),
+            decode_failover: DecodeFailoverConfig::default(),
+            pd_capacity: PdCapacityConfig::default(),
+            conditional_disaggregation: ConditionalDisaggregationConfig::default(),
+            chunked_prefill: ChunkedPrefillConfig::default(),
+            kv_transfer_protocol: KvTransferProtocolKind::default(),
+            vllm_discovery_transport: VllmDiscoveryTransport::default(),
+            rate_limit_config: None,
+            content_policy_config: None,
+            model_params: HashMap::new(),
+            admission: AdmissionConfig::default(),
             api_key: None,
             api_key_validation_urls: vec![],
+            api_keys_file: None,
+            jwt: None,
+            api_key_cache: ApiKeyCacheConfig::default(),
+            admin: AdminConfig::default(),
+            tls: None,
+            upstream_tls: UpstreamTlsConfig::default(),
+            audit: AuditConfig::default(),
             discovery: None,
             metrics: None,
             log_dir: None,
@@ -508,6 +1403,7 @@ impl RouterConfig {
 }
 
 #[cfg(test)]
+#[allow(clippy::items_after_test_module)]
 mod tests {
     use super::*;
 
@@ -783,6 +1679,10 @@ mod tests {
             prefill_selector: selector.clone(),
             decode_selector: selector.clone(),
             bootstrap_port_annotation: "custom.io/port".to_string(),
+            backend: DiscoveryBackendKind::Kubernetes,
+            file_path: None,
+            dns_names: vec![],
+            endpoint_slices: EndpointSliceConfig::default(),
         };
 
         assert!(config.enabled);
@@ -984,8 +1884,28 @@ mod tests {
             worker_startup_timeout_secs: 60,
             worker_startup_check_interval_secs: 5,
             intra_node_data_parallel_size: 1,
+            dp_discovery: DpDiscoveryConfig::default(),
+            lora: LoraConfig::default(),
+            pd_pairing: PdPairingConfig::default(),
+            decode_failover: DecodeFailoverConfig::default(),
+            pd_capacity: PdCapacityConfig::default(),
+            conditional_disaggregation: ConditionalDisaggregationConfig::default(),
+            chunked_prefill: ChunkedPrefillConfig::default(),
+            kv_transfer_protocol: KvTransferProtocolKind::default(),
+            vllm_discovery_transport: VllmDiscoveryTransport::default(),
+            rate_limit_config: None,
+            content_policy_config: None,
+            model_params: HashMap::new(),
+            admission: AdmissionConfig::default(),
             api_key: None,
             api_key_validation_urls: vec![],
+            api_keys_file: None,
+            jwt: None,
+            api_key_cache: ApiKeyCacheConfig::default(),
+            admin: AdminConfig::default(),
+            tls: None,
+            upstream_tls: UpstreamTlsConfig::default(),
+            audit: AuditConfig::default(),
             discovery: Some(DiscoveryConfig {
                 enabled: true,
                 namespace: Some("vllm".to_string()),
@@ -1051,8 +1971,28 @@ mod tests {
             worker_startup_timeout_secs: 180,
             worker_startup_check_interval_secs: 15,
             intra_node_data_parallel_size: 1,
+            dp_discovery: DpDiscoveryConfig::default(),
+            lora: LoraConfig::default(),
+            pd_pairing: PdPairingConfig::default(),
+            decode_failover: DecodeFailoverConfig::default(),
+            pd_capacity: PdCapacityConfig::default(),
+            conditional_disaggregation: ConditionalDisaggregationConfig::default(),
+            chunked_prefill: ChunkedPrefillConfig::default(),
+            kv_transfer_protocol: KvTransferProtocolKind::default(),
+            vllm_discovery_transport: VllmDiscoveryTransport::default(),
+            rate_limit_config: None,
+            content_policy_config: None,
+            model_params: HashMap::new(),
+            admission: AdmissionConfig::default(),
             api_key: None,
             api_key_validation_urls: vec![],
+            api_keys_file: None,
+            jwt: None,
+            api_key_cache: ApiKeyCacheConfig::default(),
+            admin: AdminConfig::default(),
+            tls: None,
+            upstream_tls: UpstreamTlsConfig::default(),
+            audit: AuditConfig::default(),
             discovery: Some(DiscoveryConfig {
                 enabled: true,
                 namespace: None,
@@ -1109,8 +2049,28 @@ mod tests {
             worker_startup_timeout_secs: 600,
             worker_startup_check_interval_secs: 20,
             intra_node_data_parallel_size: 1,
+            dp_discovery: DpDiscoveryConfig::default(),
+            lora: LoraConfig::default(),
+            pd_pairing: PdPairingConfig::default(),
+            decode_failover: DecodeFailoverConfig::default(),
+            pd_capacity: PdCapacityConfig::default(),
+            conditional_disaggregation: ConditionalDisaggregationConfig::default(),
+            chunked_prefill: ChunkedPrefillConfig::default(),
+            kv_transfer_protocol: KvTransferProtocolKind::default(),
+            vllm_discovery_transport: VllmDiscoveryTransport::default(),
+            rate_limit_config: None,
+            content_policy_config: None,
+            model_params: HashMap::new(),
+            admission: AdmissionConfig::default(),
             api_key: None,
             api_key_validation_urls: vec![],
+            api_keys_file: None,
+            jwt: None,
+            api_key_cache: ApiKeyCacheConfig::default(),
+            admin: AdminConfig::default(),
+            tls: None,
+            upstream_tls: UpstreamTlsConfig::default(),
+            audit: AuditConfig::default(),
             discovery: Some(DiscoveryConfig {
                 enabled: true,
                 namespace: Some("production".to_string()),
@@ -1120,6 +2080,7 @@ mod tests {
                 prefill_selector: selectors.clone(),
                 decode_selector: selectors,
                 bootstrap_port_annotation: "mycompany.io/bootstrap".to_string(),
+                ..Default::default()
             }),
             metrics: Some(MetricsConfig {
                 port: 9999,
@@ -1,4 +1,6 @@
 use super::*;
+use crate::service_discovery::{dns::DnsTarget, PodType};
+use std::collections::HashMap;
 
 /// Configuration validator
 pub struct ConfigValidator;
@@ -19,9 +21,27 @@ impl ConfigValidator {
         Self::validate_mode(&config.mode, has_service_discovery)?;
         Self::validate_policy(&config.policy)?;
         Self::validate_server_settings(config)?;
+        Self::validate_pd_pairing(&config.pd_pairing)?;
+        Self::validate_decode_failover(&config.decode_failover)?;
+        Self::validate_pd_capacity(&config.pd_capacity)?;
+        Self::validate_admission(&config.admission)?;
+        Self::validate_api_key_cache(&config.api_key_cache)?;
+        Self::validate_admin(config)?;
+        if let Some(tls) = &config.tls {
+            Self::validate_tls(tls)?;
+        }
+        Self::validate_upstream_tls(&config.upstream_tls)?;
+        Self::validate_model_params(&config.model_params)?;
+        Self::validate_audit(&config.audit)?;
+        if let Some(jwt) = &config.jwt {
+            Self::validate_jwt(jwt)?;
+        }
+        Self::validate_conditional_disaggregation(&config.conditional_disaggregation)?;
+        Self::validate_chunked_prefill(&config.chunked_prefill)?;
+        Self::validate_vllm_discovery_transport(config.vllm_discovery_transport, &config.mode)?;
 
         if let Some(discovery) = &config.discovery {
-            Self::validate_discovery(discovery, &config.mode)?;
+            Self::validate_discovery(discovery, &config.mode, config.kv_transfer_protocol)?;
         }
 
         if let Some(metrics) = &config.metrics {
@@ -285,11 +305,31 @@ impl ConfigValidator {
             });
         }
 
+        if config.dp_discovery.enabled && !config.dp_discovery.endpoint.starts_with('/') {
+            return Err(ConfigError::InvalidValue {
+                field: "dp_discovery.endpoint".to_string(),
+                value: config.dp_discovery.endpoint.clone(),
+                reason: "Must start with '/'".to_string(),
+            });
+        }
+
+        if config.lora.enabled && config.lora.max_adapters_per_worker == 0 {
+            return Err(ConfigError::InvalidValue {
+                field: "lora.max_adapters_per_worker".to_string(),
+                value: config.lora.max_adapters_per_worker.to_string(),
+                reason: "Must be > 0 when LoRA routing is enabled".to_string(),
+            });
+        }
+
         Ok(())
     }
 
     /// Validate service discovery configuration
-    fn validate_discovery(discovery: &DiscoveryConfig, mode: &RoutingMode) -> ConfigResult<()> {
+    fn validate_discovery(
+        discovery: &DiscoveryConfig,
+        mode: &RoutingMode,
+        kv_transfer_protocol: KvTransferProtocolKind,
+    ) -> ConfigResult<()> {
         if !discovery.enabled {
             return Ok(()); // No validation needed if disabled
         }
@@ -310,7 +350,66 @@ impl ConfigValidator {
             });
         }
 
-        // Validate selectors based on mode
+        if let RoutingMode::OpenAI { .. } = mode {
+            // OpenAI mode doesn't use service discovery
+            return Err(ConfigError::ValidationFailed {
+                reason: "OpenAI mode does not support service discovery".to_string(),
+            });
+        }
+
+        match discovery.backend {
+            DiscoveryBackendKind::Kubernetes => {}
+            DiscoveryBackendKind::EndpointSlices => {
+                return Self::validate_endpoint_slices(&discovery.endpoint_slices, mode);
+            }
+            DiscoveryBackendKind::File => {
+                if discovery.file_path.as_deref().is_none_or(str::is_empty) {
+                    return Err(ConfigError::MissingRequired {
+                        field: "discovery.file_path".to_string(),
+                    });
+                }
+                return Ok(());
+            }
+            DiscoveryBackendKind::Dns => {
+                if discovery.dns_names.is_empty() {
+                    return Err(ConfigError::MissingRequired {
+                        field: "discovery.dns_names".to_string(),
+                    });
+                }
+                if let Some(name) = discovery.dns_names.iter().find(|n| n.trim().is_empty()) {
+                    return Err(ConfigError::InvalidValue {
+                        field: "discovery.dns_names".to_string(),
+                        value: format!("{:?}", name),
+                        reason: "DNS names must not be empty".to_string(),
+                    });
+                }
+                // Prefill workers need a bootstrap port when the protocol uses one
+                let needs_bootstrap = mode.is_pd_mode()
+                    && kv_transfer_protocol.uses_bootstrap(mode.is_vllm_pd_mode());
+                for name in &discovery.dns_names {
+                    let target = DnsTarget::parse(name, discovery.port).map_err(|reason| {
+                        ConfigError::InvalidValue {
+                            field: "discovery.dns_names".to_string(),
+                            value: name.clone(),
+                            reason,
+                        }
+                    })?;
+                    if needs_bootstrap
+                        && *target.role() == PodType::Prefill
+                        && target.bootstrap_port().is_none()
+                    {
+                        return Err(ConfigError::InvalidValue {
+                            field: "discovery.dns_names".to_string(),
+                            value: name.clone(),
+                            reason: "Prefill names need ';bootstrap=<port>' with the bootstrap KV transfer protocol".to_string(),
+                        });
+                    }
+                }
+                return Ok(());
+            }
+        }
+
+        // Kubernetes selectors based on mode
         match mode {
             RoutingMode::Regular { .. } => {
                 if discovery.selector.is_empty() {
@@ -334,10 +433,48 @@ impl ConfigValidator {
                     });
                 }
             }
-            RoutingMode::OpenAI { .. } => {
-                // OpenAI mode doesn't use service discovery
-                return Err(ConfigError::ValidationFailed {
-                    reason: "OpenAI mode does not support service discovery".to_string(),
+            RoutingMode::OpenAI { .. } => {} // Rejected above
+        }
+
+        Ok(())
+    }
+
+    /// Validate the Services and port names followed via EndpointSlices
+    fn validate_endpoint_slices(
+        endpoint_slices: &EndpointSliceConfig,
+        mode: &RoutingMode,
+    ) -> ConfigResult<()> {
+        let is_set = |service: &Option<String>| service.as_deref().is_some_and(|s| !s.is_empty());
+        match mode {
+            RoutingMode::Regular { .. } => {
+                if !is_set(&endpoint_slices.service) {
+                    return Err(ConfigError::MissingRequired {
+                        field: "discovery.endpoint_slices.service".to_string(),
+                    });
+                }
+            }
+            RoutingMode::PrefillDecode { .. } | RoutingMode::VllmPrefillDecode { .. } => {
+                if !is_set(&endpoint_slices.prefill_service)
+                    && !is_set(&endpoint_slices.decode_service)
+                {
+                    return Err(ConfigError::ValidationFailed {
+                        reason: "PD mode with EndpointSlice discovery requires a prefill or decode service".to_string(),
+                    });
+                }
+            }
+            RoutingMode::OpenAI { .. } => {}
+        }
+
+        for (field, name) in [
+            ("http_port_name", &endpoint_slices.http_port_name),
+            ("bootstrap_port_name", &endpoint_slices.bootstrap_port_name),
+            ("zmq_port_name", &endpoint_slices.zmq_port_name),
+        ] {
+            if name.is_empty() {
+                return Err(ConfigError::InvalidValue {
+                    field: format!("discovery.endpoint_slices.{}", field),
+                    value: name.clone(),
+                    reason: "Port name cannot be empty".to_string(),
                 });
             }
         }
@@ -366,6 +503,535 @@ impl ConfigValidator {
         Ok(())
     }
 
+    /// Validate topology-aware PD pairing configuration
+    fn validate_pd_pairing(pairing: &PdPairingConfig) -> ConfigResult<()> {
+        let costs = &pairing.transfer_cost;
+        for (field, value) in [
+            ("pd_pairing.load_weight", pairing.load_weight),
+            ("pd_pairing.transfer_cost.same_node", costs.same_node),
+            ("pd_pairing.transfer_cost.same_rack", costs.same_rack),
+            ("pd_pairing.transfer_cost.same_zone", costs.same_zone),
+            ("pd_pairing.transfer_cost.cross_zone", costs.cross_zone),
+        ] {
+            if !value.is_finite() || value < 0.0 {
+                return Err(ConfigError::InvalidValue {
+                    field: field.to_string(),
+                    value: value.to_string(),
+                    reason: "Must be a finite value >= 0".to_string(),
+                });
+            }
+        }
+
+        for (url, topology) in &pairing.worker_topology {
+            if let Err(e) = crate::core::WorkerTopology::parse(topology) {
+                return Err(ConfigError::InvalidValue {
+                    field: format!("pd_pairing.worker_topology[{}]", url),
+                    value: topology.clone(),
+                    reason: e,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Validate PD decode failover configuration
+    fn validate_decode_failover(failover: &DecodeFailoverConfig) -> ConfigResult<()> {
+        if failover.enabled && failover.max_attempts < 1 {
+            return Err(ConfigError::InvalidValue {
+                field: "decode_failover.max_attempts".to_string(),
+                value: failover.max_attempts.to_string(),
+                reason: "Must be >= 1 when decode failover is enabled".to_string(),
+            });
+        }
+        Self::validate_urls(&failover.fallback_worker_urls)?;
+        Ok(())
+    }
+
+    /// Validate conditional disaggregation configuration
+    fn validate_conditional_disaggregation(
+        conditional: &ConditionalDisaggregationConfig,
+    ) -> ConfigResult<()> {
+        if let Some(threshold) = conditional.prefix_cache_threshold {
+            if !(threshold > 0.0 && threshold <= 1.0) {
+                return Err(ConfigError::InvalidValue {
+                    field: "conditional_disaggregation.prefix_cache_threshold".to_string(),
+                    value: threshold.to_string(),
+                    reason: "Must be in (0.0, 1.0]".to_string(),
+                });
+            }
+        }
+        Self::validate_urls(&conditional.mixed_worker_urls)?;
+        Ok(())
+    }
+
+    /// Validate long-prompt prefill placement configuration
+    fn validate_chunked_prefill(chunked: &ChunkedPrefillConfig) -> ConfigResult<()> {
+        if !chunked.enabled {
+            return Ok(());
+        }
+        let positive = [
+            (
+                "chunked_prefill.min_prompt_tokens",
+                chunked.min_prompt_tokens as u64,
+            ),
+            ("chunked_prefill.chunk_tokens", chunked.chunk_tokens as u64),
+            (
+                "chunked_prefill.metrics_interval_secs",
+                chunked.metrics_interval_secs,
+            ),
+        ];
+        for (field, value) in positive {
+            if value == 0 {
+                return Err(ConfigError::InvalidValue {
+                    field: field.to_string(),
+                    value: value.to_string(),
+                    reason: "Must be > 0".to_string(),
+                });
+            }
+        }
+        if chunked.context_parallel && chunked.max_chunks < 2 {
+            return Err(ConfigError::InvalidValue {
+                field: "chunked_prefill.max_chunks".to_string(),
+                value: chunked.max_chunks.to_string(),
+                reason: "Context-parallel prefill needs at least 2 chunks".to_string(),
+            });
+        }
+        if !chunked.metrics_path.starts_with('/') {
+            return Err(ConfigError::InvalidValue {
+                field: "chunked_prefill.metrics_path".to_string(),
+                value: chunked.metrics_path.clone(),
+                reason: "Must start with '/'".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Validate that the vLLM discovery transport is compiled in
+    fn validate_vllm_discovery_transport(
+        transport: VllmDiscoveryTransport,
+        mode: &RoutingMode,
+    ) -> ConfigResult<()> {
+        let uses_discovery = matches!(
+            mode,
+            RoutingMode::VllmPrefillDecode {
+                discovery_address: Some(_),
+                ..
+            }
+        );
+        let zmq_available = cfg!(any(feature = "zmq-native", feature = "zmq-pure"));
+        if uses_discovery && transport == VllmDiscoveryTransport::Zmq && !zmq_available {
+            return Err(ConfigError::InvalidValue {
+                field: "vllm_discovery_transport".to_string(),
+                value: "zmq".to_string(),
+                reason: "Built without the zmq-native or zmq-pure feature; use the http transport"
+                    .to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Validate PD capacity controller configuration
+    fn validate_pd_capacity(capacity: &PdCapacityConfig) -> ConfigResult<()> {
+        if !capacity.enabled {
+            return Ok(());
+        }
+        let positive = [
+            (
+                "pd_capacity.evaluation_interval_secs",
+                capacity.evaluation_interval_secs as f64,
+            ),
+            ("pd_capacity.window_secs", capacity.window_secs as f64),
+            (
+                "pd_capacity.prefill_time_target_ms",
+                capacity.prefill_time_target_ms,
+            ),
+            (
+                "pd_capacity.decode_itl_target_ms",
+                capacity.decode_itl_target_ms,
+            ),
+        ];
+        for (field, value) in positive {
+            if !(value.is_finite() && value > 0.0) {
+                return Err(ConfigError::InvalidValue {
+                    field: field.to_string(),
+                    value: value.to_string(),
+                    reason: "Must be > 0".to_string(),
+                });
+            }
+        }
+        if !(capacity.imbalance_ratio.is_finite() && capacity.imbalance_ratio >= 1.0) {
+            return Err(ConfigError::InvalidValue {
+                field: "pd_capacity.imbalance_ratio".to_string(),
+                value: capacity.imbalance_ratio.to_string(),
+                reason: "Must be >= 1.0".to_string(),
+            });
+        }
+        if capacity.min_workers_per_role < 1 {
+            return Err(ConfigError::InvalidValue {
+                field: "pd_capacity.min_workers_per_role".to_string(),
+                value: capacity.min_workers_per_role.to_string(),
+                reason: "Must be >= 1".to_string(),
+            });
+        }
+        match &capacity.actuator_path {
+            Some(path) if !path.starts_with('/') => {
+                return Err(ConfigError::InvalidValue {
+                    field: "pd_capacity.actuator_path".to_string(),
+                    value: path.clone(),
+                    reason: "Must start with '/'".to_string(),
+                });
+            }
+            None if capacity.auto_apply => {
+                return Err(ConfigError::MissingRequired {
+                    field: "pd_capacity.actuator_path".to_string(),
+                });
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Validate priority classes and tenant weights of the request queue
+    fn validate_admission(admission: &AdmissionConfig) -> ConfigResult<()> {
+        let mut names = std::collections::HashSet::new();
+        for class in &admission.classes {
+            if class.name.trim().is_empty() {
+                return Err(ConfigError::InvalidValue {
+                    field: "admission.classes.name".to_string(),
+                    value: class.name.clone(),
+                    reason: "Must not be empty".to_string(),
+                });
+            }
+            if !names.insert(class.name.as_str()) {
+                return Err(ConfigError::InvalidValue {
+                    field: "admission.classes.name".to_string(),
+                    value: class.name.clone(),
+                    reason: "Duplicate priority class".to_string(),
+                });
+            }
+        }
+
+        let known = |name: &str| {
+            if admission.classes.is_empty() {
+                name == crate::core::DEFAULT_PRIORITY_CLASS
+            } else {
+                names.contains(name)
+            }
+        };
+        let references = admission
+            .default_class
+            .iter()
+            .map(|class| ("admission.default_class", class))
+            .chain(
+                admission
+                    .api_key_classes
+                    .values()
+                    .map(|class| ("admission.api_key_classes", class)),
+            )
+            .chain(
+                admission
+                    .endpoint_classes
+                    .values()
+                    .map(|class| ("admission.endpoint_classes", class)),
+            );
+        for (field, class) in references {
+            if !known(class) {
+                return Err(ConfigError::InvalidValue {
+                    field: field.to_string(),
+                    value: class.clone(),
+                    reason: "Unknown priority class".to_string(),
+                });
+            }
+        }
+
+        for (tenant, weight) in &admission.tenant_weights {
+            if !(weight.is_finite() && *weight > 0.0) {
+                return Err(ConfigError::InvalidValue {
+                    field: format!("admission.tenant_weights.{}", tenant),
+                    value: weight.to_string(),
+                    reason: "Must be > 0".to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Validate JWT validation configuration
+    fn validate_jwt(jwt: &JwtConfig) -> ConfigResult<()> {
+        match (&jwt.jwks_file, &jwt.jwks_url) {
+            (None, None) => {
+                return Err(ConfigError::MissingRequired {
+                    field: "jwt.jwks_file or jwt.jwks_url".to_string(),
+                });
+            }
+            (Some(_), Some(_)) => {
+                return Err(ConfigError::ValidationFailed {
+                    reason: "jwt.jwks_file and jwt.jwks_url are mutually exclusive".to_string(),
+                });
+            }
+            (None, Some(url)) if !url.starts_with("http://") && !url.starts_with("https://") => {
+                return Err(ConfigError::InvalidValue {
+                    field: "jwt.jwks_url".to_string(),
+                    value: url.clone(),
+                    reason: "URL must start with http:// or https://".to_string(),
+                });
+            }
+            _ => {}
+        }
+        if jwt.jwks_refresh_secs == 0 {
+            return Err(ConfigError::InvalidValue {
+                field: "jwt.jwks_refresh_secs".to_string(),
+                value: jwt.jwks_refresh_secs.to_string(),
+                reason: "Must be > 0".to_string(),
+            });
+        }
+        for (field, claim) in [
+            ("jwt.tenant_claim", &jwt.tenant_claim),
+            ("jwt.admin_claim", &jwt.admin_claim),
+        ] {
+            if let Some(claim) = claim {
+                if claim.split('.').any(str::is_empty) {
+                    return Err(ConfigError::InvalidValue {
+                        field: field.to_string(),
+                        value: claim.clone(),
+                        reason: "Must be a claim name or dot-separated path".to_string(),
+                    });
+                }
+            }
+        }
+        if jwt.admin_value.trim().is_empty() {
+            return Err(ConfigError::InvalidValue {
+                field: "jwt.admin_value".to_string(),
+                value: jwt.admin_value.clone(),
+                reason: "Must not be empty".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Validate the remote API key validation cache
+    fn validate_api_key_cache(cache: &ApiKeyCacheConfig) -> ConfigResult<()> {
+        if cache.max_entries == 0 {
+            return Err(ConfigError::InvalidValue {
+                field: "api_key_cache.max_entries".to_string(),
+                value: cache.max_entries.to_string(),
+                reason: "Must be > 0".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Validate the admin listener
+    fn validate_admin(config: &RouterConfig) -> ConfigResult<()> {
+        let admin = &config.admin;
+        if let Some(port) = admin.port {
+            if port == 0 {
+                return Err(ConfigError::InvalidValue {
+                    field: "admin.port".to_string(),
+                    value: port.to_string(),
+                    reason: "Port must be > 0".to_string(),
+                });
+            }
+            if port == config.port {
+                return Err(ConfigError::InvalidValue {
+                    field: "admin.port".to_string(),
+                    value: port.to_string(),
+                    reason: "Must differ from the public port".to_string(),
+                });
+            }
+            // Without its own key the listener falls back to the inference auth
+            // chain, which may be disabled, so it must stay local
+            if admin.api_key.is_none() && !admin.is_loopback() {
+                return Err(ConfigError::InvalidValue {
+                    field: "admin.host".to_string(),
+                    value: admin.host.clone(),
+                    reason:
+                        "A separate admin listener on a non-loopback host requires admin.api_key"
+                            .to_string(),
+                });
+            }
+        }
+        if admin
+            .api_key
+            .as_deref()
+            .is_some_and(|key| key.trim().is_empty())
+        {
+            return Err(ConfigError::InvalidValue {
+                field: "admin.api_key".to_string(),
+                value: String::new(),
+                reason: "Must not be empty".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Validate listener TLS configuration
+    fn validate_tls(tls: &TlsConfig) -> ConfigResult<()> {
+        for (field, value) in [
+            ("tls.cert_file", &tls.cert_file),
+            ("tls.key_file", &tls.key_file),
+        ] {
+            if value.is_empty() {
+                return Err(ConfigError::MissingRequired {
+                    field: field.to_string(),
+                });
+            }
+        }
+        if tls.client_auth_optional && tls.client_ca_file.is_none() {
+            return Err(ConfigError::MissingRequired {
+                field: "tls.client_ca_file".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Validate worker connection TLS configuration
+    fn validate_upstream_tls(tls: &UpstreamTlsConfig) -> ConfigResult<()> {
+        if tls.client_cert_file.is_some() != tls.client_key_file.is_some() {
+            return Err(ConfigError::ValidationFailed {
+                reason: "upstream_tls.client_cert_file and upstream_tls.client_key_file must be set together"
+                    .to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Validate the audit log destinations
+    fn validate_audit(audit: &AuditConfig) -> ConfigResult<()> {
+        if audit.max_file_size_mb == 0 {
+            return Err(ConfigError::InvalidValue {
+                field: "audit.max_file_size_mb".to_string(),
+                value: "0".to_string(),
+                reason: "Must be > 0".to_string(),
+            });
+        }
+        for (field, path) in [
+            ("audit.file", &audit.file),
+            ("audit.unix_socket", &audit.unix_socket),
+        ] {
+            if path.as_deref().is_some_and(|path| path.trim().is_empty()) {
+                return Err(ConfigError::InvalidValue {
+                    field: field.to_string(),
+                    value: String::new(),
+                    reason: "Must not be empty".to_string(),
+                });
+            }
+        }
+        if let Some(url) = &audit.http_sink {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                return Err(ConfigError::InvalidValue {
+                    field: "audit.http_sink".to_string(),
+                    value: url.clone(),
+                    reason: "URL must start with http:// or https://".to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Validate per-model parameter defaults and clamps against the API ranges
+    fn validate_model_params(
+        model_params: &HashMap<String, ModelParamsConfig>,
+    ) -> ConfigResult<()> {
+        use crate::protocols::validation::constants::{
+            vllm::TOP_K_MIN, MAX_STOP_SEQUENCES, TEMPERATURE_RANGE, TOP_P_RANGE,
+        };
+
+        for (model, params) in model_params {
+            let (defaults, clamps) = (&params.defaults, &params.clamps);
+            Self::validate_param(
+                model,
+                "temperature",
+                defaults.temperature,
+                clamps.temperature,
+                TEMPERATURE_RANGE,
+            )?;
+            Self::validate_param(model, "top_p", defaults.top_p, clamps.top_p, TOP_P_RANGE)?;
+            Self::validate_param(
+                model,
+                "top_k",
+                defaults.top_k,
+                clamps.top_k,
+                (TOP_K_MIN, i32::MAX),
+            )?;
+            if defaults.top_k == Some(0) {
+                return Err(ConfigError::InvalidValue {
+                    field: format!("model_params.{}.defaults.top_k", model),
+                    value: "0".to_string(),
+                    reason: "Must be -1 (disabled) or positive".to_string(),
+                });
+            }
+            Self::validate_param(
+                model,
+                "max_tokens",
+                defaults.max_tokens,
+                clamps.max_tokens,
+                (1, u32::MAX),
+            )?;
+            if let Some(stop) = &defaults.stop {
+                if stop.len() > MAX_STOP_SEQUENCES {
+                    return Err(ConfigError::InvalidValue {
+                        field: format!("model_params.{}.defaults.stop", model),
+                        value: stop.len().to_string(),
+                        reason: format!("At most {} stop sequences", MAX_STOP_SEQUENCES),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that a parameter's default and clamp range lie within `bounds`
+    /// and agree with each other
+    fn validate_param<T>(
+        model: &str,
+        param: &str,
+        default: Option<T>,
+        clamp: Option<ParamRange<T>>,
+        bounds: (T, T),
+    ) -> ConfigResult<()>
+    where
+        T: PartialOrd + Copy + std::fmt::Display,
+    {
+        let clamp = clamp.unwrap_or_default();
+        let values = [
+            ("defaults", default),
+            ("clamps.min", clamp.min),
+            ("clamps.max", clamp.max),
+        ];
+        for (kind, value) in values {
+            if let Some(value) = value {
+                if value < bounds.0 || value > bounds.1 {
+                    return Err(ConfigError::InvalidValue {
+                        field: format!("model_params.{}.{}.{}", model, kind, param),
+                        value: value.to_string(),
+                        reason: format!("Must be in [{}, {}]", bounds.0, bounds.1),
+                    });
+                }
+            }
+        }
+        if let (Some(min), Some(max)) = (clamp.min, clamp.max) {
+            if min > max {
+                return Err(ConfigError::InvalidValue {
+                    field: format!("model_params.{}.clamps.{}", model, param),
+                    value: format!("[{}, {}]", min, max),
+                    reason: "min must be <= max".to_string(),
+                });
+            }
+        }
+        if let Some(value) = default {
+            if clamp.min.is_some_and(|min| value < min) || clamp.max.is_some_and(|max| value > max)
+            {
+                return Err(ConfigError::InvalidValue {
+                    field: format!("model_params.{}.defaults.{}", model, param),
+                    value: value.to_string(),
+                    reason: "Default lies outside the clamp range".to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+
     /// Validate retry configuration
     fn validate_retry(retry: &RetryConfig) -> ConfigResult<()> {
         if retry.max_retries < 1 {
@@ -845,4 +1511,598 @@ mod tests {
         let result = ConfigValidator::validate(&config);
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_validate_dp_discovery_endpoint() {
+        let mut config = RouterConfig::new(
+            RoutingMode::Regular {
+                worker_urls: vec!["http://worker:8000".to_string()],
+            },
+            PolicyConfig::Random,
+        );
+
+        config.dp_discovery.enabled = true;
+        assert!(ConfigValidator::validate(&config).is_ok());
+
+        config.dp_discovery.endpoint = "get_server_info".to_string();
+        let result = ConfigValidator::validate(&config);
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("dp_discovery.endpoint"));
+    }
+
+    #[test]
+    fn test_validate_lora_max_adapters() {
+        let mut config = RouterConfig::new(
+            RoutingMode::Regular {
+                worker_urls: vec!["http://worker:8000".to_string()],
+            },
+            PolicyConfig::Random,
+        );
+
+        config.lora.max_adapters_per_worker = 0;
+        assert!(ConfigValidator::validate(&config).is_ok());
+
+        config.lora.enabled = true;
+        let result = ConfigValidator::validate(&config);
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("lora.max_adapters_per_worker"));
+    }
+
+    #[test]
+    fn test_validate_pd_pairing() {
+        let mut config = RouterConfig::new(
+            RoutingMode::PrefillDecode {
+                prefill_urls: vec![("http://prefill:8000".to_string(), None)],
+                decode_urls: vec!["http://decode:8000".to_string()],
+                prefill_policy: None,
+                decode_policy: None,
+            },
+            PolicyConfig::Random,
+        );
+        config.pd_pairing.enabled = true;
+        config
+            .pd_pairing
+            .worker_topology
+            .insert("http://prefill:8000".to_string(), "z1/r1/n1".to_string());
+        assert!(ConfigValidator::validate(&config).is_ok());
+
+        config.pd_pairing.transfer_cost.cross_zone = -1.0;
+        let result = ConfigValidator::validate(&config);
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("pd_pairing.transfer_cost.cross_zone"));
+
+        config.pd_pairing.transfer_cost.cross_zone = 16.0;
+        config
+            .pd_pairing
+            .worker_topology
+            .insert("http://decode:8000".to_string(), "z1//n1".to_string());
+        let result = ConfigValidator::validate(&config);
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("pd_pairing.worker_topology"));
+    }
+
+    #[test]
+    fn test_validate_decode_failover() {
+        let mut config = RouterConfig::new(
+            RoutingMode::PrefillDecode {
+                prefill_urls: vec![("http://prefill:8000".to_string(), None)],
+                decode_urls: vec!["http://decode:8000".to_string()],
+                prefill_policy: None,
+                decode_policy: None,
+            },
+            PolicyConfig::Random,
+        );
+        config.decode_failover.enabled = true;
+        config.decode_failover.fallback_worker_urls = vec!["http://worker:8000".to_string()];
+        assert!(ConfigValidator::validate(&config).is_ok());
+
+        config.decode_failover.max_attempts = 0;
+        let result = ConfigValidator::validate(&config);
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("decode_failover.max_attempts"));
+
+        config.decode_failover.max_attempts = 2;
+        config.decode_failover.fallback_worker_urls = vec!["worker:8000".to_string()];
+        assert!(ConfigValidator::validate(&config).is_err());
+    }
+
+    #[test]
+    fn test_validate_pd_capacity() {
+        let mut config = RouterConfig::new(
+            RoutingMode::PrefillDecode {
+                prefill_urls: vec![("http://prefill:8000".to_string(), None)],
+                decode_urls: vec!["http://decode:8000".to_string()],
+                prefill_policy: None,
+                decode_policy: None,
+            },
+            PolicyConfig::Random,
+        );
+        config.pd_capacity.enabled = true;
+        assert!(ConfigValidator::validate(&config).is_ok());
+
+        config.pd_capacity.imbalance_ratio = 0.5;
+        assert!(ConfigValidator::validate(&config)
+            .unwrap_err()
+            .to_string()
+            .contains("pd_capacity.imbalance_ratio"));
+
+        config.pd_capacity.imbalance_ratio = 2.0;
+        config.pd_capacity.auto_apply = true;
+        assert!(ConfigValidator::validate(&config)
+            .unwrap_err()
+            .to_string()
+            .contains("pd_capacity.actuator_path"));
+
+        config.pd_capacity.actuator_path = Some("restart".to_string());
+        assert!(ConfigValidator::validate(&config).is_err());
+
+        config.pd_capacity.actuator_path = Some("/restart".to_string());
+        assert!(ConfigValidator::validate(&config).is_ok());
+    }
+
+    #[test]
+    fn test_validate_admission() {
+        let mut config = RouterConfig::new(
+            RoutingMode::Regular {
+                worker_urls: vec!["http://worker:8000".to_string()],
+            },
+            PolicyConfig::Random,
+        );
+        config.admission.default_class = Some("default".to_string());
+        assert!(ConfigValidator::validate(&config).is_ok());
+
+        config.admission.classes = vec![
+            PriorityClassConfig {
+                name: "interactive".to_string(),
+                queue_size: None,
+                queue_timeout_secs: Some(5),
+            },
+            PriorityClassConfig {
+                name: "batch".to_string(),
+                queue_size: Some(1000),
+                queue_timeout_secs: None,
+            },
+        ];
+        assert!(ConfigValidator::validate(&config)
+            .unwrap_err()
+            .to_string()
+            .contains("admission.default_class"));
+
+        config.admission.default_class = Some("batch".to_string());
+        config
+            .admission
+            .endpoint_classes
+            .insert("/v1/embeddings".to_string(), "batch".to_string());
+        config
+            .admission
+            .tenant_weights
+            .insert("acme".to_string(), 2.0);
+        assert!(ConfigValidator::validate(&config).is_ok());
+
+        config
+            .admission
+            .tenant_weights
+            .insert("free".to_string(), 0.0);
+        assert!(ConfigValidator::validate(&config)
+            .unwrap_err()
+            .to_string()
+            .contains("admission.tenant_weights.free"));
+
+        config.admission.tenant_weights.remove("free");
+        config.admission.classes[1].name = "interactive".to_string();
+        assert!(ConfigValidator::validate(&config).is_err());
+    }
+
+    #[test]
+    fn test_validate_jwt() {
+        let mut config = RouterConfig::new(
+            RoutingMode::Regular {
+                worker_urls: vec!["http://worker:8000".to_string()],
+            },
+            PolicyConfig::Random,
+        );
+        config.jwt = Some(JwtConfig::default());
+        assert!(ConfigValidator::validate(&config)
+            .unwrap_err()
+            .to_string()
+            .contains("jwt.jwks_file"));
+
+        config.jwt = Some(JwtConfig {
+            jwks_url: Some("https://issuer.example/.well-known/jwks.json".to_string()),
+            tenant_claim: Some("org.id".to_string()),
+            ..Default::default()
+        });
+        assert!(ConfigValidator::validate(&config).is_ok());
+
+        config.jwt.as_mut().unwrap().jwks_file = Some("jwks.json".to_string());
+        assert!(ConfigValidator::validate(&config).is_err());
+
+        config.jwt.as_mut().unwrap().jwks_url = None;
+        config.jwt.as_mut().unwrap().tenant_claim = Some("org.".to_string());
+        assert!(ConfigValidator::validate(&config)
+            .unwrap_err()
+            .to_string()
+            .contains("jwt.tenant_claim"));
+
+        config.jwt = None;
+        config.api_key_cache.max_entries = 0;
+        assert!(ConfigValidator::validate(&config).is_err());
+    }
+
+    #[test]
+    fn test_validate_admin() {
+        let mut config = RouterConfig::new(
+            RoutingMode::Regular {
+                worker_urls: vec!["http://worker:8000".to_string()],
+            },
+            PolicyConfig::Random,
+        );
+        config.port = 8000;
+        config.admin.port = Some(8001);
+        config.admin.api_key = Some("admin-secret".to_string());
+        assert!(ConfigValidator::validate(&config).is_ok());
+
+        config.admin.port = Some(8000);
+        assert!(ConfigValidator::validate(&config)
+            .unwrap_err()
+            .to_string()
+            .contains("admin.port"));
+
+        // Without an admin key the listener must be loopback-only
+        config.admin.port = Some(8001);
+        config.admin.api_key = None;
+        for host in ["127.0.0.1", "::1", "[::1]", "localhost"] {
+            config.admin.host = host.to_string();
+            assert!(ConfigValidator::validate(&config).is_ok(), "{}", host);
+        }
+        config.admin.host = "0.0.0.0".to_string();
+        assert!(ConfigValidator::validate(&config)
+            .unwrap_err()
+            .to_string()
+            .contains("admin.host"));
+        config.admin.api_key = Some("admin-secret".to_string());
+        assert!(ConfigValidator::validate(&config).is_ok());
+
+        config.admin.port = None;
+        config.admin.api_key = Some(" ".to_string());
+        assert!(ConfigValidator::validate(&config)
+            .unwrap_err()
+            .to_string()
+            .contains("admin.api_key"));
+    }
+
+    #[test]
+    fn test_validate_tls() {
+        let mut config = RouterConfig::new(
+            RoutingMode::Regular {
+                worker_urls: vec!["http://worker:8000".to_string()],
+            },
+            PolicyConfig::Random,
+        );
+        config.tls = Some(TlsConfig {
+            cert_file: "cert.pem".to_string(),
+            key_file: "key.pem".to_string(),
+            ..Default::default()
+        });
+        assert!(ConfigValidator::validate(&config).is_ok());
+
+        config.tls.as_mut().unwrap().client_auth_optional = true;
+        assert!(ConfigValidator::validate(&config)
+            .unwrap_err()
+            .to_string()
+            .contains("tls.client_ca_file"));
+
+        config.tls.as_mut().unwrap().key_file = String::new();
+        assert!(ConfigValidator::validate(&config).is_err());
+
+        config.tls = None;
+        config.upstream_tls.client_cert_file = Some("client.pem".to_string());
+        assert!(ConfigValidator::validate(&config).is_err());
+        config.upstream_tls.client_key_file = Some("client-key.pem".to_string());
+        assert!(ConfigValidator::validate(&config).is_ok());
+    }
+
+    #[test]
+    fn test_validate_audit() {
+        let mut config = RouterConfig::new(
+            RoutingMode::Regular {
+                worker_urls: vec!["http://worker:8000".to_string()],
+            },
+            PolicyConfig::Random,
+        );
+        config.audit.file = Some("/var/log/router/audit.jsonl".to_string());
+        config.audit.http_sink = Some("http://collector:8080/audit".to_string());
+        assert!(ConfigValidator::validate(&config).is_ok());
+
+        config.audit.max_file_size_mb = 0;
+        assert!(ConfigValidator::validate(&config).is_err());
+        config.audit.max_file_size_mb = 100;
+
+        config.audit.http_sink = Some("collector:8080".to_string());
+        assert!(ConfigValidator::validate(&config).is_err());
+        config.audit.http_sink = None;
+
+        config.audit.unix_socket = Some(" ".to_string());
+        assert!(ConfigValidator::validate(&config).is_err());
+    }
+
+    #[test]
+    fn test_validate_model_params() {
+        let mut config = RouterConfig::new(
+            RoutingMode::Regular {
+                worker_urls: vec!["http://worker:8000".to_string()],
+            },
+            PolicyConfig::Random,
+        );
+        let mut params = ModelParamsConfig::default();
+        params.defaults.temperature = Some(0.7);
+        params.clamps.temperature = Some(ParamRange {
+            min: None,
+            max: Some(1.0),
+        });
+        params.defaults.stop = Some(vec!["</s>".to_string()]);
+        config
+            .model_params
+            .insert("llama".to_string(), params.clone());
+        assert!(ConfigValidator::validate(&config).is_ok());
+
+        // Default outside the clamp range
+        params.defaults.temperature = Some(1.5);
+        config
+            .model_params
+            .insert("llama".to_string(), params.clone());
+        assert!(ConfigValidator::validate(&config).is_err());
+
+        // Clamp outside the API range
+        params.defaults.temperature = None;
+        params.clamps.temperature = Some(ParamRange {
+            min: None,
+            max: Some(3.0),
+        });
+        config
+            .model_params
+            .insert("llama".to_string(), params.clone());
+        assert!(ConfigValidator::validate(&config).is_err());
+
+        params.clamps.temperature = None;
+        params.clamps.max_tokens = Some(ParamRange {
+            min: Some(512),
+            max: Some(256),
+        });
+        config
+            .model_params
+            .insert("llama".to_string(), params.clone());
+        assert!(ConfigValidator::validate(&config).is_err());
+
+        params.clamps.max_tokens = None;
+        params.defaults.top_k = Some(0);
+        config.model_params.insert("llama".to_string(), params);
+        assert!(ConfigValidator::validate(&config).is_err());
+    }
+
+    #[test]
+    fn test_validate_conditional_disaggregation() {
+        let mut config = RouterConfig::new(
+            RoutingMode::PrefillDecode {
+                prefill_urls: vec![("http://prefill:8000".to_string(), None)],
+                decode_urls: vec!["http://decode:8000".to_string()],
+                prefill_policy: None,
+                decode_policy: None,
+            },
+            PolicyConfig::Random,
+        );
+        config.conditional_disaggregation.enabled = true;
+        config.conditional_disaggregation.prefix_cache_threshold = Some(0.8);
+        config.conditional_disaggregation.mixed_worker_urls = vec!["http://mixed:8000".to_string()];
+        assert!(ConfigValidator::validate(&config).is_ok());
+
+        config.conditional_disaggregation.prefix_cache_threshold = Some(1.5);
+        assert!(ConfigValidator::validate(&config)
+            .unwrap_err()
+            .to_string()
+            .contains("conditional_disaggregation.prefix_cache_threshold"));
+
+        config.conditional_disaggregation.prefix_cache_threshold = None;
+        config.conditional_disaggregation.mixed_worker_urls = vec!["mixed:8000".to_string()];
+        assert!(ConfigValidator::validate(&config).is_err());
+    }
+
+    #[test]
+    fn test_validate_chunked_prefill() {
+        let mut config = RouterConfig::new(
+            RoutingMode::PrefillDecode {
+                prefill_urls: vec![("http://prefill:8000".to_string(), None)],
+                decode_urls: vec!["http://decode:8000".to_string()],
+                prefill_policy: None,
+                decode_policy: None,
+            },
+            PolicyConfig::Random,
+        );
+        config.chunked_prefill.enabled = true;
+        config.chunked_prefill.context_parallel = true;
+        assert!(ConfigValidator::validate(&config).is_ok());
+
+        config.chunked_prefill.max_chunks = 1;
+        assert!(ConfigValidator::validate(&config)
+            .unwrap_err()
+            .to_string()
+            .contains("chunked_prefill.max_chunks"));
+
+        config.chunked_prefill.context_parallel = false;
+        assert!(ConfigValidator::validate(&config).is_ok());
+
+        config.chunked_prefill.metrics_path = "metrics".to_string();
+        assert!(ConfigValidator::validate(&config).is_err());
+    }
+
+    #[test]
+    fn test_validate_kv_transfer_protocol_in_any_pd_mode() {
+        let mut config = RouterConfig::new(
+            RoutingMode::PrefillDecode {
+                prefill_urls: vec![("http://prefill:8000".to_string(), None)],
+                decode_urls: vec!["http://decode:8000".to_string()],
+                prefill_policy: None,
+                decode_policy: None,
+            },
+            PolicyConfig::Random,
+        );
+        config.kv_transfer_protocol = KvTransferProtocolKind::Nixl;
+        assert!(ConfigValidator::validate(&config).is_ok());
+        assert!(!config.kv_transfer_protocol.uses_bootstrap(false));
+        assert!(KvTransferProtocolKind::Bootstrap.uses_bootstrap(true));
+    }
+
+    #[test]
+    fn test_validate_vllm_discovery_transport() {
+        let mut config = RouterConfig::new(
+            RoutingMode::VllmPrefillDecode {
+                prefill_urls: vec![],
+                decode_urls: vec![],
+                prefill_policy: None,
+                decode_policy: None,
+                discovery_address: Some("0.0.0.0:30001".to_string()),
+            },
+            PolicyConfig::Random,
+        );
+        config.vllm_discovery_transport = VllmDiscoveryTransport::Http;
+        assert!(ConfigValidator::validate(&config).is_ok());
+
+        // ZMQ discovery needs one of the ZMQ features
+        config.vllm_discovery_transport = VllmDiscoveryTransport::Zmq;
+        assert_eq!(
+            ConfigValidator::validate(&config).is_ok(),
+            cfg!(any(feature = "zmq-native", feature = "zmq-pure"))
+        );
+    }
+
+    #[test]
+    fn test_validate_file_and_dns_discovery_backends() {
+        let mut config = RouterConfig::new(
+            RoutingMode::Regular {
+                worker_urls: vec![],
+            },
+            PolicyConfig::Random,
+        );
+
+        // Selectors are only required by the Kubernetes backend
+        config.discovery = Some(DiscoveryConfig {
+            enabled: true,
+            backend: DiscoveryBackendKind::File,
+            ..Default::default()
+        });
+        assert!(ConfigValidator::validate(&config)
+            .unwrap_err()
+            .to_string()
+            .contains("discovery.file_path"));
+
+        config.discovery.as_mut().unwrap().file_path = Some("/etc/router/workers.yaml".into());
+        assert!(ConfigValidator::validate(&config).is_ok());
+
+        let discovery = config.discovery.as_mut().unwrap();
+        discovery.backend = DiscoveryBackendKind::Dns;
+        assert!(ConfigValidator::validate(&config)
+            .unwrap_err()
+            .to_string()
+            .contains("discovery.dns_names"));
+
+        config.discovery.as_mut().unwrap().dns_names = vec!["_decode._tcp.vllm.internal".into()];
+        assert!(ConfigValidator::validate(&config).is_ok());
+
+        config
+            .discovery
+            .as_mut()
+            .unwrap()
+            .dns_names
+            .push(" ".into());
+        assert!(ConfigValidator::validate(&config).is_err());
+
+        config.discovery.as_mut().unwrap().backend = DiscoveryBackendKind::Kubernetes;
+        assert!(ConfigValidator::validate(&config)
+            .unwrap_err()
+            .to_string()
+            .contains("selector"));
+    }
+
+    #[test]
+    fn test_validate_dns_prefill_bootstrap_port() {
+        let mut config = RouterConfig::new(
+            RoutingMode::PrefillDecode {
+                prefill_urls: vec![],
+                decode_urls: vec![],
+                prefill_policy: None,
+                decode_policy: None,
+            },
+            PolicyConfig::Random,
+        );
+        config.discovery = Some(DiscoveryConfig {
+            enabled: true,
+            backend: DiscoveryBackendKind::Dns,
+            dns_names: vec![
+                "_prefill._tcp.vllm.internal".into(),
+                "_decode._tcp.vllm.internal".into(),
+            ],
+            ..Default::default()
+        });
+
+        // The bootstrap protocol needs the prefill bootstrap port
+        assert!(ConfigValidator::validate(&config)
+            .unwrap_err()
+            .to_string()
+            .contains("bootstrap"));
+
+        config.discovery.as_mut().unwrap().dns_names[0] =
+            "_prefill._tcp.vllm.internal;bootstrap=8998".into();
+        assert!(ConfigValidator::validate(&config).is_ok());
+
+        // Other protocols do not
+        config.discovery.as_mut().unwrap().dns_names[0] = "_prefill._tcp.vllm.internal".into();
+        config.kv_transfer_protocol = KvTransferProtocolKind::Nixl;
+        assert!(ConfigValidator::validate(&config).is_ok());
+
+        config.discovery.as_mut().unwrap().dns_names[1] =
+            "_decode._tcp.vllm.internal;bootstrap=8998".into();
+        assert!(ConfigValidator::validate(&config).is_err());
+    }
+
+    #[test]
+    fn test_validate_endpoint_slice_discovery() {
+        let mut config = RouterConfig::new(
+            RoutingMode::PrefillDecode {
+                prefill_urls: vec![],
+                decode_urls: vec![],
+                prefill_policy: None,
+                decode_policy: None,
+            },
+            PolicyConfig::Random,
+        );
+        config.discovery = Some(DiscoveryConfig {
+            enabled: true,
+            backend: DiscoveryBackendKind::EndpointSlices,
+            ..Default::default()
+        });
+        assert!(ConfigValidator::validate(&config).is_err());
+
+        let endpoint_slices = &mut config.discovery.as_mut().unwrap().endpoint_slices;
+        endpoint_slices.prefill_service = Some("vllm-prefill".into());
+        assert!(ConfigValidator::validate(&config).is_ok());
+
+        let endpoint_slices = &mut config.discovery.as_mut().unwrap().endpoint_slices;
+        endpoint_slices.zmq_port_name = String::new();
+        assert!(ConfigValidator::validate(&config)
+            .unwrap_err()
+            .to_string()
+            .contains("zmq_port_name"));
+    }
 }
@@ -0,0 +1,469 @@
+//! Priority classes and weighted fair queuing for queued requests
+//!
+//! Each priority class has its own bounded queue and timeout. Classes are
+//! served strictly in priority order, so interactive traffic never waits
+//! behind a backlog of batch jobs. Within a class, tenants are interleaved by
+//! weighted fair queuing: every request gets a virtual finish tag of
+//! `max(class virtual time, tenant's last tag) + 1 / weight` and the smallest
+//! tag is served first, so a tenant with a deep backlog cannot starve one
+//! that sends a single request.
+
+use crate::config::AdmissionConfig;
+use axum::http::HeaderMap;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::time::{Duration, Instant};
+
+/// Class name used when no priority classes are configured
+pub const DEFAULT_PRIORITY_CLASS: &str = "default";
+
+/// Limits of one priority class
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriorityClass {
+    pub name: String,
+    pub queue_size: usize,
+    pub queue_timeout: Duration,
+}
+
+/// An entry taken out of the queue
+#[derive(Debug)]
+pub struct Dequeued<T> {
+    pub class: usize,
+    pub queued_at: Instant,
+    pub item: T,
+}
+
+struct Entry<T> {
+    finish: f64,
+    seq: u64,
+    queued_at: Instant,
+    item: T,
+}
+
+impl<T> PartialEq for Entry<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T> Eq for Entry<T> {}
+
+impl<T> PartialOrd for Entry<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Entry<T> {
+    // Reversed so the max-heap pops the smallest finish tag, then the oldest
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .finish
+            .total_cmp(&self.finish)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+struct ClassQueue<T> {
+    class: PriorityClass,
+    entries: BinaryHeap<Entry<T>>,
+    virtual_time: f64,
+    tenant_finish: HashMap<String, f64>,
+}
+
+impl<T> ClassQueue<T> {
+    fn new(class: PriorityClass) -> Self {
+        Self {
+            class,
+            entries: BinaryHeap::new(),
+            virtual_time: 0.0,
+            tenant_finish: HashMap::new(),
+        }
+    }
+
+    /// Forget fairness state once the class drains, so it does not grow unbounded
+    fn reset_if_idle(&mut self) {
+        if self.entries.is_empty() {
+            self.virtual_time = 0.0;
+            self.tenant_finish.clear();
+        }
+    }
+}
+
+/// Bounded per-class queues with weighted fair queuing between tenants
+pub struct AdmissionQueue<T> {
+    classes: Vec<ClassQueue<T>>,
+    tenant_weights: HashMap<String, f64>,
+    seq: u64,
+}
+
+impl<T> AdmissionQueue<T> {
+    /// Create a queue with `classes` ordered highest priority first
+    pub fn new(classes: Vec<PriorityClass>, tenant_weights: HashMap<String, f64>) -> Self {
+        Self {
+            classes: classes.into_iter().map(ClassQueue::new).collect(),
+            tenant_weights,
+            seq: 0,
+        }
+    }
+
+    pub fn classes(&self) -> impl Iterator<Item = &PriorityClass> {
+        self.classes.iter().map(|queue| &queue.class)
+    }
+
+    pub fn class(&self, class: usize) -> &PriorityClass {
+        &self.classes[class].class
+    }
+
+    /// Queued requests of one class
+    pub fn len(&self, class: usize) -> usize {
+        self.classes[class].entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.classes.iter().all(|queue| queue.entries.is_empty())
+    }
+
+    /// Queue `item` for `tenant`, handing it back if the class is full
+    pub fn push(&mut self, class: usize, tenant: &str, item: T, now: Instant) -> Result<(), T> {
+        let weight = self
+            .tenant_weights
+            .get(tenant)
+            .copied()
+            .filter(|weight| *weight > 0.0)
+            .unwrap_or(1.0);
+        let queue = &mut self.classes[class];
+        if queue.entries.len() >= queue.class.queue_size {
+            return Err(item);
+        }
+        let start = queue
+            .tenant_finish
+            .get(tenant)
+            .copied()
+            .unwrap_or(0.0)
+            .max(queue.virtual_time);
+        let finish = start + 1.0 / weight;
+        queue.tenant_finish.insert(tenant.to_string(), finish);
+        self.seq += 1;
+        queue.entries.push(Entry {
+            finish,
+            seq: self.seq,
+            queued_at: now,
+            item,
+        });
+        Ok(())
+    }
+
+    /// Next request: the highest non-empty class, smallest finish tag first
+    pub fn pop(&mut self) -> Option<Dequeued<T>> {
+        let (class, queue) = self
+            .classes
+            .iter_mut()
+            .enumerate()
+            .find(|(_, queue)| !queue.entries.is_empty())?;
+        let entry = queue.entries.pop()?;
+        queue.virtual_time = entry.finish;
+        queue.reset_if_idle();
+        Some(Dequeued {
+            class,
+            queued_at: entry.queued_at,
+            item: entry.item,
+        })
+    }
+
+    /// Remove requests that have waited longer than their class timeout
+    pub fn expire(&mut self, now: Instant) -> Vec<Dequeued<T>> {
+        let mut expired = Vec::new();
+        for (class, queue) in self.classes.iter_mut().enumerate() {
+            let timeout = queue.class.queue_timeout;
+            if !queue
+                .entries
+                .iter()
+                .any(|entry| now.duration_since(entry.queued_at) >= timeout)
+            {
+                continue;
+            }
+            let (kept, timed_out): (Vec<_>, Vec<_>) = std::mem::take(&mut queue.entries)
+                .into_iter()
+                .partition(|entry| now.duration_since(entry.queued_at) < timeout);
+            queue.entries = kept.into();
+            queue.reset_if_idle();
+            expired.extend(timed_out.into_iter().map(|entry| Dequeued {
+                class,
+                queued_at: entry.queued_at,
+                item: entry.item,
+            }));
+        }
+        expired
+    }
+
+    /// Earliest time a queued request times out
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.classes
+            .iter()
+            .filter_map(|queue| {
+                let oldest = queue.entries.iter().map(|entry| entry.queued_at).min()?;
+                Some(oldest + queue.class.queue_timeout)
+            })
+            .min()
+    }
+}
+
+/// Assigns requests to priority classes
+#[derive(Debug, Clone)]
+pub struct PriorityClassifier {
+    names: HashMap<String, usize>,
+    default_class: usize,
+    priority_header: Option<String>,
+    api_key_classes: HashMap<String, usize>,
+    /// Longest prefix first
+    endpoint_classes: Vec<(String, usize)>,
+}
+
+impl PriorityClassifier {
+    /// Build the classifier and class limits from the admission config
+    ///
+    /// Classes without their own limits use `queue_size` and `queue_timeout`.
+    pub fn from_config(
+        config: &AdmissionConfig,
+        queue_size: usize,
+        queue_timeout: Duration,
+    ) -> Result<(Self, Vec<PriorityClass>), String> {
+        let classes: Vec<PriorityClass> = if config.classes.is_empty() {
+            vec![PriorityClass {
+                name: DEFAULT_PRIORITY_CLASS.to_string(),
+                queue_size,
+                queue_timeout,
+            }]
+        } else {
+            config
+                .classes
+                .iter()
+                .map(|class| PriorityClass {
+                    name: class.name.clone(),
+                    queue_size: class.queue_size.unwrap_or(queue_size),
+                    queue_timeout: class
+                        .queue_timeout_secs
+                        .map(Duration::from_secs)
+                        .unwrap_or(queue_timeout),
+                })
+                .collect()
+        };
+
+        let names: HashMap<String, usize> = classes
+            .iter()
+            .enumerate()
+            .map(|(idx, class)| (class.name.clone(), idx))
+            .collect();
+        let lookup = |name: &str| {
+            names
+                .get(name)
+                .copied()
+                .ok_or_else(|| format!("unknown priority class '{}'", name))
+        };
+
+        let default_class = match &config.default_class {
+            Some(name) => lookup(name)?,
+            None => classes.len() - 1,
+        };
+        let api_key_classes = config
+            .api_key_classes
+            .iter()
+            .map(|(key, class)| Ok((key.clone(), lookup(class)?)))
+            .collect::<Result<_, String>>()?;
+        let mut endpoint_classes = config
+            .endpoint_classes
+            .iter()
+            .map(|(prefix, class)| Ok((prefix.clone(), lookup(class)?)))
+            .collect::<Result<Vec<_>, String>>()?;
+        endpoint_classes.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(&b.0)));
+
+        Ok((
+            Self {
+                names,
+                default_class,
+                priority_header: config.priority_header.clone(),
+                api_key_classes,
+                endpoint_classes,
+            },
+            classes,
+        ))
+    }
+
+    /// Class of a request from its API key, priority header or path
+    pub fn classify(&self, headers: &HeaderMap, path: &str) -> usize {
+        let api_key = headers
+            .get(axum::http::header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(str::trim);
+        if let Some(class) = api_key.and_then(|key| self.api_key_classes.get(key)) {
+            return *class;
+        }
+
+        let requested = self
+            .priority_header
+            .as_deref()
+            .and_then(|header| headers.get(header))
+            .and_then(|value| value.to_str().ok())
+            .and_then(|name| self.names.get(name.trim()));
+        if let Some(class) = requested {
+            return *class;
+        }
+
+        self.endpoint_classes
+            .iter()
+            .find(|(prefix, _)| path.starts_with(prefix.as_str()))
+            .map_or(self.default_class, |(_, class)| *class)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PriorityClassConfig;
+    use axum::http::HeaderValue;
+
+    fn class(name: &str, queue_size: usize) -> PriorityClass {
+        PriorityClass {
+            name: name.to_string(),
+            queue_size,
+            queue_timeout: Duration::from_secs(10),
+        }
+    }
+
+    fn drain(queue: &mut AdmissionQueue<&'static str>) -> Vec<&'static str> {
+        std::iter::from_fn(|| queue.pop().map(|entry| entry.item)).collect()
+    }
+
+    #[test]
+    fn test_higher_class_served_first() {
+        let mut queue = AdmissionQueue::new(
+            vec![class("interactive", 10), class("batch", 10)],
+            HashMap::new(),
+        );
+        let now = Instant::now();
+        queue.push(1, "", "batch-1", now).unwrap();
+        queue.push(1, "", "batch-2", now).unwrap();
+        queue.push(0, "", "chat-1", now).unwrap();
+
+        assert_eq!(drain(&mut queue), vec!["chat-1", "batch-1", "batch-2"]);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_tenants_interleaved_by_weight() {
+        let mut queue = AdmissionQueue::new(
+            vec![class("default", 100)],
+            HashMap::from([("big".to_string(), 2.0)]),
+        );
+        let now = Instant::now();
+        for item in ["a1", "a2", "a3", "a4"] {
+            queue.push(0, "a", item, now).unwrap();
+        }
+        for item in ["big1", "big2", "big3", "big4"] {
+            queue.push(0, "big", item, now).unwrap();
+        }
+        queue.push(0, "c", "c1", now).unwrap();
+
+        // A late tenant is not stuck behind the backlog, and "big" gets twice the share
+        let order = drain(&mut queue);
+        assert_eq!(
+            order,
+            vec!["big1", "a1", "big2", "c1", "big3", "a2", "big4", "a3", "a4"]
+        );
+    }
+
+    #[test]
+    fn test_class_queue_limit() {
+        let mut queue = AdmissionQueue::new(
+            vec![class("interactive", 1), class("batch", 2)],
+            HashMap::new(),
+        );
+        let now = Instant::now();
+        queue.push(0, "", "chat-1", now).unwrap();
+        assert_eq!(queue.push(0, "", "chat-2", now), Err("chat-2"));
+        queue.push(1, "", "batch-1", now).unwrap();
+        assert_eq!(queue.len(0), 1);
+        assert_eq!(queue.len(1), 1);
+    }
+
+    #[test]
+    fn test_expire_uses_class_timeout() {
+        let mut short = class("interactive", 10);
+        short.queue_timeout = Duration::from_millis(100);
+        let mut queue = AdmissionQueue::new(vec![short, class("batch", 10)], HashMap::new());
+        let start = Instant::now();
+        queue.push(0, "", "chat-1", start).unwrap();
+        queue.push(1, "", "batch-1", start).unwrap();
+        assert_eq!(
+            queue.next_deadline(),
+            Some(start + Duration::from_millis(100))
+        );
+
+        let expired = queue.expire(start + Duration::from_secs(1));
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].class, 0);
+        assert_eq!(expired[0].item, "chat-1");
+        assert_eq!(drain(&mut queue), vec!["batch-1"]);
+    }
+
+    #[test]
+    fn test_classifier_precedence() {
+        let config = AdmissionConfig {
+            classes: ["interactive", "standard", "batch"]
+                .into_iter()
+                .map(|name| PriorityClassConfig {
+                    name: name.to_string(),
+                    queue_size: None,
+                    queue_timeout_secs: None,
+                })
+                .collect(),
+            default_class: Some("standard".to_string()),
+            priority_header: Some("x-priority".to_string()),
+            api_key_classes: HashMap::from([("sk-batch".to_string(), "batch".to_string())]),
+            endpoint_classes: HashMap::from([
+                ("/v1".to_string(), "interactive".to_string()),
+                ("/v1/embeddings".to_string(), "batch".to_string()),
+            ]),
+            tenant_weights: HashMap::new(),
+        };
+        let (classifier, classes) =
+            PriorityClassifier::from_config(&config, 50, Duration::from_secs(5)).unwrap();
+        assert_eq!(classes.len(), 3);
+        assert_eq!(classes[2].queue_size, 50);
+
+        let mut headers = HeaderMap::new();
+        assert_eq!(classifier.classify(&headers, "/generate"), 1);
+        assert_eq!(classifier.classify(&headers, "/v1/chat/completions"), 0);
+        assert_eq!(classifier.classify(&headers, "/v1/embeddings"), 2);
+
+        headers.insert("x-priority", HeaderValue::from_static("batch"));
+        assert_eq!(classifier.classify(&headers, "/v1/chat/completions"), 2);
+        headers.insert("x-priority", HeaderValue::from_static("unknown"));
+        assert_eq!(classifier.classify(&headers, "/v1/chat/completions"), 0);
+
+        headers.insert("x-priority", HeaderValue::from_static("interactive"));
+        headers.insert("authorization", HeaderValue::from_static("Bearer sk-batch"));
+        assert_eq!(classifier.classify(&headers, "/v1/chat/completions"), 2);
+    }
+
+    #[test]
+    fn test_classifier_defaults() {
+        let (classifier, classes) = PriorityClassifier::from_config(
+            &AdmissionConfig::default(),
+            100,
+            Duration::from_secs(60),
+        )
+        .unwrap();
+        assert_eq!(classes.len(), 1);
+        assert_eq!(classes[0].name, DEFAULT_PRIORITY_CLASS);
+        assert_eq!(classifier.classify(&HeaderMap::new(), "/generate"), 0);
+
+        let config = AdmissionConfig {
+            default_class: Some("missing".to_string()),
+            ..Default::default()
+        };
+        assert!(PriorityClassifier::from_config(&config, 100, Duration::from_secs(60)).is_err());
+    }
+}
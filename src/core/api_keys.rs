@@ -0,0 +1,279 @@
+//! Static API keys loaded from a file
+//!
+//! Each key is stored as its SHA-256 digest together with an owner, the
+//! scopes it grants, the models it may use and an optional rate-limit class.
+//! The file is polled and reloaded when its contents change; an invalid file
+//! keeps the previous keys in place.
+//!
+//! ```yaml
+//! keys:
+//!   - key_hash: "sha256:2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae"
+//!     owner: alice
+//!     scopes: [inference, admin]
+//!     models: [llama-3-8b]
+//!     rate_limit_class: premium
+//! ```
+
+use crate::core::audit::AuditLog;
+use crate::core::watched_file::{self, parse_yaml_or_json, WatchedFiles};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::info;
+
+/// How often the keys file is checked for changes
+const KEYS_FILE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Endpoint group a key may access
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiKeyScope {
+    /// Generation, embeddings, rerank and model listing
+    Inference,
+    /// Worker management and cache control
+    Admin,
+}
+
+impl ApiKeyScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Inference => "inference",
+            Self::Admin => "admin",
+        }
+    }
+}
+
+fn default_scopes() -> Vec<ApiKeyScope> {
+    vec![ApiKeyScope::Inference]
+}
+
+/// One key of the keys file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiKeyEntry {
+    /// Hex SHA-256 digest of the key, optionally prefixed with `sha256:`
+    pub key_hash: String,
+    pub owner: String,
+    #[serde(default = "default_scopes")]
+    pub scopes: Vec<ApiKeyScope>,
+    /// Models the key may use (empty = all)
+    #[serde(default)]
+    pub models: Vec<String>,
+    /// Rate-limit class applied to the key, overriding the rate limit config
+    #[serde(default)]
+    pub rate_limit_class: Option<String>,
+}
+
+impl ApiKeyEntry {
+    pub fn has_scope(&self, scope: ApiKeyScope) -> bool {
+        self.scopes.contains(&scope)
+    }
+
+    /// Whether the key may use `model`; restricted keys must name a model
+    pub fn allows_model(&self, model: Option<&str>) -> bool {
+        self.models.is_empty()
+            || model.is_some_and(|model| self.models.iter().any(|allowed| allowed == model))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiKeysFile {
+    #[serde(default)]
+    keys: Vec<ApiKeyEntry>,
+}
+
+type KeyMap = HashMap<String, Arc<ApiKeyEntry>>;
+
+/// Keys file kept in sync with disk
+#[derive(Debug)]
+pub struct ApiKeyStore {
+    path: PathBuf,
+    keys: parking_lot::RwLock<Arc<KeyMap>>,
+    file: WatchedFiles,
+}
+
+impl ApiKeyStore {
+    /// Load the keys file; fails if it cannot be read or parsed
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self, String> {
+        let path = path.into();
+        let file = WatchedFiles::new([&path]);
+        let keys = Self::parse(&path, &file.read()?[0])?;
+        info!("Loaded {} API keys from {}", keys.len(), path.display());
+        Ok(Self {
+            path,
+            keys: parking_lot::RwLock::new(Arc::new(keys)),
+            file,
+        })
+    }
+
+    /// Hex SHA-256 digest stored for a key
+    pub fn hash_key(key: &str) -> String {
+        format!("{:x}", Sha256::digest(key.as_bytes()))
+    }
+
+    /// Entry of a presented key, if the file lists it
+    pub fn lookup(&self, key: &str) -> Option<Arc<ApiKeyEntry>> {
+        self.keys.read().get(&Self::hash_key(key)).cloned()
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Every entry currently loaded
+    pub fn entries(&self) -> Vec<Arc<ApiKeyEntry>> {
+        self.keys.read().values().cloned().collect()
+    }
+
+    /// Reload the file if its contents changed, returning whether keys were replaced
+    pub fn reload_if_changed(&self) -> Result<bool, String> {
+        let Some(contents) = self.file.read_if_changed()? else {
+            return Ok(false);
+        };
+        let keys = Self::parse(&self.path, &contents[0])?;
+        info!(
+            "Reloaded {} API keys from {}",
+            keys.len(),
+            self.path.display()
+        );
+        *self.keys.write() = Arc::new(keys);
+        Ok(true)
+    }
+
+    /// Watch the keys file for changes, auditing each reload
+    pub fn start(self: Arc<Self>, audit: Option<Arc<AuditLog>>) -> JoinHandle<()> {
+        watched_file::watch(
+            KEYS_FILE_POLL_INTERVAL,
+            self.path.display().to_string(),
+            "previous API keys",
+            audit,
+            move || self.reload_if_changed(),
+        )
+    }
+
+    fn parse(path: &Path, contents: &[u8]) -> Result<KeyMap, String> {
+        let file: ApiKeysFile = parse_yaml_or_json(path, contents)
+            .map_err(|e| format!("Invalid API keys file {}: {}", path.display(), e))?;
+
+        let mut keys = KeyMap::new();
+        for entry in file.keys {
+            let hash = entry
+                .key_hash
+                .strip_prefix("sha256:")
+                .unwrap_or(&entry.key_hash)
+                .to_ascii_lowercase();
+            if hash.len() != 64 || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return Err(format!(
+                    "Invalid API keys file {}: key of '{}' is not a SHA-256 hex digest",
+                    path.display(),
+                    entry.owner
+                ));
+            }
+            if keys.insert(hash, Arc::new(entry.clone())).is_some() {
+                return Err(format!(
+                    "Invalid API keys file {}: duplicate key for '{}'",
+                    path.display(),
+                    entry.owner
+                ));
+            }
+        }
+        Ok(keys)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_keys(path: &Path, entries: &str) {
+        std::fs::write(path, format!("keys:\n{}", entries)).unwrap();
+    }
+
+    fn entry(key: &str, owner: &str, extra: &str) -> String {
+        format!(
+            "  - key_hash: \"sha256:{}\"\n    owner: {}\n{}",
+            ApiKeyStore::hash_key(key),
+            owner,
+            extra
+        )
+    }
+
+    #[test]
+    fn test_lookup_by_hash_with_scopes_and_models() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keys.yaml");
+        write_keys(
+            &path,
+            &[
+                entry("sk-admin", "ops", "    scopes: [inference, admin]\n"),
+                entry(
+                    "sk-app",
+                    "app",
+                    "    models: [llama-3-8b]\n    rate_limit_class: premium\n",
+                ),
+            ]
+            .concat(),
+        );
+        let store = ApiKeyStore::load(&path).unwrap();
+        assert_eq!(store.len(), 2);
+        assert!(store.lookup("sk-unknown").is_none());
+
+        let admin = store.lookup("sk-admin").unwrap();
+        assert!(admin.has_scope(ApiKeyScope::Admin));
+        assert!(admin.allows_model(None));
+
+        let app = store.lookup("sk-app").unwrap();
+        assert_eq!(app.owner, "app");
+        assert!(app.has_scope(ApiKeyScope::Inference));
+        assert!(!app.has_scope(ApiKeyScope::Admin));
+        assert!(app.allows_model(Some("llama-3-8b")));
+        assert!(!app.allows_model(Some("llama-3-70b")));
+        assert!(!app.allows_model(None));
+        assert_eq!(app.rate_limit_class.as_deref(), Some("premium"));
+    }
+
+    #[test]
+    fn test_rejects_invalid_hashes_and_duplicates() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keys.yaml");
+        write_keys(&path, "  - key_hash: sk-plaintext\n    owner: app\n");
+        assert!(ApiKeyStore::load(&path).is_err());
+
+        write_keys(
+            &path,
+            &[entry("sk-app", "app", ""), entry("sk-app", "copy", "")].concat(),
+        );
+        assert!(ApiKeyStore::load(&path).unwrap_err().contains("duplicate"));
+    }
+
+    #[tokio::test]
+    async fn test_reload_on_change_keeps_keys_on_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keys.json");
+        let json = |key: &str| {
+            format!(
+                r#"{{"keys": [{{"key_hash": "{}", "owner": "app"}}]}}"#,
+                ApiKeyStore::hash_key(key)
+            )
+        };
+        std::fs::write(&path, json("sk-old")).unwrap();
+        let store = ApiKeyStore::load(&path).unwrap();
+        assert!(!store.reload_if_changed().unwrap());
+
+        std::fs::write(&path, json("sk-new")).unwrap();
+        assert!(store.reload_if_changed().unwrap());
+        assert!(store.lookup("sk-old").is_none());
+        assert!(store.lookup("sk-new").is_some());
+
+        std::fs::write(&path, "{not json").unwrap();
+        assert!(store.reload_if_changed().is_err());
+        assert!(store.lookup("sk-new").is_some());
+    }
+}
@@ -0,0 +1,595 @@
+//! Audit trail of requests and admin actions
+//!
+//! [`AuditLog`] queues records and a background task writes them as JSON
+//! lines to a size-rotated file, a Unix socket and/or an HTTP endpoint. When
+//! the writer falls behind, records are dropped and counted rather than
+//! holding up requests. Prompts and outputs are left out, hashed or kept
+//! according to the configured [`AuditRedaction`].
+
+use crate::config::{AuditConfig, AuditRedaction};
+use crate::core::ApiKeyStore;
+use async_trait::async_trait;
+use chrono::{SecondsFormat, Utc};
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+/// Records waiting for the writer before new ones are dropped
+const AUDIT_QUEUE_SIZE: usize = 8192;
+
+/// Records written per sink call
+const MAX_BATCH: usize = 256;
+
+/// Timeout of one POST to the HTTP sink
+const HTTP_SINK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Response extension naming the worker that produced a response
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServedBy(pub String);
+
+/// Response extension naming the prefill worker of a disaggregated response;
+/// [`ServedBy`] names its decode worker
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrefilledBy(pub String);
+
+/// One proxied inference request
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct RequestRecord {
+    pub request_id: Option<String>,
+    pub method: String,
+    pub path: String,
+    /// Owner from the API keys file, or a fingerprint of the presented key
+    pub api_key: Option<String>,
+    pub tenant: Option<String>,
+    pub model: Option<String>,
+    pub worker: Option<String>,
+    /// Prefill worker of a disaggregated request
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prefill_worker: Option<String>,
+    pub status: u16,
+    pub latency_ms: u64,
+    pub stream: bool,
+    pub prompt_tokens: Option<u64>,
+    pub completion_tokens: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+}
+
+/// Kind of state-changing admin operation
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AdminAction {
+    WorkerAdd,
+    WorkerRemove,
+    CacheFlush,
+    CapacityApply,
+    ConfigReload,
+}
+
+/// One admin action, either requested over HTTP or done by the router itself
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct AdminRecord {
+    pub action: AdminAction,
+    pub request_id: Option<String>,
+    /// Who asked for it; None for actions the router took on its own
+    pub actor: Option<String>,
+    /// Worker URL or reloaded file
+    pub target: Option<String>,
+    /// HTTP status of a requested action
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl AdminRecord {
+    /// Record of a configuration file the router reloaded
+    pub fn config_reload(target: &str, result: Result<(), String>) -> Self {
+        Self {
+            action: AdminAction::ConfigReload,
+            request_id: None,
+            actor: None,
+            target: Some(target.to_string()),
+            status: None,
+            success: result.is_ok(),
+            detail: result.err(),
+        }
+    }
+}
+
+/// Audit record
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AuditEvent {
+    Request(RequestRecord),
+    Admin(AdminRecord),
+}
+
+/// Record as written, stamped when it was queued
+#[derive(Debug, Serialize)]
+struct AuditEntry {
+    timestamp: String,
+    #[serde(flatten)]
+    event: AuditEvent,
+}
+
+/// Queue of audit records drained by a background writer
+#[derive(Debug)]
+pub struct AuditLog {
+    sender: mpsc::Sender<AuditEntry>,
+    prompts: AuditRedaction,
+    outputs: AuditRedaction,
+    dropped: AtomicU64,
+}
+
+impl AuditLog {
+    /// Open the configured destinations and start the writer task
+    pub fn start(config: &AuditConfig, client: reqwest::Client) -> Result<Arc<Self>, String> {
+        let mut sinks: Vec<Box<dyn AuditSink>> = Vec::new();
+        if let Some(path) = &config.file {
+            sinks.push(Box::new(FileSink::open(
+                PathBuf::from(path),
+                config.max_file_size_mb.saturating_mul(1024 * 1024),
+                config.max_files,
+            )?));
+        }
+        if let Some(path) = &config.unix_socket {
+            sinks.push(Box::new(UnixSocketSink::new(PathBuf::from(path))?));
+        }
+        if let Some(url) = &config.http_sink {
+            sinks.push(Box::new(HttpSink {
+                client,
+                url: url.clone(),
+            }));
+        }
+
+        let (sender, receiver) = mpsc::channel(AUDIT_QUEUE_SIZE);
+        tokio::spawn(run_writer(receiver, sinks));
+        info!(
+            "Audit log enabled (prompts: {:?}, outputs: {:?})",
+            config.prompts, config.outputs
+        );
+        Ok(Arc::new(Self {
+            sender,
+            prompts: config.prompts,
+            outputs: config.outputs,
+            dropped: AtomicU64::new(0),
+        }))
+    }
+
+    /// Queue a record without waiting for the writer
+    pub fn record(&self, event: AuditEvent) {
+        let entry = AuditEntry {
+            timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            event,
+        };
+        if self.sender.try_send(entry).is_err() {
+            let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+            if dropped == 1 || dropped.is_multiple_of(1000) {
+                warn!("Audit log writer is behind; {} records dropped", dropped);
+            }
+        }
+    }
+
+    /// Records dropped because the queue was full
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Whether request records carry the prompt in some form
+    pub fn captures_prompts(&self) -> bool {
+        self.prompts != AuditRedaction::Redact
+    }
+
+    /// Whether request records carry the output in some form
+    pub fn captures_outputs(&self) -> bool {
+        self.outputs != AuditRedaction::Redact
+    }
+
+    /// Prompt as it should appear in a record
+    pub fn prompt(&self, text: &str) -> Option<String> {
+        redact(self.prompts, text)
+    }
+
+    /// Output as it should appear in a record
+    pub fn output(&self, text: &str) -> Option<String> {
+        redact(self.outputs, text)
+    }
+}
+
+/// Apply a redaction mode to prompt or output text
+pub fn redact(mode: AuditRedaction, text: &str) -> Option<String> {
+    match mode {
+        AuditRedaction::Redact => None,
+        AuditRedaction::Hash => Some(format!("sha256:{}", ApiKeyStore::hash_key(text))),
+        AuditRedaction::Full => Some(text.to_string()),
+    }
+}
+
+/// Audit a polled reload: each replacement, and each distinct error once
+pub(crate) fn record_reload(
+    audit: Option<&AuditLog>,
+    target: &str,
+    result: Result<bool, String>,
+    last_error: &mut Option<String>,
+) {
+    let result = match result {
+        Ok(false) => return,
+        Ok(true) => {
+            *last_error = None;
+            Ok(())
+        }
+        Err(e) if last_error.as_ref() == Some(&e) => return,
+        Err(e) => {
+            *last_error = Some(e.clone());
+            Err(e)
+        }
+    };
+    if let Some(audit) = audit {
+        audit.record(AuditEvent::Admin(AdminRecord::config_reload(
+            target, result,
+        )));
+    }
+}
+
+/// Short, non-reversible identifier of an API key
+pub fn key_fingerprint(key: &str) -> String {
+    format!("sha256:{}", &ApiKeyStore::hash_key(key)[..12])
+}
+
+async fn run_writer(mut receiver: mpsc::Receiver<AuditEntry>, mut sinks: Vec<Box<dyn AuditSink>>) {
+    let mut healthy = vec![true; sinks.len()];
+    let mut batch = Vec::new();
+    while let Some(entry) = receiver.recv().await {
+        batch.clear();
+        append_line(&mut batch, &entry);
+        for _ in 1..MAX_BATCH {
+            match receiver.try_recv() {
+                Ok(entry) => append_line(&mut batch, &entry),
+                Err(_) => break,
+            }
+        }
+        for (sink, healthy) in sinks.iter_mut().zip(healthy.iter_mut()) {
+            // Report each outage and recovery once instead of every batch
+            match sink.write(&batch).await {
+                Ok(()) if !*healthy => {
+                    info!("Audit sink {} recovered", sink.name());
+                    *healthy = true;
+                }
+                Ok(()) => {}
+                Err(e) if *healthy => {
+                    warn!("Audit sink {} failed, records lost: {}", sink.name(), e);
+                    *healthy = false;
+                }
+                Err(_) => {}
+            }
+        }
+    }
+}
+
+fn append_line(batch: &mut Vec<u8>, entry: &AuditEntry) {
+    match serde_json::to_writer(&mut *batch, entry) {
+        Ok(()) => batch.push(b'\n'),
+        Err(e) => warn!("Failed to serialize audit record: {}", e),
+    }
+}
+
+/// Destination of audit records
+#[async_trait]
+trait AuditSink: Send {
+    fn name(&self) -> String;
+
+    /// Write newline-terminated JSON records
+    async fn write(&mut self, batch: &[u8]) -> Result<(), String>;
+}
+
+/// JSONL file rotated by size
+struct FileSink {
+    path: PathBuf,
+    file: tokio::fs::File,
+    size: u64,
+    max_size: u64,
+    max_files: usize,
+}
+
+impl FileSink {
+    fn open(path: PathBuf, max_size: u64, max_files: usize) -> Result<Self, String> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create audit log directory: {}", e))?;
+        }
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| format!("Failed to open audit log {}: {}", path.display(), e))?;
+        let size = file.metadata().map(|meta| meta.len()).unwrap_or(0);
+        Ok(Self {
+            path,
+            file: tokio::fs::File::from_std(file),
+            size,
+            max_size,
+            max_files,
+        })
+    }
+
+    fn rotated(&self, index: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{}", index));
+        path.into()
+    }
+
+    /// Shift `file.N` to `file.N+1`, dropping the oldest, and start a new file
+    async fn rotate(&mut self) -> std::io::Result<()> {
+        self.file.flush().await?;
+        if self.max_files == 0 {
+            tokio::fs::remove_file(&self.path).await?;
+        } else {
+            for index in (1..self.max_files).rev() {
+                let from = self.rotated(index);
+                if tokio::fs::try_exists(&from).await.unwrap_or(false) {
+                    tokio::fs::rename(&from, self.rotated(index + 1)).await?;
+                }
+            }
+            tokio::fs::rename(&self.path, self.rotated(1)).await?;
+        }
+        self.file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AuditSink for FileSink {
+    fn name(&self) -> String {
+        format!("file {}", self.path.display())
+    }
+
+    async fn write(&mut self, batch: &[u8]) -> Result<(), String> {
+        if self.size > 0 && self.size + batch.len() as u64 > self.max_size {
+            self.rotate()
+                .await
+                .map_err(|e| format!("rotation failed: {}", e))?;
+        }
+        self.file
+            .write_all(batch)
+            .await
+            .map_err(|e| e.to_string())?;
+        self.file.flush().await.map_err(|e| e.to_string())?;
+        self.size += batch.len() as u64;
+        Ok(())
+    }
+}
+
+/// Local Unix socket, reconnected after failures
+struct UnixSocketSink {
+    path: PathBuf,
+    #[cfg(unix)]
+    stream: Option<tokio::net::UnixStream>,
+}
+
+impl UnixSocketSink {
+    #[cfg(unix)]
+    fn new(path: PathBuf) -> Result<Self, String> {
+        Ok(Self { path, stream: None })
+    }
+
+    #[cfg(not(unix))]
+    fn new(_path: PathBuf) -> Result<Self, String> {
+        Err("Audit Unix socket sinks are only supported on Unix".to_string())
+    }
+}
+
+#[async_trait]
+impl AuditSink for UnixSocketSink {
+    fn name(&self) -> String {
+        format!("socket {}", self.path.display())
+    }
+
+    #[cfg(unix)]
+    async fn write(&mut self, batch: &[u8]) -> Result<(), String> {
+        if self.stream.is_none() {
+            let stream = tokio::net::UnixStream::connect(&self.path)
+                .await
+                .map_err(|e| e.to_string())?;
+            self.stream = Some(stream);
+        }
+        let stream = self.stream.as_mut().expect("connected above");
+        if let Err(e) = stream.write_all(batch).await {
+            self.stream = None;
+            return Err(e.to_string());
+        }
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    async fn write(&mut self, _batch: &[u8]) -> Result<(), String> {
+        Err("unsupported".to_string())
+    }
+}
+
+/// HTTP endpoint receiving batches as newline-delimited JSON
+struct HttpSink {
+    client: reqwest::Client,
+    url: String,
+}
+
+#[async_trait]
+impl AuditSink for HttpSink {
+    fn name(&self) -> String {
+        format!("endpoint {}", self.url)
+    }
+
+    async fn write(&mut self, batch: &[u8]) -> Result<(), String> {
+        let response = self
+            .client
+            .post(&self.url)
+            .timeout(HTTP_SINK_TIMEOUT)
+            .header(reqwest::header::CONTENT_TYPE, "application/x-ndjson")
+            .body(batch.to_vec())
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("status {}", response.status()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    async fn read_lines(path: &std::path::Path, expected: usize) -> Vec<Value> {
+        for _ in 0..100 {
+            let contents = std::fs::read_to_string(path).unwrap_or_default();
+            let lines: Vec<Value> = contents
+                .lines()
+                .map(|line| serde_json::from_str(line).unwrap())
+                .collect();
+            if lines.len() >= expected {
+                return lines;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("audit log {} never got {} lines", path.display(), expected);
+    }
+
+    fn request_record(status: u16) -> AuditEvent {
+        AuditEvent::Request(RequestRecord {
+            method: "POST".to_string(),
+            path: "/v1/chat/completions".to_string(),
+            api_key: Some("team-a".to_string()),
+            model: Some("llama".to_string()),
+            status,
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_writes_jsonl_records() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit").join("audit.jsonl");
+        let config = AuditConfig {
+            file: Some(path.to_str().unwrap().to_string()),
+            ..Default::default()
+        };
+        let audit = AuditLog::start(&config, reqwest::Client::new()).unwrap();
+
+        audit.record(request_record(200));
+        audit.record(AuditEvent::Admin(AdminRecord::config_reload(
+            "keys.yaml",
+            Err("Invalid API keys file".to_string()),
+        )));
+
+        let lines = read_lines(&path, 2).await;
+        assert_eq!(lines[0]["type"], "request");
+        assert_eq!(lines[0]["api_key"], "team-a");
+        assert_eq!(lines[0]["status"], 200);
+        assert!(lines[0]["timestamp"].as_str().unwrap().ends_with('Z'));
+        assert!(lines[0].get("prompt").is_none());
+        assert_eq!(lines[1]["type"], "admin");
+        assert_eq!(lines[1]["action"], "config_reload");
+        assert_eq!(lines[1]["success"], false);
+        assert_eq!(audit.dropped(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_rotates_by_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let file = FileSink::open(path.clone(), 100, 2).unwrap();
+        let mut sinks: Vec<Box<dyn AuditSink>> = vec![Box::new(file)];
+        let mut line = vec![b'x'; 59];
+        line.push(b'\n');
+        for _ in 0..4 {
+            sinks[0].write(&line).await.unwrap();
+        }
+
+        // 60-byte writes: every write after the first starts a new file
+        let rotated = |index: usize| dir.path().join(format!("audit.jsonl.{}", index));
+        assert_eq!(std::fs::read(&path).unwrap().len(), 60);
+        assert!(rotated(1).exists());
+        assert!(rotated(2).exists());
+        assert!(!rotated(3).exists());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_unix_socket_sink() {
+        use tokio::io::AsyncBufReadExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("audit.sock");
+        let listener = tokio::net::UnixListener::bind(&socket).unwrap();
+        let config = AuditConfig {
+            unix_socket: Some(socket.to_str().unwrap().to_string()),
+            ..Default::default()
+        };
+        let audit = AuditLog::start(&config, reqwest::Client::new()).unwrap();
+        audit.record(request_record(429));
+
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut line = String::new();
+        tokio::io::BufReader::new(stream)
+            .read_line(&mut line)
+            .await
+            .unwrap();
+        let record: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(record["status"], 429);
+    }
+
+    #[tokio::test]
+    async fn test_record_reload_reports_each_error_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let config = AuditConfig {
+            file: Some(path.to_str().unwrap().to_string()),
+            ..Default::default()
+        };
+        let audit = AuditLog::start(&config, reqwest::Client::new()).unwrap();
+        let mut last_error = None;
+        let error = || Err("Failed to read keys.yaml".to_string());
+
+        record_reload(Some(&audit), "keys.yaml", Ok(false), &mut last_error);
+        record_reload(Some(&audit), "keys.yaml", error(), &mut last_error);
+        record_reload(Some(&audit), "keys.yaml", error(), &mut last_error);
+        record_reload(Some(&audit), "keys.yaml", Ok(true), &mut last_error);
+
+        let lines = read_lines(&path, 2).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(read_lines(&path, 2).await.len(), 2);
+        assert_eq!(lines[0]["success"], false);
+        assert_eq!(lines[1]["success"], true);
+        assert_eq!(lines[1]["target"], "keys.yaml");
+    }
+
+    #[test]
+    fn test_redaction_modes() {
+        assert_eq!(redact(AuditRedaction::Redact, "secret"), None);
+        assert_eq!(
+            redact(AuditRedaction::Full, "secret"),
+            Some("secret".to_string())
+        );
+        let hashed = redact(AuditRedaction::Hash, "secret").unwrap();
+        assert!(hashed.starts_with("sha256:"));
+        assert!(!hashed.contains("secret"));
+
+        let fingerprint = key_fingerprint("sk-live-123");
+        assert_eq!(fingerprint.len(), "sha256:".len() + 12);
+    }
+}
@@ -0,0 +1,606 @@
+//! Bearer token validation
+//!
+//! JWTs are verified locally against a JWKS loaded from a file or URL and
+//! refreshed periodically; signature, expiry, issuer and audience are checked
+//! and a configured claim names the tenant used for rate limits. Opaque API
+//! keys are validated remotely, with results kept in a TTL-bounded LRU cache.
+//! The outcome is a [`CallerIdentity`] that rate limits and the audit trail
+//! key on instead of the raw token.
+
+use crate::config::{ApiKeyCacheConfig, JwtConfig};
+use crate::core::api_keys::{ApiKeyEntry, ApiKeyStore};
+use crate::core::keyed_rate_limiter::TENANT_HEADER;
+use axum::http::HeaderMap;
+use jsonwebtoken::jwk::Jwk;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use lru::LruCache;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::num::NonZeroUsize;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+/// Retry interval after a failed JWKS load
+const JWKS_RETRY_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Claims of a verified JWT
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerifiedToken {
+    pub subject: Option<String>,
+    pub tenant: Option<String>,
+    /// Whether the configured admin claim grants admin access
+    pub admin: bool,
+    pub claims: Value,
+}
+
+/// Caller of a request as established by authentication
+///
+/// The auth layer stores it in the request extensions (and copies it onto the
+/// response for the audit layer), so the token is verified once per request.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CallerIdentity {
+    /// Keys-file owner, JWT subject, or digest of any other bearer token
+    pub id: Option<String>,
+    /// JWT tenant claim; the tenant header only when auth is disabled
+    pub tenant: Option<String>,
+    /// Keys-file entry of the bearer token
+    pub api_key: Option<Arc<ApiKeyEntry>>,
+}
+
+impl CallerIdentity {
+    /// Identity when no authentication is configured: nothing is verified,
+    /// so the tenant header is taken at its word
+    pub fn unverified(headers: &HeaderMap) -> Self {
+        Self {
+            id: bearer_token(headers).map(Self::token_digest),
+            tenant: headers
+                .get(TENANT_HEADER)
+                .and_then(|value| value.to_str().ok())
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string),
+            api_key: None,
+        }
+    }
+
+    /// Identity of a keys-file key
+    pub fn from_api_key(api_key: Arc<ApiKeyEntry>) -> Self {
+        Self {
+            id: Some(api_key.owner.clone()),
+            tenant: None,
+            api_key: Some(api_key),
+        }
+    }
+
+    /// Identity of a verified JWT, named by its subject when it has one
+    pub fn from_jwt(token: &str, verified: VerifiedToken) -> Self {
+        Self {
+            id: Some(
+                verified
+                    .subject
+                    .unwrap_or_else(|| Self::token_digest(token)),
+            ),
+            tenant: verified.tenant,
+            api_key: None,
+        }
+    }
+
+    /// Identity of a key vouched for by a validation URL
+    pub fn from_token(token: &str) -> Self {
+        Self {
+            id: Some(Self::token_digest(token)),
+            tenant: None,
+            api_key: None,
+        }
+    }
+
+    /// `sha256:<hex>` digest standing in for a token, as in the keys file
+    pub fn token_digest(token: &str) -> String {
+        format!("sha256:{}", ApiKeyStore::hash_key(token))
+    }
+}
+
+/// Non-empty bearer token of the `Authorization` header
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+struct JwtKey {
+    kid: Option<String>,
+    /// Algorithm pinned by the JWK's `alg`, if any
+    algorithm: Option<Algorithm>,
+    key: DecodingKey,
+}
+
+/// Verifies JWTs against a JWKS
+pub struct JwtValidator {
+    config: JwtConfig,
+    client: reqwest::Client,
+    keys: parking_lot::RwLock<Arc<Vec<JwtKey>>>,
+}
+
+impl JwtValidator {
+    /// Create the validator; a JWKS file is loaded immediately, a JWKS URL on `start`
+    pub fn new(config: JwtConfig, client: reqwest::Client) -> Result<Self, String> {
+        let validator = Self {
+            config,
+            client,
+            keys: parking_lot::RwLock::new(Arc::new(Vec::new())),
+        };
+        if let Some(path) = &validator.config.jwks_file {
+            let raw = std::fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read JWKS file {}: {}", path, e))?;
+            validator.set_keys(&raw)?;
+        }
+        Ok(validator)
+    }
+
+    /// Whether a bearer token is a JWT rather than an opaque API key
+    pub fn is_jwt(token: &str) -> bool {
+        token.split('.').count() == 3 && jsonwebtoken::decode_header(token).is_ok()
+    }
+
+    pub fn key_count(&self) -> usize {
+        self.keys.read().len()
+    }
+
+    /// Reload the JWKS from its file or URL, returning the number of usable keys
+    pub async fn refresh(&self) -> Result<usize, String> {
+        let raw = if let Some(path) = &self.config.jwks_file {
+            tokio::fs::read_to_string(path)
+                .await
+                .map_err(|e| format!("Failed to read JWKS file {}: {}", path, e))?
+        } else if let Some(url) = &self.config.jwks_url {
+            let response = self
+                .client
+                .get(url)
+                .send()
+                .await
+                .map_err(|e| format!("Failed to fetch JWKS from {}: {}", url, e))?;
+            if !response.status().is_success() {
+                return Err(format!(
+                    "Failed to fetch JWKS from {}: status {}",
+                    url,
+                    response.status()
+                ));
+            }
+            response
+                .text()
+                .await
+                .map_err(|e| format!("Failed to read JWKS from {}: {}", url, e))?
+        } else {
+            return Err("No JWKS source configured".to_string());
+        };
+        self.set_keys(&raw)
+    }
+
+    /// Keep the JWKS fresh in the background
+    pub fn start(self: Arc<Self>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let refresh_interval = Duration::from_secs(self.config.jwks_refresh_secs);
+            // A file source was loaded at construction
+            let mut next = if self.config.jwks_file.is_some() {
+                refresh_interval
+            } else {
+                Duration::ZERO
+            };
+            loop {
+                tokio::time::sleep(next).await;
+                next = match self.refresh().await {
+                    Ok(count) => {
+                        debug!("Loaded {} JWKS keys", count);
+                        refresh_interval
+                    }
+                    Err(e) => {
+                        warn!("{}", e);
+                        refresh_interval.min(JWKS_RETRY_INTERVAL)
+                    }
+                };
+            }
+        })
+    }
+
+    /// Verify a JWT's signature and claims
+    pub fn validate(&self, token: &str) -> Result<VerifiedToken, String> {
+        let header = jsonwebtoken::decode_header(token).map_err(|e| e.to_string())?;
+        let keys = self.keys.read().clone();
+
+        let candidates = keys
+            .iter()
+            .filter(|key| header.kid.is_none() || key.kid.is_none() || key.kid == header.kid);
+        let mut last_error = format!("no JWKS key matches kid {:?}", header.kid);
+        for key in candidates {
+            if key.algorithm.is_some_and(|alg| alg != header.alg) {
+                continue;
+            }
+            match jsonwebtoken::decode::<Value>(token, &key.key, &self.validation(header.alg)) {
+                Ok(data) => {
+                    return Ok(VerifiedToken {
+                        subject: data
+                            .claims
+                            .get("sub")
+                            .and_then(Value::as_str)
+                            .map(str::to_string),
+                        tenant: self.tenant(&data.claims),
+                        admin: self.is_admin(&data.claims),
+                        claims: data.claims,
+                    });
+                }
+                Err(e) => last_error = e.to_string(),
+            }
+        }
+        Err(last_error)
+    }
+
+    fn validation(&self, algorithm: Algorithm) -> Validation {
+        let mut validation = Validation::new(algorithm);
+        validation.leeway = self.config.leeway_secs;
+        validation.validate_nbf = true;
+        if let Some(issuer) = &self.config.issuer {
+            validation.set_issuer(&[issuer]);
+        }
+        if self.config.audience.is_empty() {
+            validation.validate_aud = false;
+        } else {
+            validation.set_audience(&self.config.audience);
+        }
+        validation
+    }
+
+    fn tenant(&self, claims: &Value) -> Option<String> {
+        let path = self.config.tenant_claim.as_deref()?;
+        let value = path
+            .split('.')
+            .try_fold(claims, |value, part| value.get(part))?;
+        match value {
+            Value::String(tenant) if !tenant.is_empty() => Some(tenant.clone()),
+            Value::Number(tenant) => Some(tenant.to_string()),
+            _ => None,
+        }
+    }
+
+    // Scopes may be a space-separated string (OAuth `scope`) or a list of roles
+    fn is_admin(&self, claims: &Value) -> bool {
+        let Some(path) = self.config.admin_claim.as_deref() else {
+            return false;
+        };
+        let admin = self.config.admin_value.as_str();
+        match path
+            .split('.')
+            .try_fold(claims, |value, part| value.get(part))
+        {
+            Some(Value::String(scopes)) => scopes.split_whitespace().any(|scope| scope == admin),
+            Some(Value::Array(roles)) => roles.iter().any(|role| role.as_str() == Some(admin)),
+            _ => false,
+        }
+    }
+
+    /// Replace the key set; keys that cannot be used are skipped
+    fn set_keys(&self, raw: &str) -> Result<usize, String> {
+        let jwks: Value =
+            serde_json::from_str(raw).map_err(|e| format!("Invalid JWKS document: {}", e))?;
+        let entries = jwks
+            .get("keys")
+            .and_then(Value::as_array)
+            .ok_or_else(|| "Invalid JWKS document: missing 'keys' array".to_string())?;
+
+        let keys: Vec<JwtKey> = entries
+            .iter()
+            .filter_map(|entry| match parse_jwk(entry) {
+                Ok(key) => key,
+                Err(e) => {
+                    warn!("Skipping JWKS key: {}", e);
+                    None
+                }
+            })
+            .collect();
+        let count = keys.len();
+        if count == 0 && !entries.is_empty() {
+            return Err("JWKS contains no usable signing keys".to_string());
+        }
+        *self.keys.write() = Arc::new(keys);
+        info!("JWKS updated with {} keys", count);
+        Ok(count)
+    }
+}
+
+/// Parse a JWK, ignoring encryption keys
+fn parse_jwk(entry: &Value) -> Result<Option<JwtKey>, String> {
+    if entry.get("use").and_then(Value::as_str) == Some("enc") {
+        return Ok(None);
+    }
+    let jwk: Jwk = serde_json::from_value(entry.clone()).map_err(|e| e.to_string())?;
+    let algorithm = jwk
+        .common
+        .key_algorithm
+        .map(|alg| Algorithm::from_str(&alg.to_string()).map_err(|e| e.to_string()))
+        .transpose()?;
+    Ok(Some(JwtKey {
+        kid: jwk.common.key_id.clone(),
+        algorithm,
+        key: DecodingKey::from_jwk(&jwk).map_err(|e| e.to_string())?,
+    }))
+}
+
+/// TTL-bounded LRU cache of remote API key validation results
+///
+/// Keys are stored as SHA-256 digests.
+pub struct ValidationCache {
+    entries: parking_lot::Mutex<LruCache<[u8; 32], (bool, Instant)>>,
+    ttl: Duration,
+}
+
+impl ValidationCache {
+    pub fn new(config: &ApiKeyCacheConfig) -> Self {
+        let capacity = NonZeroUsize::new(config.max_entries).unwrap_or(NonZeroUsize::MIN);
+        Self {
+            entries: parking_lot::Mutex::new(LruCache::new(capacity)),
+            ttl: Duration::from_secs(config.ttl_secs),
+        }
+    }
+
+    /// Cached result for a key, if still fresh
+    pub fn get(&self, api_key: &str) -> Option<bool> {
+        let digest = Self::digest(api_key);
+        let mut entries = self.entries.lock();
+        match entries.get(&digest) {
+            Some(&(valid, cached_at)) if cached_at.elapsed() < self.ttl => Some(valid),
+            Some(_) => {
+                entries.pop(&digest);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub fn insert(&self, api_key: &str, valid: bool) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        self.entries
+            .lock()
+            .put(Self::digest(api_key), (valid, Instant::now()));
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn digest(api_key: &str) -> [u8; 32] {
+        Sha256::digest(api_key.as_bytes()).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{EncodingKey, Header};
+    use serde_json::json;
+
+    const SECRET: &[u8] = b"test-signing-secret";
+
+    fn jwks() -> String {
+        json!({
+            "keys": [{
+                "kty": "oct",
+                "kid": "test-key",
+                "alg": "HS256",
+                "k": "dGVzdC1zaWduaW5nLXNlY3JldA"
+            }]
+        })
+        .to_string()
+    }
+
+    fn validator(config: JwtConfig) -> JwtValidator {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("jwks.json");
+        std::fs::write(&path, jwks()).unwrap();
+        JwtValidator::new(
+            JwtConfig {
+                jwks_file: Some(path.to_str().unwrap().to_string()),
+                ..config
+            },
+            reqwest::Client::new(),
+        )
+        .unwrap()
+    }
+
+    fn token(kid: &str, claims: Value) -> String {
+        let header = Header {
+            kid: Some(kid.to_string()),
+            ..Header::new(Algorithm::HS256)
+        };
+        jsonwebtoken::encode(&header, &claims, &EncodingKey::from_secret(SECRET)).unwrap()
+    }
+
+    fn now() -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    #[test]
+    fn test_validates_signature_expiry_issuer_and_audience() {
+        let validator = validator(JwtConfig {
+            issuer: Some("https://issuer.example".to_string()),
+            audience: vec!["router".to_string()],
+            tenant_claim: Some("org.id".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(validator.key_count(), 1);
+
+        let claims = json!({
+            "sub": "user-1",
+            "iss": "https://issuer.example",
+            "aud": "router",
+            "exp": now() + 600,
+            "org": {"id": "acme"}
+        });
+        let verified = validator
+            .validate(&token("test-key", claims.clone()))
+            .unwrap();
+        assert_eq!(verified.subject.as_deref(), Some("user-1"));
+        assert_eq!(verified.tenant.as_deref(), Some("acme"));
+
+        let mut expired = claims.clone();
+        expired["exp"] = json!(now() - 3600);
+        assert!(validator.validate(&token("test-key", expired)).is_err());
+
+        let mut wrong_issuer = claims.clone();
+        wrong_issuer["iss"] = json!("https://other.example");
+        assert!(validator
+            .validate(&token("test-key", wrong_issuer))
+            .is_err());
+
+        let mut wrong_audience = claims.clone();
+        wrong_audience["aud"] = json!("someone-else");
+        assert!(validator
+            .validate(&token("test-key", wrong_audience))
+            .is_err());
+
+        assert!(validator.validate(&token("unknown-key", claims)).is_err());
+    }
+
+    #[test]
+    fn test_rejects_wrong_key_and_algorithm() {
+        let validator = validator(JwtConfig::default());
+        let claims = json!({"sub": "user-1", "exp": now() + 600});
+
+        let forged = jsonwebtoken::encode(
+            &Header {
+                kid: Some("test-key".to_string()),
+                ..Header::new(Algorithm::HS256)
+            },
+            &claims,
+            &EncodingKey::from_secret(b"another-secret"),
+        )
+        .unwrap();
+        assert!(validator.validate(&forged).is_err());
+
+        // The key is pinned to HS256
+        let other_alg = jsonwebtoken::encode(
+            &Header {
+                kid: Some("test-key".to_string()),
+                ..Header::new(Algorithm::HS512)
+            },
+            &claims,
+            &EncodingKey::from_secret(SECRET),
+        )
+        .unwrap();
+        assert!(validator.validate(&other_alg).is_err());
+
+        let valid = token("test-key", claims);
+        assert!(JwtValidator::is_jwt(&valid));
+        assert!(!JwtValidator::is_jwt("sk-opaque-api-key"));
+        assert!(validator.validate(&valid).unwrap().tenant.is_none());
+    }
+
+    #[test]
+    fn test_admin_claim_grants_admin() {
+        let claims = |scope: Value| json!({"sub": "user-1", "exp": now() + 600, "scope": scope});
+
+        // Without an admin claim no token is an admin
+        let verified = validator(JwtConfig::default())
+            .validate(&token("test-key", claims(json!("inference admin"))))
+            .unwrap();
+        assert!(!verified.admin);
+
+        let validator = validator(JwtConfig {
+            admin_claim: Some("scope".to_string()),
+            ..Default::default()
+        });
+        for (scope, admin) in [
+            (json!("inference admin"), true),
+            (json!(["admin"]), true),
+            (json!("inference administrator"), false),
+            (json!(["inference"]), false),
+            (json!(true), false),
+        ] {
+            let verified = validator
+                .validate(&token("test-key", claims(scope.clone())))
+                .unwrap();
+            assert_eq!(verified.admin, admin, "{}", scope);
+        }
+    }
+
+    #[test]
+    fn test_invalid_jwks_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("jwks.json");
+        std::fs::write(&path, r#"{"keys": [{"kty": "unknown"}]}"#).unwrap();
+        let config = JwtConfig {
+            jwks_file: Some(path.to_str().unwrap().to_string()),
+            ..Default::default()
+        };
+        assert!(JwtValidator::new(config, reqwest::Client::new()).is_err());
+    }
+
+    #[test]
+    fn test_validation_cache_expires_and_evicts() {
+        let cache = ValidationCache::new(&ApiKeyCacheConfig {
+            ttl_secs: 300,
+            max_entries: 2,
+        });
+        cache.insert("key-a", true);
+        cache.insert("key-b", false);
+        assert_eq!(cache.get("key-a"), Some(true));
+        assert_eq!(cache.get("key-b"), Some(false));
+
+        cache.insert("key-c", true);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get("key-a"), None);
+
+        let uncached = ValidationCache::new(&ApiKeyCacheConfig {
+            ttl_secs: 0,
+            max_entries: 2,
+        });
+        uncached.insert("key-a", true);
+        assert_eq!(uncached.get("key-a"), None);
+    }
+
+    #[test]
+    fn test_caller_identity_never_holds_the_token() {
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", "Bearer sk-1 ".parse().unwrap());
+        headers.insert(TENANT_HEADER, "acme".parse().unwrap());
+        let unverified = CallerIdentity::unverified(&headers);
+        assert_eq!(
+            unverified.id,
+            Some(format!("sha256:{}", ApiKeyStore::hash_key("sk-1")))
+        );
+        assert_eq!(unverified.tenant.as_deref(), Some("acme"));
+
+        headers.insert("authorization", "Basic abc".parse().unwrap());
+        headers.remove(TENANT_HEADER);
+        assert_eq!(
+            CallerIdentity::unverified(&headers),
+            CallerIdentity::default()
+        );
+
+        let verified = VerifiedToken {
+            subject: None,
+            tenant: Some("acme".to_string()),
+            admin: false,
+            claims: Value::Null,
+        };
+        let caller = CallerIdentity::from_jwt("header.claims.sig", verified);
+        assert_eq!(
+            caller.id,
+            Some(CallerIdentity::token_digest("header.claims.sig"))
+        );
+        assert_eq!(caller.tenant.as_deref(), Some("acme"));
+    }
+}
@@ -0,0 +1,764 @@
+//! Content policy filters run between request parsing and routing
+//!
+//! A [`ContentPolicy`] runs its [`RequestFilter`]s in order on chat and
+//! completion requests. Each filter may reject the request, rewrite it in
+//! place, or tag it; tags are passed on to the router and returned to the
+//! client in the `x-content-policy-tags` header.
+//!
+//! ```yaml
+//! filters:
+//!   - type: regex_deny
+//!     patterns: ["(?i)ignore (all )?previous instructions"]
+//!   - type: max_prompt_length
+//!     max_tokens: 8192
+//!   - type: http_classifier
+//!     url: http://classifier:8080/classify
+//!     timeout_ms: 500
+//!     fail_mode: open
+//!   - type: rewrite
+//!     system_prompt: "You are a helpful assistant."
+//!     max_tokens: 2048
+//!     strip_params: [logit_bias]
+//! ```
+
+use crate::core::parse_yaml_or_json;
+use crate::protocols::spec::{
+    ChatCompletionRequest, ChatMessage, CompletionRequest, ErrorResponse, GenerationRequest,
+    PromptInput,
+};
+use crate::routers::http::pd_bypass::{count_tokens, prompt_text};
+use crate::tokenizer::traits::Tokenizer;
+use async_trait::async_trait;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use regex::RegexSet;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Header carrying the tags filters attached to a request
+pub const CONTENT_POLICY_TAGS_HEADER: &str = "x-content-policy-tags";
+
+/// Request handed to the filters
+pub enum FilterRequest<'a> {
+    Chat(&'a mut ChatCompletionRequest),
+    Completion(&'a mut CompletionRequest),
+}
+
+impl FilterRequest<'_> {
+    pub fn model(&self) -> Option<&str> {
+        match self {
+            Self::Chat(request) => request.get_model(),
+            Self::Completion(request) => request.get_model(),
+        }
+    }
+
+    fn to_value(&self) -> Result<Value, String> {
+        match self {
+            Self::Chat(request) => serde_json::to_value(&**request),
+            Self::Completion(request) => serde_json::to_value(&**request),
+        }
+        .map_err(|e| e.to_string())
+    }
+
+    /// Text of the messages or prompt
+    pub fn prompt_text(&self) -> String {
+        self.to_value()
+            .map(|value| prompt_text(&value))
+            .unwrap_or_default()
+    }
+
+    /// Lower the requested output token limit to `limit`, setting it if absent
+    pub fn cap_max_tokens(&mut self, limit: u32) {
+        let cap = |value: &mut Option<u32>| *value = Some(value.map_or(limit, |v| v.min(limit)));
+        match self {
+            Self::Chat(request) => {
+                if request.max_completion_tokens.is_some() {
+                    cap(&mut request.max_completion_tokens);
+                } else {
+                    cap(&mut request.max_tokens);
+                }
+            }
+            Self::Completion(request) => cap(&mut request.max_tokens),
+        }
+    }
+
+    /// Prepend a system prompt; completion prompts given as token IDs are left alone
+    pub fn inject_system_prompt(&mut self, system_prompt: &str) {
+        match self {
+            Self::Chat(request) => request.messages.insert(
+                0,
+                ChatMessage::System {
+                    role: "system".to_string(),
+                    content: system_prompt.to_string(),
+                    name: None,
+                },
+            ),
+            Self::Completion(request) => match &mut request.prompt {
+                PromptInput::String(prompt) => *prompt = format!("{}\n\n{}", system_prompt, prompt),
+                PromptInput::StringArray(prompts) => {
+                    for prompt in prompts {
+                        *prompt = format!("{}\n\n{}", system_prompt, prompt);
+                    }
+                }
+                PromptInput::IntArray(_) | PromptInput::IntBatch(_) => {}
+            },
+        }
+    }
+
+    /// Remove top-level request parameters; returns the ones that were set
+    pub fn strip_params(&mut self, params: &[String]) -> Result<Vec<String>, String> {
+        let mut value = self.to_value()?;
+        let Some(fields) = value.as_object_mut() else {
+            return Ok(vec![]);
+        };
+        let stripped: Vec<String> = params
+            .iter()
+            .filter(|param| fields.remove(param.as_str()).is_some())
+            .cloned()
+            .collect();
+        if stripped.is_empty() {
+            return Ok(stripped);
+        }
+        match self {
+            Self::Chat(request) => {
+                **request = serde_json::from_value(value).map_err(|e| e.to_string())?
+            }
+            Self::Completion(request) => {
+                **request = serde_json::from_value(value).map_err(|e| e.to_string())?
+            }
+        }
+        Ok(stripped)
+    }
+}
+
+/// Why a filter turned a request away
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterRejection {
+    pub status: StatusCode,
+    pub message: String,
+}
+
+impl FilterRejection {
+    pub fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+impl IntoResponse for FilterRejection {
+    fn into_response(self) -> Response {
+        let error_type = if self.status.is_server_error() {
+            "server_error"
+        } else {
+            "invalid_request_error"
+        };
+        let body = ErrorResponse::new(self.message, error_type, Some("content_policy_violation"));
+        (self.status, Json(body)).into_response()
+    }
+}
+
+/// Result of one filter
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterOutcome {
+    Continue,
+    Reject(FilterRejection),
+}
+
+/// One stage of the content policy
+///
+/// Filters may rewrite the request in place and push tags; returning
+/// [`FilterOutcome::Reject`] stops the chain.
+#[async_trait]
+pub trait RequestFilter: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    async fn apply(&self, request: &mut FilterRequest<'_>, tags: &mut Vec<String>)
+        -> FilterOutcome;
+}
+
+/// What a deny-list match does
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DenyAction {
+    #[default]
+    Reject,
+    Tag,
+}
+
+/// What happens when the classifier cannot be reached
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailMode {
+    /// Let the request through, tagged `classifier_unavailable`
+    Open,
+    /// Reject the request with 503
+    #[default]
+    Closed,
+}
+
+fn default_deny_message() -> String {
+    "Request blocked by content policy".to_string()
+}
+
+fn default_deny_tag() -> String {
+    "denied_content".to_string()
+}
+
+fn default_classifier_timeout_ms() -> u64 {
+    1000
+}
+
+/// One entry of the content policy file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FilterConfig {
+    /// Reject (or tag) prompts matching any of the patterns
+    RegexDeny {
+        patterns: Vec<String>,
+        #[serde(default)]
+        action: DenyAction,
+        #[serde(default = "default_deny_message")]
+        message: String,
+        #[serde(default = "default_deny_tag")]
+        tag: String,
+    },
+    /// Reject prompts longer than `max_tokens`, counted with the tokenizer
+    /// when one is loaded and estimated from characters otherwise
+    MaxPromptLength { max_tokens: usize },
+    /// Ask an external classifier whether to allow the request
+    HttpClassifier {
+        url: String,
+        #[serde(default = "default_classifier_timeout_ms")]
+        timeout_ms: u64,
+        #[serde(default)]
+        fail_mode: FailMode,
+    },
+    /// Inject a system prompt, cap output tokens and drop parameters
+    Rewrite {
+        #[serde(default)]
+        system_prompt: Option<String>,
+        #[serde(default)]
+        max_tokens: Option<u32>,
+        #[serde(default)]
+        strip_params: Vec<String>,
+    },
+}
+
+/// Content policy file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ContentPolicyConfig {
+    #[serde(default)]
+    pub filters: Vec<FilterConfig>,
+}
+
+impl ContentPolicyConfig {
+    /// Load from a YAML (`.yaml`/`.yml`) or JSON file
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read content policy {}: {}", path, e))?;
+        let config: Self = parse_yaml_or_json(path, &contents)
+            .map_err(|e| format!("Invalid content policy {}: {}", path, e))?;
+        Ok(config)
+    }
+
+    /// Whether a filter counts prompt tokens
+    pub fn needs_tokenizer(&self) -> bool {
+        self.filters
+            .iter()
+            .any(|filter| matches!(filter, FilterConfig::MaxPromptLength { .. }))
+    }
+}
+
+/// Deny-list of regular expressions over the prompt text
+pub struct RegexDenyFilter {
+    patterns: RegexSet,
+    action: DenyAction,
+    message: String,
+    tag: String,
+}
+
+impl RegexDenyFilter {
+    pub fn new(
+        patterns: &[String],
+        action: DenyAction,
+        message: String,
+        tag: String,
+    ) -> Result<Self, String> {
+        let patterns =
+            RegexSet::new(patterns).map_err(|e| format!("Invalid deny-list pattern: {}", e))?;
+        Ok(Self {
+            patterns,
+            action,
+            message,
+            tag,
+        })
+    }
+}
+
+#[async_trait]
+impl RequestFilter for RegexDenyFilter {
+    fn name(&self) -> &'static str {
+        "regex_deny"
+    }
+
+    async fn apply(
+        &self,
+        request: &mut FilterRequest<'_>,
+        tags: &mut Vec<String>,
+    ) -> FilterOutcome {
+        if !self.patterns.is_match(&request.prompt_text()) {
+            return FilterOutcome::Continue;
+        }
+        match self.action {
+            DenyAction::Reject => FilterOutcome::Reject(FilterRejection::new(
+                StatusCode::BAD_REQUEST,
+                self.message.clone(),
+            )),
+            DenyAction::Tag => {
+                tags.push(self.tag.clone());
+                FilterOutcome::Continue
+            }
+        }
+    }
+}
+
+/// Upper bound on prompt tokens
+pub struct MaxPromptLengthFilter {
+    max_tokens: usize,
+    tokenizer: Option<Arc<dyn Tokenizer>>,
+}
+
+impl MaxPromptLengthFilter {
+    pub fn new(max_tokens: usize, tokenizer: Option<Arc<dyn Tokenizer>>) -> Self {
+        Self {
+            max_tokens,
+            tokenizer,
+        }
+    }
+}
+
+#[async_trait]
+impl RequestFilter for MaxPromptLengthFilter {
+    fn name(&self) -> &'static str {
+        "max_prompt_length"
+    }
+
+    async fn apply(
+        &self,
+        request: &mut FilterRequest<'_>,
+        _tags: &mut Vec<String>,
+    ) -> FilterOutcome {
+        let tokens = count_tokens(self.tokenizer.as_deref(), &request.prompt_text());
+        if tokens <= self.max_tokens {
+            return FilterOutcome::Continue;
+        }
+        FilterOutcome::Reject(FilterRejection::new(
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Prompt is {} tokens, above the limit of {}",
+                tokens, self.max_tokens
+            ),
+        ))
+    }
+}
+
+/// Verdict returned by an external classifier
+#[derive(Debug, Deserialize)]
+struct ClassifierVerdict {
+    allow: bool,
+    #[serde(default)]
+    reason: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+/// Filter delegating the decision to an external HTTP classifier
+///
+/// The classifier receives `{"model", "prompt"}` and answers
+/// `{"allow": bool, "reason": "...", "tags": [...]}`.
+pub struct HttpClassifierFilter {
+    client: reqwest::Client,
+    url: String,
+    timeout: Duration,
+    fail_mode: FailMode,
+}
+
+impl HttpClassifierFilter {
+    pub fn new(
+        client: reqwest::Client,
+        url: String,
+        timeout: Duration,
+        fail_mode: FailMode,
+    ) -> Self {
+        Self {
+            client,
+            url,
+            timeout,
+            fail_mode,
+        }
+    }
+
+    async fn classify(&self, request: &FilterRequest<'_>) -> Result<ClassifierVerdict, String> {
+        let response = self
+            .client
+            .post(&self.url)
+            .timeout(self.timeout)
+            .json(&json!({
+                "model": request.model(),
+                "prompt": request.prompt_text(),
+            }))
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("status {}", response.status()));
+        }
+        response.json().await.map_err(|e| e.to_string())
+    }
+}
+
+#[async_trait]
+impl RequestFilter for HttpClassifierFilter {
+    fn name(&self) -> &'static str {
+        "http_classifier"
+    }
+
+    async fn apply(
+        &self,
+        request: &mut FilterRequest<'_>,
+        tags: &mut Vec<String>,
+    ) -> FilterOutcome {
+        match self.classify(request).await {
+            Ok(verdict) => {
+                tags.extend(verdict.tags);
+                if verdict.allow {
+                    FilterOutcome::Continue
+                } else {
+                    FilterOutcome::Reject(FilterRejection::new(
+                        StatusCode::BAD_REQUEST,
+                        verdict.reason.unwrap_or_else(default_deny_message),
+                    ))
+                }
+            }
+            Err(e) => {
+                warn!("Content classifier {} failed: {}", self.url, e);
+                match self.fail_mode {
+                    FailMode::Open => {
+                        tags.push("classifier_unavailable".to_string());
+                        FilterOutcome::Continue
+                    }
+                    FailMode::Closed => FilterOutcome::Reject(FilterRejection::new(
+                        StatusCode::SERVICE_UNAVAILABLE,
+                        "Content classifier unavailable",
+                    )),
+                }
+            }
+        }
+    }
+}
+
+/// Fixed rewrites applied to every request
+pub struct RewriteFilter {
+    system_prompt: Option<String>,
+    max_tokens: Option<u32>,
+    strip_params: Vec<String>,
+}
+
+impl RewriteFilter {
+    pub fn new(
+        system_prompt: Option<String>,
+        max_tokens: Option<u32>,
+        strip_params: Vec<String>,
+    ) -> Self {
+        Self {
+            system_prompt,
+            max_tokens,
+            strip_params,
+        }
+    }
+}
+
+#[async_trait]
+impl RequestFilter for RewriteFilter {
+    fn name(&self) -> &'static str {
+        "rewrite"
+    }
+
+    async fn apply(
+        &self,
+        request: &mut FilterRequest<'_>,
+        _tags: &mut Vec<String>,
+    ) -> FilterOutcome {
+        if !self.strip_params.is_empty() {
+            match request.strip_params(&self.strip_params) {
+                Ok(stripped) if !stripped.is_empty() => {
+                    debug!("Stripped request parameters {:?}", stripped)
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    return FilterOutcome::Reject(FilterRejection::new(
+                        StatusCode::BAD_REQUEST,
+                        format!("Failed to rewrite request: {}", e),
+                    ))
+                }
+            }
+        }
+        if let Some(system_prompt) = &self.system_prompt {
+            request.inject_system_prompt(system_prompt);
+        }
+        if let Some(max_tokens) = self.max_tokens {
+            request.cap_max_tokens(max_tokens);
+        }
+        FilterOutcome::Continue
+    }
+}
+
+/// Ordered chain of request filters
+#[derive(Default)]
+pub struct ContentPolicy {
+    filters: Vec<Box<dyn RequestFilter>>,
+}
+
+impl ContentPolicy {
+    pub fn new(filters: Vec<Box<dyn RequestFilter>>) -> Self {
+        Self { filters }
+    }
+
+    /// Build the built-in filters listed in `config`
+    pub fn from_config(
+        config: &ContentPolicyConfig,
+        tokenizer: Option<Arc<dyn Tokenizer>>,
+        client: reqwest::Client,
+    ) -> Result<Self, String> {
+        let mut filters: Vec<Box<dyn RequestFilter>> = Vec::new();
+        for filter in &config.filters {
+            filters.push(match filter {
+                FilterConfig::RegexDeny {
+                    patterns,
+                    action,
+                    message,
+                    tag,
+                } => Box::new(RegexDenyFilter::new(
+                    patterns,
+                    *action,
+                    message.clone(),
+                    tag.clone(),
+                )?),
+                FilterConfig::MaxPromptLength { max_tokens } => {
+                    if *max_tokens == 0 {
+                        return Err("max_prompt_length: max_tokens must be > 0".to_string());
+                    }
+                    Box::new(MaxPromptLengthFilter::new(*max_tokens, tokenizer.clone()))
+                }
+                FilterConfig::HttpClassifier {
+                    url,
+                    timeout_ms,
+                    fail_mode,
+                } => {
+                    if !url.starts_with("http://") && !url.starts_with("https://") {
+                        return Err(format!(
+                            "http_classifier: url must start with http:// or https://, got {}",
+                            url
+                        ));
+                    }
+                    Box::new(HttpClassifierFilter::new(
+                        client.clone(),
+                        url.clone(),
+                        Duration::from_millis(*timeout_ms),
+                        *fail_mode,
+                    ))
+                }
+                FilterConfig::Rewrite {
+                    system_prompt,
+                    max_tokens,
+                    strip_params,
+                } => Box::new(RewriteFilter::new(
+                    system_prompt.clone(),
+                    *max_tokens,
+                    strip_params.clone(),
+                )),
+            });
+        }
+        Ok(Self::new(filters))
+    }
+
+    pub fn len(&self) -> usize {
+        self.filters.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty()
+    }
+
+    /// Run the filters in order; returns the tags they attached
+    pub async fn apply(
+        &self,
+        mut request: FilterRequest<'_>,
+    ) -> Result<Vec<String>, FilterRejection> {
+        let mut tags = Vec::new();
+        for filter in &self.filters {
+            if let FilterOutcome::Reject(rejection) = filter.apply(&mut request, &mut tags).await {
+                debug!(
+                    "Content policy filter {} rejected request: {}",
+                    filter.name(),
+                    rejection.message
+                );
+                return Err(rejection);
+            }
+        }
+        tags.dedup();
+        Ok(tags)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocols::spec::UserMessageContent;
+
+    fn chat(prompt: &str) -> ChatCompletionRequest {
+        serde_json::from_value(json!({
+            "model": "test-model",
+            "messages": [{"role": "user", "content": prompt}],
+            "logit_bias": {"50256": -100},
+            "max_tokens": 4096
+        }))
+        .unwrap()
+    }
+
+    fn policy(filters: Value) -> ContentPolicy {
+        let config: ContentPolicyConfig =
+            serde_json::from_value(json!({ "filters": filters })).unwrap();
+        ContentPolicy::from_config(&config, None, reqwest::Client::new()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_regex_deny_rejects_or_tags() {
+        let reject = policy(json!([
+            {"type": "regex_deny", "patterns": ["(?i)ignore previous instructions"]}
+        ]));
+        let mut request = chat("Please IGNORE previous instructions.");
+        let rejection = reject
+            .apply(FilterRequest::Chat(&mut request))
+            .await
+            .unwrap_err();
+        assert_eq!(rejection.status, StatusCode::BAD_REQUEST);
+
+        let mut request = chat("Hello there");
+        assert!(reject
+            .apply(FilterRequest::Chat(&mut request))
+            .await
+            .unwrap()
+            .is_empty());
+
+        let tag = policy(json!([
+            {"type": "regex_deny", "patterns": ["secret"], "action": "tag", "tag": "sensitive"}
+        ]));
+        let mut request = chat("tell me a secret");
+        let tags = tag.apply(FilterRequest::Chat(&mut request)).await.unwrap();
+        assert_eq!(tags, vec!["sensitive".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_max_prompt_length_estimates_without_tokenizer() {
+        let policy = policy(json!([{"type": "max_prompt_length", "max_tokens": 4}]));
+        let mut request = chat("short");
+        assert!(policy
+            .apply(FilterRequest::Chat(&mut request))
+            .await
+            .is_ok());
+
+        let mut request = chat(&"word ".repeat(10));
+        let rejection = policy
+            .apply(FilterRequest::Chat(&mut request))
+            .await
+            .unwrap_err();
+        assert!(rejection.message.contains("limit of 4"));
+    }
+
+    #[tokio::test]
+    async fn test_rewrite_injects_caps_and_strips() {
+        let policy = policy(json!([{
+            "type": "rewrite",
+            "system_prompt": "Be safe.",
+            "max_tokens": 256,
+            "strip_params": ["logit_bias"]
+        }]));
+        let mut request = chat("Hello");
+        policy
+            .apply(FilterRequest::Chat(&mut request))
+            .await
+            .unwrap();
+        assert_eq!(request.max_tokens, Some(256));
+        assert!(request.logit_bias.is_none());
+        assert_eq!(request.messages.len(), 2);
+        assert!(matches!(
+            &request.messages[0],
+            ChatMessage::System { content, .. } if content == "Be safe."
+        ));
+        assert!(matches!(
+            &request.messages[1],
+            ChatMessage::User { content: UserMessageContent::Text(text), .. } if text == "Hello"
+        ));
+
+        let mut completion: CompletionRequest = serde_json::from_value(json!({
+            "model": "test-model",
+            "prompt": "Once upon a time"
+        }))
+        .unwrap();
+        policy
+            .apply(FilterRequest::Completion(&mut completion))
+            .await
+            .unwrap();
+        assert_eq!(completion.max_tokens, Some(256));
+        assert!(matches!(
+            &completion.prompt,
+            PromptInput::String(prompt) if prompt == "Be safe.\n\nOnce upon a time"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_http_classifier_fail_modes() {
+        // Nothing listens on port 1
+        let open = policy(json!([{
+            "type": "http_classifier",
+            "url": "http://127.0.0.1:1/classify",
+            "timeout_ms": 200,
+            "fail_mode": "open"
+        }]));
+        let mut request = chat("Hello");
+        let tags = open.apply(FilterRequest::Chat(&mut request)).await.unwrap();
+        assert_eq!(tags, vec!["classifier_unavailable".to_string()]);
+
+        let closed = policy(json!([{
+            "type": "http_classifier",
+            "url": "http://127.0.0.1:1/classify",
+            "timeout_ms": 200
+        }]));
+        let rejection = closed
+            .apply(FilterRequest::Chat(&mut request))
+            .await
+            .unwrap_err();
+        assert_eq!(rejection.status, StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[test]
+    fn test_from_config_rejects_bad_filters() {
+        let config: ContentPolicyConfig =
+            serde_yaml::from_str("filters:\n  - type: regex_deny\n    patterns: ['(unclosed']\n")
+                .unwrap();
+        assert!(ContentPolicy::from_config(&config, None, reqwest::Client::new()).is_err());
+
+        let config: ContentPolicyConfig =
+            serde_yaml::from_str("filters:\n  - type: max_prompt_length\n    max_tokens: 0\n")
+                .unwrap();
+        assert!(config.needs_tokenizer());
+        assert!(ContentPolicy::from_config(&config, None, reqwest::Client::new()).is_err());
+    }
+}
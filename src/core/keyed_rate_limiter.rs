@@ -0,0 +1,750 @@
+//! Keyed request rate limiting
+//!
+//! The global [`TokenBucket`](super::token_bucket::TokenBucket) caps the router
+//! as a whole. This limiter adds buckets per API key, tenant and model so that a
+//! single caller cannot exhaust the shared capacity.
+//!
+//! Limits are grouped into named classes and keys are mapped onto classes in a
+//! YAML or JSON file. Token limits in the same classes are enforced by the
+//! [`TokenRateLimiter`](super::token_rate_limiter::TokenRateLimiter):
+//!
+//! ```yaml
+//! max_buckets: 10000
+//! quota_store: /var/lib/vllm-router/quotas.json
+//! classes:
+//!   standard: { requests_per_second: 5, burst: 10, tokens_per_minute: 20000 }
+//!   premium: { requests_per_second: 50, daily_tokens: 10000000 }
+//! api_key:
+//!   default_class: standard
+//!   keys:
+//!     partner-team: premium
+//! tenant:
+//!   keys:
+//!     acme: premium
+//! ```
+//!
+//! API keys are named by the authenticated caller: the keys-file owner, the
+//! JWT subject, or `sha256:<hex>` of any other token. Tenants come from the
+//! JWT tenant claim, and from the `x-tenant-id` header only when
+//! authentication is disabled.
+//!
+//! A request is admitted only if every applicable bucket has a token. Buckets
+//! live in an LRU so idle keys are evicted once `max_buckets` is reached; an
+//! evicted bucket would have refilled anyway, so eviction never loosens a limit
+//! that is actively being hit.
+
+use crate::core::{parse_yaml_or_json, CallerIdentity};
+use lru::LruCache;
+use parking_lot::Mutex;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::num::NonZeroUsize;
+use std::time::{Duration, Instant};
+
+/// Header carrying the tenant identifier
+pub const TENANT_HEADER: &str = "x-tenant-id";
+
+fn default_max_buckets() -> usize {
+    10_000
+}
+
+fn default_quota_flush_interval_secs() -> u64 {
+    10
+}
+
+/// Rate limit file contents
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct KeyedRateLimitConfig {
+    /// Upper bound on tracked buckets across all dimensions
+    #[serde(default = "default_max_buckets")]
+    pub max_buckets: usize,
+    /// JSON file persisting daily and monthly token usage across restarts
+    #[serde(default)]
+    pub quota_store: Option<String>,
+    /// Seconds between writes of the quota store
+    #[serde(default = "default_quota_flush_interval_secs")]
+    pub quota_flush_interval_secs: u64,
+    /// Named limits referenced by the dimension mappings
+    #[serde(default)]
+    pub classes: HashMap<String, RateLimitClass>,
+    #[serde(default)]
+    pub api_key: Option<DimensionConfig>,
+    #[serde(default)]
+    pub tenant: Option<DimensionConfig>,
+    #[serde(default)]
+    pub model: Option<DimensionConfig>,
+}
+
+/// A named set of request and token limits
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimitClass {
+    #[serde(default)]
+    pub requests_per_second: Option<f64>,
+    /// Request bucket capacity; defaults to one second worth of requests
+    #[serde(default)]
+    pub burst: Option<f64>,
+    /// Prompt plus completion tokens per minute
+    #[serde(default)]
+    pub tokens_per_minute: Option<f64>,
+    /// Tokens per UTC calendar day
+    #[serde(default)]
+    pub daily_tokens: Option<u64>,
+    /// Tokens per UTC calendar month
+    #[serde(default)]
+    pub monthly_tokens: Option<u64>,
+}
+
+/// Refill rate and capacity of a token bucket
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct Rate {
+    pub per_second: f64,
+    pub capacity: f64,
+}
+
+impl RateLimitClass {
+    pub(super) fn request_rate(&self) -> Option<Rate> {
+        let per_second = self.requests_per_second?;
+        Some(Rate {
+            per_second,
+            capacity: self.burst.unwrap_or(per_second).max(1.0),
+        })
+    }
+
+    pub(super) fn token_rate(&self) -> Option<Rate> {
+        let per_minute = self.tokens_per_minute?;
+        Some(Rate {
+            per_second: per_minute / 60.0,
+            capacity: per_minute,
+        })
+    }
+
+    /// Whether any token rate or quota is set
+    pub fn limits_tokens(&self) -> bool {
+        self.tokens_per_minute.is_some() || self.has_quota()
+    }
+
+    /// Whether a daily or monthly token quota is set
+    pub fn has_quota(&self) -> bool {
+        self.daily_tokens.is_some() || self.monthly_tokens.is_some()
+    }
+}
+
+/// Key to class mapping for one dimension
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DimensionConfig {
+    /// Class for keys without an explicit mapping; unmapped keys are unlimited if unset
+    #[serde(default)]
+    pub default_class: Option<String>,
+    #[serde(default)]
+    pub keys: HashMap<String, String>,
+}
+
+impl DimensionConfig {
+    fn class_for(&self, key: &str) -> Option<&str> {
+        self.keys
+            .get(key)
+            .or(self.default_class.as_ref())
+            .map(String::as_str)
+    }
+}
+
+impl KeyedRateLimitConfig {
+    /// Load from a YAML (`.yaml`/`.yml`) or JSON file
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read rate limit config {}: {}", path, e))?;
+        let config: Self = parse_yaml_or_json(path, &contents)
+            .map_err(|e| format!("Invalid rate limit config {}: {}", path, e))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check that limits are positive and every referenced class exists
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_buckets == 0 {
+            return Err("max_buckets must be greater than 0".to_string());
+        }
+        let positive = |value: Option<f64>| value.is_none_or(|v| v > 0.0 && v.is_finite());
+        for (name, class) in &self.classes {
+            if class.requests_per_second.is_none() && !class.limits_tokens() {
+                return Err(format!("class '{}' does not set any limit", name));
+            }
+            if !positive(class.requests_per_second) {
+                return Err(format!(
+                    "class '{}': requests_per_second must be positive",
+                    name
+                ));
+            }
+            if !positive(class.burst) {
+                return Err(format!("class '{}': burst must be positive", name));
+            }
+            if !positive(class.tokens_per_minute) {
+                return Err(format!(
+                    "class '{}': tokens_per_minute must be positive",
+                    name
+                ));
+            }
+            if class.daily_tokens == Some(0) || class.monthly_tokens == Some(0) {
+                return Err(format!("class '{}': token quotas must be positive", name));
+            }
+        }
+        for dimension in RateLimitDimension::ALL {
+            let Some(mapping) = self.dimension(dimension) else {
+                continue;
+            };
+            let referenced = mapping.default_class.iter().chain(mapping.keys.values());
+            for class in referenced {
+                if !self.classes.contains_key(class) {
+                    return Err(format!(
+                        "{}: unknown rate limit class '{}'",
+                        dimension, class
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub(super) fn dimension(&self, dimension: RateLimitDimension) -> Option<&DimensionConfig> {
+        match dimension {
+            RateLimitDimension::ApiKey => self.api_key.as_ref(),
+            RateLimitDimension::Tenant => self.tenant.as_ref(),
+            RateLimitDimension::Model => self.model.as_ref(),
+        }
+    }
+
+    /// Whether buckets are keyed by model, which requires reading the request body
+    pub fn limits_models(&self) -> bool {
+        self.model.is_some()
+    }
+
+    /// Whether any class limits tokens, which requires counting prompt tokens
+    pub fn limits_tokens(&self) -> bool {
+        self.classes.values().any(RateLimitClass::limits_tokens)
+    }
+
+    /// Class of every dimension that applies to `keys`
+    pub(super) fn applicable_classes(
+        &self,
+        keys: &RateLimitKeys,
+    ) -> Vec<(RateLimitDimension, String, RateLimitClass)> {
+        RateLimitDimension::ALL
+            .into_iter()
+            .filter_map(|dimension| {
+                let key = keys.get(dimension)?;
+                let assigned = match dimension {
+                    RateLimitDimension::ApiKey => keys.api_key_class.as_deref(),
+                    _ => None,
+                };
+                let class_name = match assigned {
+                    Some(class_name) => class_name,
+                    None => self.dimension(dimension)?.class_for(key)?,
+                };
+                let class = *self.classes.get(class_name)?;
+                Some((dimension, key.to_string(), class))
+            })
+            .collect()
+    }
+}
+
+/// What a bucket is keyed by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RateLimitDimension {
+    ApiKey,
+    Tenant,
+    Model,
+}
+
+impl RateLimitDimension {
+    pub const ALL: [Self; 3] = [Self::ApiKey, Self::Tenant, Self::Model];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ApiKey => "api_key",
+            Self::Tenant => "tenant",
+            Self::Model => "model",
+        }
+    }
+}
+
+impl fmt::Display for RateLimitDimension {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Identity of a request for keyed limits
+///
+/// `api_key` is the authenticated caller (see [`CallerIdentity::id`]), never
+/// the bearer token itself.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RateLimitKeys {
+    pub api_key: Option<String>,
+    pub tenant: Option<String>,
+    pub model: Option<String>,
+    /// Class assigned to the API key outside the rate limit config (e.g. by the keys file)
+    pub api_key_class: Option<String>,
+}
+
+impl RateLimitKeys {
+    /// Key on the authenticated caller; the model comes from the body
+    pub fn from_identity(identity: &CallerIdentity) -> Self {
+        Self {
+            api_key: identity.id.clone(),
+            tenant: identity.tenant.clone(),
+            model: None,
+            // Keys from the keys file may carry their own rate-limit class
+            api_key_class: identity
+                .api_key
+                .as_ref()
+                .and_then(|api_key| api_key.rate_limit_class.clone()),
+        }
+    }
+
+    pub fn with_model(mut self, model: Option<String>) -> Self {
+        self.model = model;
+        self
+    }
+
+    pub fn get(&self, dimension: RateLimitDimension) -> Option<&str> {
+        match dimension {
+            RateLimitDimension::ApiKey => self.api_key.as_deref(),
+            RateLimitDimension::Tenant => self.tenant.as_deref(),
+            RateLimitDimension::Model => self.model.as_deref(),
+        }
+    }
+}
+
+/// Limit state reported back to the client
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimitStatus {
+    pub dimension: RateLimitDimension,
+    pub limit: u64,
+    pub remaining: u64,
+    /// Time until the bucket is full again
+    pub reset: Duration,
+}
+
+/// Outcome of [`KeyedRateLimiter::check`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RateLimitDecision {
+    /// No bucket applies to this request
+    Unlimited,
+    /// Admitted; carries the most constrained bucket
+    Allowed(RateLimitStatus),
+    /// Rejected by the bucket that needs the longest wait
+    Limited {
+        status: RateLimitStatus,
+        retry_after: Duration,
+    },
+}
+
+#[derive(Debug)]
+pub(super) struct Bucket {
+    pub tokens: f64,
+    last_refill: Instant,
+}
+
+impl Bucket {
+    pub fn full(rate: &Rate, now: Instant) -> Self {
+        Self {
+            tokens: rate.capacity,
+            last_refill: now,
+        }
+    }
+
+    pub fn refill(&mut self, rate: &Rate, now: Instant) {
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate.per_second).min(rate.capacity);
+        self.last_refill = now;
+    }
+
+    /// Time until the bucket holds `amount` tokens
+    pub fn wait_for(&self, rate: &Rate, amount: f64) -> Duration {
+        Duration::from_secs_f64((amount - self.tokens).max(0.0) / rate.per_second)
+    }
+
+    pub fn status(&self, dimension: RateLimitDimension, rate: &Rate) -> RateLimitStatus {
+        RateLimitStatus {
+            dimension,
+            limit: rate.capacity as u64,
+            remaining: self.tokens.max(0.0) as u64,
+            reset: self.wait_for(rate, rate.capacity),
+        }
+    }
+}
+
+pub(super) type BucketKey = (RateLimitDimension, String);
+
+/// Token buckets per API key, tenant and model
+pub struct KeyedRateLimiter {
+    config: KeyedRateLimitConfig,
+    buckets: Mutex<LruCache<BucketKey, Bucket>>,
+}
+
+impl fmt::Debug for KeyedRateLimiter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyedRateLimiter")
+            .field("config", &self.config)
+            .field("buckets", &self.buckets.lock().len())
+            .finish()
+    }
+}
+
+impl KeyedRateLimiter {
+    pub fn new(config: KeyedRateLimitConfig) -> Result<Self, String> {
+        config.validate()?;
+        let capacity = NonZeroUsize::new(config.max_buckets)
+            .ok_or_else(|| "max_buckets must be greater than 0".to_string())?;
+        Ok(Self {
+            config,
+            buckets: Mutex::new(LruCache::new(capacity)),
+        })
+    }
+
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        Self::new(KeyedRateLimitConfig::from_file(path)?)
+    }
+
+    pub fn config(&self) -> &KeyedRateLimitConfig {
+        &self.config
+    }
+
+    /// Number of buckets currently tracked
+    pub fn bucket_count(&self) -> usize {
+        self.buckets.lock().len()
+    }
+
+    /// Take one token from every bucket that applies to `keys`
+    ///
+    /// Tokens are only taken if all buckets have one, so a request rejected by
+    /// its tenant limit does not also consume its API key budget.
+    pub fn check(&self, keys: &RateLimitKeys) -> RateLimitDecision {
+        let applicable: Vec<(RateLimitDimension, String, Rate)> = self
+            .config
+            .applicable_classes(keys)
+            .into_iter()
+            .filter_map(|(dimension, key, class)| Some((dimension, key, class.request_rate()?)))
+            .collect();
+        if applicable.is_empty() {
+            return RateLimitDecision::Unlimited;
+        }
+
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock();
+        // Pull the buckets out so inserting one cannot evict another in use
+        let mut taken: Vec<(BucketKey, Rate, Bucket)> = applicable
+            .into_iter()
+            .map(|(dimension, key, rate)| {
+                let key = (dimension, key);
+                let mut bucket = buckets
+                    .pop(&key)
+                    .unwrap_or_else(|| Bucket::full(&rate, now));
+                bucket.refill(&rate, now);
+                (key, rate, bucket)
+            })
+            .collect();
+
+        let limited = taken
+            .iter()
+            .filter(|(_, _, bucket)| bucket.tokens < 1.0)
+            .map(|((dimension, _), rate, bucket)| {
+                (bucket.status(*dimension, rate), bucket.wait_for(rate, 1.0))
+            })
+            .max_by(|a, b| a.1.cmp(&b.1));
+
+        let decision = match limited {
+            Some((status, retry_after)) => RateLimitDecision::Limited {
+                status,
+                retry_after,
+            },
+            None => {
+                for (_, _, bucket) in taken.iter_mut() {
+                    bucket.tokens -= 1.0;
+                }
+                let status = taken
+                    .iter()
+                    .map(|((dimension, _), rate, bucket)| bucket.status(*dimension, rate))
+                    .min_by_key(|status| status.remaining)
+                    .expect("at least one bucket applies");
+                RateLimitDecision::Allowed(status)
+            }
+        };
+
+        for (key, _, bucket) in taken {
+            buckets.put(key, bucket);
+        }
+        decision
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn class(requests_per_second: f64, burst: Option<f64>) -> RateLimitClass {
+        RateLimitClass {
+            requests_per_second: Some(requests_per_second),
+            burst,
+            ..Default::default()
+        }
+    }
+
+    fn config() -> KeyedRateLimitConfig {
+        KeyedRateLimitConfig {
+            max_buckets: 100,
+            quota_store: None,
+            quota_flush_interval_secs: 10,
+            classes: HashMap::from([
+                ("standard".to_string(), class(1.0, Some(2.0))),
+                ("premium".to_string(), class(100.0, Some(100.0))),
+            ]),
+            api_key: Some(DimensionConfig {
+                default_class: Some("standard".to_string()),
+                keys: HashMap::from([("sk-premium".to_string(), "premium".to_string())]),
+            }),
+            tenant: Some(DimensionConfig {
+                default_class: None,
+                keys: HashMap::from([("acme".to_string(), "standard".to_string())]),
+            }),
+            model: None,
+        }
+    }
+
+    fn keys(api_key: Option<&str>, tenant: Option<&str>) -> RateLimitKeys {
+        RateLimitKeys {
+            api_key: api_key.map(str::to_string),
+            tenant: tenant.map(str::to_string),
+            model: None,
+            api_key_class: None,
+        }
+    }
+
+    #[test]
+    fn test_keys_from_identity() {
+        let identity = CallerIdentity {
+            id: Some("alice".to_string()),
+            tenant: Some("acme".to_string()),
+            api_key: None,
+        };
+        let keys = RateLimitKeys::from_identity(&identity).with_model(Some("llama".to_string()));
+        assert_eq!(keys.api_key.as_deref(), Some("alice"));
+        assert_eq!(keys.tenant.as_deref(), Some("acme"));
+        assert_eq!(keys.model.as_deref(), Some("llama"));
+        assert_eq!(keys.api_key_class, None);
+
+        assert_eq!(
+            RateLimitKeys::from_identity(&CallerIdentity::default()),
+            RateLimitKeys::default()
+        );
+    }
+
+    #[test]
+    fn test_limits_each_key_independently() {
+        let limiter = KeyedRateLimiter::new(config()).unwrap();
+        let alice = keys(Some("sk-alice"), None);
+        let bob = keys(Some("sk-bob"), None);
+
+        assert!(matches!(
+            limiter.check(&alice),
+            RateLimitDecision::Allowed(status) if status.limit == 2 && status.remaining == 1
+        ));
+        assert!(matches!(
+            limiter.check(&alice),
+            RateLimitDecision::Allowed(_)
+        ));
+        match limiter.check(&alice) {
+            RateLimitDecision::Limited {
+                status,
+                retry_after,
+            } => {
+                assert_eq!(status.dimension, RateLimitDimension::ApiKey);
+                assert_eq!(status.remaining, 0);
+                assert!(retry_after > Duration::ZERO && retry_after <= Duration::from_secs(1));
+            }
+            other => panic!("expected limit, got {:?}", other),
+        }
+
+        // Another key still has its own budget
+        assert!(matches!(limiter.check(&bob), RateLimitDecision::Allowed(_)));
+        assert_eq!(limiter.bucket_count(), 2);
+    }
+
+    #[test]
+    fn test_unmapped_keys_without_default_are_unlimited() {
+        let limiter = KeyedRateLimiter::new(config()).unwrap();
+        assert_eq!(
+            limiter.check(&keys(None, Some("other"))),
+            RateLimitDecision::Unlimited
+        );
+        assert_eq!(
+            limiter.check(&keys(None, None)),
+            RateLimitDecision::Unlimited
+        );
+        assert_eq!(limiter.bucket_count(), 0);
+    }
+
+    #[test]
+    fn test_assigned_api_key_class_overrides_mapping() {
+        let limiter = KeyedRateLimiter::new(config()).unwrap();
+        let assigned = RateLimitKeys {
+            api_key_class: Some("standard".to_string()),
+            ..keys(Some("sk-premium"), None)
+        };
+        assert!(matches!(
+            limiter.check(&assigned),
+            RateLimitDecision::Allowed(_)
+        ));
+        assert!(matches!(
+            limiter.check(&assigned),
+            RateLimitDecision::Allowed(_)
+        ));
+        assert!(matches!(
+            limiter.check(&assigned),
+            RateLimitDecision::Limited { .. }
+        ));
+    }
+
+    #[test]
+    fn test_rejection_does_not_consume_other_buckets() {
+        let limiter = KeyedRateLimiter::new(config()).unwrap();
+        let tenant_only = keys(None, Some("acme"));
+        let premium = keys(Some("sk-premium"), Some("acme"));
+
+        limiter.check(&tenant_only);
+        limiter.check(&tenant_only);
+        match limiter.check(&premium) {
+            RateLimitDecision::Limited { status, .. } => {
+                assert_eq!(status.dimension, RateLimitDimension::Tenant)
+            }
+            other => panic!("expected tenant limit, got {:?}", other),
+        }
+
+        // The API key bucket was created but not charged
+        let mut buckets = limiter.buckets.lock();
+        let bucket = buckets
+            .get(&(RateLimitDimension::ApiKey, "sk-premium".to_string()))
+            .unwrap();
+        assert_eq!(bucket.tokens, 100.0);
+    }
+
+    #[test]
+    fn test_allowed_reports_most_constrained_bucket() {
+        let limiter = KeyedRateLimiter::new(config()).unwrap();
+        match limiter.check(&keys(Some("sk-premium"), Some("acme"))) {
+            RateLimitDecision::Allowed(status) => {
+                assert_eq!(status.dimension, RateLimitDimension::Tenant);
+                assert_eq!(status.limit, 2);
+                assert_eq!(status.remaining, 1);
+                assert!(status.reset <= Duration::from_secs(1));
+            }
+            other => panic!("expected allowed, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_refills_over_time() {
+        let mut config = config();
+        config
+            .classes
+            .insert("standard".to_string(), class(50.0, Some(1.0)));
+        let limiter = KeyedRateLimiter::new(config).unwrap();
+        let alice = keys(Some("sk-alice"), None);
+
+        assert!(matches!(
+            limiter.check(&alice),
+            RateLimitDecision::Allowed(_)
+        ));
+        assert!(matches!(
+            limiter.check(&alice),
+            RateLimitDecision::Limited { .. }
+        ));
+        std::thread::sleep(Duration::from_millis(40));
+        assert!(matches!(
+            limiter.check(&alice),
+            RateLimitDecision::Allowed(_)
+        ));
+    }
+
+    #[test]
+    fn test_lru_evicts_idle_buckets() {
+        let mut config = config();
+        config.max_buckets = 2;
+        let limiter = KeyedRateLimiter::new(config).unwrap();
+        let first = keys(Some("sk-1"), None);
+
+        limiter.check(&first);
+        limiter.check(&first);
+        assert!(matches!(
+            limiter.check(&first),
+            RateLimitDecision::Limited { .. }
+        ));
+
+        limiter.check(&keys(Some("sk-2"), None));
+        limiter.check(&keys(Some("sk-3"), None));
+        assert_eq!(limiter.bucket_count(), 2);
+
+        // The evicted bucket starts over
+        assert!(matches!(
+            limiter.check(&first),
+            RateLimitDecision::Allowed(_)
+        ));
+    }
+
+    #[test]
+    fn test_config_validation() {
+        let mut bad = config();
+        bad.api_key.as_mut().unwrap().default_class = Some("missing".to_string());
+        assert!(bad
+            .validate()
+            .unwrap_err()
+            .contains("unknown rate limit class"));
+
+        let mut bad = config();
+        bad.classes.insert("zero".to_string(), class(0.0, None));
+        assert!(bad.validate().is_err());
+
+        let mut bad = config();
+        bad.classes
+            .insert("empty".to_string(), RateLimitClass::default());
+        assert!(bad
+            .validate()
+            .unwrap_err()
+            .contains("does not set any limit"));
+
+        let mut bad = config();
+        bad.max_buckets = 0;
+        assert!(KeyedRateLimiter::new(bad).is_err());
+    }
+
+    #[test]
+    fn test_load_yaml_and_json_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let yaml = dir.path().join("limits.yaml");
+        std::fs::write(
+            &yaml,
+            "classes:\n  basic: { requests_per_second: 2 }\nmodel:\n  default_class: basic\n",
+        )
+        .unwrap();
+        let config = KeyedRateLimitConfig::from_file(yaml.to_str().unwrap()).unwrap();
+        assert_eq!(config.max_buckets, 10_000);
+        assert!(config.limits_models());
+        assert_eq!(
+            config.classes["basic"].request_rate().unwrap().capacity,
+            2.0
+        );
+        assert!(!config.limits_tokens());
+
+        let json = dir.path().join("limits.json");
+        std::fs::write(
+            &json,
+            r#"{"classes": {"basic": {"requests_per_second": 1}}, "tenant": {"keys": {"a": "nope"}}}"#,
+        )
+        .unwrap();
+        assert!(KeyedRateLimitConfig::from_file(json.to_str().unwrap()).is_err());
+    }
+}
@@ -0,0 +1,551 @@
+//! LoRA adapter lifecycle management
+//!
+//! Tracks which adapters each worker has loaded, loads adapters on demand via
+//! vLLM's dynamic LoRA endpoints (or the gRPC LoadLoRA RPC) and unloads the
+//! least recently used adapters once a worker reaches its adapter cap.
+//! Adapters with requests in flight are never unloaded.
+
+use super::{ConnectionMode, Worker, WorkerRegistry};
+use crate::config::LoraConfig;
+use crate::grpc::VllmSchedulerClient;
+use crate::metrics::RouterMetrics;
+use crate::protocols::spec::LoRAPath;
+use crate::routers::http::dp_utils::parse_worker_url;
+use axum::response::Response;
+use dashmap::DashMap;
+use futures_util::StreamExt;
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+/// Loads, unloads and tracks LoRA adapters across workers
+///
+/// Adapters are tracked per server (base URL), so all DP ranks of a server
+/// share the same adapter set.
+#[derive(Debug)]
+pub struct LoraManager {
+    client: reqwest::Client,
+    api_key: Option<String>,
+    config: LoraConfig,
+    worker_registry: Arc<WorkerRegistry>,
+    /// Last time each adapter was used, keyed by base worker URL
+    last_used: Mutex<HashMap<String, HashMap<String, Instant>>>,
+    /// Serializes load/unload calls per base worker URL
+    load_locks: DashMap<String, Arc<tokio::sync::Mutex<()>>>,
+    /// Requests in flight per (base worker URL, adapter)
+    in_flight: Arc<Mutex<HashMap<(String, String), usize>>>,
+}
+
+/// Keeps an adapter from being evicted while a request uses it
+///
+/// Attach it to the response with [`LoraLease::hold_until_body_end`] so it
+/// lasts for the whole (possibly streamed) generation.
+#[derive(Debug)]
+pub struct LoraLease {
+    in_flight: Arc<Mutex<HashMap<(String, String), usize>>>,
+    key: (String, String),
+}
+
+impl LoraLease {
+    /// Release the lease once the response body has been sent or dropped
+    pub fn hold_until_body_end(self, response: Response) -> Response {
+        let (parts, body) = response.into_parts();
+        let stream = body.into_data_stream().map(move |chunk| {
+            let _lease = &self;
+            chunk
+        });
+        Response::from_parts(parts, axum::body::Body::from_stream(stream))
+    }
+}
+
+impl Drop for LoraLease {
+    fn drop(&mut self) {
+        let mut in_flight = self.in_flight.lock().unwrap();
+        if let Some(count) = in_flight.get_mut(&self.key) {
+            *count -= 1;
+            if *count == 0 {
+                in_flight.remove(&self.key);
+            }
+        }
+    }
+}
+
+impl LoraManager {
+    pub fn new(
+        client: reqwest::Client,
+        api_key: Option<String>,
+        config: LoraConfig,
+        worker_registry: Arc<WorkerRegistry>,
+    ) -> Self {
+        Self {
+            client,
+            api_key,
+            config,
+            worker_registry,
+            last_used: Mutex::new(HashMap::new()),
+            load_locks: DashMap::new(),
+            in_flight: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Resolve the adapter requested through `lora_path`
+    pub fn requested_adapter(lora_path: Option<&LoRAPath>) -> Result<Option<String>, String> {
+        match lora_path {
+            Some(path) => Ok(path.single_adapter()?.map(str::to_string)),
+            None => Ok(None),
+        }
+    }
+
+    /// Point a request body at a loaded adapter
+    ///
+    /// vLLM selects the adapter through the `model` field, using the name the
+    /// adapter was loaded under.
+    pub fn target_adapter(body: &mut serde_json::Value, adapter: &str) {
+        if let Some(obj) = body.as_object_mut() {
+            obj.insert("model".to_string(), json!(adapter));
+        }
+    }
+
+    /// Narrow candidate workers to those that already have the adapter loaded
+    ///
+    /// Returns all candidates when none has it, so the caller can pick one and
+    /// load the adapter there.
+    pub fn prefer_loaded(workers: &[Arc<dyn Worker>], adapter: &str) -> Vec<Arc<dyn Worker>> {
+        let loaded: Vec<Arc<dyn Worker>> = workers
+            .iter()
+            .filter(|w| w.capabilities().lora_adapters.contains(adapter))
+            .cloned()
+            .collect();
+        if loaded.is_empty() {
+            workers.to_vec()
+        } else {
+            loaded
+        }
+    }
+
+    /// Make sure `adapter` is loaded on `worker`, loading it if needed
+    ///
+    /// Unloads least recently used idle adapters first when the worker is at
+    /// its cap, and fails when every loaded adapter has requests in flight.
+    /// The returned lease keeps the adapter loaded until it is dropped.
+    pub async fn ensure_loaded(
+        &self,
+        worker: &Arc<dyn Worker>,
+        adapter: &str,
+    ) -> Result<LoraLease, String> {
+        let (base_url, _) = parse_worker_url(worker.url());
+
+        if let Some(lease) = self.acquire(worker, &base_url, adapter) {
+            self.touch(&base_url, adapter);
+            return Ok(lease);
+        }
+
+        let lock = self
+            .load_locks
+            .entry(base_url.clone())
+            .or_insert_with(|| Arc::new(tokio::sync::Mutex::new(())))
+            .clone();
+        let _guard = lock.lock().await;
+
+        // Another request may have loaded it while we waited
+        if let Some(lease) = self.acquire(worker, &base_url, adapter) {
+            self.touch(&base_url, adapter);
+            return Ok(lease);
+        }
+
+        let mut loaded = worker.capabilities().lora_adapters;
+        while loaded.len() >= self.config.max_adapters_per_worker {
+            let Some(victim) = self.claim_idle_adapter(&base_url, &loaded) else {
+                RouterMetrics::record_lora_operation(&base_url, "evict", false);
+                return Err(format!(
+                    "All {} LoRA adapters loaded on {} are in use",
+                    loaded.len(),
+                    base_url
+                ));
+            };
+            if let Err(e) = self
+                .unload(worker.connection_mode(), &base_url, &victim)
+                .await
+            {
+                // The adapter may still be loaded; keep tracking it
+                self.update_adapters(&base_url, |adapters| {
+                    adapters.insert(victim.clone());
+                });
+                return Err(e);
+            }
+            loaded.remove(&victim);
+            self.forget(&base_url, &victim);
+            info!("Evicted LoRA adapter {} from {}", victim, base_url);
+        }
+
+        self.load(worker.connection_mode(), &base_url, adapter)
+            .await?;
+        self.update_adapters(&base_url, |adapters| {
+            adapters.insert(adapter.to_string());
+        });
+        self.touch(&base_url, adapter);
+        info!("Loaded LoRA adapter {} on {}", adapter, base_url);
+        self.acquire(worker, &base_url, adapter)
+            .ok_or_else(|| format!("LoRA adapter {} vanished from {}", adapter, base_url))
+    }
+
+    // Lease an adapter the worker has loaded. Checked under the in-flight lock
+    // so it cannot race with an eviction claiming the adapter.
+    fn acquire(
+        &self,
+        worker: &Arc<dyn Worker>,
+        base_url: &str,
+        adapter: &str,
+    ) -> Option<LoraLease> {
+        let mut in_flight = self.in_flight.lock().unwrap();
+        if !worker.capabilities().lora_adapters.contains(adapter) {
+            return None;
+        }
+        let key = (base_url.to_string(), adapter.to_string());
+        *in_flight.entry(key.clone()).or_default() += 1;
+        Some(LoraLease {
+            in_flight: self.in_flight.clone(),
+            key,
+        })
+    }
+
+    // Pick the least recently used adapter without requests in flight and
+    // stop advertising it, so no new lease can be taken on it. Adapters never
+    // used through the router (e.g. preloaded at startup) count as oldest.
+    fn claim_idle_adapter(&self, base_url: &str, loaded: &HashSet<String>) -> Option<String> {
+        let in_flight = self.in_flight.lock().unwrap();
+        let victim = {
+            let last_used = self.last_used.lock().unwrap();
+            let usage = last_used.get(base_url);
+            loaded
+                .iter()
+                .filter(|adapter| {
+                    !in_flight.contains_key(&(base_url.to_string(), adapter.to_string()))
+                })
+                .min_by_key(|adapter| (usage.and_then(|u| u.get(*adapter)).copied(), *adapter))
+                .cloned()
+        }?;
+        self.update_adapters(base_url, |adapters| {
+            adapters.remove(&victim);
+        });
+        Some(victim)
+    }
+
+    fn touch(&self, base_url: &str, adapter: &str) {
+        self.last_used
+            .lock()
+            .unwrap()
+            .entry(base_url.to_string())
+            .or_default()
+            .insert(adapter.to_string(), Instant::now());
+    }
+
+    fn forget(&self, base_url: &str, adapter: &str) {
+        if let Some(usage) = self.last_used.lock().unwrap().get_mut(base_url) {
+            usage.remove(adapter);
+        }
+    }
+
+    // Apply an adapter set change to every worker (DP rank) of a server
+    fn update_adapters(&self, base_url: &str, update: impl Fn(&mut HashSet<String>)) {
+        for worker in self.worker_registry.get_all() {
+            if parse_worker_url(worker.url()).0 == base_url {
+                let mut capabilities = worker.capabilities();
+                update(&mut capabilities.lora_adapters);
+                worker.set_capabilities(capabilities);
+            }
+        }
+    }
+
+    async fn load(
+        &self,
+        mode: ConnectionMode,
+        base_url: &str,
+        adapter: &str,
+    ) -> Result<(), String> {
+        let result = match mode {
+            ConnectionMode::Http => {
+                self.post_http(
+                    base_url,
+                    "/v1/load_lora_adapter",
+                    json!({"lora_name": adapter, "lora_path": adapter}),
+                )
+                .await
+            }
+            ConnectionMode::Grpc { .. } => {
+                let mut client = VllmSchedulerClient::connect(base_url)
+                    .await
+                    .map_err(|e| format!("Failed to connect to {}: {}", base_url, e))?;
+                match client
+                    .load_lora(adapter.to_string(), adapter.to_string())
+                    .await
+                {
+                    Ok(res) if res.success => Ok(()),
+                    Ok(res) => Err(res.message),
+                    Err(e) => Err(e.to_string()),
+                }
+            }
+        };
+
+        RouterMetrics::record_lora_operation(base_url, "load", result.is_ok());
+        result.map_err(|e| format!("Failed to load LoRA adapter {}: {}", adapter, e))
+    }
+
+    async fn unload(
+        &self,
+        mode: ConnectionMode,
+        base_url: &str,
+        adapter: &str,
+    ) -> Result<(), String> {
+        let result = match mode {
+            ConnectionMode::Http => {
+                self.post_http(
+                    base_url,
+                    "/v1/unload_lora_adapter",
+                    json!({"lora_name": adapter}),
+                )
+                .await
+            }
+            ConnectionMode::Grpc { .. } => {
+                let mut client = VllmSchedulerClient::connect(base_url)
+                    .await
+                    .map_err(|e| format!("Failed to connect to {}: {}", base_url, e))?;
+                match client.unload_lora(adapter.to_string()).await {
+                    Ok(res) if res.success => Ok(()),
+                    Ok(res) => Err(res.message),
+                    Err(e) => Err(e.to_string()),
+                }
+            }
+        };
+
+        RouterMetrics::record_lora_operation(base_url, "unload", result.is_ok());
+        result.map_err(|e| format!("Failed to unload LoRA adapter {}: {}", adapter, e))
+    }
+
+    async fn post_http(
+        &self,
+        base_url: &str,
+        path: &str,
+        body: serde_json::Value,
+    ) -> Result<(), String> {
+        let mut request = self
+            .client
+            .post(format!("{}{}", base_url, path))
+            .timeout(Duration::from_secs(self.config.load_timeout_secs))
+            .json(&body);
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+
+        let res = request.send().await.map_err(|e| e.to_string())?;
+        let status = res.status();
+        if status.is_success() {
+            return Ok(());
+        }
+
+        let text = res.text().await.unwrap_or_default();
+        // vLLM answers 400 when the adapter is already (un)loaded; the desired state holds
+        if status == reqwest::StatusCode::BAD_REQUEST
+            && (text.contains("already been loaded") || text.contains("cannot be found"))
+        {
+            debug!("{}{} is a no-op: {}", base_url, path, text);
+            return Ok(());
+        }
+
+        warn!("{}{} returned {}: {}", base_url, path, status, text);
+        Err(format!("status {}: {}", status, text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{BasicWorker, WorkerType};
+    use axum::{extract::State, routing::post, Json, Router};
+
+    type Calls = Arc<Mutex<Vec<String>>>;
+
+    async fn start_lora_mock_server(calls: Calls) -> String {
+        async fn load(
+            State(calls): State<Calls>,
+            Json(body): Json<serde_json::Value>,
+        ) -> &'static str {
+            calls
+                .lock()
+                .unwrap()
+                .push(format!("load:{}", body["lora_name"].as_str().unwrap()));
+            "ok"
+        }
+        async fn unload(
+            State(calls): State<Calls>,
+            Json(body): Json<serde_json::Value>,
+        ) -> &'static str {
+            calls
+                .lock()
+                .unwrap()
+                .push(format!("unload:{}", body["lora_name"].as_str().unwrap()));
+            "ok"
+        }
+
+        let app = Router::new()
+            .route("/v1/load_lora_adapter", post(load))
+            .route("/v1/unload_lora_adapter", post(unload))
+            .with_state(calls);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        format!("http://{}", addr)
+    }
+
+    fn test_manager(registry: Arc<WorkerRegistry>, max_adapters: usize) -> LoraManager {
+        LoraManager::new(
+            reqwest::Client::new(),
+            None,
+            LoraConfig {
+                enabled: true,
+                max_adapters_per_worker: max_adapters,
+                load_timeout_secs: 5,
+            },
+            registry,
+        )
+    }
+
+    #[test]
+    fn test_prefer_loaded() {
+        let with_adapter: Arc<dyn Worker> = Arc::new(BasicWorker::new(
+            "http://w1:8000".to_string(),
+            WorkerType::Regular,
+        ));
+        let without_adapter: Arc<dyn Worker> = Arc::new(BasicWorker::new(
+            "http://w2:8000".to_string(),
+            WorkerType::Regular,
+        ));
+        let mut caps = with_adapter.capabilities();
+        caps.lora_adapters.insert("sql".to_string());
+        with_adapter.set_capabilities(caps);
+
+        let workers = vec![with_adapter.clone(), without_adapter.clone()];
+        let preferred = LoraManager::prefer_loaded(&workers, "sql");
+        assert_eq!(preferred.len(), 1);
+        assert_eq!(preferred[0].url(), "http://w1:8000");
+
+        // No worker has the adapter: every candidate stays eligible
+        assert_eq!(LoraManager::prefer_loaded(&workers, "chat").len(), 2);
+    }
+
+    #[test]
+    fn test_requested_and_target_adapter() {
+        let path = LoRAPath::Single(Some("/adapters/sql".to_string()));
+        assert_eq!(
+            LoraManager::requested_adapter(Some(&path)).unwrap(),
+            Some("/adapters/sql".to_string())
+        );
+        assert_eq!(LoraManager::requested_adapter(None).unwrap(), None);
+
+        let mut body = json!({"model": "llama", "prompt": "hi"});
+        LoraManager::target_adapter(&mut body, "/adapters/sql");
+        assert_eq!(body["model"], "/adapters/sql");
+    }
+
+    #[tokio::test]
+    async fn test_ensure_loaded_updates_all_dp_ranks() {
+        let calls: Calls = Arc::new(Mutex::new(Vec::new()));
+        let base_url = start_lora_mock_server(calls.clone()).await;
+        let registry = Arc::new(WorkerRegistry::new());
+        for rank in 0..2 {
+            registry.register(Arc::new(BasicWorker::new(
+                format!("{}@{}", base_url, rank),
+                WorkerType::Regular,
+            )));
+        }
+        let manager = test_manager(registry.clone(), 4);
+
+        let rank0 = registry.get_by_url(&format!("{}@0", base_url)).unwrap();
+        manager.ensure_loaded(&rank0, "sql").await.unwrap();
+        // Already loaded: no second call
+        manager.ensure_loaded(&rank0, "sql").await.unwrap();
+
+        assert_eq!(*calls.lock().unwrap(), vec!["load:sql".to_string()]);
+        let rank1 = registry.get_by_url(&format!("{}@1", base_url)).unwrap();
+        assert!(rank1.capabilities().lora_adapters.contains("sql"));
+    }
+
+    #[tokio::test]
+    async fn test_ensure_loaded_evicts_lru() {
+        let calls: Calls = Arc::new(Mutex::new(Vec::new()));
+        let base_url = start_lora_mock_server(calls.clone()).await;
+        let registry = Arc::new(WorkerRegistry::new());
+        registry.register(Arc::new(BasicWorker::new(
+            base_url.clone(),
+            WorkerType::Regular,
+        )));
+        let worker = registry.get_by_url(&base_url).unwrap();
+        let manager = test_manager(registry, 2);
+
+        manager.ensure_loaded(&worker, "a").await.unwrap();
+        manager.ensure_loaded(&worker, "b").await.unwrap();
+        // Use "a" again so "b" becomes least recently used
+        manager.ensure_loaded(&worker, "a").await.unwrap();
+        manager.ensure_loaded(&worker, "c").await.unwrap();
+
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec![
+                "load:a".to_string(),
+                "load:b".to_string(),
+                "unload:b".to_string(),
+                "load:c".to_string(),
+            ]
+        );
+        let adapters = worker.capabilities().lora_adapters;
+        assert!(adapters.contains("a") && adapters.contains("c"));
+        assert!(!adapters.contains("b"));
+    }
+
+    #[tokio::test]
+    async fn test_ensure_loaded_skips_busy_adapters() {
+        let calls: Calls = Arc::new(Mutex::new(Vec::new()));
+        let base_url = start_lora_mock_server(calls.clone()).await;
+        let registry = Arc::new(WorkerRegistry::new());
+        registry.register(Arc::new(BasicWorker::new(
+            base_url.clone(),
+            WorkerType::Regular,
+        )));
+        let worker = registry.get_by_url(&base_url).unwrap();
+        let manager = test_manager(registry, 2);
+
+        // "a" is least recently used but still generating
+        let lease_a = manager.ensure_loaded(&worker, "a").await.unwrap();
+        manager.ensure_loaded(&worker, "b").await.unwrap();
+        let lease_c = manager.ensure_loaded(&worker, "c").await.unwrap();
+        assert_eq!(calls.lock().unwrap()[2], "unload:b");
+
+        // Every loaded adapter is busy: refuse instead of evicting
+        assert!(manager.ensure_loaded(&worker, "d").await.is_err());
+        assert!(worker.capabilities().lora_adapters.contains("a"));
+
+        drop(lease_a);
+        let _lease_d = manager.ensure_loaded(&worker, "d").await.unwrap();
+        assert_eq!(calls.lock().unwrap()[4], "unload:a");
+        drop(lease_c);
+    }
+
+    #[tokio::test]
+    async fn test_ensure_loaded_reports_failure() {
+        let registry = Arc::new(WorkerRegistry::new());
+        registry.register(Arc::new(BasicWorker::new(
+            "http://127.0.0.1:1".to_string(),
+            WorkerType::Regular,
+        )));
+        let worker = registry.get_by_url("http://127.0.0.1:1").unwrap();
+        let manager = test_manager(registry, 2);
+
+        let result = manager.ensure_loaded(&worker, "sql").await;
+        assert!(result.is_err());
+        assert!(worker.capabilities().lora_adapters.is_empty());
+    }
+}
@@ -4,24 +4,77 @@
 //! - Worker trait and implementations
 //! - Error types
 //! - Circuit breaker for reliability
+//! - Worker capabilities for request filtering
+//! - LoRA adapter lifecycle management
+//! - Worker topology for locality-aware routing
+//! - PD capacity controller for prefill/decode role flips
+//! - Keyed rate limiting per API key, tenant and model
+//! - Token rate limits and quotas
+//! - Priority classes and fair queuing for queued requests
+//! - JWT and API key validation
+//! - Static API keys with scopes and model allowlists
+//! - TLS termination and worker connection TLS
+//! - Audit trail of requests and admin actions
+//! - Config files polled for changes
 //! - Common utilities
 
+pub mod admission_queue;
+pub mod api_keys;
+pub mod audit;
+pub mod auth;
+pub mod capabilities;
 pub mod circuit_breaker;
+pub mod content_policy;
 pub mod error;
+pub mod keyed_rate_limiter;
+pub mod lora;
+pub mod pd_capacity;
 pub mod retry;
+pub mod tls;
 pub mod token_bucket;
+pub mod token_rate_limiter;
+pub mod topology;
+pub mod watched_file;
 pub mod worker;
 pub mod worker_registry;
 
 // Re-export commonly used types at the module level
+pub use admission_queue::{
+    AdmissionQueue, PriorityClass, PriorityClassifier, DEFAULT_PRIORITY_CLASS,
+};
+pub use api_keys::{ApiKeyEntry, ApiKeyScope, ApiKeyStore};
+pub use audit::{
+    AdminAction, AdminRecord, AuditEvent, AuditLog, PrefilledBy, RequestRecord, ServedBy,
+};
+pub use auth::{bearer_token, CallerIdentity, JwtValidator, ValidationCache, VerifiedToken};
+pub use capabilities::{RequestRequirements, WorkerCapabilities, WorkerEndpoint};
 pub use circuit_breaker::{
     CircuitBreaker, CircuitBreakerConfig, CircuitBreakerStats, CircuitState,
 };
+pub use content_policy::{
+    ContentPolicy, ContentPolicyConfig, FilterRequest, CONTENT_POLICY_TAGS_HEADER,
+};
 pub use error::{WorkerError, WorkerResult};
+pub use keyed_rate_limiter::{
+    KeyedRateLimitConfig, KeyedRateLimiter, RateLimitDecision, RateLimitDimension, RateLimitKeys,
+    RateLimitStatus,
+};
+pub use lora::LoraManager;
+pub use pd_capacity::{
+    CapacityAction, CapacityRecommendation, HttpRoleActuator, PdCapacityController, PdRole,
+    RoleActuator,
+};
 pub use retry::{is_retryable_status, BackoffCalculator, RetryError, RetryExecutor};
+pub use tls::{ServerTls, TlsListener, UpstreamTls};
+pub use token_rate_limiter::{
+    QuotaPeriod, StreamUsageScanner, TokenLimitError, TokenRateLimiter, TokenReservation,
+    TokenUsage,
+};
+pub use topology::{Locality, WorkerTopology};
+pub use watched_file::{parse_yaml_or_json, WatchedFiles};
 pub use worker::{
     start_health_checker, BasicWorker, ConnectionMode, DPAwareWorker, HealthChecker, HealthConfig,
-    Worker, WorkerCollection, WorkerFactory, WorkerLoadGuard, WorkerType,
+    Worker, WorkerCollection, WorkerFactory, WorkerLoadGuard, WorkerLoadLease, WorkerType,
 };
 pub use worker_registry::{WorkerId, WorkerRegistry, WorkerRegistryStats};
-pub mod rate_monitor;
\ No newline at end of file
+pub mod rate_monitor;
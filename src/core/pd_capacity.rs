@@ -0,0 +1,817 @@
+//! Prefill/decode capacity controller
+//!
+//! Tracks how long requests spend in prefill (queueing plus compute) and the
+//! decode inter-token latency, each relative to its target. When one role is
+//! clearly more overloaded than the other, the controller recommends moving a
+//! worker from the less loaded role to the more loaded one. A [`RoleActuator`]
+//! can carry out the flip, after which the worker is re-registered under its
+//! new role.
+
+use crate::config::PdCapacityConfig;
+use crate::core::{BasicWorker, CircuitBreakerConfig, Worker, WorkerRegistry, WorkerType};
+use crate::metrics::RouterMetrics;
+use crate::policies::{LoadBalancingPolicy, PolicyRegistry};
+use crate::routers::http::dp_utils::parse_worker_url;
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures_util::{Stream, StreamExt};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+/// Samples kept per signal regardless of the window length
+const MAX_SAMPLES: usize = 10_000;
+
+/// Worker label giving the bootstrap port a worker would use as prefill
+pub const BOOTSTRAP_PORT_LABEL: &str = "bootstrap_port";
+
+/// Role a worker can be switched to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PdRole {
+    Prefill,
+    Decode,
+}
+
+impl PdRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PdRole::Prefill => "prefill",
+            PdRole::Decode => "decode",
+        }
+    }
+
+    fn of(worker_type: &WorkerType) -> Option<Self> {
+        match worker_type {
+            WorkerType::Prefill { .. } => Some(PdRole::Prefill),
+            WorkerType::Decode => Some(PdRole::Decode),
+            WorkerType::Regular => None,
+        }
+    }
+
+    fn worker_type(&self, bootstrap_port: Option<u16>) -> WorkerType {
+        match self {
+            PdRole::Prefill => WorkerType::Prefill { bootstrap_port },
+            PdRole::Decode => WorkerType::Decode,
+        }
+    }
+}
+
+/// Outcome of a capacity evaluation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CapacityAction {
+    /// Both roles are within their targets or equally loaded
+    Hold,
+    /// Prefill is the bottleneck: move a decode worker to prefill
+    DecodeToPrefill,
+    /// Decode is the bottleneck: move a prefill worker to decode
+    PrefillToDecode,
+}
+
+impl CapacityAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CapacityAction::Hold => "hold",
+            CapacityAction::DecodeToPrefill => "decode_to_prefill",
+            CapacityAction::PrefillToDecode => "prefill_to_decode",
+        }
+    }
+
+    fn roles(&self) -> Option<(PdRole, PdRole)> {
+        match self {
+            CapacityAction::Hold => None,
+            CapacityAction::DecodeToPrefill => Some((PdRole::Decode, PdRole::Prefill)),
+            CapacityAction::PrefillToDecode => Some((PdRole::Prefill, PdRole::Decode)),
+        }
+    }
+}
+
+/// Recommendation produced by [`PdCapacityController::evaluate`]
+#[derive(Debug, Clone, Serialize)]
+pub struct CapacityRecommendation {
+    pub action: CapacityAction,
+    /// Worker (base URL, without DP rank) to flip
+    pub worker_url: Option<String>,
+    pub reason: String,
+    pub prefill_time_ms: Option<f64>,
+    pub decode_itl_ms: Option<f64>,
+    pub prefill_samples: usize,
+    pub decode_samples: usize,
+    pub prefill_workers: usize,
+    pub decode_workers: usize,
+}
+
+/// Performs a role flip on a worker
+///
+/// Implementations typically ask the worker (or its supervisor) to restart in
+/// the new role. The controller updates the worker registry once this returns.
+#[async_trait]
+pub trait RoleActuator: Send + Sync + fmt::Debug {
+    async fn switch_role(&self, worker_url: &str, role: PdRole) -> Result<(), String>;
+}
+
+/// Actuator that POSTs `{"role": "prefill"|"decode"}` to an endpoint on the worker
+///
+/// Matches restart hooks such as the `/restart` endpoint of the spec-route
+/// mock worker.
+#[derive(Debug)]
+pub struct HttpRoleActuator {
+    client: reqwest::Client,
+    path: String,
+    api_key: Option<String>,
+}
+
+impl HttpRoleActuator {
+    pub fn new(client: reqwest::Client, path: String, api_key: Option<String>) -> Self {
+        Self {
+            client,
+            path,
+            api_key,
+        }
+    }
+}
+
+#[async_trait]
+impl RoleActuator for HttpRoleActuator {
+    async fn switch_role(&self, worker_url: &str, role: PdRole) -> Result<(), String> {
+        let url = format!("{}{}", worker_url.trim_end_matches('/'), self.path);
+        let mut request = self
+            .client
+            .post(&url)
+            .json(&serde_json::json!({ "role": role.as_str() }));
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| format!("Role flip request to {} failed: {}", url, e))?;
+        if !response.status().is_success() {
+            return Err(format!(
+                "Role flip request to {} returned {}",
+                url,
+                response.status()
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Timing samples over a sliding window
+#[derive(Debug, Default)]
+struct SampleWindow {
+    samples: Mutex<VecDeque<(Instant, f64)>>,
+}
+
+impl SampleWindow {
+    fn record(&self, value_ms: f64) {
+        let mut samples = self.samples.lock().unwrap();
+        if samples.len() >= MAX_SAMPLES {
+            samples.pop_front();
+        }
+        samples.push_back((Instant::now(), value_ms));
+    }
+
+    /// Sample count and mean over the window, dropping older samples
+    fn summary(&self, window: Duration) -> (usize, Option<f64>) {
+        let mut samples = self.samples.lock().unwrap();
+        while samples.front().is_some_and(|(at, _)| at.elapsed() > window) {
+            samples.pop_front();
+        }
+        if samples.is_empty() {
+            return (0, None);
+        }
+        let sum: f64 = samples.iter().map(|(_, value)| value).sum();
+        (samples.len(), Some(sum / samples.len() as f64))
+    }
+
+    fn clear(&self) {
+        self.samples.lock().unwrap().clear();
+    }
+}
+
+/// Recommends and optionally performs prefill/decode role flips
+pub struct PdCapacityController {
+    config: PdCapacityConfig,
+    worker_registry: Arc<WorkerRegistry>,
+    policy_registry: Arc<PolicyRegistry>,
+    circuit_breaker_config: CircuitBreakerConfig,
+    actuator: Option<Arc<dyn RoleActuator>>,
+    prefill_times: SampleWindow,
+    decode_itls: SampleWindow,
+    last_flip: Mutex<Option<Instant>>,
+    /// Prefill requests carry the prefill worker's bootstrap port
+    bootstrap_protocol: bool,
+    /// Bootstrap ports of workers when they were last registered as prefill
+    bootstrap_ports: Mutex<HashMap<String, u16>>,
+}
+
+impl fmt::Debug for PdCapacityController {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PdCapacityController")
+            .field("config", &self.config)
+            .field("actuator", &self.actuator)
+            .finish()
+    }
+}
+
+impl PdCapacityController {
+    pub fn new(
+        config: PdCapacityConfig,
+        worker_registry: Arc<WorkerRegistry>,
+        policy_registry: Arc<PolicyRegistry>,
+        circuit_breaker_config: CircuitBreakerConfig,
+        actuator: Option<Arc<dyn RoleActuator>>,
+    ) -> Self {
+        Self {
+            config,
+            worker_registry,
+            policy_registry,
+            circuit_breaker_config,
+            actuator,
+            prefill_times: SampleWindow::default(),
+            decode_itls: SampleWindow::default(),
+            last_flip: Mutex::new(None),
+            bootstrap_protocol: false,
+            bootstrap_ports: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Require a known bootstrap port before flipping a worker to prefill
+    pub fn with_bootstrap_protocol(mut self, enabled: bool) -> Self {
+        self.bootstrap_protocol = enabled;
+        self
+    }
+
+    pub fn config(&self) -> &PdCapacityConfig {
+        &self.config
+    }
+
+    /// Record the time a request spent waiting for and running prefill
+    pub fn record_prefill_time(&self, duration: Duration) {
+        self.prefill_times.record(duration.as_secs_f64() * 1000.0);
+    }
+
+    /// Record the gap between two decode stream chunks
+    pub fn record_inter_token_latency(&self, duration: Duration) {
+        self.decode_itls.record(duration.as_secs_f64() * 1000.0);
+    }
+
+    /// Record inter-token latency from the chunks of a decode stream
+    ///
+    /// With `first_chunk_is_prefill`, the delay until the first chunk is
+    /// recorded as prefill time; use it when the prefill stage is not timed
+    /// separately (the decode worker only starts streaming once the KV cache
+    /// has arrived).
+    pub fn observe_stream<S>(
+        self: &Arc<Self>,
+        stream: S,
+        started: Instant,
+        first_chunk_is_prefill: bool,
+    ) -> Pin<Box<dyn Stream<Item = Result<Bytes, reqwest::Error>> + Send>>
+    where
+        S: Stream<Item = Result<Bytes, reqwest::Error>> + Send + 'static,
+    {
+        let controller = Arc::clone(self);
+        let mut last_chunk: Option<Instant> = None;
+        Box::pin(stream.inspect(move |chunk| {
+            if chunk.is_err() {
+                return;
+            }
+            let now = Instant::now();
+            match last_chunk {
+                Some(previous) => controller.record_inter_token_latency(now - previous),
+                None if first_chunk_is_prefill => controller.record_prefill_time(now - started),
+                None => {}
+            }
+            last_chunk = Some(now);
+        }))
+    }
+
+    /// Compare both roles against their targets and recommend a flip
+    pub fn evaluate(&self) -> CapacityRecommendation {
+        let window = Duration::from_secs(self.config.window_secs);
+        let (prefill_samples, prefill_time_ms) = self.prefill_times.summary(window);
+        let (decode_samples, decode_itl_ms) = self.decode_itls.summary(window);
+        let prefill_workers = self.group_by_base_url(PdRole::Prefill);
+        let decode_workers = self.group_by_base_url(PdRole::Decode);
+
+        RouterMetrics::set_pd_capacity_signals(
+            prefill_time_ms.unwrap_or(0.0),
+            decode_itl_ms.unwrap_or(0.0),
+        );
+
+        let mut recommendation = CapacityRecommendation {
+            action: CapacityAction::Hold,
+            worker_url: None,
+            reason: String::new(),
+            prefill_time_ms,
+            decode_itl_ms,
+            prefill_samples,
+            decode_samples,
+            prefill_workers: prefill_workers.len(),
+            decode_workers: decode_workers.len(),
+        };
+
+        let (Some(prefill_ms), Some(decode_ms)) = (prefill_time_ms, decode_itl_ms) else {
+            recommendation.reason = "No prefill or decode timings recorded yet".to_string();
+            return self.finish(recommendation);
+        };
+        if prefill_samples < self.config.min_samples || decode_samples < self.config.min_samples {
+            recommendation.reason = format!(
+                "Need {} samples per role (have {} prefill, {} decode)",
+                self.config.min_samples, prefill_samples, decode_samples
+            );
+            return self.finish(recommendation);
+        }
+
+        let prefill_pressure = prefill_ms / self.config.prefill_time_target_ms;
+        let decode_pressure = decode_ms / self.config.decode_itl_target_ms;
+        let ratio = self.config.imbalance_ratio;
+        let action = if prefill_pressure > 1.0 && prefill_pressure >= ratio * decode_pressure {
+            CapacityAction::DecodeToPrefill
+        } else if decode_pressure > 1.0 && decode_pressure >= ratio * prefill_pressure {
+            CapacityAction::PrefillToDecode
+        } else {
+            recommendation.reason = format!(
+                "Balanced: prefill at {:.2}x target, decode at {:.2}x target",
+                prefill_pressure, decode_pressure
+            );
+            return self.finish(recommendation);
+        };
+
+        let mut donors = match action {
+            CapacityAction::DecodeToPrefill => decode_workers,
+            _ => prefill_workers,
+        };
+        if donors.len() <= self.config.min_workers_per_role {
+            recommendation.reason = format!(
+                "{} would benefit from another worker, but only {} {} worker(s) remain",
+                if action == CapacityAction::DecodeToPrefill {
+                    "Prefill"
+                } else {
+                    "Decode"
+                },
+                donors.len(),
+                if action == CapacityAction::DecodeToPrefill {
+                    "decode"
+                } else {
+                    "prefill"
+                }
+            );
+            return self.finish(recommendation);
+        }
+
+        // A new prefill worker is useless without a bootstrap port to send
+        if action == CapacityAction::DecodeToPrefill && self.bootstrap_protocol {
+            donors.retain(|url, workers| self.bootstrap_port(url, workers).is_some());
+            if donors.is_empty() {
+                recommendation.reason =
+                    "Prefill would benefit from another worker, but no decode worker has a known bootstrap port"
+                        .to_string();
+                return self.finish(recommendation);
+            }
+        }
+
+        // Least loaded donor, preferring healthy workers
+        let candidate = donors
+            .iter()
+            .min_by_key(|(_, workers)| {
+                let healthy = workers.iter().all(|w| w.is_healthy());
+                let load: usize = workers.iter().map(|w| w.load()).sum();
+                (!healthy, load)
+            })
+            .map(|(url, _)| url.clone());
+
+        recommendation.action = action;
+        recommendation.worker_url = candidate;
+        recommendation.reason = format!(
+            "Prefill at {:.2}x target ({:.1}ms), decode at {:.2}x target ({:.1}ms)",
+            prefill_pressure, prefill_ms, decode_pressure, decode_ms
+        );
+        self.finish(recommendation)
+    }
+
+    fn finish(&self, recommendation: CapacityRecommendation) -> CapacityRecommendation {
+        RouterMetrics::set_pd_role_flip_recommendation(match recommendation.action {
+            CapacityAction::Hold => None,
+            action => Some(action.as_str()),
+        });
+        recommendation
+    }
+
+    /// Registered workers of a role, grouped by base URL so DP ranks flip together
+    fn group_by_base_url(&self, role: PdRole) -> BTreeMap<String, Vec<Arc<dyn Worker>>> {
+        let workers = match role {
+            PdRole::Prefill => self.worker_registry.get_prefill_workers(),
+            PdRole::Decode => self.worker_registry.get_decode_workers(),
+        };
+        let mut groups: BTreeMap<String, Vec<Arc<dyn Worker>>> = BTreeMap::new();
+        for worker in workers {
+            groups
+                .entry(parse_worker_url(worker.url()).0)
+                .or_default()
+                .push(worker);
+        }
+        groups
+    }
+
+    /// Evaluate and, if a flip is recommended, perform it
+    pub async fn apply_recommended(&self) -> Result<CapacityRecommendation, String> {
+        let recommendation = self.evaluate();
+        self.apply(&recommendation).await?;
+        Ok(recommendation)
+    }
+
+    /// Perform the flip described by a recommendation
+    pub async fn apply(&self, recommendation: &CapacityRecommendation) -> Result<(), String> {
+        let (Some((from, to)), Some(worker_url)) =
+            (recommendation.action.roles(), &recommendation.worker_url)
+        else {
+            return Err(format!(
+                "No role flip recommended: {}",
+                recommendation.reason
+            ));
+        };
+        let actuator = self
+            .actuator
+            .as_ref()
+            .ok_or_else(|| "No role flip actuator configured".to_string())?;
+        if let Some(last) = *self.last_flip.lock().unwrap() {
+            let cooldown = Duration::from_secs(self.config.cooldown_secs);
+            if last.elapsed() < cooldown {
+                return Err(format!(
+                    "Last role flip was {}s ago (cooldown {}s)",
+                    last.elapsed().as_secs(),
+                    self.config.cooldown_secs
+                ));
+            }
+        }
+
+        let bootstrap_port = match to {
+            PdRole::Prefill if self.bootstrap_protocol => {
+                let workers = self.group_by_base_url(from).remove(worker_url);
+                let port = self.bootstrap_port(worker_url, workers.as_deref().unwrap_or_default());
+                Some(port.ok_or_else(|| {
+                    format!(
+                        "Cannot flip {} to prefill: its bootstrap port is unknown",
+                        worker_url
+                    )
+                })?)
+            }
+            _ => None,
+        };
+
+        let direction = recommendation.action.as_str();
+        if let Err(e) = actuator.switch_role(worker_url, to).await {
+            warn!("Failed to flip {} to {}: {}", worker_url, to.as_str(), e);
+            RouterMetrics::record_pd_role_flip(direction, false);
+            return Err(e);
+        }
+
+        let moved = self.reassign_role(worker_url, from, to, bootstrap_port);
+        info!(
+            "Flipped {} from {} to {} ({} worker(s) re-registered)",
+            worker_url,
+            from.as_str(),
+            to.as_str(),
+            moved
+        );
+        RouterMetrics::record_pd_role_flip(direction, true);
+        *self.last_flip.lock().unwrap() = Some(Instant::now());
+        // Samples taken before the flip describe the old split
+        self.prefill_times.clear();
+        self.decode_itls.clear();
+        Ok(())
+    }
+
+    /// Bootstrap port a worker would serve as prefill: from its last prefill
+    /// registration, its `bootstrap_port` label, or the configured default
+    fn bootstrap_port(&self, base_url: &str, workers: &[Arc<dyn Worker>]) -> Option<u16> {
+        if let Some(port) = self.bootstrap_ports.lock().unwrap().get(base_url) {
+            return Some(*port);
+        }
+        workers
+            .iter()
+            .find_map(|w| w.metadata().labels.get(BOOTSTRAP_PORT_LABEL)?.parse().ok())
+            .or(self.config.bootstrap_port)
+    }
+
+    /// Re-register every DP rank of a worker under its new role
+    fn reassign_role(
+        &self,
+        base_url: &str,
+        from: PdRole,
+        to: PdRole,
+        bootstrap_port: Option<u16>,
+    ) -> usize {
+        let workers: Vec<Arc<dyn Worker>> = self
+            .worker_registry
+            .get_all()
+            .into_iter()
+            .filter(|w| {
+                parse_worker_url(w.url()).0 == base_url
+                    && PdRole::of(&w.worker_type()) == Some(from)
+            })
+            .collect();
+
+        for old in &workers {
+            // Remember the port in case the worker is flipped back
+            if let WorkerType::Prefill {
+                bootstrap_port: Some(port),
+            } = old.worker_type()
+            {
+                self.bootstrap_ports
+                    .lock()
+                    .unwrap()
+                    .insert(base_url.to_string(), port);
+            }
+            let model_id = old.model_id().to_string();
+            if self.worker_registry.remove_by_url(old.url()).is_some() {
+                self.policy_registry.on_worker_removed(&model_id);
+                if let Some(policy) = self.policy_registry.get_policy(&model_id) {
+                    if let Some(cache_aware) = policy
+                        .as_any()
+                        .downcast_ref::<crate::policies::CacheAwarePolicy>()
+                    {
+                        cache_aware.remove_worker_by_url(old.url());
+                    }
+                }
+            }
+
+            let worker = BasicWorker::new(old.url().to_string(), to.worker_type(bootstrap_port))
+                .with_labels(old.metadata().labels.clone())
+                .with_health_config(old.metadata().health_config.clone())
+                .with_circuit_breaker_config(self.circuit_breaker_config.clone());
+            worker.set_capabilities(old.capabilities());
+            // The worker restarts into its new role; the health checker brings it back
+            worker.set_healthy(false);
+            let worker: Arc<dyn Worker> = Arc::new(worker);
+            self.worker_registry.register(worker.clone());
+
+            let model_id = worker.model_id();
+            let policy = self.policy_registry.on_worker_added(model_id, None);
+            if let Some(cache_aware) = policy
+                .as_any()
+                .downcast_ref::<crate::policies::CacheAwarePolicy>()
+            {
+                cache_aware.init_workers(&self.worker_registry.get_by_model_fast(model_id));
+            }
+        }
+        workers.len()
+    }
+
+    /// Start periodic evaluation, applying flips when `auto_apply` is set
+    pub fn start(self: Arc<Self>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_secs(self.config.evaluation_interval_secs));
+            // The first tick completes immediately
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                let recommendation = self.evaluate();
+                if recommendation.action == CapacityAction::Hold {
+                    debug!("PD capacity: {}", recommendation.reason);
+                    continue;
+                }
+                info!(
+                    "PD capacity recommends {} for {:?}: {}",
+                    recommendation.action.as_str(),
+                    recommendation.worker_url,
+                    recommendation.reason
+                );
+                if self.config.auto_apply {
+                    if let Err(e) = self.apply(&recommendation).await {
+                        warn!("PD capacity flip not applied: {}", e);
+                    }
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PolicyConfig;
+
+    #[derive(Debug, Default)]
+    struct RecordingActuator {
+        calls: Mutex<Vec<(String, PdRole)>>,
+    }
+
+    #[async_trait]
+    impl RoleActuator for RecordingActuator {
+        async fn switch_role(&self, worker_url: &str, role: PdRole) -> Result<(), String> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push((worker_url.to_string(), role));
+            Ok(())
+        }
+    }
+
+    fn setup(
+        prefills: &[&str],
+        decodes: &[&str],
+        actuator: Option<Arc<dyn RoleActuator>>,
+    ) -> PdCapacityController {
+        let registry = Arc::new(WorkerRegistry::new());
+        for url in prefills {
+            registry.register(Arc::new(BasicWorker::new(
+                url.to_string(),
+                WorkerType::Prefill {
+                    bootstrap_port: Some(8998),
+                },
+            )));
+        }
+        for url in decodes {
+            registry.register(Arc::new(BasicWorker::new(
+                url.to_string(),
+                WorkerType::Decode,
+            )));
+        }
+        let config = PdCapacityConfig {
+            enabled: true,
+            min_samples: 3,
+            prefill_time_target_ms: 100.0,
+            decode_itl_target_ms: 10.0,
+            ..Default::default()
+        };
+        PdCapacityController::new(
+            config,
+            registry,
+            Arc::new(PolicyRegistry::new(PolicyConfig::RoundRobin)),
+            CircuitBreakerConfig::default(),
+            actuator,
+        )
+    }
+
+    fn feed(controller: &PdCapacityController, prefill_ms: u64, itl_ms: u64) {
+        for _ in 0..3 {
+            controller.record_prefill_time(Duration::from_millis(prefill_ms));
+            controller.record_inter_token_latency(Duration::from_millis(itl_ms));
+        }
+    }
+
+    #[test]
+    fn test_holds_without_enough_samples() {
+        let controller = setup(&["http://p1:8000"], &["http://d1:8000"], None);
+        assert_eq!(controller.evaluate().action, CapacityAction::Hold);
+
+        controller.record_prefill_time(Duration::from_millis(500));
+        controller.record_inter_token_latency(Duration::from_millis(5));
+        let recommendation = controller.evaluate();
+        assert_eq!(recommendation.action, CapacityAction::Hold);
+        assert_eq!(recommendation.prefill_samples, 1);
+    }
+
+    #[test]
+    fn test_recommends_flip_toward_overloaded_role() {
+        let controller = setup(
+            &["http://p1:8000", "http://p2:8000"],
+            &["http://d1:8000", "http://d2:8000"],
+            None,
+        );
+        controller.worker_registry.get_decode_workers()[0].increment_load();
+        let busy_decode = controller.worker_registry.get_decode_workers()[0]
+            .url()
+            .to_string();
+
+        // Prefill 5x over target, decode within target
+        feed(&controller, 500, 5);
+        let recommendation = controller.evaluate();
+        assert_eq!(recommendation.action, CapacityAction::DecodeToPrefill);
+        let url = recommendation.worker_url.unwrap();
+        assert!(url.starts_with("http://d"));
+        assert_ne!(url, busy_decode);
+
+        // Decode 4x over target, prefill within target
+        let controller = setup(
+            &["http://p1:8000", "http://p2:8000"],
+            &["http://d1:8000"],
+            None,
+        );
+        feed(&controller, 50, 40);
+        let recommendation = controller.evaluate();
+        assert_eq!(recommendation.action, CapacityAction::PrefillToDecode);
+        assert!(recommendation.worker_url.unwrap().starts_with("http://p"));
+    }
+
+    #[test]
+    fn test_keeps_minimum_workers_per_role() {
+        let controller = setup(&["http://p1:8000"], &["http://d1:8000"], None);
+        feed(&controller, 500, 5);
+        let recommendation = controller.evaluate();
+        assert_eq!(recommendation.action, CapacityAction::Hold);
+        assert!(recommendation.reason.contains("decode worker"));
+    }
+
+    #[tokio::test]
+    async fn test_apply_flips_worker_role() {
+        let actuator = Arc::new(RecordingActuator::default());
+        let controller = setup(
+            &["http://p1:8000"],
+            &["http://d1:8000@0", "http://d1:8000@1", "http://d2:8000"],
+            Some(actuator.clone()),
+        );
+        controller
+            .worker_registry
+            .get_by_url("http://d2:8000")
+            .unwrap()
+            .increment_load();
+
+        feed(&controller, 500, 5);
+        let recommendation = controller.apply_recommended().await.unwrap();
+        assert_eq!(recommendation.worker_url.as_deref(), Some("http://d1:8000"));
+        assert_eq!(
+            *actuator.calls.lock().unwrap(),
+            vec![("http://d1:8000".to_string(), PdRole::Prefill)]
+        );
+
+        let registry = &controller.worker_registry;
+        assert_eq!(registry.get_prefill_workers().len(), 3);
+        assert_eq!(registry.get_decode_workers().len(), 1);
+        let flipped = registry.get_by_url("http://d1:8000@1").unwrap();
+        assert!(matches!(flipped.worker_type(), WorkerType::Prefill { .. }));
+        assert!(!flipped.is_healthy());
+
+        // Samples were reset and the cooldown applies to the next flip
+        assert_eq!(controller.evaluate().action, CapacityAction::Hold);
+        feed(&controller, 5, 100);
+        let err = controller.apply_recommended().await.unwrap_err();
+        assert!(err.contains("cooldown"));
+    }
+
+    #[tokio::test]
+    async fn test_flip_to_prefill_keeps_bootstrap_port() {
+        use crate::routers::http::pd_transfer::{
+            BootstrapProtocol, KvTransferProtocol, TransferPair,
+        };
+
+        let actuator = Arc::new(RecordingActuator::default());
+        let controller = setup(
+            &["http://p1:8000"],
+            &["http://d1:8000", "http://d2:8000"],
+            Some(actuator.clone()),
+        )
+        .with_bootstrap_protocol(true);
+
+        // No decode worker has a known bootstrap port
+        feed(&controller, 500, 5);
+        let recommendation = controller.evaluate();
+        assert_eq!(recommendation.action, CapacityAction::Hold);
+        assert!(recommendation.reason.contains("bootstrap port"));
+
+        let labelled: Arc<dyn Worker> = Arc::new(
+            BasicWorker::new("http://d2:8000".to_string(), WorkerType::Decode).with_labels(
+                HashMap::from([(BOOTSTRAP_PORT_LABEL.to_string(), "9000".to_string())]),
+            ),
+        );
+        controller.worker_registry.remove_by_url("http://d2:8000");
+        controller.worker_registry.register(labelled);
+        let recommendation = controller.apply_recommended().await.unwrap();
+        assert_eq!(recommendation.worker_url.as_deref(), Some("http://d2:8000"));
+
+        let flipped = controller
+            .worker_registry
+            .get_by_url("http://d2:8000")
+            .unwrap();
+        let decode = controller
+            .worker_registry
+            .get_by_url("http://d1:8000")
+            .unwrap();
+        let mut request = serde_json::json!({"prompt": "hi"});
+        BootstrapProtocol
+            .prepare_request(
+                &mut request,
+                &TransferPair {
+                    prefill: flipped.as_ref(),
+                    decode: decode.as_ref(),
+                    batch_size: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(request["bootstrap_port"], 9000);
+        assert_eq!(request["bootstrap_host"], "d2");
+    }
+
+    #[tokio::test]
+    async fn test_apply_requires_actuator() {
+        let controller = setup(
+            &["http://p1:8000"],
+            &["http://d1:8000", "http://d2:8000"],
+            None,
+        );
+        feed(&controller, 500, 5);
+        let err = controller.apply_recommended().await.unwrap_err();
+        assert!(err.contains("actuator"));
+    }
+}
@@ -0,0 +1,844 @@
+//! Token rate limits and quotas
+//!
+//! Request counts are a poor proxy for GPU cost, so classes in the keyed rate
+//! limit file can also cap tokens per minute and tokens per UTC day or month.
+//!
+//! Prompt tokens are estimated and taken from the per-minute buckets before
+//! dispatch. Once the response reports its `usage` (or its stream ends) the
+//! reservation is reconciled with the actual prompt and completion tokens, and
+//! the total is charged to the daily and monthly quotas. A request is admitted
+//! while its buckets have room for the prompt, so a long completion can leave
+//! a key in debt that its next requests wait out. Until it is settled, the
+//! prompt of an admitted request counts against the quotas, so concurrent
+//! requests cannot overshoot them together.
+//!
+//! Quota usage is kept in memory and, with `quota_store` set, written to a JSON
+//! file every `quota_flush_interval_secs` and loaded again on startup. Buckets
+//! key on the authenticated caller the request limiter uses, never on the
+//! bearer token, so the file holds no secrets.
+
+use super::keyed_rate_limiter::{
+    Bucket, BucketKey, KeyedRateLimitConfig, RateLimitClass, RateLimitDimension, RateLimitKeys,
+    RateLimitStatus,
+};
+use crate::metrics::RouterMetrics;
+use chrono::{DateTime, Datelike, Months, NaiveTime, Utc};
+use lru::LruCache;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+/// Calendar period of a token quota
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotaPeriod {
+    Daily,
+    Monthly,
+}
+
+impl QuotaPeriod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QuotaPeriod::Daily => "daily",
+            QuotaPeriod::Monthly => "monthly",
+        }
+    }
+
+    fn limit(&self, class: &RateLimitClass) -> Option<u64> {
+        match self {
+            QuotaPeriod::Daily => class.daily_tokens,
+            QuotaPeriod::Monthly => class.monthly_tokens,
+        }
+    }
+
+    /// Time until the period containing `now` ends
+    fn remaining(&self, now: DateTime<Utc>) -> Duration {
+        let today = now.date_naive();
+        let next = match self {
+            QuotaPeriod::Daily => today.succ_opt(),
+            QuotaPeriod::Monthly => today
+                .with_day(1)
+                .and_then(|first| first.checked_add_months(Months::new(1))),
+        };
+        next.map(|date| date.and_time(NaiveTime::MIN).and_utc() - now)
+            .and_then(|delta| delta.to_std().ok())
+            .unwrap_or_default()
+    }
+}
+
+impl fmt::Display for QuotaPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Token counts reported by a response
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TokenUsage {
+    /// Prompt tokens as counted by the worker, if reported
+    pub prompt_tokens: Option<u64>,
+    pub completion_tokens: u64,
+}
+
+impl TokenUsage {
+    /// Read the OpenAI-style `usage` object of a response or stream chunk
+    pub fn from_json(value: &Value) -> Option<Self> {
+        let usage = value.get("usage").filter(|usage| usage.is_object())?;
+        Some(Self {
+            prompt_tokens: usage.get("prompt_tokens").and_then(Value::as_u64),
+            completion_tokens: usage
+                .get("completion_tokens")
+                .and_then(Value::as_u64)
+                .unwrap_or(0),
+        })
+    }
+}
+
+/// Collects token usage from a server-sent event stream
+///
+/// The `usage` of the last chunk that carries one wins. Streams without usage
+/// (no `stream_options.include_usage`) are charged one completion token per
+/// chunk with choices, which matches how workers stream tokens.
+#[derive(Debug, Default)]
+pub struct StreamUsageScanner {
+    pending: Vec<u8>,
+    usage: Option<TokenUsage>,
+    chunks: u64,
+    text: Option<String>,
+}
+
+impl StreamUsageScanner {
+    /// Scanner that also collects the streamed choice text
+    pub fn collecting_text() -> Self {
+        Self {
+            text: Some(String::new()),
+            ..Default::default()
+        }
+    }
+
+    pub fn feed(&mut self, bytes: &[u8]) {
+        self.pending.extend_from_slice(bytes);
+        while let Some(end) = self.pending.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=end).collect();
+            self.scan_line(&line);
+        }
+    }
+
+    fn scan_line(&mut self, line: &[u8]) {
+        let Some(data) = std::str::from_utf8(line)
+            .ok()
+            .and_then(|line| line.trim().strip_prefix("data:"))
+            .map(str::trim)
+        else {
+            return;
+        };
+        let Ok(chunk) = serde_json::from_str::<Value>(data) else {
+            return;
+        };
+        if let Some(usage) = TokenUsage::from_json(&chunk) {
+            self.usage = Some(usage);
+        }
+        if chunk
+            .get("choices")
+            .and_then(Value::as_array)
+            .is_some_and(|choices| !choices.is_empty())
+        {
+            self.chunks += 1;
+        }
+        if let Some(text) = &mut self.text {
+            text.push_str(&choices_text(&chunk));
+        }
+    }
+
+    pub fn finish(self) -> TokenUsage {
+        self.finish_with_text().0
+    }
+
+    /// Usage and, for [`Self::collecting_text`], the streamed text
+    pub fn finish_with_text(mut self) -> (TokenUsage, Option<String>) {
+        let rest = std::mem::take(&mut self.pending);
+        self.scan_line(&rest);
+        let usage = self.usage.unwrap_or(TokenUsage {
+            prompt_tokens: None,
+            completion_tokens: self.chunks,
+        });
+        (usage, self.text)
+    }
+}
+
+/// Generated text of a response or stream chunk, choices concatenated
+pub fn choices_text(value: &Value) -> String {
+    let Some(choices) = value.get("choices").and_then(Value::as_array) else {
+        return String::new();
+    };
+    choices
+        .iter()
+        .filter_map(|choice| {
+            choice
+                .get("text")
+                .or_else(|| choice.pointer("/message/content"))
+                .or_else(|| choice.pointer("/delta/content"))
+                .and_then(Value::as_str)
+        })
+        .collect()
+}
+
+/// Prompt tokens per quota store key of requests not yet charged to a quota
+type InFlightTokens = Arc<Mutex<HashMap<String, u64>>>;
+
+/// Prompt tokens taken from the buckets of an admitted request
+///
+/// The prompt stays reserved against the quotas until the reservation is
+/// cancelled, reconciled or dropped.
+#[derive(Debug)]
+pub struct TokenReservation {
+    entries: Vec<(RateLimitDimension, String, RateLimitClass)>,
+    prompt_tokens: u64,
+    status: Option<RateLimitStatus>,
+    quota_keys: Vec<String>,
+    in_flight: InFlightTokens,
+}
+
+impl TokenReservation {
+    pub fn prompt_tokens(&self) -> u64 {
+        self.prompt_tokens
+    }
+
+    /// Most constrained per-minute bucket after the reservation, if any applies
+    pub fn status(&self) -> Option<&RateLimitStatus> {
+        self.status.as_ref()
+    }
+}
+
+impl Drop for TokenReservation {
+    fn drop(&mut self) {
+        let mut in_flight = self.in_flight.lock();
+        for key in &self.quota_keys {
+            if let Some(tokens) = in_flight.get_mut(key) {
+                *tokens = tokens.saturating_sub(self.prompt_tokens);
+                if *tokens == 0 {
+                    in_flight.remove(key);
+                }
+            }
+        }
+    }
+}
+
+/// Why a request was refused by [`TokenRateLimiter::reserve`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenLimitError {
+    /// A tokens-per-minute bucket is empty
+    RateLimited {
+        status: RateLimitStatus,
+        retry_after: Duration,
+    },
+    /// A daily or monthly quota is used up
+    QuotaExceeded {
+        dimension: RateLimitDimension,
+        period: QuotaPeriod,
+        limit: u64,
+        retry_after: Duration,
+    },
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct QuotaUsage {
+    day: String,
+    day_tokens: u64,
+    month: String,
+    month_tokens: u64,
+}
+
+fn day_key(now: DateTime<Utc>) -> String {
+    now.format("%Y-%m-%d").to_string()
+}
+
+fn month_key(now: DateTime<Utc>) -> String {
+    now.format("%Y-%m").to_string()
+}
+
+impl QuotaUsage {
+    fn used(&self, period: QuotaPeriod, now: DateTime<Utc>) -> u64 {
+        match period {
+            QuotaPeriod::Daily if self.day == day_key(now) => self.day_tokens,
+            QuotaPeriod::Monthly if self.month == month_key(now) => self.month_tokens,
+            _ => 0,
+        }
+    }
+
+    fn add(&mut self, tokens: u64, now: DateTime<Utc>) {
+        let (day, month) = (day_key(now), month_key(now));
+        if self.day != day {
+            self.day = day;
+            self.day_tokens = 0;
+        }
+        if self.month != month {
+            self.month = month;
+            self.month_tokens = 0;
+        }
+        self.day_tokens += tokens;
+        self.month_tokens += tokens;
+    }
+}
+
+/// Daily and monthly token usage, optionally persisted to a JSON file
+#[derive(Debug)]
+struct QuotaStore {
+    path: Option<PathBuf>,
+    usage: Mutex<HashMap<String, QuotaUsage>>,
+    in_flight: InFlightTokens,
+    dirty: AtomicBool,
+}
+
+impl QuotaStore {
+    fn open(path: Option<PathBuf>) -> Result<Self, String> {
+        let usage = match &path {
+            Some(path) if path.exists() => {
+                let contents = std::fs::read_to_string(path)
+                    .map_err(|e| format!("Failed to read quota store {}: {}", path.display(), e))?;
+                serde_json::from_str(&contents)
+                    .map_err(|e| format!("Invalid quota store {}: {}", path.display(), e))?
+            }
+            _ => HashMap::new(),
+        };
+        Ok(Self {
+            path,
+            usage: Mutex::new(usage),
+            in_flight: InFlightTokens::default(),
+            dirty: AtomicBool::new(false),
+        })
+    }
+
+    /// Store key of a bucket
+    fn key(dimension: RateLimitDimension, key: &str) -> String {
+        format!("{}:{}", dimension, key)
+    }
+
+    fn used(
+        &self,
+        dimension: RateLimitDimension,
+        key: &str,
+        period: QuotaPeriod,
+        now: DateTime<Utc>,
+    ) -> u64 {
+        self.usage
+            .lock()
+            .get(&Self::key(dimension, key))
+            .map_or(0, |usage| usage.used(period, now))
+    }
+
+    fn add(&self, dimension: RateLimitDimension, key: &str, tokens: u64, now: DateTime<Utc>) {
+        self.usage
+            .lock()
+            .entry(Self::key(dimension, key))
+            .or_default()
+            .add(tokens, now);
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+
+    /// Write the usage file if anything changed, dropping past months
+    fn flush(&self, now: DateTime<Utc>) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if !self.dirty.swap(false, Ordering::Relaxed) {
+            return Ok(());
+        }
+        let contents = {
+            let mut usage = self.usage.lock();
+            let month = month_key(now);
+            usage.retain(|_, usage| usage.month == month);
+            serde_json::to_string(&*usage).map_err(|e| e.to_string())?
+        };
+        // Write then rename so a crash never leaves a truncated file
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, contents)
+            .and_then(|_| std::fs::rename(&tmp, path))
+            .map_err(|e| {
+                self.dirty.store(true, Ordering::Relaxed);
+                format!("Failed to write quota store {}: {}", path.display(), e)
+            })
+    }
+}
+
+/// Tokens-per-minute buckets and token quotas per API key, tenant and model
+pub struct TokenRateLimiter {
+    config: KeyedRateLimitConfig,
+    buckets: Mutex<LruCache<BucketKey, Bucket>>,
+    quotas: QuotaStore,
+}
+
+impl fmt::Debug for TokenRateLimiter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TokenRateLimiter")
+            .field("config", &self.config)
+            .field("buckets", &self.buckets.lock().len())
+            .field("quota_store", &self.quotas.path)
+            .finish()
+    }
+}
+
+impl TokenRateLimiter {
+    pub fn new(config: KeyedRateLimitConfig) -> Result<Self, String> {
+        config.validate()?;
+        let capacity = NonZeroUsize::new(config.max_buckets)
+            .ok_or_else(|| "max_buckets must be greater than 0".to_string())?;
+        let quotas = QuotaStore::open(config.quota_store.as_ref().map(PathBuf::from))?;
+        Ok(Self {
+            config,
+            buckets: Mutex::new(LruCache::new(capacity)),
+            quotas,
+        })
+    }
+
+    /// Check quotas and take the prompt tokens from every per-minute bucket
+    ///
+    /// Returns `None` when no token limit applies to `keys`. As with request
+    /// limits, nothing is taken unless every bucket has room.
+    pub fn reserve(
+        &self,
+        keys: &RateLimitKeys,
+        prompt_tokens: u64,
+    ) -> Result<Option<TokenReservation>, TokenLimitError> {
+        self.reserve_at(keys, prompt_tokens, Utc::now())
+    }
+
+    fn reserve_at(
+        &self,
+        keys: &RateLimitKeys,
+        prompt_tokens: u64,
+        now_utc: DateTime<Utc>,
+    ) -> Result<Option<TokenReservation>, TokenLimitError> {
+        let entries: Vec<_> = self
+            .config
+            .applicable_classes(keys)
+            .into_iter()
+            .filter(|(_, _, class)| class.limits_tokens())
+            .collect();
+        if entries.is_empty() {
+            return Ok(None);
+        }
+
+        // Held until the prompt is reserved so concurrent requests see each other
+        let mut in_flight = self.quotas.in_flight.lock();
+        let mut quota_keys = Vec::new();
+        for (dimension, key, class) in &entries {
+            let quota_key = QuotaStore::key(*dimension, key);
+            let reserved = in_flight.get(&quota_key).copied().unwrap_or(0);
+            for period in [QuotaPeriod::Daily, QuotaPeriod::Monthly] {
+                let Some(limit) = period.limit(class) else {
+                    continue;
+                };
+                let used = self.quotas.used(*dimension, key, period, now_utc) + reserved;
+                if used >= limit || used + prompt_tokens > limit {
+                    return Err(TokenLimitError::QuotaExceeded {
+                        dimension: *dimension,
+                        period,
+                        limit,
+                        retry_after: period.remaining(now_utc),
+                    });
+                }
+            }
+            if class.has_quota() {
+                quota_keys.push(quota_key);
+            }
+        }
+
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock();
+        let mut taken: Vec<_> = entries
+            .iter()
+            .filter_map(|(dimension, key, class)| {
+                let rate = class.token_rate()?;
+                let key = (*dimension, key.clone());
+                let mut bucket = buckets
+                    .pop(&key)
+                    .unwrap_or_else(|| Bucket::full(&rate, now));
+                bucket.refill(&rate, now);
+                Some((key, rate, bucket))
+            })
+            .collect();
+
+        // Prompts larger than a bucket wait for a full bucket rather than forever
+        let limited = taken
+            .iter()
+            .filter_map(|((dimension, _), rate, bucket)| {
+                let needed = (prompt_tokens as f64).clamp(1.0, rate.capacity);
+                (bucket.tokens < needed).then(|| {
+                    (
+                        bucket.status(*dimension, rate),
+                        bucket.wait_for(rate, needed),
+                    )
+                })
+            })
+            .max_by(|a, b| a.1.cmp(&b.1));
+
+        let result = match limited {
+            Some((status, retry_after)) => Err(TokenLimitError::RateLimited {
+                status,
+                retry_after,
+            }),
+            None => {
+                for (_, _, bucket) in taken.iter_mut() {
+                    bucket.tokens -= prompt_tokens as f64;
+                }
+                let status = taken
+                    .iter()
+                    .map(|((dimension, _), rate, bucket)| bucket.status(*dimension, rate))
+                    .min_by_key(|status| status.remaining);
+                for key in &quota_keys {
+                    *in_flight.entry(key.clone()).or_default() += prompt_tokens;
+                }
+                Ok(Some(TokenReservation {
+                    entries,
+                    prompt_tokens,
+                    status,
+                    quota_keys,
+                    in_flight: self.quotas.in_flight.clone(),
+                }))
+            }
+        };
+
+        for (key, _, bucket) in taken {
+            buckets.put(key, bucket);
+        }
+        result
+    }
+
+    /// Return the reserved prompt tokens of a request that was not served
+    pub fn cancel(&self, reservation: TokenReservation) {
+        self.adjust_buckets(&reservation, -(reservation.prompt_tokens as f64));
+    }
+
+    /// Settle a served request against its actual token usage
+    ///
+    /// Without reported usage the prompt estimate stands and no completion
+    /// tokens are charged.
+    pub fn reconcile(&self, reservation: TokenReservation, usage: Option<TokenUsage>) {
+        self.reconcile_at(reservation, usage, Utc::now())
+    }
+
+    fn reconcile_at(
+        &self,
+        reservation: TokenReservation,
+        usage: Option<TokenUsage>,
+        now_utc: DateTime<Utc>,
+    ) {
+        let usage = usage.unwrap_or_default();
+        let prompt_tokens = usage.prompt_tokens.unwrap_or(reservation.prompt_tokens);
+        let total = prompt_tokens + usage.completion_tokens;
+        RouterMetrics::record_rate_limit_tokens(prompt_tokens, usage.completion_tokens);
+
+        self.adjust_buckets(
+            &reservation,
+            total as f64 - reservation.prompt_tokens as f64,
+        );
+        for (dimension, key, class) in &reservation.entries {
+            if class.has_quota() {
+                self.quotas.add(*dimension, key, total, now_utc);
+            }
+        }
+    }
+
+    /// Take `tokens` more from the request's buckets (negative to refund)
+    fn adjust_buckets(&self, reservation: &TokenReservation, tokens: f64) {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock();
+        for (dimension, key, class) in &reservation.entries {
+            let Some(rate) = class.token_rate() else {
+                continue;
+            };
+            // Evicted buckets have refilled by now, so there is nothing to settle
+            if let Some(bucket) = buckets.peek_mut(&(*dimension, key.clone())) {
+                bucket.refill(&rate, now);
+                bucket.tokens = (bucket.tokens - tokens).min(rate.capacity);
+            }
+        }
+    }
+
+    /// Write quota usage to the quota store, if one is configured
+    pub fn flush(&self) -> Result<(), String> {
+        self.quotas.flush(Utc::now())
+    }
+
+    /// Periodically flush the quota store
+    pub fn start(self: Arc<Self>) -> Option<JoinHandle<()>> {
+        self.quotas.path.as_ref()?;
+        let interval = Duration::from_secs(self.config.quota_flush_interval_secs.max(1));
+        info!(
+            "Persisting token quotas to {:?} every {:?}",
+            self.quotas.path, interval
+        );
+        Some(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.flush() {
+                    warn!("{}", e);
+                }
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::keyed_rate_limiter::DimensionConfig;
+    use chrono::TimeZone;
+
+    fn config(class: RateLimitClass) -> KeyedRateLimitConfig {
+        KeyedRateLimitConfig {
+            max_buckets: 100,
+            quota_store: None,
+            quota_flush_interval_secs: 10,
+            classes: HashMap::from([("metered".to_string(), class)]),
+            api_key: Some(DimensionConfig {
+                default_class: Some("metered".to_string()),
+                keys: HashMap::new(),
+            }),
+            tenant: None,
+            model: None,
+        }
+    }
+
+    fn per_minute(tokens_per_minute: f64) -> RateLimitClass {
+        RateLimitClass {
+            tokens_per_minute: Some(tokens_per_minute),
+            ..Default::default()
+        }
+    }
+
+    fn key(api_key: &str) -> RateLimitKeys {
+        RateLimitKeys {
+            api_key: Some(api_key.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn usage(prompt_tokens: u64, completion_tokens: u64) -> Option<TokenUsage> {
+        Some(TokenUsage {
+            prompt_tokens: Some(prompt_tokens),
+            completion_tokens,
+        })
+    }
+
+    #[test]
+    fn test_reconciled_completion_tokens_consume_budget() {
+        let limiter = TokenRateLimiter::new(config(per_minute(100.0))).unwrap();
+        let alice = key("alice");
+
+        let reservation = limiter.reserve(&alice, 30).unwrap().unwrap();
+        assert_eq!(reservation.status().unwrap().remaining, 70);
+        limiter.reconcile(reservation, usage(40, 50));
+
+        // 10 left: a 20 token prompt must wait, a 5 token prompt fits
+        match limiter.reserve(&alice, 20) {
+            Err(TokenLimitError::RateLimited {
+                status,
+                retry_after,
+            }) => {
+                assert_eq!(status.dimension, RateLimitDimension::ApiKey);
+                assert!(status.remaining <= 10);
+                assert!(retry_after > Duration::from_secs(5));
+            }
+            other => panic!("expected rate limit, got {:?}", other),
+        }
+        assert!(limiter.reserve(&alice, 5).unwrap().is_some());
+
+        // Other keys have their own bucket
+        assert!(limiter.reserve(&key("sk-bob"), 90).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_cancel_refunds_prompt_tokens() {
+        let limiter = TokenRateLimiter::new(config(per_minute(100.0))).unwrap();
+        let alice = key("alice");
+        let reservation = limiter.reserve(&alice, 80).unwrap().unwrap();
+        assert!(limiter.reserve(&alice, 80).is_err());
+        limiter.cancel(reservation);
+        assert!(limiter.reserve(&alice, 80).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_oversized_prompt_waits_for_full_bucket() {
+        let limiter = TokenRateLimiter::new(config(per_minute(100.0))).unwrap();
+        let alice = key("alice");
+        let reservation = limiter.reserve(&alice, 500).unwrap().unwrap();
+        limiter.reconcile(reservation, None);
+        assert!(matches!(
+            limiter.reserve(&alice, 1),
+            Err(TokenLimitError::RateLimited { .. })
+        ));
+    }
+
+    #[test]
+    fn test_unlimited_without_token_classes() {
+        let limiter = TokenRateLimiter::new(config(RateLimitClass {
+            requests_per_second: Some(1.0),
+            ..Default::default()
+        }))
+        .unwrap();
+        assert!(limiter.reserve(&key("alice"), 10).unwrap().is_none());
+        assert!(limiter
+            .reserve(&RateLimitKeys::default(), 10)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_daily_quota_resets_next_day() {
+        let limiter = TokenRateLimiter::new(config(RateLimitClass {
+            daily_tokens: Some(100),
+            ..Default::default()
+        }))
+        .unwrap();
+        let alice = key("alice");
+        let noon = Utc.with_ymd_and_hms(2026, 3, 10, 12, 0, 0).unwrap();
+
+        let reservation = limiter.reserve_at(&alice, 10, noon).unwrap().unwrap();
+        limiter.reconcile_at(reservation, usage(10, 85), noon);
+        match limiter.reserve_at(&alice, 10, noon) {
+            Err(TokenLimitError::QuotaExceeded {
+                period,
+                limit,
+                retry_after,
+                ..
+            }) => {
+                assert_eq!(period, QuotaPeriod::Daily);
+                assert_eq!(limit, 100);
+                assert_eq!(retry_after, Duration::from_secs(12 * 3600));
+            }
+            other => panic!("expected quota error, got {:?}", other),
+        }
+        assert!(limiter.reserve_at(&alice, 5, noon).unwrap().is_some());
+
+        let tomorrow = noon + chrono::Duration::days(1);
+        assert!(limiter.reserve_at(&alice, 50, tomorrow).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_in_flight_prompts_count_against_quota() {
+        let limiter = TokenRateLimiter::new(config(RateLimitClass {
+            daily_tokens: Some(100),
+            ..Default::default()
+        }))
+        .unwrap();
+        let alice = key("alice");
+        let noon = Utc.with_ymd_and_hms(2026, 3, 10, 12, 0, 0).unwrap();
+
+        // Only one of two concurrent 60 token prompts fits the quota
+        let first = limiter.reserve_at(&alice, 60, noon).unwrap().unwrap();
+        assert!(matches!(
+            limiter.reserve_at(&alice, 60, noon),
+            Err(TokenLimitError::QuotaExceeded {
+                period: QuotaPeriod::Daily,
+                ..
+            })
+        ));
+
+        // A cancelled reservation frees its share, a settled one charges usage
+        limiter.cancel(first);
+        let second = limiter.reserve_at(&alice, 60, noon).unwrap().unwrap();
+        limiter.reconcile_at(second, usage(60, 0), noon);
+        assert!(limiter.reserve_at(&alice, 60, noon).is_err());
+        assert!(limiter.reserve_at(&alice, 40, noon).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_monthly_quota_period_end() {
+        let end_of_year = Utc.with_ymd_and_hms(2026, 12, 31, 23, 0, 0).unwrap();
+        assert_eq!(
+            QuotaPeriod::Monthly.remaining(end_of_year),
+            Duration::from_secs(3600)
+        );
+        assert_eq!(
+            QuotaPeriod::Daily.remaining(end_of_year),
+            Duration::from_secs(3600)
+        );
+    }
+
+    #[test]
+    fn test_quota_store_survives_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("quotas.json");
+        let mut config = config(RateLimitClass {
+            monthly_tokens: Some(1000),
+            ..Default::default()
+        });
+        config.quota_store = Some(path.to_str().unwrap().to_string());
+        let alice = key("alice");
+
+        let limiter = TokenRateLimiter::new(config.clone()).unwrap();
+        let reservation = limiter.reserve(&alice, 100).unwrap().unwrap();
+        limiter.reconcile(reservation, usage(100, 850));
+        limiter.flush().unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(contents.contains("api_key:alice"));
+
+        // A restarted limiter picks up where the old one left off
+        let restarted = TokenRateLimiter::new(config).unwrap();
+        assert!(matches!(
+            restarted.reserve(&alice, 100),
+            Err(TokenLimitError::QuotaExceeded {
+                period: QuotaPeriod::Monthly,
+                ..
+            })
+        ));
+        assert!(restarted.reserve(&alice, 50).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_stream_usage_scanner() {
+        let mut scanner = StreamUsageScanner::default();
+        scanner.feed(b"data: {\"choices\":[{\"delta\":{\"content\":\"a\"}}]}\n\ndata: {\"cho");
+        scanner.feed(b"ices\":[{\"delta\":{\"content\":\"b\"}}]}\n\ndata: [DONE]\n\n");
+        assert_eq!(
+            scanner.finish(),
+            TokenUsage {
+                prompt_tokens: None,
+                completion_tokens: 2
+            }
+        );
+
+        let mut scanner = StreamUsageScanner::default();
+        scanner.feed(b"data: {\"choices\":[{\"delta\":{}}]}\n\n");
+        scanner.feed(
+            b"data: {\"choices\":[],\"usage\":{\"prompt_tokens\":7,\"completion_tokens\":3}}",
+        );
+        assert_eq!(
+            scanner.finish(),
+            TokenUsage {
+                prompt_tokens: Some(7),
+                completion_tokens: 3
+            }
+        );
+
+        let mut scanner = StreamUsageScanner::collecting_text();
+        scanner.feed(b"data: {\"choices\":[{\"delta\":{\"content\":\"Hel\"}}]}\n\n");
+        scanner.feed(b"data: {\"choices\":[{\"delta\":{\"content\":\"lo\"}}]}\n\ndata: [DONE]\n\n");
+        let (usage, text) = scanner.finish_with_text();
+        assert_eq!(usage.completion_tokens, 2);
+        assert_eq!(text.as_deref(), Some("Hello"));
+    }
+
+    #[test]
+    fn test_usage_from_json() {
+        let body = serde_json::json!({"usage": {"prompt_tokens": 10, "completion_tokens": 5}});
+        assert_eq!(TokenUsage::from_json(&body), usage(10, 5));
+        assert_eq!(
+            TokenUsage::from_json(&serde_json::json!({"usage": null})),
+            None
+        );
+    }
+}
@@ -0,0 +1,163 @@
+//! Worker topology
+//!
+//! Workers carry optional `zone`, `rack` and `node` labels describing where
+//! they run. PD routing uses them to estimate how expensive a KV-cache transfer
+//! between a prefill and a decode worker is.
+
+use std::collections::HashMap;
+
+/// Label key for the node a worker runs on
+pub const NODE_LABEL: &str = "node";
+/// Label key for the rack a worker runs in
+pub const RACK_LABEL: &str = "rack";
+/// Label key for the zone a worker runs in
+pub const ZONE_LABEL: &str = "zone";
+
+/// Placement of a worker, from coarsest to finest level
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorkerTopology {
+    pub zone: Option<String>,
+    pub rack: Option<String>,
+    pub node: Option<String>,
+}
+
+/// How close two workers are to each other
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Locality {
+    SameNode,
+    SameRack,
+    SameZone,
+    CrossZone,
+    /// Not enough topology labels to tell
+    Unknown,
+}
+
+impl WorkerTopology {
+    /// Read topology from worker labels
+    pub fn from_labels(labels: &HashMap<String, String>) -> Self {
+        Self {
+            zone: labels.get(ZONE_LABEL).cloned(),
+            rack: labels.get(RACK_LABEL).cloned(),
+            node: labels.get(NODE_LABEL).cloned(),
+        }
+    }
+
+    /// Parse a "zone/rack/node" spec; trailing levels may be omitted
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let parts: Vec<&str> = spec.split('/').collect();
+        if parts.len() > 3 || parts.iter().any(|p| p.trim().is_empty()) {
+            return Err(format!(
+                "Invalid topology '{}': expected zone[/rack[/node]]",
+                spec
+            ));
+        }
+        let part = |i: usize| parts.get(i).map(|p| p.trim().to_string());
+        Ok(Self {
+            zone: part(0),
+            rack: part(1),
+            node: part(2),
+        })
+    }
+
+    /// Convert to worker labels
+    pub fn to_labels(&self) -> HashMap<String, String> {
+        [
+            (ZONE_LABEL, &self.zone),
+            (RACK_LABEL, &self.rack),
+            (NODE_LABEL, &self.node),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| value.clone().map(|v| (key.to_string(), v)))
+        .collect()
+    }
+
+    /// Determine the locality between two workers
+    ///
+    /// Coarser levels decide first, so e.g. "node-1" in two different zones
+    /// is not considered the same node. Levels missing on either side are
+    /// skipped.
+    pub fn locality(&self, other: &WorkerTopology) -> Locality {
+        let same = |a: &Option<String>, b: &Option<String>| match (a, b) {
+            (Some(a), Some(b)) => Some(a == b),
+            _ => None,
+        };
+
+        let zone = same(&self.zone, &other.zone);
+        if zone == Some(false) {
+            return Locality::CrossZone;
+        }
+        let rack = same(&self.rack, &other.rack);
+        if rack == Some(false) {
+            return if zone == Some(true) {
+                Locality::SameZone
+            } else {
+                Locality::Unknown
+            };
+        }
+        match same(&self.node, &other.node) {
+            Some(true) => Locality::SameNode,
+            _ if rack == Some(true) => Locality::SameRack,
+            _ if zone == Some(true) => Locality::SameZone,
+            _ => Locality::Unknown,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn topo(spec: &str) -> WorkerTopology {
+        WorkerTopology::parse(spec).unwrap()
+    }
+
+    #[test]
+    fn test_parse_topology() {
+        let t = topo("us-east-1a/rack-3/node-7");
+        assert_eq!(t.zone.as_deref(), Some("us-east-1a"));
+        assert_eq!(t.rack.as_deref(), Some("rack-3"));
+        assert_eq!(t.node.as_deref(), Some("node-7"));
+
+        let t = topo("us-east-1a");
+        assert_eq!(t.rack, None);
+        assert_eq!(t.node, None);
+
+        assert!(WorkerTopology::parse("").is_err());
+        assert!(WorkerTopology::parse("a//c").is_err());
+        assert!(WorkerTopology::parse("a/b/c/d").is_err());
+    }
+
+    #[test]
+    fn test_labels_roundtrip() {
+        let t = topo("z1/r1");
+        let labels = t.to_labels();
+        assert_eq!(labels.len(), 2);
+        assert_eq!(WorkerTopology::from_labels(&labels), t);
+    }
+
+    #[test]
+    fn test_locality() {
+        assert_eq!(
+            topo("z1/r1/n1").locality(&topo("z1/r1/n1")),
+            Locality::SameNode
+        );
+        assert_eq!(
+            topo("z1/r1/n1").locality(&topo("z1/r1/n2")),
+            Locality::SameRack
+        );
+        assert_eq!(
+            topo("z1/r1/n1").locality(&topo("z1/r2/n1")),
+            Locality::SameZone
+        );
+        assert_eq!(
+            topo("z1/r1/n1").locality(&topo("z2/r1/n1")),
+            Locality::CrossZone
+        );
+        assert_eq!(topo("z1/r1").locality(&topo("z1/r1")), Locality::SameRack);
+        assert_eq!(topo("z1").locality(&topo("z1/r2")), Locality::SameZone);
+        assert_eq!(
+            WorkerTopology::default().locality(&topo("z1/r1/n1")),
+            Locality::Unknown
+        );
+    }
+}
@@ -0,0 +1,146 @@
+//! Config files read from disk and polled for changes
+//!
+//! [`WatchedFiles`] remembers the last contents it read so callers only act
+//! on a change, and [`watch`] runs a reload on an interval, keeping the
+//! previous state in place on errors. [`parse_yaml_or_json`] picks the format
+//! of a config file from its extension.
+
+use crate::core::audit::{record_reload, AuditLog};
+use parking_lot::Mutex;
+use serde::de::DeserializeOwned;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+/// Parse a config file as YAML when it ends in `.yaml`/`.yml`, JSON otherwise
+pub fn parse_yaml_or_json<T: DeserializeOwned>(
+    path: impl AsRef<Path>,
+    contents: impl AsRef<[u8]>,
+) -> Result<T, String> {
+    let yaml = matches!(
+        path.as_ref().extension().and_then(|ext| ext.to_str()),
+        Some("yaml" | "yml")
+    );
+    if yaml {
+        serde_yaml::from_slice(contents.as_ref()).map_err(|e| e.to_string())
+    } else {
+        serde_json::from_slice(contents.as_ref()).map_err(|e| e.to_string())
+    }
+}
+
+/// Files read together, remembering their last contents
+#[derive(Debug)]
+pub struct WatchedFiles {
+    paths: Vec<PathBuf>,
+    last_contents: Mutex<Option<Vec<Vec<u8>>>>,
+}
+
+impl WatchedFiles {
+    pub fn new<P: Into<PathBuf>>(paths: impl IntoIterator<Item = P>) -> Self {
+        Self {
+            paths: paths.into_iter().map(Into::into).collect(),
+            last_contents: Mutex::new(None),
+        }
+    }
+
+    /// Read every file, in the order they were given
+    pub fn read(&self) -> Result<Vec<Vec<u8>>, String> {
+        let contents = self.read_files()?;
+        *self.last_contents.lock() = Some(contents.clone());
+        Ok(contents)
+    }
+
+    /// Read every file, returning the contents only if they changed since the
+    /// last read. Changed contents are remembered even if the caller rejects
+    /// them, so an invalid file is reported once per change.
+    pub fn read_if_changed(&self) -> Result<Option<Vec<Vec<u8>>>, String> {
+        let contents = self.read_files()?;
+        let mut last_contents = self.last_contents.lock();
+        if last_contents.as_ref() == Some(&contents) {
+            return Ok(None);
+        }
+        *last_contents = Some(contents.clone());
+        Ok(Some(contents))
+    }
+
+    fn read_files(&self) -> Result<Vec<Vec<u8>>, String> {
+        self.paths
+            .iter()
+            .map(|path| {
+                std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+            })
+            .collect()
+    }
+}
+
+/// Run `reload` every `interval`, auditing each reload of `target`
+///
+/// `reload` returns whether anything changed; on errors the caller keeps its
+/// previous state, described by `keeping` in the warning.
+pub fn watch<F>(
+    interval: Duration,
+    target: String,
+    keeping: &'static str,
+    audit: Option<Arc<AuditLog>>,
+    reload: F,
+) -> JoinHandle<()>
+where
+    F: Fn() -> Result<bool, String> + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut last_error = None;
+        loop {
+            tokio::time::sleep(interval).await;
+            let result = reload();
+            if let Err(e) = &result {
+                warn!("{}; keeping {}", e, keeping);
+            }
+            record_reload(audit.as_deref(), &target, result, &mut last_error);
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Limits {
+        max: u32,
+    }
+
+    #[test]
+    fn test_parse_yaml_or_json_by_extension() {
+        let yaml: Limits = parse_yaml_or_json("limits.yml", "max: 3").unwrap();
+        assert_eq!(yaml, Limits { max: 3 });
+        let json: Limits = parse_yaml_or_json("limits.json", r#"{"max": 4}"#).unwrap();
+        assert_eq!(json, Limits { max: 4 });
+        assert!(parse_yaml_or_json::<Limits>("limits", "max: 3").is_err());
+    }
+
+    #[test]
+    fn test_read_if_changed() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("first");
+        let second = dir.path().join("second");
+        std::fs::write(&first, "a").unwrap();
+        std::fs::write(&second, "b").unwrap();
+
+        let files = WatchedFiles::new([&first, &second]);
+        assert_eq!(files.read().unwrap(), vec![b"a".to_vec(), b"b".to_vec()]);
+        assert_eq!(files.read_if_changed().unwrap(), None);
+
+        std::fs::write(&second, "c").unwrap();
+        assert_eq!(
+            files.read_if_changed().unwrap(),
+            Some(vec![b"a".to_vec(), b"c".to_vec()])
+        );
+        assert_eq!(files.read_if_changed().unwrap(), None);
+
+        std::fs::remove_file(&first).unwrap();
+        assert!(files.read_if_changed().unwrap_err().contains("first"));
+    }
+}
@@ -1,4 +1,7 @@
-use super::{CircuitBreaker, CircuitBreakerConfig, WorkerError, WorkerResult};
+use super::{
+    CircuitBreaker, CircuitBreakerConfig, RequestRequirements, WorkerCapabilities, WorkerError,
+    WorkerResult, WorkerTopology,
+};
 use crate::grpc::VllmSchedulerClient;
 use crate::metrics::RouterMetrics;
 use async_trait::async_trait;
@@ -6,17 +9,9 @@ use futures;
 use serde_json;
 use std::fmt;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
-use std::sync::{Arc, LazyLock};
+use std::sync::{Arc, RwLock};
 use tokio::sync::Mutex;
 
-// Shared HTTP client for worker operations (health checks, server info, etc.)
-static WORKER_CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
-    reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(30)) // Default timeout, overridden per request
-        .build()
-        .expect("Failed to create worker HTTP client")
-});
-
 /// Core worker abstraction that represents a backend service
 #[async_trait]
 pub trait Worker: Send + Sync + fmt::Debug {
@@ -35,11 +30,11 @@ pub trait Worker: Send + Sync + fmt::Debug {
     /// Set the worker's health status
     fn set_healthy(&self, healthy: bool);
 
-    /// Perform an async health check on the worker
-    async fn check_health_async(&self) -> WorkerResult<()>;
+    /// Perform an async health check on the worker, over `client` for HTTP workers
+    async fn check_health_async(&self, client: &reqwest::Client) -> WorkerResult<()>;
 
     /// Synchronous health check wrapper (for compatibility)
-    fn check_health(&self) -> WorkerResult<()> {
+    fn check_health(&self, client: &reqwest::Client) -> WorkerResult<()> {
         // Use a small runtime for synchronous contexts
         tokio::runtime::Builder::new_current_thread()
             .enable_all()
@@ -48,7 +43,7 @@ pub trait Worker: Send + Sync + fmt::Debug {
                 url: self.url().to_string(),
                 reason: format!("Failed to create runtime: {}", e),
             })?
-            .block_on(self.check_health_async())
+            .block_on(self.check_health_async(client))
     }
 
     /// Get the current load (number of active requests)
@@ -148,9 +143,20 @@ pub trait Worker: Send + Sync + fmt::Debug {
         format!("{}{}", self.base_url(), route)
     }
 
-    /// Check if this worker can handle a specific request
-    fn can_handle(&self, _req: &serde_json::Value) -> bool {
-        true
+    /// Get the capabilities discovered for this worker
+    fn capabilities(&self) -> WorkerCapabilities {
+        WorkerCapabilities::default()
+    }
+
+    /// Update the capabilities of this worker
+    fn set_capabilities(&self, _capabilities: WorkerCapabilities) {
+        // Default implementation - does nothing
+        // Workers that track capabilities should override this
+    }
+
+    /// Check if this worker can handle a request with the given requirements
+    fn can_handle(&self, requirements: &RequestRequirements) -> bool {
+        self.capabilities().satisfies(requirements)
     }
 
     // === Multi-router support ===
@@ -212,6 +218,11 @@ pub trait Worker: Send + Sync + fmt::Debug {
             .get("chat_template")
             .map(|s| s.as_str())
     }
+
+    /// Get the placement (zone/rack/node labels) of this worker
+    fn topology(&self) -> WorkerTopology {
+        WorkerTopology::from_labels(&self.metadata().labels)
+    }
 }
 
 /// Connection mode for worker communication
@@ -317,6 +328,8 @@ pub struct BasicWorker {
     consecutive_failures: Arc<AtomicUsize>,
     consecutive_successes: Arc<AtomicUsize>,
     circuit_breaker: CircuitBreaker,
+    /// Capabilities discovered from the worker
+    capabilities: Arc<RwLock<WorkerCapabilities>>,
     /// Optional gRPC client for gRPC workers
     grpc_client: Option<Arc<Mutex<VllmSchedulerClient>>>,
 }
@@ -358,6 +371,7 @@ impl BasicWorker {
             consecutive_failures: Arc::new(AtomicUsize::new(0)),
             consecutive_successes: Arc::new(AtomicUsize::new(0)),
             circuit_breaker: CircuitBreaker::new(),
+            capabilities: Arc::new(RwLock::new(WorkerCapabilities::default())),
             grpc_client: None,
         }
     }
@@ -428,7 +442,7 @@ impl Worker for BasicWorker {
         RouterMetrics::set_worker_health(self.url(), healthy);
     }
 
-    async fn check_health_async(&self) -> WorkerResult<()> {
+    async fn check_health_async(&self, client: &reqwest::Client) -> WorkerResult<()> {
         use std::time::Duration;
 
         let health_result = match &self.metadata.connection_mode {
@@ -438,8 +452,8 @@ impl Worker for BasicWorker {
                 let health_url = format!("{}{}", url, self.metadata.health_config.endpoint);
                 let timeout = Duration::from_secs(self.metadata.health_config.timeout_secs);
 
-                // Use the shared client with a custom timeout for this request
-                match WORKER_CLIENT.get(&health_url).timeout(timeout).send().await {
+                // Use the caller's client with a custom timeout for this request
+                match client.get(&health_url).timeout(timeout).send().await {
                     Ok(response) => response.status().is_success(),
                     Err(_) => false,
                 }
@@ -541,6 +555,18 @@ impl Worker for BasicWorker {
     fn circuit_breaker(&self) -> &CircuitBreaker {
         &self.circuit_breaker
     }
+
+    fn capabilities(&self) -> WorkerCapabilities {
+        self.capabilities.read().unwrap().clone()
+    }
+
+    fn set_capabilities(&self, capabilities: WorkerCapabilities) {
+        *self.capabilities.write().unwrap() = capabilities;
+    }
+
+    fn can_handle(&self, requirements: &RequestRequirements) -> bool {
+        self.capabilities.read().unwrap().satisfies(requirements)
+    }
 }
 
 /// A DP-aware worker that handles data-parallel routing
@@ -594,9 +620,9 @@ impl Worker for DPAwareWorker {
         self.base_worker.set_healthy(healthy);
     }
 
-    async fn check_health_async(&self) -> WorkerResult<()> {
+    async fn check_health_async(&self, client: &reqwest::Client) -> WorkerResult<()> {
         // Delegate to the base worker's health check logic
-        self.base_worker.check_health_async().await
+        self.base_worker.check_health_async(client).await
     }
 
     fn load(&self) -> usize {
@@ -631,6 +657,18 @@ impl Worker for DPAwareWorker {
         self.base_worker.circuit_breaker()
     }
 
+    fn capabilities(&self) -> WorkerCapabilities {
+        self.base_worker.capabilities()
+    }
+
+    fn set_capabilities(&self, capabilities: WorkerCapabilities) {
+        self.base_worker.set_capabilities(capabilities);
+    }
+
+    fn can_handle(&self, requirements: &RequestRequirements) -> bool {
+        self.base_worker.can_handle(requirements)
+    }
+
     // DP-aware specific implementations
 
     fn is_dp_aware(&self) -> bool {
@@ -919,6 +957,40 @@ impl<'a> Drop for WorkerLoadGuard<'a> {
     }
 }
 
+/// Load guard that owns its worker, for load that lasts as long as a
+/// (possibly streamed) response body
+pub struct WorkerLoadLease {
+    worker: Arc<dyn Worker>,
+}
+
+impl WorkerLoadLease {
+    pub fn new(worker: Arc<dyn Worker>) -> Self {
+        worker.increment_load();
+        Self { worker }
+    }
+
+    /// Release the load once the response body has been sent or dropped
+    pub fn hold_until_body_end(
+        self,
+        response: axum::response::Response,
+    ) -> axum::response::Response {
+        use futures::StreamExt;
+
+        let (parts, body) = response.into_parts();
+        let stream = body.into_data_stream().map(move |chunk| {
+            let _lease = &self;
+            chunk
+        });
+        axum::response::Response::from_parts(parts, axum::body::Body::from_stream(stream))
+    }
+}
+
+impl Drop for WorkerLoadLease {
+    fn drop(&mut self) {
+        self.worker.decrement_load();
+    }
+}
+
 /// Health checker handle with graceful shutdown
 pub struct HealthChecker {
     handle: tokio::task::JoinHandle<()>,
@@ -950,6 +1022,7 @@ impl HealthChecker {
 pub fn start_health_checker(
     workers: std::sync::Arc<std::sync::RwLock<Vec<std::sync::Arc<dyn Worker>>>>,
     check_interval_secs: u64,
+    client: reqwest::Client,
 ) -> HealthChecker {
     let shutdown = Arc::new(AtomicBool::new(false));
     let shutdown_clone = shutdown.clone();
@@ -1002,9 +1075,10 @@ pub fn start_health_checker(
             let health_checks = workers_to_check.iter().map(|worker| {
                 let worker_url = worker.url().to_string();
                 let was_healthy = worker.is_healthy();
+                let client = &client;
 
                 async move {
-                    match worker.check_health_async().await {
+                    match worker.check_health_async(client).await {
                         Ok(_) => {
                             if !was_healthy {
                                 tracing::info!("Worker {} is now healthy", worker_url);
@@ -1508,6 +1582,27 @@ mod tests {
         assert_eq!(workers[2].load(), 0);
     }
 
+    #[tokio::test]
+    async fn test_load_lease_held_until_body_end() {
+        use axum::body::Body;
+        use axum::response::Response;
+
+        let worker: Arc<dyn Worker> = Arc::new(BasicWorker::new(
+            "http://test:8080".to_string(),
+            WorkerType::Decode,
+        ));
+        let lease = WorkerLoadLease::new(worker.clone());
+        let response = lease.hold_until_body_end(Response::new(Body::from("tokens")));
+        // Returning the response does not release the load
+        assert_eq!(worker.load(), 1);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(body, "tokens");
+        assert_eq!(worker.load(), 0);
+    }
+
     #[test]
     fn test_load_guard_panic_safety() {
         let worker = Arc::new(BasicWorker::new(
@@ -1570,7 +1665,7 @@ mod tests {
 
         // This will fail because there's no server at this URL,
         // but it tests that the sync wrapper doesn't panic
-        let result = worker.check_health();
+        let result = worker.check_health(&reqwest::Client::new());
         assert!(result.is_err());
     }
 
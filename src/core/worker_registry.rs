@@ -2,7 +2,7 @@
 //!
 //! Provides centralized registry for workers with model-based indexing
 
-use crate::{config::{RateMonitorConfig, RateMonitorHandle}, core::{ConnectionMode, Worker, WorkerType}};
+use crate::{config::RateMonitorHandle, core::{ConnectionMode, Worker, WorkerType}};
 use dashmap::DashMap;
 use std::sync::{Arc, RwLock};
 use uuid::Uuid;
@@ -360,7 +360,11 @@ impl WorkerRegistry {
 
     /// Start a health checker for all workers in the registry
     /// This should be called once after the registry is populated with workers
-    pub fn start_health_checker(&self, check_interval_secs: u64) -> crate::core::HealthChecker {
+    pub fn start_health_checker(
+        &self,
+        check_interval_secs: u64,
+        client: reqwest::Client,
+    ) -> crate::core::HealthChecker {
         use std::sync::atomic::{AtomicBool, Ordering};
         use std::sync::Arc;
 
@@ -393,7 +397,7 @@ impl WorkerRegistry {
 
                 // Perform health checks
                 for worker in &workers {
-                    let _ = worker.check_health_async().await; // Use async version directly
+                    let _ = worker.check_health_async(&client).await; // Use async version directly
                 }
 
                 // Reset loads periodically
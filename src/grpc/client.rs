@@ -75,6 +75,35 @@ impl VllmSchedulerClient {
         self.client.abort(request).await?;
         Ok(())
     }
+
+    /// Load a LoRA adapter
+    pub async fn load_lora(
+        &mut self,
+        adapter_id: String,
+        adapter_path: String,
+    ) -> Result<proto::LoadLoRaResponse, Box<dyn std::error::Error>> {
+        debug!("Loading LoRA adapter {} from {}", adapter_id, adapter_path);
+        let request = Request::new(proto::LoadLoRaRequest {
+            adapter_id,
+            adapter_path,
+            rank: 0,
+        });
+
+        let response = self.client.load_lo_ra(request).await?;
+        Ok(response.into_inner())
+    }
+
+    /// Unload a LoRA adapter
+    pub async fn unload_lora(
+        &mut self,
+        adapter_id: String,
+    ) -> Result<proto::UnloadLoRaResponse, Box<dyn std::error::Error>> {
+        debug!("Unloading LoRA adapter {}", adapter_id);
+        let request = Request::new(proto::UnloadLoRaRequest { adapter_id });
+
+        let response = self.client.unload_lo_ra(request).await?;
+        Ok(response.into_inner())
+    }
 }
 
 #[cfg(test)]
@@ -181,6 +181,7 @@ impl Router {
                 prefill_selector: self.prefill_selector.clone(),
                 decode_selector: self.decode_selector.clone(),
                 bootstrap_port_annotation: self.bootstrap_port_annotation.clone(),
+                ..Default::default() // File and DNS backends are CLI-only
             })
         } else {
             None
@@ -206,8 +207,28 @@ impl Router {
             worker_startup_timeout_secs: self.worker_startup_timeout_secs,
             worker_startup_check_interval_secs: self.worker_startup_check_interval,
             intra_node_data_parallel_size: self.intra_node_data_parallel_size,
+            dp_discovery: config::DpDiscoveryConfig::default(), // DP size discovery not exposed in Python binding
+            lora: config::LoraConfig::default(), // LoRA routing not exposed in Python binding
+            pd_pairing: config::PdPairingConfig::default(), // Topology-aware PD pairing not exposed in Python binding
+            decode_failover: config::DecodeFailoverConfig::default(), // Decode failover not exposed in Python binding
+            pd_capacity: config::PdCapacityConfig::default(), // PD capacity controller not exposed in Python binding
+            conditional_disaggregation: config::ConditionalDisaggregationConfig::default(), // Conditional disaggregation not exposed in Python binding
+            chunked_prefill: config::ChunkedPrefillConfig::default(), // Chunked prefill not exposed in Python binding
+            kv_transfer_protocol: config::KvTransferProtocolKind::default(), // Routing mode default
+            vllm_discovery_transport: config::VllmDiscoveryTransport::default(), // ZMQ registrations
+            rate_limit_config: None,      // Keyed rate limits are CLI-only
+            content_policy_config: None,  // Content policy is CLI-only
+            model_params: HashMap::new(), // Per-model parameters are CLI-only
+            admission: config::AdmissionConfig::default(), // Single FIFO queue class
             api_key: self.api_key.clone(),
             api_key_validation_urls: self.api_key_validation_urls.clone(),
+            api_keys_file: None, // API keys file is CLI-only
+            jwt: None,           // JWT validation is CLI-only
+            api_key_cache: config::ApiKeyCacheConfig::default(),
+            admin: config::AdminConfig::default(), // Separate admin listener is CLI-only
+            tls: None,                             // TLS is CLI-only
+            upstream_tls: config::UpstreamTlsConfig::default(),
+            audit: config::AuditConfig::default(), // Audit log is CLI-only
             discovery,
             metrics,
             log_dir: self.log_dir.clone(),
@@ -486,6 +507,7 @@ impl Router {
                 prefill_selector: self.prefill_selector.clone(),
                 decode_selector: self.decode_selector.clone(),
                 bootstrap_port_annotation: self.bootstrap_port_annotation.clone(),
+                ..Default::default() // File and DNS backends are CLI-only
             })
         } else {
             None
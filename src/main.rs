@@ -1,10 +1,15 @@
 use clap::{ArgAction, Parser, ValueEnum};
 use std::collections::HashMap;
 use vllm_router_rs::config::{
-    CircuitBreakerConfig, ConfigError, ConfigResult, ConnectionMode, DiscoveryConfig,
-    HealthCheckConfig, HistoryBackend, MetricsConfig, PolicyConfig, RetryConfig, RouterConfig,
-    RoutingMode,
+    AdminConfig, AdmissionConfig, ApiKeyCacheConfig, AuditConfig, AuditRedaction,
+    ChunkedPrefillConfig, CircuitBreakerConfig, ConditionalDisaggregationConfig, ConfigError,
+    ConfigResult, ConnectionMode, DecodeFailoverConfig, DiscoveryBackendKind, DiscoveryConfig,
+    DpDiscoveryConfig, EndpointSliceConfig, HealthCheckConfig, HistoryBackend, JwtConfig,
+    KvTransferProtocolKind, LoraConfig, MetricsConfig, ModelParamsConfig, PdCapacityConfig,
+    PdPairingConfig, PolicyConfig, PriorityClassConfig, RetryConfig, RouterConfig, RoutingMode,
+    TlsConfig, TransferCostConfig, UpstreamTlsConfig, VllmDiscoveryTransport,
 };
+use vllm_router_rs::core::parse_yaml_or_json;
 use vllm_router_rs::metrics::PrometheusConfig;
 use vllm_router_rs::server::{self, ServerConfig};
 use vllm_router_rs::service_discovery::ServiceDiscoveryConfig;
@@ -139,6 +144,10 @@ struct CliArgs {
     #[arg(long)]
     vllm_discovery_address: Option<String>,
 
+    /// How vLLM instances register at the discovery address: zmq (MessagePack over ZMQ) or http (POST /register)
+    #[arg(long, default_value = "zmq", value_parser = ["zmq", "http"])]
+    vllm_discovery_transport: String,
+
     /// Decode server URL (can be specified multiple times)
     #[arg(long, action = ArgAction::Append)]
     decode: Vec<String>,
@@ -187,6 +196,162 @@ struct CliArgs {
     #[arg(long, default_value_t = 1)]
     intra_node_data_parallel_size: usize,
 
+    /// Query each worker for its data parallel size instead of using --intra-node-data-parallel-size
+    #[arg(long, default_value_t = false)]
+    enable_dp_discovery: bool,
+
+    /// Worker endpoint reporting data_parallel_size
+    #[arg(long, default_value = "/get_server_info")]
+    dp_discovery_endpoint: String,
+
+    /// Interval in seconds between DP size re-checks (0 = only discover at registration)
+    #[arg(long, default_value_t = 30)]
+    dp_discovery_refresh_interval_secs: u64,
+
+    /// Route LoRA requests to workers that have the adapter loaded, loading it on demand otherwise
+    #[arg(long, default_value_t = false)]
+    enable_lora_routing: bool,
+
+    /// Maximum LoRA adapters loaded per worker before the least recently used one is unloaded
+    #[arg(long, default_value_t = 8)]
+    lora_max_adapters_per_worker: usize,
+
+    /// Timeout in seconds for LoRA adapter load/unload calls
+    #[arg(long, default_value_t = 60)]
+    lora_load_timeout_secs: u64,
+
+    /// Select prefill/decode pairs jointly by combined load plus KV-transfer cost (PD mode)
+    #[arg(long, default_value_t = false)]
+    pd_topology_aware_pairing: bool,
+
+    /// Weight of in-flight requests relative to transfer cost in PD pair scoring
+    #[arg(long, default_value_t = 1.0)]
+    pd_pairing_load_weight: f64,
+
+    /// KV-transfer cost for prefill/decode pairs on the same node
+    #[arg(long, default_value_t = 0.0)]
+    pd_transfer_cost_same_node: f64,
+
+    /// KV-transfer cost for prefill/decode pairs in the same rack
+    #[arg(long, default_value_t = 1.0)]
+    pd_transfer_cost_same_rack: f64,
+
+    /// KV-transfer cost for prefill/decode pairs in the same zone (also used when topology is unknown)
+    #[arg(long, default_value_t = 4.0)]
+    pd_transfer_cost_same_zone: f64,
+
+    /// KV-transfer cost for prefill/decode pairs in different zones
+    #[arg(long, default_value_t = 16.0)]
+    pd_transfer_cost_cross_zone: f64,
+
+    /// Worker placement as URL=zone[/rack[/node]] (e.g. http://10.0.0.1:8000=us-east-1a/rack-3/node-7)
+    #[arg(long, num_args = 0..)]
+    worker_topology: Vec<String>,
+
+    /// Re-run PD requests on another prefill/decode pair when the decode stage fails
+    #[arg(long, default_value_t = false)]
+    pd_decode_failover: bool,
+
+    /// Maximum prefill/decode pairs tried per request when decode failover is enabled
+    #[arg(long, default_value_t = 2)]
+    pd_decode_failover_max_attempts: u32,
+
+    /// Regular worker URLs that serve PD requests once every prefill/decode pair has failed
+    #[arg(long, num_args = 0..)]
+    pd_fallback_worker_urls: Vec<String>,
+
+    /// Measure prefill time vs decode inter-token latency and recommend prefill/decode role flips
+    #[arg(long, default_value_t = false)]
+    enable_pd_capacity_controller: bool,
+
+    /// Seconds between PD capacity evaluations
+    #[arg(long, default_value_t = 30)]
+    pd_capacity_interval_secs: u64,
+
+    /// Seconds of timing samples considered by a PD capacity evaluation
+    #[arg(long, default_value_t = 300)]
+    pd_capacity_window_secs: u64,
+
+    /// Minimum prefill and decode samples before a role flip is recommended
+    #[arg(long, default_value_t = 50)]
+    pd_capacity_min_samples: usize,
+
+    /// Target prefill time (queueing plus compute) in milliseconds
+    #[arg(long, default_value_t = 1000.0)]
+    pd_prefill_time_target_ms: f64,
+
+    /// Target decode inter-token latency in milliseconds
+    #[arg(long, default_value_t = 50.0)]
+    pd_decode_itl_target_ms: f64,
+
+    /// How much more one role must exceed its target than the other before flipping a worker
+    #[arg(long, default_value_t = 1.5)]
+    pd_capacity_imbalance_ratio: f64,
+
+    /// Workers that must remain in each role after a flip
+    #[arg(long, default_value_t = 1)]
+    pd_capacity_min_workers_per_role: usize,
+
+    /// Minimum seconds between two role flips
+    #[arg(long, default_value_t = 600)]
+    pd_capacity_cooldown_secs: u64,
+
+    /// Perform recommended role flips automatically (requires --pd-role-flip-actuator-path)
+    #[arg(long, default_value_t = false)]
+    pd_capacity_auto_apply: bool,
+
+    /// Worker endpoint POSTed with {"role": "prefill"|"decode"} to flip its role (e.g. /restart)
+    #[arg(long)]
+    pd_role_flip_actuator_path: Option<String>,
+
+    /// Bootstrap port for decode workers flipped to prefill when it is not otherwise known
+    #[arg(long)]
+    pd_role_flip_bootstrap_port: Option<u16>,
+
+    /// Skip the prefill stage for short prompts (vLLM PD mode)
+    #[arg(long, default_value_t = false)]
+    pd_conditional_disaggregation: bool,
+
+    /// Prompts with fewer tokens than this go straight to a decode (or mixed) worker
+    #[arg(long, default_value_t = 256)]
+    pd_min_prefill_tokens: usize,
+
+    /// Skip remote prefill when a decode worker's prefix cache matches at least this fraction of the request (cache_aware decode policy)
+    #[arg(long)]
+    pd_prefix_cache_threshold: Option<f64>,
+
+    /// Regular worker URLs that serve short prompts instead of the decode workers
+    #[arg(long, num_args = 0..)]
+    pd_mixed_worker_urls: Vec<String>,
+
+    /// Place long prompts on the least loaded prefill worker with enough free KV cache (vLLM PD mode)
+    #[arg(long, default_value_t = false)]
+    pd_chunked_prefill: bool,
+
+    /// Prompts with at least this many tokens are placed by prefill KV capacity
+    #[arg(long, default_value_t = 32768)]
+    pd_long_prompt_tokens: usize,
+
+    /// Split long prompts into chunks prefilled concurrently on several workers (experimental, needs connector support)
+    #[arg(long, default_value_t = false)]
+    pd_context_parallel_prefill: bool,
+
+    /// Target tokens per context-parallel prefill chunk
+    #[arg(long, default_value_t = 32768)]
+    pd_prefill_chunk_tokens: usize,
+
+    /// Maximum prefill workers a prompt is split across
+    #[arg(long, default_value_t = 4)]
+    pd_max_prefill_chunks: usize,
+
+    /// Seconds between KV capacity scrapes of the prefill workers
+    #[arg(long, default_value_t = 10)]
+    pd_kv_metrics_interval_secs: u64,
+
+    /// KV transfer protocol (auto picks bootstrap in PD mode, nixl with p2p_nccl request ids in vLLM PD mode)
+    #[arg(long, default_value = "auto", value_parser = ["auto", "bootstrap", "nixl", "p2p_nccl"])]
+    kv_transfer_protocol: String,
+
     /// API key for worker authorization
     #[arg(long)]
     api_key: Option<String>,
@@ -195,6 +360,123 @@ struct CliArgs {
     #[arg(long, num_args = 0..)]
     api_key_validation_urls: Vec<String>,
 
+    /// Host address to bind the admin listener; non-loopback hosts require --admin-api-key
+    #[arg(long, default_value = "127.0.0.1")]
+    admin_host: String,
+
+    /// Serve the worker management endpoints on this port instead of the public one
+    #[arg(long)]
+    admin_port: Option<u16>,
+
+    /// Bearer token required on the worker management endpoints (defaults to env file)
+    #[arg(long)]
+    admin_api_key: Option<String>,
+
+    /// PEM certificate chain for TLS on the router's listeners (reloaded on change)
+    #[arg(long, requires = "tls_key_file")]
+    tls_cert_file: Option<String>,
+
+    /// PEM private key for --tls-cert-file
+    #[arg(long, requires = "tls_cert_file")]
+    tls_key_file: Option<String>,
+
+    /// PEM bundle of CAs trusted for client certificates; enables mTLS
+    #[arg(long, requires = "tls_cert_file")]
+    tls_client_ca_file: Option<String>,
+
+    /// Accept clients without a certificate when --tls-client-ca-file is set
+    #[arg(long, default_value_t = false, requires = "tls_client_ca_file")]
+    tls_client_auth_optional: bool,
+
+    /// PEM bundle of CAs trusted for worker connections, in addition to the system roots
+    #[arg(long)]
+    upstream_ca_file: Option<String>,
+
+    /// PEM client certificate presented to workers
+    #[arg(long, requires = "upstream_client_key_file")]
+    upstream_client_cert_file: Option<String>,
+
+    /// PKCS#8 PEM key for --upstream-client-cert-file
+    #[arg(long, requires = "upstream_client_cert_file")]
+    upstream_client_key_file: Option<String>,
+
+    /// JSONL file the audit trail of requests and admin actions is appended to
+    #[arg(long)]
+    audit_log_file: Option<String>,
+
+    /// Size in MB at which the audit log file is rotated
+    #[arg(long, default_value_t = 100)]
+    audit_log_max_size_mb: u64,
+
+    /// Rotated audit log files to keep
+    #[arg(long, default_value_t = 10)]
+    audit_log_max_files: usize,
+
+    /// Unix socket audit records are also written to
+    #[arg(long)]
+    audit_unix_socket: Option<String>,
+
+    /// URL audit records are also POSTed to as newline-delimited JSON
+    #[arg(long)]
+    audit_http_sink: Option<String>,
+
+    /// How prompts appear in audit records (redact, hash or full)
+    #[arg(long, default_value = "redact", value_parser = ["redact", "hash", "full"])]
+    audit_prompts: String,
+
+    /// How model outputs appear in audit records (redact, hash or full)
+    #[arg(long, default_value = "redact", value_parser = ["redact", "hash", "full"])]
+    audit_outputs: String,
+
+    /// YAML or JSON file of hashed API keys with scopes, allowed models and rate-limit classes
+    #[arg(long)]
+    api_keys_file: Option<String>,
+
+    /// Seconds remote API key validation results are cached
+    #[arg(long, default_value_t = 300)]
+    api_key_cache_ttl_secs: u64,
+
+    /// Maximum number of cached API key validation results
+    #[arg(long, default_value_t = 10000)]
+    api_key_cache_size: usize,
+
+    /// JWKS file for local validation of JWT bearer tokens
+    #[arg(long, conflicts_with = "jwt_jwks_url")]
+    jwt_jwks_file: Option<String>,
+
+    /// JWKS URL for local validation of JWT bearer tokens (e.g. an OIDC jwks_uri)
+    #[arg(long)]
+    jwt_jwks_url: Option<String>,
+
+    /// Interval in seconds between JWKS reloads
+    #[arg(long, default_value_t = 300)]
+    jwt_jwks_refresh_secs: u64,
+
+    /// Required JWT issuer (iss)
+    #[arg(long)]
+    jwt_issuer: Option<String>,
+
+    /// Accepted JWT audiences (aud); not checked when empty
+    #[arg(long, num_args = 0..)]
+    jwt_audience: Vec<String>,
+
+    /// JWT claim holding the tenant for rate limits, dot-separated for nested claims
+    #[arg(long)]
+    jwt_tenant_claim: Option<String>,
+
+    /// JWT claim listing scopes or roles (e.g. scope or realm_access.roles); tokens
+    /// need --jwt-admin-value in it to use the admin endpoints
+    #[arg(long)]
+    jwt_admin_claim: Option<String>,
+
+    /// Scope or role in --jwt-admin-claim granting admin access
+    #[arg(long, default_value = "admin")]
+    jwt_admin_value: String,
+
+    /// Allowed clock skew in seconds for JWT expiry checks
+    #[arg(long, default_value_t = 60)]
+    jwt_leeway_secs: u64,
+
     /// Backend to route requests to (vllm, trtllm, openai, anthropic)
     #[arg(long, value_enum, default_value_t = Backend::Vllm, alias = "runtime")]
     backend: Backend,
@@ -223,6 +505,42 @@ struct CliArgs {
     #[arg(long)]
     service_discovery_namespace: Option<String>,
 
+    /// Service discovery backend: kubernetes (pod watch), endpoint_slices (Service EndpointSlice watch), file (JSON/YAML worker file) or dns (A/SRV polling)
+    #[arg(long, default_value = "kubernetes", value_parser = ["kubernetes", "endpoint_slices", "file", "dns"])]
+    service_discovery_backend: String,
+
+    /// Service whose endpoints are regular workers (endpoint_slices backend)
+    #[arg(long)]
+    service_discovery_service: Option<String>,
+
+    /// Service whose endpoints are prefill workers in PD mode (endpoint_slices backend)
+    #[arg(long)]
+    prefill_service: Option<String>,
+
+    /// Service whose endpoints are decode workers in PD mode (endpoint_slices backend)
+    #[arg(long)]
+    decode_service: Option<String>,
+
+    /// Name of the worker HTTP port in EndpointSlices
+    #[arg(long, default_value = "http")]
+    service_discovery_http_port_name: String,
+
+    /// Name of the prefill bootstrap port in EndpointSlices
+    #[arg(long, default_value = "bootstrap")]
+    service_discovery_bootstrap_port_name: String,
+
+    /// Name of the ZMQ port in EndpointSlices
+    #[arg(long, default_value = "zmq")]
+    service_discovery_zmq_port_name: String,
+
+    /// Worker file watched by the file discovery backend
+    #[arg(long)]
+    service_discovery_file: Option<String>,
+
+    /// Names polled by the dns discovery backend (SRV names start with "_"; prefix with prefill= or decode= to set the role)
+    #[arg(long, num_args = 0..)]
+    service_discovery_dns_names: Vec<String>,
+
     /// Label selector for prefill server pods in PD mode
     #[arg(long, num_args = 0..)]
     prefill_selector: Vec<String>,
@@ -251,6 +569,43 @@ struct CliArgs {
     #[arg(long, default_value_t = 32768)]
     max_concurrent_requests: usize,
 
+    /// YAML or JSON file with rate limits per authenticated caller, tenant and model
+    #[arg(long)]
+    rate_limit_config: Option<String>,
+
+    /// YAML or JSON file of content policy filters (deny-lists, prompt length, classifier, rewrites)
+    #[arg(long)]
+    content_policy_config: Option<String>,
+
+    /// YAML or JSON file mapping model names ('*' for any other model) to sampling
+    /// parameter defaults and clamps
+    #[arg(long)]
+    model_params_config: Option<String>,
+
+    /// Priority classes of the request queue, highest first, as NAME[:QUEUE_SIZE[:TIMEOUT_SECS]]
+    #[arg(long, num_args = 0..)]
+    priority_classes: Vec<String>,
+
+    /// Priority class for requests not matched by any rule (defaults to the lowest class)
+    #[arg(long)]
+    default_priority_class: Option<String>,
+
+    /// Header clients may use to request a priority class by name
+    #[arg(long)]
+    priority_header: Option<String>,
+
+    /// API key to priority class mappings as KEY=CLASS
+    #[arg(long, num_args = 0..)]
+    priority_api_keys: Vec<String>,
+
+    /// Endpoint path prefix to priority class mappings as PREFIX=CLASS
+    #[arg(long, num_args = 0..)]
+    priority_endpoints: Vec<String>,
+
+    /// Fair-queuing weights of tenants as TENANT=WEIGHT
+    #[arg(long, num_args = 0..)]
+    tenant_weights: Vec<String>,
+
     /// CORS allowed origins
     #[arg(long, num_args = 0..)]
     cors_allowed_origins: Vec<String>,
@@ -358,6 +713,28 @@ impl CliArgs {
         ConnectionMode::Http
     }
 
+    /// Service discovery backend selected on the command line
+    fn discovery_backend(&self) -> DiscoveryBackendKind {
+        match self.service_discovery_backend.as_str() {
+            "file" => DiscoveryBackendKind::File,
+            "dns" => DiscoveryBackendKind::Dns,
+            "endpoint_slices" => DiscoveryBackendKind::EndpointSlices,
+            _ => DiscoveryBackendKind::Kubernetes,
+        }
+    }
+
+    /// Services and port names for the EndpointSlice backend
+    fn endpoint_slice_config(&self) -> EndpointSliceConfig {
+        EndpointSliceConfig {
+            service: self.service_discovery_service.clone(),
+            prefill_service: self.prefill_service.clone(),
+            decode_service: self.decode_service.clone(),
+            http_port_name: self.service_discovery_http_port_name.clone(),
+            bootstrap_port_name: self.service_discovery_bootstrap_port_name.clone(),
+            zmq_port_name: self.service_discovery_zmq_port_name.clone(),
+        }
+    }
+
     /// Parse selector strings into HashMap
     fn parse_selector(selector_list: &[String]) -> HashMap<String, String> {
         let mut map = HashMap::new();
@@ -371,6 +748,87 @@ impl CliArgs {
         map
     }
 
+    /// Parse URL=zone[/rack[/node]] strings into a URL -> topology map
+    ///
+    /// Splits at the last '=' so URLs containing '=' still parse.
+    fn parse_worker_topology(topology_list: &[String]) -> HashMap<String, String> {
+        topology_list
+            .iter()
+            .filter_map(|item| item.rsplit_once('='))
+            .map(|(url, topology)| (url.to_string(), topology.to_string()))
+            .collect()
+    }
+
+    /// Parse NAME[:QUEUE_SIZE[:TIMEOUT_SECS]] priority class specs
+    fn parse_priority_classes(specs: &[String]) -> ConfigResult<Vec<PriorityClassConfig>> {
+        specs
+            .iter()
+            .map(|spec| {
+                let mut parts = spec.split(':');
+                let name = parts.next().unwrap_or_default().to_string();
+                let mut number = |field: &str| {
+                    parts
+                        .next()
+                        .filter(|value| !value.is_empty())
+                        .map(|value| {
+                            value.parse().map_err(|_| ConfigError::InvalidValue {
+                                field: format!("priority_classes.{}", field),
+                                value: spec.clone(),
+                                reason: "Expected NAME[:QUEUE_SIZE[:TIMEOUT_SECS]]".to_string(),
+                            })
+                        })
+                        .transpose()
+                };
+                let queue_size = number("queue_size")?;
+                let queue_timeout_secs = number("queue_timeout_secs")?;
+                Ok(PriorityClassConfig {
+                    name,
+                    queue_size: queue_size.map(|size: u64| size as usize),
+                    queue_timeout_secs,
+                })
+            })
+            .collect()
+    }
+
+    /// Load per-model parameter defaults and clamps from a YAML or JSON file
+    fn load_model_params(path: Option<&str>) -> ConfigResult<HashMap<String, ModelParamsConfig>> {
+        let Some(path) = path else {
+            return Ok(HashMap::new());
+        };
+        let invalid = |reason: String| ConfigError::InvalidValue {
+            field: "model_params_config".to_string(),
+            value: path.to_string(),
+            reason,
+        };
+        let contents = std::fs::read_to_string(path).map_err(|e| invalid(e.to_string()))?;
+        parse_yaml_or_json(path, &contents).map_err(invalid)
+    }
+
+    /// Map an --audit-prompts/--audit-outputs value to its redaction mode
+    fn parse_audit_redaction(value: &str) -> AuditRedaction {
+        match value {
+            "hash" => AuditRedaction::Hash,
+            "full" => AuditRedaction::Full,
+            _ => AuditRedaction::Redact,
+        }
+    }
+
+    /// Parse TENANT=WEIGHT strings into a tenant -> weight map
+    fn parse_tenant_weights(weights: &[String]) -> ConfigResult<HashMap<String, f64>> {
+        weights
+            .iter()
+            .map(|item| {
+                item.rsplit_once('=')
+                    .and_then(|(tenant, weight)| Some((tenant.to_string(), weight.parse().ok()?)))
+                    .ok_or_else(|| ConfigError::InvalidValue {
+                        field: "tenant_weights".to_string(),
+                        value: item.clone(),
+                        reason: "Expected TENANT=WEIGHT".to_string(),
+                    })
+            })
+            .collect()
+    }
+
     /// Convert policy string to PolicyConfig
     fn parse_policy(&self, policy_str: &str) -> PolicyConfig {
         match policy_str {
@@ -444,11 +902,18 @@ impl CliArgs {
             let use_static_urls = !prefill_urls.is_empty() || !decode_urls.is_empty();
             let use_vllm_discovery = self.vllm_discovery_address.is_some();
             let use_k8s_discovery = self.service_discovery
+                && self.discovery_backend() == DiscoveryBackendKind::Kubernetes
                 && (!self.prefill_selector.is_empty() || !self.decode_selector.is_empty());
+            let use_polling_discovery = self.service_discovery
+                && self.discovery_backend() != DiscoveryBackendKind::Kubernetes;
 
-            if !use_static_urls && !use_vllm_discovery && !use_k8s_discovery {
+            if !use_static_urls
+                && !use_vllm_discovery
+                && !use_k8s_discovery
+                && !use_polling_discovery
+            {
                 return Err(ConfigError::ValidationFailed {
-                    reason: "vLLM PD disaggregation mode requires one of: --vllm-discovery-address, --prefill/--decode URLs, or --service-discovery with --prefill-selector/--decode-selector".to_string(),
+                    reason: "vLLM PD disaggregation mode requires one of: --vllm-discovery-address, --prefill/--decode URLs, or --service-discovery with --prefill-selector/--decode-selector or another discovery backend".to_string(),
                 });
             }
 
@@ -466,6 +931,11 @@ impl CliArgs {
                         prefill_urls, final_decode_urls
                     );
                 }
+            } else if use_polling_discovery {
+                eprintln!(
+                    "ℹ️  INFO: Using {} service discovery mode for vLLM PD disaggregation.",
+                    self.service_discovery_backend
+                );
             } else if use_static_urls && use_vllm_discovery {
                 eprintln!("ℹ️  INFO: Using hybrid mode - static URLs as fallback, vLLM ZMQ discovery for dynamic workers.");
                 eprintln!("   Prefill URLs: {:?}", prefill_urls);
@@ -514,6 +984,10 @@ impl CliArgs {
                 prefill_selector: Self::parse_selector(&self.prefill_selector),
                 decode_selector: Self::parse_selector(&self.decode_selector),
                 bootstrap_port_annotation: "vllm.ai/bootstrap-port".to_string(),
+                backend: self.discovery_backend(),
+                file_path: self.service_discovery_file.clone(),
+                dns_names: self.service_discovery_dns_names.clone(),
+                endpoint_slices: self.endpoint_slice_config(),
             })
         } else {
             None
@@ -572,6 +1046,11 @@ impl CliArgs {
             Vec::new()
         };
 
+        let admin_api_key = self
+            .admin_api_key
+            .clone()
+            .or_else(|| std::env::var("ADMIN_API_KEY").ok());
+
         // Build RouterConfig
         Ok(RouterConfig {
             mode,
@@ -584,8 +1063,121 @@ impl CliArgs {
             worker_startup_timeout_secs: self.worker_startup_timeout_secs,
             worker_startup_check_interval_secs: self.worker_startup_check_interval,
             intra_node_data_parallel_size: self.intra_node_data_parallel_size,
+            dp_discovery: DpDiscoveryConfig {
+                enabled: self.enable_dp_discovery,
+                endpoint: self.dp_discovery_endpoint.clone(),
+                refresh_interval_secs: self.dp_discovery_refresh_interval_secs,
+            },
+            lora: LoraConfig {
+                enabled: self.enable_lora_routing,
+                max_adapters_per_worker: self.lora_max_adapters_per_worker,
+                load_timeout_secs: self.lora_load_timeout_secs,
+            },
+            pd_pairing: PdPairingConfig {
+                enabled: self.pd_topology_aware_pairing,
+                load_weight: self.pd_pairing_load_weight,
+                transfer_cost: TransferCostConfig {
+                    same_node: self.pd_transfer_cost_same_node,
+                    same_rack: self.pd_transfer_cost_same_rack,
+                    same_zone: self.pd_transfer_cost_same_zone,
+                    cross_zone: self.pd_transfer_cost_cross_zone,
+                },
+                worker_topology: Self::parse_worker_topology(&self.worker_topology),
+            },
+            decode_failover: DecodeFailoverConfig {
+                enabled: self.pd_decode_failover,
+                max_attempts: self.pd_decode_failover_max_attempts,
+                fallback_worker_urls: self.pd_fallback_worker_urls.clone(),
+            },
+            pd_capacity: PdCapacityConfig {
+                enabled: self.enable_pd_capacity_controller,
+                evaluation_interval_secs: self.pd_capacity_interval_secs,
+                window_secs: self.pd_capacity_window_secs,
+                min_samples: self.pd_capacity_min_samples,
+                prefill_time_target_ms: self.pd_prefill_time_target_ms,
+                decode_itl_target_ms: self.pd_decode_itl_target_ms,
+                imbalance_ratio: self.pd_capacity_imbalance_ratio,
+                min_workers_per_role: self.pd_capacity_min_workers_per_role,
+                cooldown_secs: self.pd_capacity_cooldown_secs,
+                auto_apply: self.pd_capacity_auto_apply,
+                actuator_path: self.pd_role_flip_actuator_path.clone(),
+                bootstrap_port: self.pd_role_flip_bootstrap_port,
+            },
+            conditional_disaggregation: ConditionalDisaggregationConfig {
+                enabled: self.pd_conditional_disaggregation,
+                min_prefill_tokens: self.pd_min_prefill_tokens,
+                prefix_cache_threshold: self.pd_prefix_cache_threshold,
+                mixed_worker_urls: self.pd_mixed_worker_urls.clone(),
+            },
+            chunked_prefill: ChunkedPrefillConfig {
+                enabled: self.pd_chunked_prefill,
+                min_prompt_tokens: self.pd_long_prompt_tokens,
+                context_parallel: self.pd_context_parallel_prefill,
+                chunk_tokens: self.pd_prefill_chunk_tokens,
+                max_chunks: self.pd_max_prefill_chunks,
+                metrics_interval_secs: self.pd_kv_metrics_interval_secs,
+                ..Default::default()
+            },
+            kv_transfer_protocol: match self.kv_transfer_protocol.as_str() {
+                "bootstrap" => KvTransferProtocolKind::Bootstrap,
+                "nixl" => KvTransferProtocolKind::Nixl,
+                "p2p_nccl" => KvTransferProtocolKind::P2pNccl,
+                _ => KvTransferProtocolKind::Auto,
+            },
+            vllm_discovery_transport: match self.vllm_discovery_transport.as_str() {
+                "http" => VllmDiscoveryTransport::Http,
+                _ => VllmDiscoveryTransport::Zmq,
+            },
+            rate_limit_config: self.rate_limit_config.clone(),
+            content_policy_config: self.content_policy_config.clone(),
+            model_params: Self::load_model_params(self.model_params_config.as_deref())?,
             api_key: self.api_key.clone(),
             api_key_validation_urls,
+            api_keys_file: self.api_keys_file.clone(),
+            jwt: (self.jwt_jwks_file.is_some() || self.jwt_jwks_url.is_some()).then(|| JwtConfig {
+                jwks_file: self.jwt_jwks_file.clone(),
+                jwks_url: self.jwt_jwks_url.clone(),
+                jwks_refresh_secs: self.jwt_jwks_refresh_secs,
+                issuer: self.jwt_issuer.clone(),
+                audience: self.jwt_audience.clone(),
+                tenant_claim: self.jwt_tenant_claim.clone(),
+                admin_claim: self.jwt_admin_claim.clone(),
+                admin_value: self.jwt_admin_value.clone(),
+                leeway_secs: self.jwt_leeway_secs,
+            }),
+            api_key_cache: ApiKeyCacheConfig {
+                ttl_secs: self.api_key_cache_ttl_secs,
+                max_entries: self.api_key_cache_size,
+            },
+            admin: AdminConfig {
+                host: self.admin_host.clone(),
+                port: self.admin_port,
+                api_key: admin_api_key,
+            },
+            tls: self
+                .tls_cert_file
+                .clone()
+                .zip(self.tls_key_file.clone())
+                .map(|(cert_file, key_file)| TlsConfig {
+                    cert_file,
+                    key_file,
+                    client_ca_file: self.tls_client_ca_file.clone(),
+                    client_auth_optional: self.tls_client_auth_optional,
+                }),
+            upstream_tls: UpstreamTlsConfig {
+                ca_file: self.upstream_ca_file.clone(),
+                client_cert_file: self.upstream_client_cert_file.clone(),
+                client_key_file: self.upstream_client_key_file.clone(),
+            },
+            audit: AuditConfig {
+                file: self.audit_log_file.clone(),
+                max_file_size_mb: self.audit_log_max_size_mb,
+                max_files: self.audit_log_max_files,
+                unix_socket: self.audit_unix_socket.clone(),
+                http_sink: self.audit_http_sink.clone(),
+                prompts: Self::parse_audit_redaction(&self.audit_prompts),
+                outputs: Self::parse_audit_redaction(&self.audit_outputs),
+            },
             discovery,
             metrics,
             log_dir: self.log_dir.clone(),
@@ -598,6 +1190,14 @@ impl CliArgs {
             max_concurrent_requests: self.max_concurrent_requests,
             queue_size: 100,        // Default queue size
             queue_timeout_secs: 60, // Default timeout
+            admission: AdmissionConfig {
+                classes: Self::parse_priority_classes(&self.priority_classes)?,
+                default_class: self.default_priority_class.clone(),
+                priority_header: self.priority_header.clone(),
+                api_key_classes: Self::parse_selector(&self.priority_api_keys),
+                endpoint_classes: Self::parse_selector(&self.priority_endpoints),
+                tenant_weights: Self::parse_tenant_weights(&self.tenant_weights)?,
+            },
             cors_allowed_origins: self.cors_allowed_origins.clone(),
             retry: RetryConfig {
                 max_retries: self.retry_max_retries,
@@ -649,6 +1249,10 @@ impl CliArgs {
                 prefill_selector: Self::parse_selector(&self.prefill_selector),
                 decode_selector: Self::parse_selector(&self.decode_selector),
                 bootstrap_port_annotation: "vllm.ai/bootstrap-port".to_string(),
+                backend: self.discovery_backend(),
+                file_path: self.service_discovery_file.clone(),
+                dns_names: self.service_discovery_dns_names.clone(),
+                endpoint_slices: self.endpoint_slice_config(),
             })
         } else {
             None
@@ -44,6 +44,18 @@ pub fn init_metrics() {
         "vllm_router_retries_exhausted_total",
         "Total number of requests that exhausted retries by route"
     );
+    describe_counter!(
+        "vllm_router_rate_limited_total",
+        "Total number of requests rejected by keyed rate limits by dimension"
+    );
+    describe_counter!(
+        "vllm_router_token_limited_total",
+        "Total number of requests rejected by token rate limits or quotas by dimension and limit"
+    );
+    describe_counter!(
+        "vllm_router_rate_limit_tokens_total",
+        "Total number of tokens charged to token rate limits by kind"
+    );
 
     // Circuit breaker metrics
     describe_gauge!(
@@ -157,6 +169,94 @@ pub fn init_metrics() {
         "Embedding request errors"
     );
     describe_gauge!("vllm_router_embeddings_queue_size", "Embedding queue size");
+    describe_gauge!(
+        "vllm_router_queue_depth",
+        "Requests waiting in the admission queue by priority class"
+    );
+    describe_histogram!(
+        "vllm_router_queue_wait_duration_seconds",
+        "Time requests waited in the admission queue by priority class"
+    );
+    describe_counter!(
+        "vllm_router_queue_timeouts_total",
+        "Requests that timed out in the admission queue by priority class"
+    );
+    describe_counter!(
+        "vllm_router_queue_rejected_total",
+        "Requests rejected because their priority class queue was full"
+    );
+
+    // LoRA adapter metrics
+    describe_counter!(
+        "vllm_router_lora_operations_total",
+        "LoRA adapter load/unload operations per worker"
+    );
+
+    // PD pairing metrics
+    describe_counter!(
+        "vllm_router_pd_pair_locality_total",
+        "PD pairs selected by topology-aware pairing, by KV-transfer locality"
+    );
+    describe_counter!(
+        "vllm_router_pd_decode_failovers_total",
+        "Decode failures that triggered a retry on another PD pair, by failed decode worker"
+    );
+    describe_counter!(
+        "vllm_router_pd_failover_results_total",
+        "Outcome of PD requests that needed decode failover (recovered, fallback, failed)"
+    );
+    describe_histogram!(
+        "vllm_router_pd_failover_attempts",
+        "PD pairs tried for requests that needed decode failover"
+    );
+    describe_counter!(
+        "vllm_router_pd_prefill_bypass_total",
+        "PD requests that skipped the prefill stage, by reason (short_prompt, prefix_cache)"
+    );
+    describe_gauge!(
+        "vllm_router_pd_prefill_kv_free_tokens",
+        "Free KV cache tokens reported by each prefill worker"
+    );
+    describe_counter!(
+        "vllm_router_pd_long_prompt_placements_total",
+        "Long prompts placed by prefill KV capacity, by mode (single, context_parallel, unplaced)"
+    );
+
+    // PD stage timing metrics
+    describe_histogram!(
+        "vllm_router_pd_prefill_duration_seconds",
+        "Time from dispatch until the prefill worker finished, by prefill worker"
+    );
+    describe_histogram!(
+        "vllm_router_pd_kv_transfer_wait_seconds",
+        "Time from the end of prefill until the first decode token, by prefill and decode worker"
+    );
+    describe_histogram!(
+        "vllm_router_pd_ttft_seconds",
+        "Time to first token of streamed PD requests, by prefill and decode worker"
+    );
+    describe_histogram!(
+        "vllm_router_pd_inter_token_latency_seconds",
+        "Time between streamed decode chunks, by decode worker"
+    );
+
+    // PD capacity controller metrics
+    describe_gauge!(
+        "vllm_router_pd_prefill_time_ms",
+        "Average prefill time (queueing plus compute) over the capacity window"
+    );
+    describe_gauge!(
+        "vllm_router_pd_decode_itl_ms",
+        "Average decode inter-token latency over the capacity window"
+    );
+    describe_gauge!(
+        "vllm_router_pd_role_flip_recommended",
+        "1 when the capacity controller recommends a role flip in this direction"
+    );
+    describe_counter!(
+        "vllm_router_pd_role_flips_total",
+        "Prefill/decode role flips performed, by direction and result"
+    );
 
     // Running requests gauge for cache-aware policy
     describe_gauge!(
@@ -327,6 +427,28 @@ impl RouterMetrics {
         .increment(1);
     }
 
+    pub fn record_rate_limited(dimension: &str) {
+        counter!("vllm_router_rate_limited_total",
+            "dimension" => dimension.to_string()
+        )
+        .increment(1);
+    }
+
+    pub fn record_token_limited(dimension: &str, limit: &str) {
+        counter!("vllm_router_token_limited_total",
+            "dimension" => dimension.to_string(),
+            "limit" => limit.to_string()
+        )
+        .increment(1);
+    }
+
+    pub fn record_rate_limit_tokens(prompt_tokens: u64, completion_tokens: u64) {
+        counter!("vllm_router_rate_limit_tokens_total", "kind" => "prompt")
+            .increment(prompt_tokens);
+        counter!("vllm_router_rate_limit_tokens_total", "kind" => "completion")
+            .increment(completion_tokens);
+    }
+
     // Worker metrics
     pub fn set_active_workers(count: usize) {
         gauge!("vllm_router_active_workers").set(count as f64);
@@ -476,6 +598,154 @@ impl RouterMetrics {
         gauge!("vllm_router_embeddings_queue_size").set(size as f64);
     }
 
+    // Admission queue metrics
+    pub fn set_queue_depth(class: &str, depth: usize) {
+        gauge!("vllm_router_queue_depth",
+            "class" => class.to_string()
+        )
+        .set(depth as f64);
+    }
+
+    pub fn record_queue_wait(class: &str, duration: Duration) {
+        histogram!("vllm_router_queue_wait_duration_seconds",
+            "class" => class.to_string()
+        )
+        .record(duration.as_secs_f64());
+    }
+
+    pub fn record_queue_timeout(class: &str) {
+        counter!("vllm_router_queue_timeouts_total",
+            "class" => class.to_string()
+        )
+        .increment(1);
+    }
+
+    pub fn record_queue_rejected(class: &str) {
+        counter!("vllm_router_queue_rejected_total",
+            "class" => class.to_string()
+        )
+        .increment(1);
+    }
+
+    // LoRA adapter metrics
+    pub fn record_lora_operation(worker: &str, operation: &str, success: bool) {
+        counter!("vllm_router_lora_operations_total",
+            "worker" => worker.to_string(),
+            "operation" => operation.to_string(),
+            "result" => if success { "success" } else { "failure" }
+        )
+        .increment(1);
+    }
+
+    // PD pairing metrics
+    pub fn record_pd_pair_locality(locality: &str) {
+        counter!("vllm_router_pd_pair_locality_total",
+            "locality" => locality.to_string()
+        )
+        .increment(1);
+    }
+
+    pub fn record_pd_decode_failover(decode_url: &str) {
+        counter!("vllm_router_pd_decode_failovers_total",
+            "worker" => decode_url.to_string()
+        )
+        .increment(1);
+    }
+
+    pub fn record_pd_failover_result(route: &str, result: &str, attempts: u32) {
+        counter!("vllm_router_pd_failover_results_total",
+            "route" => route.to_string(),
+            "result" => result.to_string()
+        )
+        .increment(1);
+        histogram!("vllm_router_pd_failover_attempts",
+            "route" => route.to_string()
+        )
+        .record(attempts as f64);
+    }
+
+    pub fn record_pd_prefill_duration(prefill_worker: &str, duration: Duration) {
+        histogram!("vllm_router_pd_prefill_duration_seconds",
+            "prefill_worker" => prefill_worker.to_string()
+        )
+        .record(duration.as_secs_f64());
+    }
+
+    pub fn record_pd_kv_transfer_wait(
+        prefill_worker: &str,
+        decode_worker: &str,
+        duration: Duration,
+    ) {
+        histogram!("vllm_router_pd_kv_transfer_wait_seconds",
+            "prefill_worker" => prefill_worker.to_string(),
+            "decode_worker" => decode_worker.to_string()
+        )
+        .record(duration.as_secs_f64());
+    }
+
+    pub fn record_pd_ttft(prefill_worker: &str, decode_worker: &str, duration: Duration) {
+        histogram!("vllm_router_pd_ttft_seconds",
+            "prefill_worker" => prefill_worker.to_string(),
+            "decode_worker" => decode_worker.to_string()
+        )
+        .record(duration.as_secs_f64());
+    }
+
+    pub fn record_pd_inter_token_latency(decode_worker: &str, duration: Duration) {
+        histogram!("vllm_router_pd_inter_token_latency_seconds",
+            "decode_worker" => decode_worker.to_string()
+        )
+        .record(duration.as_secs_f64());
+    }
+
+    pub fn record_pd_prefill_bypass(reason: &str) {
+        counter!("vllm_router_pd_prefill_bypass_total",
+            "reason" => reason.to_string()
+        )
+        .increment(1);
+    }
+
+    pub fn set_pd_prefill_kv_free_tokens(worker: &str, tokens: usize) {
+        gauge!("vllm_router_pd_prefill_kv_free_tokens",
+            "worker" => worker.to_string()
+        )
+        .set(tokens as f64);
+    }
+
+    pub fn record_pd_long_prompt_placement(mode: &str) {
+        counter!("vllm_router_pd_long_prompt_placements_total",
+            "mode" => mode.to_string()
+        )
+        .increment(1);
+    }
+
+    pub fn set_pd_capacity_signals(prefill_time_ms: f64, decode_itl_ms: f64) {
+        gauge!("vllm_router_pd_prefill_time_ms").set(prefill_time_ms);
+        gauge!("vllm_router_pd_decode_itl_ms").set(decode_itl_ms);
+    }
+
+    pub fn set_pd_role_flip_recommendation(recommended: Option<&str>) {
+        for direction in ["decode_to_prefill", "prefill_to_decode"] {
+            let value = if recommended == Some(direction) {
+                1.0
+            } else {
+                0.0
+            };
+            gauge!("vllm_router_pd_role_flip_recommended",
+                "direction" => direction
+            )
+            .set(value);
+        }
+    }
+
+    pub fn record_pd_role_flip(direction: &str, success: bool) {
+        counter!("vllm_router_pd_role_flips_total",
+            "direction" => direction.to_string(),
+            "result" => if success { "success" } else { "failure" }
+        )
+        .increment(1);
+    }
+
     // Running requests for cache-aware policy
     pub fn set_running_requests(worker: &str, count: usize) {
         gauge!("vllm_router_running_requests",
@@ -861,6 +1131,13 @@ mod tests {
         RouterMetrics::record_request_duration("/generate", Duration::from_millis(100));
         RouterMetrics::record_request_error("/generate", "timeout");
         RouterMetrics::record_retry("/generate");
+        RouterMetrics::record_rate_limited("api_key");
+        RouterMetrics::record_token_limited("tenant", "tokens_per_minute");
+        RouterMetrics::record_rate_limit_tokens(10, 5);
+        RouterMetrics::set_queue_depth("interactive", 3);
+        RouterMetrics::record_queue_wait("interactive", Duration::from_millis(20));
+        RouterMetrics::record_queue_timeout("batch");
+        RouterMetrics::record_queue_rejected("batch");
 
         RouterMetrics::set_active_workers(5);
         RouterMetrics::set_worker_health("http://worker1", true);
@@ -886,6 +1163,28 @@ mod tests {
         RouterMetrics::record_discovery_update(3, 1);
         RouterMetrics::record_generate_duration(Duration::from_secs(2));
         RouterMetrics::set_running_requests("http://worker1", 15);
+        RouterMetrics::record_lora_operation("http://worker1", "load", true);
+        RouterMetrics::record_pd_pair_locality("same_rack");
+        RouterMetrics::record_pd_decode_failover("http://decode1");
+        RouterMetrics::record_pd_failover_result("/generate", "recovered", 2);
+        RouterMetrics::record_pd_prefill_bypass("short_prompt");
+        RouterMetrics::set_pd_prefill_kv_free_tokens("http://prefill1", 65536);
+        RouterMetrics::record_pd_long_prompt_placement("single");
+        RouterMetrics::record_pd_prefill_duration("http://prefill1", Duration::from_millis(80));
+        RouterMetrics::record_pd_kv_transfer_wait(
+            "http://prefill1",
+            "http://decode1",
+            Duration::from_millis(5),
+        );
+        RouterMetrics::record_pd_ttft(
+            "http://prefill1",
+            "http://decode1",
+            Duration::from_millis(90),
+        );
+        RouterMetrics::record_pd_inter_token_latency("http://decode1", Duration::from_millis(20));
+        RouterMetrics::set_pd_capacity_signals(850.0, 42.0);
+        RouterMetrics::set_pd_role_flip_recommendation(Some("decode_to_prefill"));
+        RouterMetrics::record_pd_role_flip("decode_to_prefill", true);
     }
 
     #[test]
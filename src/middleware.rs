@@ -1,20 +1,34 @@
 use axum::{
-    extract::Request, extract::State, http::HeaderValue, http::StatusCode, middleware::Next,
-    response::IntoResponse, response::Response,
+    extract::FromRequestParts, extract::RawPathParams, extract::Request, extract::State,
+    http::header, http::HeaderName, http::HeaderValue, http::StatusCode, middleware::Next,
+    response::IntoResponse, response::Response, Json,
 };
+use futures_util::StreamExt;
 use rand::Rng;
+use serde_json::Value;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
 use std::time::Duration;
 use std::time::Instant;
-use tokio::sync::{mpsc, oneshot};
+use tokio::sync::{oneshot, Notify};
 use tower::{Layer, Service};
 use tower_http::trace::{MakeSpan, OnRequest, OnResponse, TraceLayer};
 use tracing::{debug, error, field::Empty, info, info_span, warn, Span};
 
 pub use crate::core::token_bucket::TokenBucket;
 
+use crate::config::AdmissionConfig;
+use crate::core::admission_queue::{AdmissionQueue, PriorityClassifier};
+use crate::core::audit::key_fingerprint;
+use crate::core::token_rate_limiter::choices_text;
+use crate::core::{
+    bearer_token, AdminAction, AdminRecord, AuditEvent, AuditLog, CallerIdentity, PrefilledBy,
+    RateLimitDecision, RateLimitKeys, RateLimitStatus, RequestRecord, ServedBy, StreamUsageScanner,
+    TokenLimitError, TokenRateLimiter, TokenReservation, TokenUsage,
+};
 use crate::metrics::RouterMetrics;
+use crate::protocols::spec::ErrorResponse;
+use crate::routers::http::pd_bypass::{count_tokens, prompt_text};
 use crate::server::AppState;
 
 /// Generate OpenAI-compatible request ID based on endpoint
@@ -251,6 +265,8 @@ pub struct RequestLogEntry {
 }
 
 /// Log a request with structured data
+///
+/// Durable per-request records are written by [`audit_middleware`].
 pub fn log_request(entry: RequestLogEntry) {
     if entry.status >= 500 {
         tracing::error!(
@@ -296,8 +312,6 @@ pub fn log_request(entry: RequestLogEntry) {
 
 /// Request queue entry
 pub struct QueuedRequest {
-    /// Time when the request was queued
-    queued_at: Instant,
     /// Channel to send the permit back when acquired
     permit_tx: oneshot::Sender<Result<(), StatusCode>>,
 }
@@ -311,64 +325,123 @@ pub struct QueueMetrics {
     pub total_rejected: std::sync::atomic::AtomicU64,
 }
 
+/// Priority-class request queue shared by the middleware and the queue processor
+pub struct RequestQueue {
+    queue: parking_lot::Mutex<AdmissionQueue<QueuedRequest>>,
+    classifier: PriorityClassifier,
+    notify: Notify,
+}
+
+impl RequestQueue {
+    pub fn new(classifier: PriorityClassifier, queue: AdmissionQueue<QueuedRequest>) -> Self {
+        Self {
+            queue: parking_lot::Mutex::new(queue),
+            classifier,
+            notify: Notify::new(),
+        }
+    }
+
+    /// Priority class of a request
+    pub fn classify(&self, headers: &http::HeaderMap, path: &str) -> usize {
+        self.classifier.classify(headers, path)
+    }
+
+    pub fn class_name(&self, class: usize) -> String {
+        self.queue.lock().class(class).name.clone()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.lock().is_empty()
+    }
+
+    /// Queue a request for a permit, or `None` if its class queue is full
+    pub fn enqueue(
+        &self,
+        class: usize,
+        tenant: &str,
+    ) -> Option<oneshot::Receiver<Result<(), StatusCode>>> {
+        let (permit_tx, permit_rx) = oneshot::channel();
+        {
+            let mut queue = self.queue.lock();
+            if queue
+                .push(class, tenant, QueuedRequest { permit_tx }, Instant::now())
+                .is_err()
+            {
+                RouterMetrics::record_queue_rejected(&queue.class(class).name);
+                return None;
+            }
+            RouterMetrics::set_queue_depth(&queue.class(class).name, queue.len(class));
+        }
+        self.notify.notify_one();
+        Some(permit_rx)
+    }
+
+    /// Take the next request to admit
+    fn pop(&self) -> Option<QueuedRequest> {
+        let mut queue = self.queue.lock();
+        let entry = queue.pop()?;
+        let name = &queue.class(entry.class).name;
+        RouterMetrics::record_queue_wait(name, entry.queued_at.elapsed());
+        RouterMetrics::set_queue_depth(name, queue.len(entry.class));
+        Some(entry.item)
+    }
+
+    /// Answer requests that waited past their class timeout
+    fn expire(&self) {
+        let mut queue = self.queue.lock();
+        for entry in queue.expire(Instant::now()) {
+            let name = &queue.class(entry.class).name;
+            warn!("Queue: {} request timed out waiting for token", name);
+            RouterMetrics::record_queue_timeout(name);
+            RouterMetrics::set_queue_depth(name, queue.len(entry.class));
+            let _ = entry.item.permit_tx.send(Err(StatusCode::REQUEST_TIMEOUT));
+        }
+    }
+}
+
 /// Queue processor that handles queued requests
 pub struct QueueProcessor {
     token_bucket: Arc<TokenBucket>,
-    queue_rx: mpsc::Receiver<QueuedRequest>,
-    queue_timeout: Duration,
+    queue: Weak<RequestQueue>,
 }
 
 impl QueueProcessor {
-    pub fn new(
-        token_bucket: Arc<TokenBucket>,
-        queue_rx: mpsc::Receiver<QueuedRequest>,
-        queue_timeout: Duration,
-    ) -> Self {
+    pub fn new(token_bucket: Arc<TokenBucket>, queue: &Arc<RequestQueue>) -> Self {
         Self {
             token_bucket,
-            queue_rx,
-            queue_timeout,
+            queue: Arc::downgrade(queue),
         }
     }
 
-    pub async fn run(mut self) {
+    /// Hand out tokens to queued requests, highest priority class first
+    ///
+    /// Runs until the queue is dropped.
+    pub async fn run(self) {
         info!("Starting concurrency queue processor");
 
-        // Process requests in a single task to reduce overhead
-        while let Some(queued) = self.queue_rx.recv().await {
-            // Check timeout immediately
-            let elapsed = queued.queued_at.elapsed();
-            if elapsed >= self.queue_timeout {
-                warn!("Request already timed out in queue");
-                let _ = queued.permit_tx.send(Err(StatusCode::REQUEST_TIMEOUT));
+        while let Some(queue) = self.queue.upgrade() {
+            queue.expire();
+
+            if queue.is_empty() {
+                // Bounded wait so a dropped queue is noticed
+                let _ = tokio::time::timeout(Duration::from_secs(1), queue.notify.notified()).await;
                 continue;
             }
 
-            let remaining_timeout = self.queue_timeout - elapsed;
-
-            // Try to acquire token for this request
-            if self.token_bucket.try_acquire(1.0).await.is_ok() {
-                // Got token immediately
-                debug!("Queue: acquired token immediately for queued request");
-                let _ = queued.permit_tx.send(Ok(()));
-            } else {
-                // Need to wait for token
-                let token_bucket = self.token_bucket.clone();
-
-                // Spawn task only when we actually need to wait
-                tokio::spawn(async move {
-                    if token_bucket
-                        .acquire_timeout(1.0, remaining_timeout)
-                        .await
-                        .is_ok()
-                    {
-                        debug!("Queue: acquired token after waiting");
-                        let _ = queued.permit_tx.send(Ok(()));
-                    } else {
-                        warn!("Queue: request timed out waiting for token");
-                        let _ = queued.permit_tx.send(Err(StatusCode::REQUEST_TIMEOUT));
+            if self.token_bucket.try_acquire(1.0).await.is_err() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                continue;
+            }
+
+            match queue.pop() {
+                Some(queued) => {
+                    debug!("Queue: acquired token for queued request");
+                    // The client may have gone away while queued
+                    if queued.permit_tx.send(Ok(())).is_err() {
+                        self.token_bucket.return_tokens(1.0).await;
                     }
-                });
+                }
+                None => self.token_bucket.return_tokens(1.0).await,
             }
         }
 
@@ -378,29 +451,32 @@ impl QueueProcessor {
 
 /// State for the concurrency limiter
 pub struct ConcurrencyLimiter {
-    pub queue_tx: Option<mpsc::Sender<QueuedRequest>>,
+    pub queue: Option<Arc<RequestQueue>>,
 }
 
 impl ConcurrencyLimiter {
-    /// Create new concurrency limiter with optional queue
+    /// Create new concurrency limiter with an optional priority queue
+    ///
+    /// Priority classes without their own limits use `queue_size` and
+    /// `queue_timeout`; the queue is disabled when no class can hold a request.
     pub fn new(
         token_bucket: Arc<TokenBucket>,
         queue_size: usize,
         queue_timeout: Duration,
-    ) -> (Self, Option<QueueProcessor>) {
-        if queue_size > 0 {
-            let (queue_tx, queue_rx) = mpsc::channel(queue_size);
-            let processor = QueueProcessor::new(token_bucket, queue_rx, queue_timeout);
-
-            (
-                Self {
-                    queue_tx: Some(queue_tx),
-                },
-                Some(processor),
-            )
-        } else {
-            (Self { queue_tx: None }, None)
+        admission: &AdmissionConfig,
+    ) -> Result<(Self, Option<QueueProcessor>), String> {
+        let (classifier, classes) =
+            PriorityClassifier::from_config(admission, queue_size, queue_timeout)?;
+        if classes.iter().all(|class| class.queue_size == 0) {
+            return Ok((Self { queue: None }, None));
         }
+
+        let queue = Arc::new(RequestQueue::new(
+            classifier,
+            AdmissionQueue::new(classes, admission.tenant_weights.clone()),
+        ));
+        let processor = QueueProcessor::new(token_bucket, &queue);
+        Ok((Self { queue: Some(queue) }, Some(processor)))
     }
 }
 
@@ -411,15 +487,17 @@ pub async fn concurrency_limit_middleware(
     next: Next,
 ) -> Response {
     // Static counter for embeddings queue size
-    let rate = app_state.rate_monitor.record();
+    let _rate = app_state.rate_monitor.record();
     static EMBEDDINGS_QUEUE_SIZE: AtomicU64 = AtomicU64::new(0);
 
     // Identify if this is an embeddings request based on path
     let is_embeddings = request.uri().path().contains("/v1/embeddings");
     let token_bucket = app_state.context.rate_limiter.clone();
+    let request_queue = app_state.request_queue.as_ref();
 
-    // Try to acquire token immediately
-    if token_bucket.try_acquire(1.0).await.is_ok() {
+    // Try to acquire token immediately, unless requests are already waiting
+    let queue_is_empty = request_queue.is_none_or(|queue| queue.is_empty());
+    if queue_is_empty && token_bucket.try_acquire(1.0).await.is_ok() {
         debug!("Acquired token immediately");
         let response = next.run(request).await;
 
@@ -429,20 +507,19 @@ pub async fn concurrency_limit_middleware(
         response
     } else {
         // No tokens available, try to queue if enabled
-        if let Some(queue_tx) = &app_state.concurrency_queue_tx {
+        if let Some(request_queue) = request_queue {
             debug!("No tokens available, attempting to queue request");
 
-            // Create a channel for the token response
-            let (permit_tx, permit_rx) = oneshot::channel();
+            let class = request_queue.classify(request.headers(), request.uri().path());
+            let tenant = request
+                .extensions()
+                .get::<CallerIdentity>()
+                .and_then(|caller| caller.tenant.as_deref())
+                .unwrap_or_default();
 
-            let queued = QueuedRequest {
-                queued_at: Instant::now(),
-                permit_tx,
-            };
-
-            // Try to send to queue
-            match queue_tx.try_send(queued) {
-                Ok(_) => {
+            // Try to add to the queue of the request's priority class
+            match request_queue.enqueue(class, tenant) {
+                Some(permit_rx) => {
                     // On successful enqueue, update embeddings queue gauge if applicable
                     if is_embeddings {
                         let new_val = EMBEDDINGS_QUEUE_SIZE.fetch_add(1, Ordering::Relaxed) + 1;
@@ -489,8 +566,11 @@ pub async fn concurrency_limit_middleware(
                         }
                     }
                 }
-                Err(_) => {
-                    warn!("Request queue is full, returning 429");
+                None => {
+                    warn!(
+                        "Request queue for class {} is full, returning 429",
+                        request_queue.class_name(class)
+                    );
                     StatusCode::TOO_MANY_REQUESTS.into_response()
                 }
             }
@@ -500,3 +580,523 @@ pub async fn concurrency_limit_middleware(
         }
     }
 }
+
+/// Enforce per API key, tenant and model limits ahead of the global limiter
+///
+/// Token limits reserve the estimated prompt tokens here and settle the
+/// reservation once the response body (or stream) reports its usage.
+pub async fn keyed_rate_limit_middleware(
+    State(app_state): State<Arc<AppState>>,
+    request: Request<axum::body::Body>,
+    next: Next,
+) -> Response {
+    let context = &app_state.context;
+    let (limiter, token_limiter) = match (
+        context.keyed_rate_limiter.clone(),
+        context.token_rate_limiter.clone(),
+    ) {
+        (None, None) => return next.run(request).await,
+        limiters => limiters,
+    };
+
+    // Set by the auth layer, which runs first
+    let keys = request
+        .extensions()
+        .get::<CallerIdentity>()
+        .map(RateLimitKeys::from_identity)
+        .unwrap_or_default();
+    let models_limited = limiter
+        .as_ref()
+        .is_some_and(|limiter| limiter.config().limits_models());
+    // Only buffer the body when buckets are keyed by model or count tokens
+    let (keys, prompt_tokens, request) = if models_limited || token_limiter.is_some() {
+        let (parts, body) = request.into_parts();
+        let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    format!("Failed to read request body: {}", e),
+                )
+                    .into_response()
+            }
+        };
+        let json = serde_json::from_slice::<Value>(&bytes).unwrap_or(Value::Null);
+        let model = json
+            .get("model")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        let prompt_tokens = token_limiter.as_ref().map_or(0, |_| {
+            count_tokens(context.tokenizer.as_deref(), &prompt_text(&json)) as u64
+        });
+        (
+            keys.with_model(model),
+            prompt_tokens,
+            Request::from_parts(parts, axum::body::Body::from(bytes)),
+        )
+    } else {
+        (keys, 0, request)
+    };
+
+    // Reserve tokens first: they can be refunded if the request limit rejects
+    let reservation = match &token_limiter {
+        Some(token_limiter) => match token_limiter.reserve(&keys, prompt_tokens) {
+            Ok(reservation) => reservation,
+            Err(error) => return token_limit_response(error),
+        },
+        None => None,
+    };
+
+    let decision = limiter
+        .as_ref()
+        .map_or(RateLimitDecision::Unlimited, |limiter| limiter.check(&keys));
+    let request_status = match decision {
+        RateLimitDecision::Unlimited => None,
+        RateLimitDecision::Allowed(status) => Some(status),
+        RateLimitDecision::Limited {
+            status,
+            retry_after,
+        } => {
+            if let (Some(token_limiter), Some(reservation)) = (&token_limiter, reservation) {
+                token_limiter.cancel(reservation);
+            }
+            debug!(
+                "Rate limited by {} bucket, retry after {:?}",
+                status.dimension, retry_after
+            );
+            RouterMetrics::record_rate_limited(status.dimension.as_str());
+            let message = format!(
+                "Rate limit reached for requests per {}. Please try again in {}.",
+                status.dimension,
+                format_reset(retry_after)
+            );
+            let mut response =
+                rate_limit_error(message, "requests", "rate_limit_exceeded", retry_after);
+            insert_rate_limit_headers(&mut response, &status, "requests");
+            return response;
+        }
+    };
+
+    let mut response = next.run(request).await;
+    if let Some(status) = &request_status {
+        insert_rate_limit_headers(&mut response, status, "requests");
+    }
+    match (token_limiter, reservation) {
+        (Some(token_limiter), Some(reservation)) => {
+            if let Some(status) = reservation.status() {
+                insert_rate_limit_headers(&mut response, status, "tokens");
+            }
+            settle_token_usage(response, token_limiter, reservation).await
+        }
+        _ => response,
+    }
+}
+
+/// 429 with an OpenAI-style error body and `Retry-After`
+fn rate_limit_error(
+    message: String,
+    error_type: &str,
+    code: &str,
+    retry_after: Duration,
+) -> Response {
+    let body = ErrorResponse::new(message, error_type, Some(code));
+    let mut response = (StatusCode::TOO_MANY_REQUESTS, Json(body)).into_response();
+    let retry_after_secs = retry_after.as_secs_f64().ceil().max(1.0) as u64;
+    response
+        .headers_mut()
+        .insert(header::RETRY_AFTER, HeaderValue::from(retry_after_secs));
+    response
+}
+
+fn token_limit_response(error: TokenLimitError) -> Response {
+    match error {
+        TokenLimitError::RateLimited {
+            status,
+            retry_after,
+        } => {
+            debug!(
+                "Token rate limited by {} bucket, retry after {:?}",
+                status.dimension, retry_after
+            );
+            RouterMetrics::record_token_limited(status.dimension.as_str(), "tokens_per_minute");
+            let message = format!(
+                "Rate limit reached for tokens per minute per {}. Please try again in {}.",
+                status.dimension,
+                format_reset(retry_after)
+            );
+            let mut response =
+                rate_limit_error(message, "tokens", "rate_limit_exceeded", retry_after);
+            insert_rate_limit_headers(&mut response, &status, "tokens");
+            response
+        }
+        TokenLimitError::QuotaExceeded {
+            dimension,
+            period,
+            limit,
+            retry_after,
+        } => {
+            debug!(
+                "{} token quota of {} exceeded for {}",
+                period, limit, dimension
+            );
+            RouterMetrics::record_token_limited(dimension.as_str(), period.as_str());
+            let message = format!(
+                "You exceeded your {} quota of {} tokens for this {}.",
+                period, limit, dimension
+            );
+            rate_limit_error(
+                message,
+                "insufficient_quota",
+                "insufficient_quota",
+                retry_after,
+            )
+        }
+    }
+}
+
+/// Reconcile a token reservation with the usage reported by the response
+///
+/// Failed requests are refunded. Streams are settled when they end or are
+/// dropped, so a client disconnect still charges what was generated.
+async fn settle_token_usage(
+    response: Response,
+    token_limiter: Arc<TokenRateLimiter>,
+    reservation: TokenReservation,
+) -> Response {
+    if !response.status().is_success() {
+        token_limiter.cancel(reservation);
+        return response;
+    }
+
+    let is_stream = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("text/event-stream"));
+    let (parts, body) = response.into_parts();
+
+    if is_stream {
+        let mut settlement = StreamSettlement {
+            token_limiter,
+            reservation: Some(reservation),
+            scanner: StreamUsageScanner::default(),
+        };
+        let stream = body.into_data_stream().inspect(move |chunk| {
+            if let Ok(bytes) = chunk {
+                settlement.scanner.feed(bytes);
+            }
+        });
+        return Response::from_parts(parts, axum::body::Body::from_stream(stream));
+    }
+
+    match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(bytes) => {
+            let usage = serde_json::from_slice::<Value>(&bytes)
+                .ok()
+                .and_then(|json| TokenUsage::from_json(&json));
+            token_limiter.reconcile(reservation, usage);
+            Response::from_parts(parts, axum::body::Body::from(bytes))
+        }
+        Err(e) => {
+            token_limiter.reconcile(reservation, None);
+            error!("Failed to read response body for token accounting: {}", e);
+            (StatusCode::BAD_GATEWAY, "Failed to read response body").into_response()
+        }
+    }
+}
+
+/// Settles a streamed response's reservation when its body is dropped
+struct StreamSettlement {
+    token_limiter: Arc<TokenRateLimiter>,
+    reservation: Option<TokenReservation>,
+    scanner: StreamUsageScanner,
+}
+
+impl Drop for StreamSettlement {
+    fn drop(&mut self) {
+        if let Some(reservation) = self.reservation.take() {
+            let usage = std::mem::take(&mut self.scanner).finish();
+            self.token_limiter.reconcile(reservation, Some(usage));
+        }
+    }
+}
+
+/// Add OpenAI-style `x-ratelimit-*-{requests,tokens}` headers
+fn insert_rate_limit_headers(response: &mut Response, status: &RateLimitStatus, unit: &str) {
+    let headers = response.headers_mut();
+    let name = |field: &str| {
+        HeaderName::try_from(format!("x-ratelimit-{}-{}", field, unit)).expect("valid header name")
+    };
+    headers.insert(name("limit"), HeaderValue::from(status.limit));
+    headers.insert(name("remaining"), HeaderValue::from(status.remaining));
+    if let Ok(value) = HeaderValue::from_str(&format_reset(status.reset)) {
+        headers.insert(name("reset"), value);
+    }
+}
+
+/// Format a duration as `250ms` or `1.5s`
+fn format_reset(duration: Duration) -> String {
+    let millis = duration.as_millis();
+    if millis < 1000 {
+        format!("{}ms", millis)
+    } else {
+        format!("{}s", millis as f64 / 1000.0)
+    }
+}
+
+/// Caller recorded for a request: the identity the auth layer attached to
+/// the response, or a fingerprint of the rejected token
+fn audit_caller(
+    response: &Response,
+    presented: Option<String>,
+) -> (Option<String>, Option<String>) {
+    match response.extensions().get::<CallerIdentity>() {
+        Some(caller) => (caller.id.clone(), caller.tenant.clone()),
+        None => (presented, None),
+    }
+}
+
+/// Write an audit record for every proxied inference request
+///
+/// Streamed responses are recorded when their body ends or is dropped, so
+/// the record carries the usage reported by the final chunk.
+pub async fn audit_middleware(
+    State(app_state): State<Arc<AppState>>,
+    request: Request<axum::body::Body>,
+    next: Next,
+) -> Response {
+    let Some(audit) = app_state.context.audit_log.clone() else {
+        return next.run(request).await;
+    };
+    let started = Instant::now();
+    let presented = bearer_token(request.headers()).map(key_fingerprint);
+    let mut record = RequestRecord {
+        request_id: request
+            .extensions()
+            .get::<RequestId>()
+            .map(|id| id.0.clone()),
+        method: request.method().to_string(),
+        path: request.uri().path().to_string(),
+        ..Default::default()
+    };
+
+    let request = if request.method() == axum::http::Method::POST {
+        let (parts, body) = request.into_parts();
+        let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    format!("Failed to read request body: {}", e),
+                )
+                    .into_response()
+            }
+        };
+        let json = serde_json::from_slice::<Value>(&bytes).unwrap_or(Value::Null);
+        record.model = json
+            .get("model")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        record.stream = json.get("stream").and_then(Value::as_bool).unwrap_or(false);
+        if audit.captures_prompts() {
+            record.prompt = audit.prompt(&prompt_text(&json));
+        }
+        Request::from_parts(parts, axum::body::Body::from(bytes))
+    } else {
+        request
+    };
+
+    let response = next.run(request).await;
+    (record.api_key, record.tenant) = audit_caller(&response, presented);
+    record.status = response.status().as_u16();
+    record.worker = response
+        .extensions()
+        .get::<ServedBy>()
+        .map(|served_by| served_by.0.clone());
+    record.prefill_worker = response
+        .extensions()
+        .get::<PrefilledBy>()
+        .map(|prefilled_by| prefilled_by.0.clone());
+
+    if !response.status().is_success() {
+        record.latency_ms = started.elapsed().as_millis() as u64;
+        audit.record(AuditEvent::Request(record));
+        return response;
+    }
+
+    let is_stream = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("text/event-stream"));
+    let (parts, body) = response.into_parts();
+
+    if is_stream {
+        record.stream = true;
+        let scanner = if audit.captures_outputs() {
+            StreamUsageScanner::collecting_text()
+        } else {
+            StreamUsageScanner::default()
+        };
+        let mut stream_audit = StreamAudit {
+            audit,
+            record: Some(record),
+            scanner,
+            started,
+        };
+        let stream = body.into_data_stream().inspect(move |chunk| {
+            if let Ok(bytes) = chunk {
+                stream_audit.scanner.feed(bytes);
+            }
+        });
+        return Response::from_parts(parts, axum::body::Body::from_stream(stream));
+    }
+
+    match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(bytes) => {
+            if let Ok(json) = serde_json::from_slice::<Value>(&bytes) {
+                if let Some(usage) = TokenUsage::from_json(&json) {
+                    record.prompt_tokens = usage.prompt_tokens;
+                    record.completion_tokens = Some(usage.completion_tokens);
+                }
+                if audit.captures_outputs() {
+                    record.output = audit.output(&choices_text(&json));
+                }
+            }
+            record.latency_ms = started.elapsed().as_millis() as u64;
+            audit.record(AuditEvent::Request(record));
+            Response::from_parts(parts, axum::body::Body::from(bytes))
+        }
+        Err(e) => {
+            error!("Failed to read response body for audit: {}", e);
+            record.status = StatusCode::BAD_GATEWAY.as_u16();
+            record.latency_ms = started.elapsed().as_millis() as u64;
+            audit.record(AuditEvent::Request(record));
+            (StatusCode::BAD_GATEWAY, "Failed to read response body").into_response()
+        }
+    }
+}
+
+/// Records a streamed response when its body is dropped
+struct StreamAudit {
+    audit: Arc<AuditLog>,
+    record: Option<RequestRecord>,
+    scanner: StreamUsageScanner,
+    started: Instant,
+}
+
+impl Drop for StreamAudit {
+    fn drop(&mut self) {
+        if let Some(mut record) = self.record.take() {
+            let (usage, text) = std::mem::take(&mut self.scanner).finish_with_text();
+            record.prompt_tokens = usage.prompt_tokens;
+            record.completion_tokens = Some(usage.completion_tokens);
+            record.output = text.and_then(|text| self.audit.output(&text));
+            record.latency_ms = self.started.elapsed().as_millis() as u64;
+            self.audit.record(AuditEvent::Request(record));
+        }
+    }
+}
+
+/// Longest failure body kept as the detail of an admin record
+const ADMIN_DETAIL_LIMIT: usize = 512;
+
+/// State-changing admin endpoint a request targets
+fn admin_action(method: &axum::http::Method, path: &str) -> Option<AdminAction> {
+    use axum::http::Method;
+    match (method, path) {
+        (&Method::POST, "/add_worker" | "/workers") => Some(AdminAction::WorkerAdd),
+        (&Method::POST, "/remove_worker") => Some(AdminAction::WorkerRemove),
+        (&Method::DELETE, path) if path.starts_with("/workers/") => Some(AdminAction::WorkerRemove),
+        (&Method::POST, "/flush_cache") => Some(AdminAction::CacheFlush),
+        (&Method::POST, "/pd/capacity/apply") => Some(AdminAction::CapacityApply),
+        _ => None,
+    }
+}
+
+/// Write an audit record for every state-changing admin request
+///
+/// Runs outside admin authentication so rejected attempts are recorded too.
+pub async fn admin_audit_middleware(
+    State(app_state): State<Arc<AppState>>,
+    request: Request<axum::body::Body>,
+    next: Next,
+) -> Response {
+    let Some(audit) = app_state.context.audit_log.clone() else {
+        return next.run(request).await;
+    };
+    let Some(action) = admin_action(request.method(), request.uri().path()) else {
+        return next.run(request).await;
+    };
+
+    let presented = bearer_token(request.headers()).map(key_fingerprint);
+
+    let (mut parts, body) = request.into_parts();
+    let request_id = parts.extensions.get::<RequestId>().map(|id| id.0.clone());
+    // Worker URL from `?url=`, the `/workers/{url}` path or a JSON body
+    let mut target = parts.uri.query().and_then(|query| {
+        url::form_urlencoded::parse(query.as_bytes())
+            .find(|(name, _)| name == "url")
+            .map(|(_, value)| value.into_owned())
+    });
+    if target.is_none() {
+        target = RawPathParams::from_request_parts(&mut parts, &())
+            .await
+            .ok()
+            .and_then(|params| {
+                params
+                    .iter()
+                    .find(|(name, _)| *name == "url")
+                    .map(|(_, value)| value.to_string())
+            });
+    }
+    let body = if target.is_none() && action == AdminAction::WorkerAdd {
+        let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    format!("Failed to read request body: {}", e),
+                )
+                    .into_response()
+            }
+        };
+        target = serde_json::from_slice::<Value>(&bytes)
+            .ok()
+            .and_then(|json| json.get("url")?.as_str().map(str::to_string));
+        axum::body::Body::from(bytes)
+    } else {
+        body
+    };
+
+    let response = next.run(Request::from_parts(parts, body)).await;
+    let (actor, _) = audit_caller(&response, presented);
+    let status = response.status();
+    let (response, detail) = if status.is_success() {
+        (response, None)
+    } else {
+        let (parts, body) = response.into_parts();
+        let bytes = axum::body::to_bytes(body, usize::MAX)
+            .await
+            .unwrap_or_default();
+        let detail: String = String::from_utf8_lossy(&bytes)
+            .chars()
+            .take(ADMIN_DETAIL_LIMIT)
+            .collect();
+        (
+            Response::from_parts(parts, axum::body::Body::from(bytes)),
+            Some(detail).filter(|detail| !detail.is_empty()),
+        )
+    };
+
+    audit.record(AuditEvent::Admin(AdminRecord {
+        action,
+        request_id,
+        actor,
+        target,
+        status: Some(status.as_u16()),
+        success: status.is_success(),
+        detail,
+    }));
+    response
+}
@@ -171,6 +171,44 @@ impl CacheAwarePolicy {
         }
     }
 
+    /// Find the healthy worker whose cached prefix best matches `text`
+    ///
+    /// Returns the worker index and the fraction of `text` already cached on it.
+    pub fn best_prefix_match(
+        &self,
+        workers: &[Arc<dyn Worker>],
+        text: &str,
+    ) -> Option<(usize, f64)> {
+        let healthy_indices = get_healthy_worker_indices(workers);
+        let model_id = normalize_model_key(workers[*healthy_indices.first()?].model_id());
+        let tree = self
+            .trees
+            .get(model_id)
+            .map(|entry| entry.value().clone())?;
+        let input_chars = text.chars().count();
+        if input_chars == 0 {
+            return None;
+        }
+
+        healthy_indices
+            .into_iter()
+            .map(|idx| {
+                let matched = tree
+                    .prefix_match_tenant(text, workers[idx].url())
+                    .chars()
+                    .count();
+                (idx, matched as f64 / input_chars as f64)
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    /// Record that `text` was routed to `worker` outside of `select_worker`
+    pub fn record_routed(&self, worker: &dyn Worker, text: &str) {
+        if let Some(tree) = self.trees.get(normalize_model_key(worker.model_id())) {
+            tree.insert(text, worker.url());
+        }
+    }
+
     fn select_worker_min_load(
         &self,
         workers: &[Arc<dyn Worker>],
@@ -479,6 +517,39 @@ mod tests {
     use super::*;
     use crate::core::{BasicWorker, WorkerType};
 
+    #[test]
+    fn test_best_prefix_match() {
+        let config = CacheAwareConfig {
+            eviction_interval_secs: 0,
+            ..Default::default()
+        };
+        let policy = CacheAwarePolicy::with_config(config);
+        let workers: Vec<Arc<dyn Worker>> = vec![
+            Arc::new(BasicWorker::new(
+                "http://w1:8000".to_string(),
+                WorkerType::Decode,
+            )),
+            Arc::new(BasicWorker::new(
+                "http://w2:8000".to_string(),
+                WorkerType::Decode,
+            )),
+        ];
+        policy.init_workers(&workers);
+
+        policy.record_routed(workers[1].as_ref(), "shared system prompt");
+        let (idx, rate) = policy
+            .best_prefix_match(&workers, "shared system prompt, new question")
+            .unwrap();
+        assert_eq!(idx, 1);
+        assert!(rate > 0.5 && rate < 1.0);
+
+        workers[1].set_healthy(false);
+        let (idx, rate) = policy
+            .best_prefix_match(&workers, "shared system prompt")
+            .unwrap();
+        assert_eq!((idx, rate), (0, 0.0));
+    }
+
     #[test]
     fn test_cache_aware_with_balanced_load() {
         // Create policy without eviction thread for testing
@@ -0,0 +1,242 @@
+//! Topology-aware PD pairing policy
+//!
+//! Selects prefill and decode workers together so the KV cache does not have to
+//! cross racks or zones when a closer, similarly loaded pair is available.
+
+use super::{get_healthy_worker_indices, LoadBalancingPolicy, RequestHeaders};
+use crate::config::TransferCostConfig;
+use crate::core::{Locality, Worker};
+use crate::metrics::RouterMetrics;
+use rand::Rng;
+use std::sync::Arc;
+use tracing::debug;
+
+/// Scores prefill/decode pairs by combined load plus KV-transfer cost
+///
+/// `score = load_weight * (prefill load + decode load) + transfer_cost(locality)`.
+/// The lowest score wins; ties are broken randomly so idle clusters still
+/// spread requests across equivalent pairs.
+#[derive(Debug)]
+pub struct TopologyAwarePairPolicy {
+    load_weight: f64,
+    transfer_cost: TransferCostConfig,
+}
+
+impl TopologyAwarePairPolicy {
+    pub fn new(load_weight: f64, transfer_cost: TransferCostConfig) -> Self {
+        Self {
+            load_weight,
+            transfer_cost,
+        }
+    }
+
+    /// KV-transfer cost for a locality level
+    pub fn transfer_cost(&self, locality: Locality) -> f64 {
+        match locality {
+            Locality::SameNode => self.transfer_cost.same_node,
+            Locality::SameRack => self.transfer_cost.same_rack,
+            Locality::SameZone | Locality::Unknown => self.transfer_cost.same_zone,
+            Locality::CrossZone => self.transfer_cost.cross_zone,
+        }
+    }
+
+    /// Score a prefill/decode pair (lower is better)
+    pub fn score_pair(&self, prefill: &dyn Worker, decode: &dyn Worker) -> f64 {
+        let load = (prefill.load() + decode.load()) as f64;
+        let locality = prefill.topology().locality(&decode.topology());
+        self.load_weight * load + self.transfer_cost(locality)
+    }
+
+    // Pick uniformly among the candidates with the lowest score
+    fn pick_min<T: Copy>(candidates: Vec<(T, f64)>) -> Option<T> {
+        let best = candidates
+            .iter()
+            .map(|(_, score)| *score)
+            .fold(f64::INFINITY, f64::min);
+        let ties: Vec<T> = candidates
+            .into_iter()
+            .filter(|(_, score)| *score <= best)
+            .map(|(candidate, _)| candidate)
+            .collect();
+        match ties.len() {
+            0 => None,
+            1 => Some(ties[0]),
+            n => Some(ties[rand::rng().random_range(0..n)]),
+        }
+    }
+}
+
+impl LoadBalancingPolicy for TopologyAwarePairPolicy {
+    /// Single-worker selection falls back to least loaded
+    fn select_worker_with_headers(
+        &self,
+        workers: &[Arc<dyn Worker>],
+        _request_text: Option<&str>,
+        _headers: Option<&RequestHeaders>,
+    ) -> Option<usize> {
+        let candidates = get_healthy_worker_indices(workers)
+            .into_iter()
+            .map(|idx| (idx, workers[idx].load() as f64))
+            .collect();
+        let selected = Self::pick_min(candidates)?;
+
+        workers[selected].increment_processed();
+        RouterMetrics::record_processed_request(workers[selected].url());
+        RouterMetrics::record_policy_decision(self.name(), workers[selected].url());
+        Some(selected)
+    }
+
+    fn select_worker_pair_with_headers(
+        &self,
+        prefill_workers: &[Arc<dyn Worker>],
+        decode_workers: &[Arc<dyn Worker>],
+        _request_text: Option<&str>,
+        _headers: Option<&RequestHeaders>,
+    ) -> Option<(usize, usize)> {
+        let healthy_decode = get_healthy_worker_indices(decode_workers);
+        let mut candidates = Vec::new();
+        for p in get_healthy_worker_indices(prefill_workers) {
+            for &d in &healthy_decode {
+                let score =
+                    self.score_pair(prefill_workers[p].as_ref(), decode_workers[d].as_ref());
+                candidates.push(((p, d), score));
+            }
+        }
+        let (p, d) = Self::pick_min(candidates)?;
+
+        let prefill = &prefill_workers[p];
+        let decode = &decode_workers[d];
+        let locality = prefill.topology().locality(&decode.topology());
+        debug!(
+            "Topology-aware pairing selected prefill={} decode={} locality={:?}",
+            prefill.url(),
+            decode.url(),
+            locality
+        );
+        RouterMetrics::record_pd_pair_locality(locality_label(locality));
+        for worker in [prefill, decode] {
+            worker.increment_processed();
+            RouterMetrics::record_processed_request(worker.url());
+            RouterMetrics::record_policy_decision(self.name(), worker.url());
+        }
+        Some((p, d))
+    }
+
+    fn name(&self) -> &'static str {
+        "topology_aware"
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+fn locality_label(locality: Locality) -> &'static str {
+    match locality {
+        Locality::SameNode => "same_node",
+        Locality::SameRack => "same_rack",
+        Locality::SameZone => "same_zone",
+        Locality::CrossZone => "cross_zone",
+        Locality::Unknown => "unknown",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{BasicWorker, WorkerTopology, WorkerType};
+
+    fn worker(url: &str, worker_type: WorkerType, topology: &str) -> Arc<dyn Worker> {
+        Arc::new(
+            BasicWorker::new(url.to_string(), worker_type)
+                .with_labels(WorkerTopology::parse(topology).unwrap().to_labels()),
+        )
+    }
+
+    fn prefill(url: &str, topology: &str) -> Arc<dyn Worker> {
+        worker(
+            url,
+            WorkerType::Prefill {
+                bootstrap_port: None,
+            },
+            topology,
+        )
+    }
+
+    fn decode(url: &str, topology: &str) -> Arc<dyn Worker> {
+        worker(url, WorkerType::Decode, topology)
+    }
+
+    #[test]
+    fn test_prefers_same_node_pair() {
+        let policy = TopologyAwarePairPolicy::new(1.0, TransferCostConfig::default());
+        let prefills = vec![
+            prefill("http://p1:8000", "z1/r1/n1"),
+            prefill("http://p2:8000", "z1/r2/n3"),
+        ];
+        let decodes = vec![
+            decode("http://d1:8000", "z2/r9/n9"),
+            decode("http://d2:8000", "z1/r2/n3"),
+        ];
+
+        for _ in 0..20 {
+            let (p, d) = policy
+                .select_worker_pair(&prefills, &decodes, None)
+                .unwrap();
+            assert_eq!((p, d), (1, 1));
+        }
+    }
+
+    #[test]
+    fn test_load_outweighs_locality() {
+        let policy = TopologyAwarePairPolicy::new(1.0, TransferCostConfig::default());
+        let prefills = vec![prefill("http://p1:8000", "z1/r1/n1")];
+        let decodes = vec![
+            decode("http://d1:8000", "z1/r1/n1"),
+            decode("http://d2:8000", "z1/r1/n2"),
+        ];
+
+        // Same-node decode is busy: 5 in-flight requests > same-rack cost of 1
+        for _ in 0..5 {
+            decodes[0].increment_load();
+        }
+        let (_, d) = policy
+            .select_worker_pair(&prefills, &decodes, None)
+            .unwrap();
+        assert_eq!(d, 1);
+    }
+
+    #[test]
+    fn test_skips_unhealthy_workers() {
+        let policy = TopologyAwarePairPolicy::new(1.0, TransferCostConfig::default());
+        let prefills = vec![prefill("http://p1:8000", "z1/r1/n1")];
+        let decodes = vec![
+            decode("http://d1:8000", "z1/r1/n1"),
+            decode("http://d2:8000", "z2"),
+        ];
+        decodes[0].set_healthy(false);
+
+        let (_, d) = policy
+            .select_worker_pair(&prefills, &decodes, None)
+            .unwrap();
+        assert_eq!(d, 1);
+
+        decodes[1].set_healthy(false);
+        assert!(policy
+            .select_worker_pair(&prefills, &decodes, None)
+            .is_none());
+    }
+
+    #[test]
+    fn test_unknown_topology_uses_same_zone_cost() {
+        let policy = TopologyAwarePairPolicy::new(1.0, TransferCostConfig::default());
+        let p: Arc<dyn Worker> = Arc::new(BasicWorker::new(
+            "http://p1:8000".to_string(),
+            WorkerType::Prefill {
+                bootstrap_port: None,
+            },
+        ));
+        let d = decode("http://d1:8000", "z1/r1/n1");
+        assert_eq!(policy.score_pair(p.as_ref(), d.as_ref()), 4.0);
+    }
+}
@@ -0,0 +1,358 @@
+// Per-model sampling parameter defaults and clamps
+//
+// Fills parameters the client left unset and clamps out-of-range values
+// before a request is dispatched. Current values are read through the
+// validation provider traits; the adjustments made are reported to the
+// client in the `x-param-adjustments` response header.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use crate::config::{ModelParamsConfig, ParamRange};
+use crate::protocols::spec::{ChatCompletionRequest, CompletionRequest, StringOrArray};
+use crate::protocols::validation::{
+    SamplingOptionsProvider, StopConditionsProvider, TokenLimitsProvider, VLLMExtensionsProvider,
+};
+
+/// Response header listing the adjustments made to the request
+pub const PARAM_ADJUSTMENTS_HEADER: &str = "x-param-adjustments";
+
+/// Key of the entry applied to models without one of their own
+pub const ANY_MODEL: &str = "*";
+
+/// Requests whose sampling parameters can be adjusted
+pub trait SamplingParamsMut:
+    SamplingOptionsProvider + StopConditionsProvider + TokenLimitsProvider + VLLMExtensionsProvider
+{
+    fn set_temperature(&mut self, value: f32);
+    fn set_top_p(&mut self, value: f32);
+    fn set_top_k(&mut self, value: i32);
+    /// Set the limit reported by `get_max_tokens`
+    fn set_max_tokens(&mut self, value: u32);
+    fn set_min_tokens(&mut self, value: u32);
+    fn set_stop_sequences(&mut self, stop: Vec<String>);
+}
+
+impl SamplingParamsMut for ChatCompletionRequest {
+    fn set_temperature(&mut self, value: f32) {
+        self.temperature = Some(value);
+    }
+    fn set_top_p(&mut self, value: f32) {
+        self.top_p = Some(value);
+    }
+    fn set_top_k(&mut self, value: i32) {
+        self.top_k = Some(value);
+    }
+    fn set_max_tokens(&mut self, value: u32) {
+        // max_completion_tokens takes precedence when the client used it
+        if self.max_completion_tokens.is_some() {
+            self.max_completion_tokens = Some(value);
+        } else {
+            self.max_tokens = Some(value);
+        }
+    }
+    fn set_min_tokens(&mut self, value: u32) {
+        self.min_tokens = Some(value);
+    }
+    fn set_stop_sequences(&mut self, stop: Vec<String>) {
+        self.stop = Some(StringOrArray::Array(stop));
+    }
+}
+
+impl SamplingParamsMut for CompletionRequest {
+    fn set_temperature(&mut self, value: f32) {
+        self.temperature = Some(value);
+    }
+    fn set_top_p(&mut self, value: f32) {
+        self.top_p = Some(value);
+    }
+    fn set_top_k(&mut self, value: i32) {
+        self.top_k = Some(value);
+    }
+    fn set_max_tokens(&mut self, value: u32) {
+        self.max_tokens = Some(value);
+    }
+    fn set_min_tokens(&mut self, value: u32) {
+        self.min_tokens = Some(value);
+    }
+    fn set_stop_sequences(&mut self, stop: Vec<String>) {
+        self.stop = Some(StringOrArray::Array(stop));
+    }
+}
+
+/// How a parameter was changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdjustmentKind {
+    Default,
+    Clamped,
+}
+
+/// One parameter change, rendered as `param:kind=value`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParamAdjustment {
+    pub param: &'static str,
+    pub kind: AdjustmentKind,
+    pub value: String,
+}
+
+impl Display for ParamAdjustment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self.kind {
+            AdjustmentKind::Default => "default",
+            AdjustmentKind::Clamped => "clamped",
+        };
+        write!(f, "{}:{}={}", self.param, kind, self.value)
+    }
+}
+
+/// Entry for `model`, falling back to the `*` entry
+pub fn lookup<'a>(
+    model_params: &'a HashMap<String, ModelParamsConfig>,
+    model: Option<&str>,
+) -> Option<&'a ModelParamsConfig> {
+    model
+        .and_then(|model| model_params.get(model))
+        .or_else(|| model_params.get(ANY_MODEL))
+}
+
+/// Fill unset parameters and clamp the rest; returns the changes made
+pub fn apply<R: SamplingParamsMut + ?Sized>(
+    params: &ModelParamsConfig,
+    request: &mut R,
+) -> Vec<ParamAdjustment> {
+    let (defaults, clamps) = (&params.defaults, &params.clamps);
+    let mut adjustments = Vec::new();
+
+    if let Some(value) = adjust(
+        "temperature",
+        request.get_temperature(),
+        defaults.temperature,
+        clamps.temperature,
+        &mut adjustments,
+    ) {
+        request.set_temperature(value);
+    }
+    if let Some(value) = adjust(
+        "top_p",
+        request.get_top_p(),
+        defaults.top_p,
+        clamps.top_p,
+        &mut adjustments,
+    ) {
+        request.set_top_p(value);
+    }
+
+    // -1 disables top_k, so it only needs clamping from above
+    let top_k = match (
+        request.get_top_k(),
+        clamps.top_k.and_then(|range| range.max),
+    ) {
+        (Some(-1), Some(max)) => {
+            adjustments.push(ParamAdjustment {
+                param: "top_k",
+                kind: AdjustmentKind::Clamped,
+                value: max.to_string(),
+            });
+            Some(max)
+        }
+        (Some(-1), None) => None,
+        (top_k, _) => adjust(
+            "top_k",
+            top_k,
+            defaults.top_k,
+            clamps.top_k,
+            &mut adjustments,
+        ),
+    };
+    if let Some(value) = top_k {
+        request.set_top_k(value);
+    }
+
+    if let Some(value) = adjust(
+        "max_tokens",
+        request.get_max_tokens(),
+        defaults.max_tokens,
+        clamps.max_tokens,
+        &mut adjustments,
+    ) {
+        request.set_max_tokens(value);
+    }
+    // Keep min_tokens satisfiable under the adjusted limit
+    if let (Some(min_tokens), Some(max_tokens)) =
+        (request.get_min_tokens(), request.get_max_tokens())
+    {
+        if min_tokens > max_tokens {
+            request.set_min_tokens(max_tokens);
+            adjustments.push(ParamAdjustment {
+                param: "min_tokens",
+                kind: AdjustmentKind::Clamped,
+                value: max_tokens.to_string(),
+            });
+        }
+    }
+
+    if request.get_stop_sequences().is_none() {
+        if let Some(stop) = &defaults.stop {
+            request.set_stop_sequences(stop.clone());
+            adjustments.push(ParamAdjustment {
+                param: "stop",
+                kind: AdjustmentKind::Default,
+                value: stop.len().to_string(),
+            });
+        }
+    }
+
+    adjustments
+}
+
+/// New value of a parameter, if it has to change
+fn adjust<T>(
+    param: &'static str,
+    current: Option<T>,
+    default: Option<T>,
+    clamp: Option<ParamRange<T>>,
+    adjustments: &mut Vec<ParamAdjustment>,
+) -> Option<T>
+where
+    T: PartialOrd + Copy + Display,
+{
+    let (value, kind) = match (current, default) {
+        (Some(value), _) => (value, None),
+        (None, Some(default)) => (default, Some(AdjustmentKind::Default)),
+        (None, None) => return None,
+    };
+    let clamp = clamp.unwrap_or_default();
+    let clamped = match (clamp.min, clamp.max) {
+        (Some(min), _) if value < min => Some(min),
+        (_, Some(max)) if value > max => Some(max),
+        _ => None,
+    };
+    let (value, kind) = match clamped {
+        Some(clamped) => (clamped, AdjustmentKind::Clamped),
+        None => (value, kind?),
+    };
+    adjustments.push(ParamAdjustment {
+        param,
+        kind,
+        value: value.to_string(),
+    });
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ModelParamClamps, ModelParamDefaults};
+    use serde_json::json;
+
+    fn params() -> ModelParamsConfig {
+        ModelParamsConfig {
+            defaults: ModelParamDefaults {
+                temperature: Some(0.6),
+                top_p: None,
+                top_k: Some(40),
+                max_tokens: Some(512),
+                stop: Some(vec!["<|eot_id|>".to_string()]),
+            },
+            clamps: ModelParamClamps {
+                temperature: Some(ParamRange {
+                    min: Some(0.1),
+                    max: Some(1.0),
+                }),
+                top_p: None,
+                top_k: Some(ParamRange {
+                    min: Some(1),
+                    max: Some(100),
+                }),
+                max_tokens: Some(ParamRange {
+                    min: None,
+                    max: Some(1024),
+                }),
+            },
+        }
+    }
+
+    #[test]
+    fn test_fills_missing_parameters() {
+        let mut request: CompletionRequest = serde_json::from_value(json!({
+            "model": "llama",
+            "prompt": "Hello"
+        }))
+        .unwrap();
+
+        let adjustments = apply(&params(), &mut request);
+        assert_eq!(request.temperature, Some(0.6));
+        assert_eq!(request.top_k, Some(40));
+        assert_eq!(request.max_tokens, Some(512));
+        assert_eq!(
+            request.stop,
+            Some(StringOrArray::Array(vec!["<|eot_id|>".to_string()]))
+        );
+        let rendered: Vec<String> = adjustments.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            rendered,
+            vec![
+                "temperature:default=0.6",
+                "top_k:default=40",
+                "max_tokens:default=512",
+                "stop:default=1"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_clamps_out_of_range_values() {
+        let mut request: ChatCompletionRequest = serde_json::from_value(json!({
+            "model": "llama",
+            "messages": [{"role": "user", "content": "Hello"}],
+            "temperature": 1.8,
+            "top_k": -1,
+            "max_completion_tokens": 4096,
+            "min_tokens": 2048,
+            "stop": ["\n"]
+        }))
+        .unwrap();
+
+        let adjustments = apply(&params(), &mut request);
+        assert_eq!(request.temperature, Some(1.0));
+        assert_eq!(request.top_k, Some(100));
+        assert_eq!(request.max_completion_tokens, Some(1024));
+        assert_eq!(request.max_tokens, None);
+        assert_eq!(request.min_tokens, Some(1024));
+        assert_eq!(
+            request.stop,
+            Some(StringOrArray::Array(vec!["\n".to_string()]))
+        );
+        assert!(adjustments
+            .iter()
+            .all(|a| a.kind == AdjustmentKind::Clamped));
+        assert_eq!(adjustments.len(), 4);
+
+        // In-range values are left alone
+        let mut request: ChatCompletionRequest = serde_json::from_value(json!({
+            "model": "llama",
+            "messages": [{"role": "user", "content": "Hello"}],
+            "temperature": 0.2,
+            "top_k": 10,
+            "max_tokens": 64,
+            "stop": "END"
+        }))
+        .unwrap();
+        assert!(apply(&params(), &mut request).is_empty());
+    }
+
+    #[test]
+    fn test_lookup_falls_back_to_wildcard() {
+        let mut model_params = HashMap::new();
+        model_params.insert("llama".to_string(), params());
+        assert!(lookup(&model_params, Some("llama")).is_some());
+        assert!(lookup(&model_params, Some("mistral")).is_none());
+        assert!(lookup(&model_params, None).is_none());
+
+        model_params.insert(ANY_MODEL.to_string(), ModelParamsConfig::default());
+        assert_eq!(
+            lookup(&model_params, Some("mistral")),
+            Some(&ModelParamsConfig::default())
+        );
+    }
+}
@@ -551,6 +551,10 @@ impl GenerationRequest for ChatCompletionRequest {
         // Return empty string if no session_id - let routing policy handle this case
         String::new()
     }
+
+    fn get_lora_path(&self) -> Option<&LoRAPath> {
+        self.lora_path.as_ref()
+    }
 }
 
 // ============= Regular Response =============
@@ -761,6 +765,10 @@ impl GenerationRequest for CompletionRequest {
     fn extract_text_for_routing(&self) -> String {
         self.prompt.extract_text_for_routing()
     }
+
+    fn get_lora_path(&self) -> Option<&LoRAPath> {
+        self.lora_path.as_ref()
+    }
 }
 
 // ============= Regular Response =============
@@ -1793,6 +1801,19 @@ pub struct ErrorDetail {
     pub code: Option<String>,
 }
 
+impl ErrorResponse {
+    pub fn new(message: impl Into<String>, error_type: &str, code: Option<&str>) -> Self {
+        Self {
+            error: ErrorDetail {
+                message: message.into(),
+                error_type: error_type.to_string(),
+                param: None,
+                code: code.map(str::to_string),
+            },
+        }
+    }
+}
+
 // ==================================================================
 // =            VLLM SPEC - GENERATE API                          =
 // ==================================================================
@@ -1965,6 +1986,10 @@ impl GenerationRequest for GenerateRequest {
         // No text input found
         String::new()
     }
+
+    fn get_lora_path(&self) -> Option<&LoRAPath> {
+        self.lora_path.as_ref()
+    }
 }
 
 // ==================================================================
@@ -2243,6 +2268,11 @@ pub trait GenerationRequest: Send + Sync {
 
     /// Extract text content for routing decisions
     fn extract_text_for_routing(&self) -> String;
+
+    /// Get the requested LoRA adapter(s), if the request supports them
+    fn get_lora_path(&self) -> Option<&LoRAPath> {
+        None
+    }
 }
 
 /// Helper type for string or array of strings
@@ -2364,6 +2394,33 @@ pub enum LoRAPath {
     Batch(Vec<Option<String>>),
 }
 
+impl LoRAPath {
+    /// Get the single adapter targeted by this request, if any
+    ///
+    /// Batches may repeat the same adapter or leave entries empty; batches
+    /// mixing different adapters cannot be served by one worker and are rejected.
+    pub fn single_adapter(&self) -> Result<Option<&str>, String> {
+        match self {
+            LoRAPath::Single(path) => Ok(path.as_deref()),
+            LoRAPath::Batch(paths) => {
+                let mut adapter: Option<&str> = None;
+                for path in paths.iter().flatten() {
+                    match adapter {
+                        Some(existing) if existing != path => {
+                            return Err(format!(
+                                "Batch mixes LoRA adapters '{}' and '{}'",
+                                existing, path
+                            ));
+                        }
+                        _ => adapter = Some(path),
+                    }
+                }
+                Ok(adapter)
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -3667,4 +3724,48 @@ mod tests {
             _ => panic!("Expected Assistant message"),
         }
     }
+
+    // ==================================================================
+    // =            LORA PATH TESTS                                      =
+    // ==================================================================
+
+    #[test]
+    fn test_lora_path_single_adapter() {
+        let single: LoRAPath = serde_json::from_str(r#""/adapters/sql""#).unwrap();
+        assert_eq!(single.single_adapter().unwrap(), Some("/adapters/sql"));
+
+        let none: LoRAPath = serde_json::from_str("null").unwrap();
+        assert_eq!(none.single_adapter().unwrap(), None);
+
+        let batch: LoRAPath =
+            serde_json::from_str(r#"["/adapters/sql", null, "/adapters/sql"]"#).unwrap();
+        assert_eq!(batch.single_adapter().unwrap(), Some("/adapters/sql"));
+
+        let mixed: LoRAPath =
+            serde_json::from_str(r#"["/adapters/sql", "/adapters/chat"]"#).unwrap();
+        assert!(mixed.single_adapter().is_err());
+    }
+
+    #[test]
+    fn test_generation_request_lora_path() {
+        let req: CompletionRequest = serde_json::from_str(
+            r#"{"model": "llama", "prompt": "hi", "lora_path": "/adapters/sql"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            req.get_lora_path().unwrap().single_adapter().unwrap(),
+            Some("/adapters/sql")
+        );
+
+        let rerank = RerankRequest {
+            query: "q".to_string(),
+            documents: vec![],
+            model: "m".to_string(),
+            top_k: None,
+            return_documents: true,
+            rid: None,
+            user: None,
+        };
+        assert!(rerank.get_lora_path().is_none());
+    }
 }
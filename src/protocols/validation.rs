@@ -6,7 +6,8 @@ use std::fmt::Display;
 
 // Import types from spec module
 use crate::protocols::spec::{
-    ChatCompletionRequest, ChatMessage, ResponseFormat, StringOrArray, UserMessageContent,
+    ChatCompletionRequest, ChatMessage, CompletionRequest, ResponseFormat, StringOrArray,
+    UserMessageContent,
 };
 
 /// Validation constants for OpenAI API parameters
@@ -754,6 +755,55 @@ impl ValidatableRequest for ChatCompletionRequest {
     }
 }
 
+// ==================================================================
+// =            OPENAI COMPLETION PARAMETERS                        =
+// ==================================================================
+
+impl SamplingOptionsProvider for CompletionRequest {
+    fn get_temperature(&self) -> Option<f32> {
+        self.temperature
+    }
+    fn get_top_p(&self) -> Option<f32> {
+        self.top_p
+    }
+    fn get_frequency_penalty(&self) -> Option<f32> {
+        self.frequency_penalty
+    }
+    fn get_presence_penalty(&self) -> Option<f32> {
+        self.presence_penalty
+    }
+}
+
+impl StopConditionsProvider for CompletionRequest {
+    fn get_stop_sequences(&self) -> Option<&StringOrArray> {
+        self.stop.as_ref()
+    }
+}
+
+impl TokenLimitsProvider for CompletionRequest {
+    fn get_max_tokens(&self) -> Option<u32> {
+        self.max_tokens
+    }
+
+    fn get_min_tokens(&self) -> Option<u32> {
+        self.min_tokens
+    }
+}
+
+impl VLLMExtensionsProvider for CompletionRequest {
+    fn get_top_k(&self) -> Option<i32> {
+        self.top_k
+    }
+
+    fn get_min_p(&self) -> Option<f32> {
+        self.min_p
+    }
+
+    fn get_repetition_penalty(&self) -> Option<f32> {
+        self.repetition_penalty
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::constants::*;
@@ -45,6 +45,24 @@ pub struct WorkerConfigRequest {
     pub labels: HashMap<String, String>,
 }
 
+impl WorkerConfigRequest {
+    /// Labels with the model ID, priority and cost folded in, as read by
+    /// `Worker::model_id`, `Worker::priority` and `Worker::cost`
+    pub fn worker_labels(&self) -> HashMap<String, String> {
+        let mut labels = self.labels.clone();
+        if let Some(model_id) = &self.model_id {
+            labels.insert("model_id".to_string(), model_id.clone());
+        }
+        if let Some(priority) = self.priority {
+            labels.insert("priority".to_string(), priority.to_string());
+        }
+        if let Some(cost) = self.cost {
+            labels.insert("cost".to_string(), cost.to_string());
+        }
+        labels
+    }
+}
+
 /// Worker information for API responses
 #[derive(Debug, Clone, Serialize)]
 pub struct WorkerInfo {
@@ -179,10 +179,12 @@ impl GrpcPDRouter {
         let prefill_health_checker = crate::core::start_health_checker(
             Arc::clone(&prefill_workers),
             ctx.router_config.worker_startup_check_interval_secs,
+            ctx.client.clone(),
         );
         let decode_health_checker = crate::core::start_health_checker(
             Arc::clone(&decode_workers),
             ctx.router_config.worker_startup_check_interval_secs,
+            ctx.client.clone(),
         );
 
         Ok(GrpcPDRouter {
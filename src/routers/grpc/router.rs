@@ -127,6 +127,7 @@ impl GrpcRouter {
         let health_checker = crate::core::start_health_checker(
             Arc::clone(&workers),
             ctx.router_config.worker_startup_check_interval_secs,
+            ctx.client.clone(),
         );
 
         Ok(GrpcRouter {
@@ -3,8 +3,14 @@
 pub mod dp_utils;
 pub mod logprobs_merge;
 pub mod openai_router;
+pub mod pd_bypass;
+pub mod pd_chunked_prefill;
+pub mod pd_failover;
 pub mod pd_router;
+pub mod pd_timing;
+pub mod pd_transfer;
 pub mod pd_types;
 pub mod router;
+pub mod vllm_discovery_transport;
 pub mod vllm_pd_router;
 pub mod vllm_service_discovery;
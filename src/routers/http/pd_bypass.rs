@@ -0,0 +1,223 @@
+//! Conditional disaggregation for the vLLM PD router
+//!
+//! A separate prefill stage costs a KV-transfer round trip, which is pure
+//! overhead for short prompts and for prompts a decode worker already has
+//! cached. Such requests skip prefill and are served by a single worker.
+
+use super::pd_failover::FallbackPool;
+use crate::config::ConditionalDisaggregationConfig;
+use crate::core::{capabilities, Worker};
+use crate::policies::{CacheAwarePolicy, LoadBalancingPolicy};
+use crate::tokenizer::traits::Tokenizer;
+use serde_json::Value;
+use std::fmt;
+use std::sync::Arc;
+
+/// Why the prefill stage was skipped
+pub const BYPASS_HEADER: &str = "x-pd-prefill-bypass";
+/// Mixed worker that served a short prompt
+pub const MIXED_WORKER_HEADER: &str = "x-pd-mixed-worker";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BypassReason {
+    /// Prompt is below the prefill token threshold
+    ShortPrompt,
+    /// Decode worker already caches most of the prompt
+    PrefixCache,
+}
+
+impl BypassReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BypassReason::ShortPrompt => "short_prompt",
+            BypassReason::PrefixCache => "prefix_cache",
+        }
+    }
+}
+
+/// Decides which requests skip the prefill stage
+pub struct PrefillBypass {
+    config: ConditionalDisaggregationConfig,
+    tokenizer: Option<Arc<dyn Tokenizer>>,
+    mixed_pool: FallbackPool,
+}
+
+impl fmt::Debug for PrefillBypass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PrefillBypass")
+            .field("config", &self.config)
+            .field("tokenizer", &self.tokenizer.is_some())
+            .field("mixed_pool", &self.mixed_pool)
+            .finish()
+    }
+}
+
+impl PrefillBypass {
+    pub fn new(
+        config: ConditionalDisaggregationConfig,
+        tokenizer: Option<Arc<dyn Tokenizer>>,
+    ) -> Self {
+        let mixed_pool = FallbackPool::new(config.mixed_worker_urls.clone())
+            .with_worker_header(MIXED_WORKER_HEADER);
+        Self {
+            config,
+            tokenizer,
+            mixed_pool,
+        }
+    }
+
+    /// Prompt length in tokens, estimated from characters without a tokenizer
+    pub fn prompt_tokens(&self, prompt: &str) -> usize {
+        count_tokens(self.tokenizer.as_deref(), prompt)
+    }
+
+    pub fn is_short_prompt(&self, prompt: &str) -> bool {
+        self.prompt_tokens(prompt) < self.config.min_prefill_tokens
+    }
+
+    /// Decode worker whose prefix cache covers enough of the request
+    ///
+    /// Only available with a cache-aware decode policy, whose tree tracks what
+    /// each decode worker has seen. The request is recorded against the chosen
+    /// worker so follow-up turns keep their affinity.
+    pub fn cached_decode_worker(
+        &self,
+        decode_policy: &dyn LoadBalancingPolicy,
+        decode_workers: &[Arc<dyn Worker>],
+        request_text: &str,
+    ) -> Option<usize> {
+        let threshold = self.config.prefix_cache_threshold?;
+        let cache_aware = decode_policy.as_any().downcast_ref::<CacheAwarePolicy>()?;
+        let (idx, match_rate) = cache_aware.best_prefix_match(decode_workers, request_text)?;
+        if match_rate < threshold {
+            return None;
+        }
+        cache_aware.record_routed(decode_workers[idx].as_ref(), request_text);
+        Some(idx)
+    }
+
+    /// Regular workers that serve short prompts, if any are configured
+    pub fn mixed_pool(&self) -> &FallbackPool {
+        &self.mixed_pool
+    }
+}
+
+/// Token count of a prompt, estimated from characters without a tokenizer
+pub fn count_tokens(tokenizer: Option<&dyn Tokenizer>, prompt: &str) -> usize {
+    tokenizer
+        .and_then(|tokenizer| tokenizer.encode(prompt).ok())
+        .map(|encoding| encoding.token_ids().len())
+        .unwrap_or_else(|| capabilities::estimate_tokens(prompt))
+}
+
+/// Prompt text of an OpenAI-style request (`prompt`, `messages` or `text`)
+pub fn prompt_text(request: &Value) -> String {
+    fn collect(value: &Value, out: &mut String) {
+        match value {
+            Value::String(text) => out.push_str(text),
+            Value::Array(items) => items.iter().for_each(|item| collect(item, out)),
+            Value::Object(obj) => {
+                if let Some(text) = obj.get("text").or_else(|| obj.get("content")) {
+                    collect(text, out);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut text = String::new();
+    for field in ["prompt", "messages", "text"] {
+        if let Some(value) = request.get(field) {
+            collect(value, &mut text);
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{BasicWorker, WorkerType};
+    use crate::policies::{CacheAwareConfig, RandomPolicy};
+    use serde_json::json;
+
+    fn bypass(prefix_cache_threshold: Option<f64>) -> PrefillBypass {
+        PrefillBypass::new(
+            ConditionalDisaggregationConfig {
+                enabled: true,
+                min_prefill_tokens: 16,
+                prefix_cache_threshold,
+                mixed_worker_urls: Vec::new(),
+            },
+            None,
+        )
+    }
+
+    #[test]
+    fn test_prompt_text() {
+        let chat = json!({
+            "messages": [
+                {"role": "system", "content": "Be brief."},
+                {"role": "user", "content": [{"type": "text", "text": " Hi"}]}
+            ]
+        });
+        assert_eq!(prompt_text(&chat), "Be brief. Hi");
+        assert_eq!(prompt_text(&json!({"prompt": ["a", "b"]})), "ab");
+        assert_eq!(prompt_text(&json!({"input_ids": [1, 2]})), "");
+    }
+
+    #[test]
+    fn test_short_prompt_uses_char_estimate() {
+        let bypass = bypass(None);
+        // 60 chars ~ 15 tokens, 64 chars ~ 16 tokens
+        assert!(bypass.is_short_prompt(&"a".repeat(60)));
+        assert!(!bypass.is_short_prompt(&"a".repeat(64)));
+        assert!(bypass.mixed_pool().is_empty());
+    }
+
+    #[test]
+    fn test_cached_decode_worker() {
+        let policy = CacheAwarePolicy::with_config(CacheAwareConfig {
+            eviction_interval_secs: 0,
+            ..Default::default()
+        });
+        let workers: Vec<Arc<dyn Worker>> = vec![
+            Arc::new(BasicWorker::new(
+                "http://d1:8000".to_string(),
+                WorkerType::Decode,
+            )),
+            Arc::new(BasicWorker::new(
+                "http://d2:8000".to_string(),
+                WorkerType::Decode,
+            )),
+        ];
+        policy.init_workers(&workers);
+        let cached = "long shared document ".repeat(10);
+        policy.record_routed(workers[1].as_ref(), &cached);
+
+        let request = format!("{}and a question", cached);
+        assert_eq!(
+            bypass(Some(0.99)).cached_decode_worker(&policy, &workers, &request),
+            None
+        );
+        // Disabled without a threshold or a cache-aware decode policy
+        assert_eq!(
+            bypass(None).cached_decode_worker(&policy, &workers, &request),
+            None
+        );
+        assert_eq!(
+            bypass(Some(0.8)).cached_decode_worker(&RandomPolicy::new(), &workers, &request),
+            None
+        );
+
+        // A hit records the full request, so the next turn matches completely
+        assert_eq!(
+            bypass(Some(0.8)).cached_decode_worker(&policy, &workers, &request),
+            Some(1)
+        );
+        assert_eq!(
+            bypass(Some(0.99)).cached_decode_worker(&policy, &workers, &request),
+            Some(1)
+        );
+    }
+}
@@ -0,0 +1,464 @@
+//! Long-prompt prefill placement for the vLLM PD router
+//!
+//! A 100k-token prompt can exhaust the KV cache of whichever prefill worker
+//! the policy happens to pick. Prompts above a token threshold are instead
+//! placed by the free KV capacity each prefill worker exports, or split into
+//! token-range chunks prefilled concurrently on several workers when the KV
+//! connector supports context-parallel prefill.
+
+use super::dp_utils;
+use super::pd_bypass;
+use crate::config::ChunkedPrefillConfig;
+use crate::core::{Worker, WorkerRegistry};
+use crate::metrics::RouterMetrics;
+use crate::tokenizer::traits::Tokenizer;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tracing::debug;
+
+/// KV cache state scraped from a prefill worker's Prometheus endpoint
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KvCapacity {
+    /// Fraction of the KV cache in use (0.0-1.0)
+    pub usage: f64,
+    /// KV cache size in tokens, if the worker reports its cache config
+    pub total_tokens: Option<usize>,
+    /// Requests queued on the worker
+    pub waiting: usize,
+}
+
+impl KvCapacity {
+    /// Free KV cache in tokens, if the cache size is known
+    pub fn free_tokens(&self) -> Option<usize> {
+        self.total_tokens
+            .map(|total| (total as f64 * (1.0 - self.usage.clamp(0.0, 1.0))) as usize)
+    }
+}
+
+/// Parse the KV cache metrics of a vLLM `/metrics` response
+///
+/// With several engines behind one endpoint, the highest usage and the
+/// smallest cache are kept, since a request lands on a single engine.
+pub fn parse_kv_capacity(metrics: &str) -> Option<KvCapacity> {
+    let mut usage: Option<f64> = None;
+    let mut total_tokens: Option<usize> = None;
+    let mut waiting = 0.0;
+
+    for line in metrics.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (series, value) = match line.rsplit_once(' ') {
+            Some(parts) => parts,
+            None => continue,
+        };
+        let value: f64 = match value.parse() {
+            Ok(value) => value,
+            Err(_) => continue,
+        };
+        let (name, labels) = match series.split_once('{') {
+            Some((name, labels)) => (name, labels.trim_end_matches('}')),
+            None => (series, ""),
+        };
+        match name {
+            "vllm:kv_cache_usage_perc" | "vllm:gpu_cache_usage_perc" => {
+                usage = Some(usage.map_or(value, |cur| cur.max(value)));
+            }
+            "vllm:num_requests_waiting" => waiting += value,
+            "vllm:cache_config_info" => {
+                let blocks =
+                    label_value(labels, "num_gpu_blocks").and_then(|v| v.parse::<usize>().ok());
+                let block_size =
+                    label_value(labels, "block_size").and_then(|v| v.parse::<usize>().ok());
+                if let (Some(blocks), Some(block_size)) = (blocks, block_size) {
+                    let tokens: usize = blocks * block_size;
+                    total_tokens = Some(total_tokens.map_or(tokens, |cur| cur.min(tokens)));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    usage.map(|usage| KvCapacity {
+        usage,
+        total_tokens,
+        waiting: waiting as usize,
+    })
+}
+
+fn label_value<'a>(labels: &'a str, key: &str) -> Option<&'a str> {
+    labels.split(',').find_map(|pair| {
+        let (k, v) = pair.split_once('=')?;
+        (k.trim() == key).then(|| v.trim().trim_matches('"'))
+    })
+}
+
+/// Token range of a prompt prefilled on one worker
+#[derive(Debug, Clone)]
+pub struct PrefillChunk {
+    pub worker: Arc<dyn Worker>,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Where a long prompt is prefilled
+#[derive(Debug, Clone)]
+pub enum PrefillPlan {
+    /// Least loaded prefill worker with room for the whole prompt
+    Single(Arc<dyn Worker>),
+    /// Token-range chunks prefilled concurrently, in prompt order
+    ContextParallel(Vec<PrefillChunk>),
+}
+
+/// Split `total` tokens into `n` contiguous ranges of near-equal size
+pub fn chunk_ranges(total: usize, n: usize) -> Vec<(usize, usize)> {
+    let n = n.max(1);
+    (0..n)
+        .map(|i| (total * i / n, total * (i + 1) / n))
+        .collect()
+}
+
+/// `context_parallel` entry of the `kv_transfer_params` sent to one chunk's worker
+pub fn chunk_params(rank: usize, chunks: &[PrefillChunk]) -> Value {
+    json!({
+        "rank": rank,
+        "world_size": chunks.len(),
+        "token_start": chunks[rank].start,
+        "token_end": chunks[rank].end,
+        "total_tokens": chunks.last().map_or(0, |chunk| chunk.end),
+    })
+}
+
+/// `kv_transfer_params` for the decode stage of a context-parallel prefill
+///
+/// Keeps the first chunk's parameters at the top level, so connectors without
+/// chunk support still find a valid remote, and lists every chunk's parameters
+/// with its token range under `context_parallel_chunks`.
+pub fn merge_chunk_params(chunk_params: &[Option<Value>], chunks: &[PrefillChunk]) -> Value {
+    let mut merged = chunk_params
+        .first()
+        .cloned()
+        .flatten()
+        .unwrap_or_else(|| json!({}));
+    let entries: Vec<Value> = chunk_params
+        .iter()
+        .zip(chunks)
+        .map(|(params, chunk)| {
+            json!({
+                "token_start": chunk.start,
+                "token_end": chunk.end,
+                "kv_transfer_params": params.clone().unwrap_or(Value::Null),
+            })
+        })
+        .collect();
+    if let Some(obj) = merged.as_object_mut() {
+        obj.insert("context_parallel_chunks".to_string(), Value::Array(entries));
+    }
+    merged
+}
+
+/// Places long prompts by prefill KV capacity
+pub struct ChunkedPrefill {
+    config: ChunkedPrefillConfig,
+    tokenizer: Option<Arc<dyn Tokenizer>>,
+    // Latest KV capacity per prefill base URL
+    capacity: RwLock<HashMap<String, KvCapacity>>,
+}
+
+impl fmt::Debug for ChunkedPrefill {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChunkedPrefill")
+            .field("config", &self.config)
+            .field("tokenizer", &self.tokenizer.is_some())
+            .finish()
+    }
+}
+
+impl ChunkedPrefill {
+    pub fn new(config: ChunkedPrefillConfig, tokenizer: Option<Arc<dyn Tokenizer>>) -> Self {
+        Self {
+            config,
+            tokenizer,
+            capacity: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Scrape the prefill workers' KV capacity in the background
+    pub fn start(
+        self: &Arc<Self>,
+        client: reqwest::Client,
+        worker_registry: Arc<WorkerRegistry>,
+    ) -> tokio::task::JoinHandle<()> {
+        let this = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_secs(this.config.metrics_interval_secs));
+            loop {
+                interval.tick().await;
+                this.refresh(&client, &worker_registry.get_prefill_workers())
+                    .await;
+            }
+        })
+    }
+
+    /// Scrape the KV capacity of the given prefill workers once
+    pub async fn refresh(&self, client: &reqwest::Client, workers: &[Arc<dyn Worker>]) {
+        let mut base_urls: Vec<&str> = workers.iter().map(|w| base_url(w.url())).collect();
+        base_urls.sort_unstable();
+        base_urls.dedup();
+
+        let scrapes = base_urls.into_iter().map(|url| async move {
+            let metrics_url = format!("{}{}", url, self.config.metrics_path);
+            let result = match client.get(&metrics_url).send().await {
+                Ok(res) if res.status().is_success() => res.text().await.ok(),
+                Ok(res) => {
+                    debug!("KV metrics from {} returned {}", metrics_url, res.status());
+                    None
+                }
+                Err(e) => {
+                    debug!("Failed to scrape KV metrics from {}: {}", metrics_url, e);
+                    None
+                }
+            };
+            (url, result.as_deref().and_then(parse_kv_capacity))
+        });
+        let results = futures_util::future::join_all(scrapes).await;
+
+        let mut capacity = self.capacity.write().unwrap();
+        capacity.clear();
+        for (url, kv) in results {
+            if let Some(kv) = kv {
+                if let Some(free) = kv.free_tokens() {
+                    RouterMetrics::set_pd_prefill_kv_free_tokens(url, free);
+                }
+                capacity.insert(url.to_string(), kv);
+            }
+        }
+    }
+
+    /// Record the KV capacity of a prefill worker
+    pub fn update_capacity(&self, url: &str, kv: KvCapacity) {
+        self.capacity
+            .write()
+            .unwrap()
+            .insert(base_url(url).to_string(), kv);
+    }
+
+    pub fn capacity(&self, url: &str) -> Option<KvCapacity> {
+        self.capacity.read().unwrap().get(base_url(url)).copied()
+    }
+
+    /// Placement for a request, or `None` to keep the policy's choice
+    pub fn plan(
+        &self,
+        request: &Value,
+        prefill_workers: &[Arc<dyn Worker>],
+    ) -> Option<PrefillPlan> {
+        let prompt = pd_bypass::prompt_text(request);
+        let tokens = pd_bypass::count_tokens(self.tokenizer.as_deref(), &prompt);
+        if tokens < self.config.min_prompt_tokens {
+            return None;
+        }
+
+        let plan = self.plan_for_tokens(tokens, prefill_workers);
+        let mode = match &plan {
+            Some(PrefillPlan::Single(_)) => "single",
+            Some(PrefillPlan::ContextParallel(_)) => "context_parallel",
+            None => "unplaced",
+        };
+        RouterMetrics::record_pd_long_prompt_placement(mode);
+        plan
+    }
+
+    /// Placement for a prompt of `tokens` tokens
+    ///
+    /// Workers whose KV capacity is unknown are assumed to fit but rank after
+    /// workers known to have room.
+    pub fn plan_for_tokens(
+        &self,
+        tokens: usize,
+        prefill_workers: &[Arc<dyn Worker>],
+    ) -> Option<PrefillPlan> {
+        // (worker, free tokens, load including queued requests)
+        let mut candidates: Vec<(&Arc<dyn Worker>, Option<usize>, usize)> = prefill_workers
+            .iter()
+            .filter(|w| w.is_available())
+            .map(|w| {
+                let kv = self.capacity(w.url());
+                let free = kv.and_then(|kv| kv.free_tokens());
+                let waiting = kv.map_or(0, |kv| kv.waiting);
+                (w, free, w.load() + waiting)
+            })
+            .collect();
+        // Known capacity first, then least loaded, then most free KV
+        candidates.sort_by_key(|(_, free, load)| {
+            (free.is_none(), *load, std::cmp::Reverse(free.unwrap_or(0)))
+        });
+
+        if self.config.context_parallel && tokens > self.config.chunk_tokens {
+            let n = tokens
+                .div_ceil(self.config.chunk_tokens)
+                .min(self.config.max_chunks)
+                .min(candidates.len());
+            if n >= 2 {
+                let ranges = chunk_ranges(tokens, n);
+                let chunk_len = ranges.iter().map(|(start, end)| end - start).max()?;
+                let fitting: Vec<&Arc<dyn Worker>> = candidates
+                    .iter()
+                    .filter(|(_, free, _)| free.is_none_or(|free| free >= chunk_len))
+                    .map(|(w, _, _)| *w)
+                    .take(n)
+                    .collect();
+                if fitting.len() == n {
+                    return Some(PrefillPlan::ContextParallel(
+                        fitting
+                            .into_iter()
+                            .zip(ranges)
+                            .map(|(worker, (start, end))| PrefillChunk {
+                                worker: Arc::clone(worker),
+                                start,
+                                end,
+                            })
+                            .collect(),
+                    ));
+                }
+            }
+        }
+
+        candidates
+            .into_iter()
+            .find(|(_, free, _)| free.is_none_or(|free| free >= tokens))
+            .map(|(w, _, _)| PrefillPlan::Single(Arc::clone(w)))
+    }
+}
+
+// Metrics are served once per endpoint, not per data-parallel rank
+fn base_url(url: &str) -> &str {
+    dp_utils::extract_dp_rank(url)
+        .map(|(base, _)| base)
+        .unwrap_or(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{BasicWorker, WorkerType};
+
+    const METRICS: &str = r#"# HELP vllm:kv_cache_usage_perc KV-cache usage. 1 means 100 percent usage.
+# TYPE vllm:kv_cache_usage_perc gauge
+vllm:kv_cache_usage_perc{engine="0",model_name="llama"} 0.25
+vllm:num_requests_waiting{engine="0",model_name="llama"} 3.0
+vllm:cache_config_info{block_size="16",cache_dtype="auto",engine="0",num_gpu_blocks="8192"} 1.0
+"#;
+
+    fn prefill_workers(n: usize) -> Vec<Arc<dyn Worker>> {
+        (0..n)
+            .map(|i| {
+                Arc::new(BasicWorker::new(
+                    format!("http://p{}:8000", i),
+                    WorkerType::Prefill {
+                        bootstrap_port: None,
+                    },
+                )) as Arc<dyn Worker>
+            })
+            .collect()
+    }
+
+    fn planner(context_parallel: bool) -> ChunkedPrefill {
+        ChunkedPrefill::new(
+            ChunkedPrefillConfig {
+                enabled: true,
+                min_prompt_tokens: 1000,
+                context_parallel,
+                chunk_tokens: 1000,
+                max_chunks: 3,
+                ..Default::default()
+            },
+            None,
+        )
+    }
+
+    fn kv(total_tokens: usize, usage: f64) -> KvCapacity {
+        KvCapacity {
+            usage,
+            total_tokens: Some(total_tokens),
+            waiting: 0,
+        }
+    }
+
+    #[test]
+    fn test_parse_kv_capacity() {
+        let kv = parse_kv_capacity(METRICS).unwrap();
+        assert_eq!(kv.usage, 0.25);
+        assert_eq!(kv.total_tokens, Some(8192 * 16));
+        assert_eq!(kv.waiting, 3);
+        assert_eq!(kv.free_tokens(), Some(98304));
+
+        let legacy = parse_kv_capacity("vllm:gpu_cache_usage_perc{engine=\"0\"} 0.5\n").unwrap();
+        assert_eq!(legacy.free_tokens(), None);
+        assert!(parse_kv_capacity("vllm:num_requests_running 1.0\n").is_none());
+    }
+
+    #[test]
+    fn test_chunk_ranges() {
+        assert_eq!(chunk_ranges(10, 3), vec![(0, 3), (3, 6), (6, 10)]);
+        assert_eq!(chunk_ranges(10, 1), vec![(0, 10)]);
+    }
+
+    #[test]
+    fn test_single_placement_needs_free_kv() {
+        let planner = planner(false);
+        let workers = prefill_workers(3);
+        planner.update_capacity(workers[0].url(), kv(10_000, 0.9));
+        planner.update_capacity(workers[1].url(), kv(10_000, 0.5));
+        planner.update_capacity(workers[2].url(), kv(10_000, 0.1));
+        workers[2].increment_load();
+
+        // p0 has 1k free, so the less loaded p1 wins for a 4k-token prompt
+        match planner.plan_for_tokens(4000, &workers) {
+            Some(PrefillPlan::Single(worker)) => assert_eq!(worker.url(), "http://p1:8000"),
+            other => panic!("unexpected plan: {:?}", other),
+        }
+        // Only p2 has room for 8k tokens
+        match planner.plan_for_tokens(8000, &workers) {
+            Some(PrefillPlan::Single(worker)) => assert_eq!(worker.url(), "http://p2:8000"),
+            other => panic!("unexpected plan: {:?}", other),
+        }
+        assert!(planner.plan_for_tokens(20_000, &workers).is_none());
+
+        // Short prompts keep the policy's choice
+        assert!(planner
+            .plan(&json!({"prompt": "short"}), &workers)
+            .is_none());
+    }
+
+    #[test]
+    fn test_context_parallel_plan() {
+        let planner = planner(true);
+        let workers = prefill_workers(4);
+
+        match planner.plan_for_tokens(2500, &workers) {
+            Some(PrefillPlan::ContextParallel(chunks)) => {
+                let ranges: Vec<(usize, usize)> = chunks.iter().map(|c| (c.start, c.end)).collect();
+                assert_eq!(ranges, vec![(0, 833), (833, 1666), (1666, 2500)]);
+                assert_eq!(chunk_params(2, &chunks)["total_tokens"], 2500);
+
+                let params = vec![Some(json!({"remote_engine_id": "e0"})), None, None];
+                let merged = merge_chunk_params(&params, &chunks);
+                assert_eq!(merged["remote_engine_id"], "e0");
+                assert_eq!(merged["context_parallel_chunks"][1]["token_start"], 833);
+            }
+            other => panic!("unexpected plan: {:?}", other),
+        }
+
+        // Prompts that fit one chunk are placed on a single worker
+        assert!(matches!(
+            planner.plan_for_tokens(1000, &workers),
+            Some(PrefillPlan::Single(_))
+        ));
+    }
+}
@@ -0,0 +1,299 @@
+//! Decode failover shared by the PD routers
+//!
+//! A decode failure is recoverable as long as nothing has been sent to the
+//! client yet: the request can be re-run on another prefill/decode pair or, as
+//! a last resort, on a regular co-located worker.
+
+use super::pd_types::error_chain;
+use crate::core::{PrefilledBy, ServedBy};
+use crate::routers::header_utils;
+use axum::{
+    body::Body,
+    http::{HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use bytes::Bytes;
+use futures_util::{Stream, StreamExt};
+use serde_json::Value;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tracing::warn;
+
+/// Number of prefill/decode pairs tried for the request
+pub const ATTEMPTS_HEADER: &str = "x-pd-attempts";
+/// Prefill worker that served the request
+pub const PREFILL_WORKER_HEADER: &str = "x-pd-prefill-worker";
+/// Decode worker that served the request
+pub const DECODE_WORKER_HEADER: &str = "x-pd-decode-worker";
+/// Regular worker that served the request after all pairs failed
+pub const FALLBACK_WORKER_HEADER: &str = "x-pd-fallback-worker";
+
+/// Fields that only make sense for a disaggregated prefill/decode exchange
+const PD_ONLY_FIELDS: &[&str] = &[
+    "bootstrap_host",
+    "bootstrap_port",
+    "bootstrap_room",
+    "kv_transfer_params",
+];
+
+/// Response extension marking a decode failure that another pair may recover from
+#[derive(Debug, Clone, Copy)]
+pub struct DecodeFailure;
+
+pub type ByteStream = Pin<Box<dyn Stream<Item = Result<Bytes, reqwest::Error>> + Send>>;
+
+/// Mark a response as a recoverable decode failure
+pub fn mark_decode_failure(mut response: Response) -> Response {
+    response.extensions_mut().insert(DecodeFailure);
+    response
+}
+
+pub fn is_decode_failure(response: &Response) -> bool {
+    response.extensions().get::<DecodeFailure>().is_some()
+}
+
+/// Response extension naming the prefill worker whose stage failed, so the
+/// next pair avoids it and the decode worker is not blamed for it
+#[derive(Debug, Clone)]
+pub struct PrefillFailure(pub String);
+
+/// Mark a response as a failed prefill stage on the given worker
+pub fn mark_prefill_failure(mut response: Response, prefill_url: &str) -> Response {
+    response
+        .extensions_mut()
+        .insert(PrefillFailure(prefill_url.to_string()));
+    response
+}
+
+/// The prefill worker whose stage produced this response, if it failed
+pub fn prefill_failure(response: &Response) -> Option<&str> {
+    response
+        .extensions()
+        .get::<PrefillFailure>()
+        .map(|failure| failure.0.as_str())
+}
+
+/// Record the pair that produced a response for the audit trail
+pub fn mark_served_by(response: &mut Response, prefill: &str, decode: &str) {
+    let extensions = response.extensions_mut();
+    extensions.insert(PrefilledBy(prefill.to_string()));
+    extensions.insert(ServedBy(decode.to_string()));
+}
+
+/// Report the attempt count and the pair that produced the response
+pub fn annotate_response(response: &mut Response, attempts: u32, prefill: &str, decode: &str) {
+    let headers = response.headers_mut();
+    headers.insert(ATTEMPTS_HEADER, HeaderValue::from(attempts));
+    for (name, value) in [
+        (PREFILL_WORKER_HEADER, prefill),
+        (DECODE_WORKER_HEADER, decode),
+    ] {
+        if let Ok(value) = HeaderValue::from_str(value) {
+            headers.insert(name, value);
+        }
+    }
+}
+
+/// Wait for the first chunk of a decode stream
+///
+/// Returns the stream with the chunk put back in front, or an error if the
+/// stream failed or ended before producing anything, in which case the client
+/// has not seen a token yet and the request can still be re-run.
+pub async fn wait_for_first_chunk<S>(stream: S) -> Result<ByteStream, String>
+where
+    S: Stream<Item = Result<Bytes, reqwest::Error>> + Send + 'static,
+{
+    let mut stream: ByteStream = Box::pin(stream);
+    match stream.next().await {
+        Some(Ok(chunk)) => Ok(Box::pin(
+            futures_util::stream::once(async move { Ok(chunk) }).chain(stream),
+        )),
+        Some(Err(e)) => Err(format!("Decode stream failed: {}", error_chain(&e))),
+        None => Err("Decode stream ended before the first chunk".to_string()),
+    }
+}
+
+/// Regular workers that serve PD requests outside the prefill/decode flow
+///
+/// Used as the fallback once every pair has failed, and as the mixed pool for
+/// requests that skip prefill.
+#[derive(Debug)]
+pub struct FallbackPool {
+    urls: Vec<String>,
+    next: AtomicUsize,
+    // Response header naming the worker that served the request
+    worker_header: &'static str,
+}
+
+impl Default for FallbackPool {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl FallbackPool {
+    pub fn new(urls: Vec<String>) -> Self {
+        Self {
+            urls,
+            next: AtomicUsize::new(0),
+            worker_header: FALLBACK_WORKER_HEADER,
+        }
+    }
+
+    /// Report the serving worker under a different response header
+    pub fn with_worker_header(mut self, header: &'static str) -> Self {
+        self.worker_header = header;
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.urls.is_empty()
+    }
+
+    /// Strip PD-only fields so a co-located worker can serve the request
+    pub fn prepare_request(mut request: Value) -> Value {
+        if let Some(obj) = request.as_object_mut() {
+            for field in PD_ONLY_FIELDS {
+                obj.remove(*field);
+            }
+        }
+        request
+    }
+
+    /// Send the request to the fallback workers in round-robin order
+    ///
+    /// `build` creates the request for a worker URL. The first response that
+    /// is not a server error is passed through; otherwise the last error is
+    /// returned.
+    pub async fn dispatch<F>(&self, build: F) -> Response
+    where
+        F: Fn(&str) -> reqwest::RequestBuilder,
+    {
+        if self.urls.is_empty() {
+            return (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "No fallback workers configured",
+            )
+                .into_response();
+        }
+
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        let mut last_error = None;
+        for offset in 0..self.urls.len() {
+            let url = &self.urls[(start + offset) % self.urls.len()];
+            match build(url).send().await {
+                Ok(res) if !res.status().is_server_error() => {
+                    let status = StatusCode::from_u16(res.status().as_u16())
+                        .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+                    let mut headers = header_utils::preserve_response_headers(res.headers());
+                    headers.remove(axum::http::header::CONTENT_LENGTH);
+                    if let Ok(value) = HeaderValue::from_str(url) {
+                        headers.insert(self.worker_header, value);
+                    }
+                    let mut response = Response::new(Body::from_stream(res.bytes_stream()));
+                    *response.status_mut() = status;
+                    *response.headers_mut() = headers;
+                    response.extensions_mut().insert(ServedBy(url.to_string()));
+                    return response;
+                }
+                Ok(res) => {
+                    warn!("Fallback worker {} returned {}", url, res.status());
+                    last_error = Some(format!("Fallback worker {} returned {}", url, res.status()));
+                }
+                Err(e) => {
+                    warn!("Fallback worker {} failed: {}", url, e);
+                    last_error = Some(format!(
+                        "Fallback worker {} failed: {}",
+                        url,
+                        error_chain(&e)
+                    ));
+                }
+            }
+        }
+
+        (
+            StatusCode::BAD_GATEWAY,
+            last_error.unwrap_or_else(|| "All fallback workers failed".to_string()),
+        )
+            .into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::post, Json, Router};
+    use serde_json::json;
+
+    async fn spawn_worker(status: StatusCode) -> String {
+        let app = Router::new().route(
+            "/v1/completions",
+            post(move |Json(body): Json<Value>| async move { (status, Json(body)) }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}", addr)
+    }
+
+    #[test]
+    fn test_prepare_request_strips_pd_fields() {
+        let request = FallbackPool::prepare_request(json!({
+            "prompt": "hi",
+            "bootstrap_host": "10.0.0.1",
+            "bootstrap_room": 42,
+            "kv_transfer_params": {"do_remote_decode": true}
+        }));
+        assert_eq!(request, json!({"prompt": "hi"}));
+    }
+
+    #[test]
+    fn test_annotate_response() {
+        let mut response = mark_decode_failure(StatusCode::OK.into_response());
+        assert!(is_decode_failure(&response));
+        annotate_response(&mut response, 2, "http://p1:8000", "http://d2:8000");
+        assert_eq!(response.headers()[ATTEMPTS_HEADER], "2");
+        assert_eq!(response.headers()[DECODE_WORKER_HEADER], "http://d2:8000");
+        assert!(!is_decode_failure(&StatusCode::OK.into_response()));
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_first_chunk() {
+        let chunks: Vec<Result<Bytes, reqwest::Error>> =
+            vec![Ok(Bytes::from("a")), Ok(Bytes::from("b"))];
+        let stream = wait_for_first_chunk(futures_util::stream::iter(chunks))
+            .await
+            .unwrap();
+        let collected: Vec<Bytes> = stream.map(|c| c.unwrap()).collect().await;
+        assert_eq!(collected, vec![Bytes::from("a"), Bytes::from("b")]);
+
+        let empty: Vec<Result<Bytes, reqwest::Error>> = vec![];
+        assert!(wait_for_first_chunk(futures_util::stream::iter(empty))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fallback_skips_failing_worker() {
+        let bad = spawn_worker(StatusCode::INTERNAL_SERVER_ERROR).await;
+        let good = spawn_worker(StatusCode::OK).await;
+        let pool = FallbackPool::new(vec![bad, good.clone()]);
+        let client = reqwest::Client::new();
+
+        for _ in 0..2 {
+            let response = pool
+                .dispatch(|url| {
+                    client
+                        .post(format!("{}/v1/completions", url))
+                        .json(&json!({"prompt": "hi"}))
+                })
+                .await;
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(response.headers()[FALLBACK_WORKER_HEADER], good.as_str());
+        }
+
+        let empty = FallbackPool::default();
+        let response = empty.dispatch(|url| client.post(url.to_string())).await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+}
@@ -1,17 +1,24 @@
 // PD (Prefill-Decode) Router Implementation
 // This module handles routing for disaggregated prefill-decode systems
 use super::logprobs_merge;
-use super::pd_types::{api_path, PDRouterError};
-use crate::config::types::RetryConfig;
+use super::pd_chunked_prefill::{self, ChunkedPrefill, PrefillChunk, PrefillPlan};
+use super::pd_failover::{self, FallbackPool};
+use super::pd_timing::PdTimer;
+use super::pd_transfer::{self, DispatchMode, KvTransferProtocol, TransferPair};
+use super::pd_types::{api_path, error_chain, PDRouterError};
+use crate::config::types::{DecodeFailoverConfig, RetryConfig};
 use crate::core::{
-    is_retryable_status, BasicWorker, CircuitBreakerConfig, HealthConfig, RetryExecutor, Worker,
-    WorkerFactory, WorkerLoadGuard, WorkerRegistry, WorkerType,
+    capabilities, is_retryable_status, BasicWorker, CircuitBreakerConfig, HealthConfig,
+    LoraManager, PdCapacityController, RequestRequirements, RetryExecutor, Worker, WorkerLoadGuard,
+    WorkerLoadLease, WorkerRegistry, WorkerTopology, WorkerType,
 };
 use crate::metrics::RouterMetrics;
-use crate::policies::{LoadBalancingPolicy, PolicyRegistry};
+use crate::policies::{
+    LoadBalancingPolicy, PolicyRegistry, RequestHeaders, TopologyAwarePairPolicy,
+};
 use crate::protocols::spec::{
-    ChatCompletionRequest, ChatMessage, CompletionRequest, GenerateRequest, RerankRequest,
-    ResponsesRequest, StringOrArray, UserMessageContent,
+    ChatCompletionRequest, ChatMessage, CompletionRequest, GenerateRequest, GenerationRequest,
+    RerankRequest, ResponsesRequest, StringOrArray, UserMessageContent,
 };
 use crate::routers::header_utils;
 use crate::routers::{RouterTrait, WorkerManagement};
@@ -19,7 +26,10 @@ use async_trait::async_trait;
 use axum::{
     body::Body,
     extract::Request,
-    http::{header::CONTENT_TYPE, HeaderMap, HeaderValue, Method, StatusCode},
+    http::{
+        header::{CONTENT_LENGTH, CONTENT_TYPE},
+        HeaderMap, HeaderValue, Method, StatusCode,
+    },
     response::{IntoResponse, Response},
     Json,
 };
@@ -27,8 +37,9 @@ use futures_util::StreamExt;
 use reqwest::Client;
 use serde::Serialize;
 use serde_json::{json, Value};
-use std::collections::HashMap;
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 use tokio_stream::wrappers::UnboundedReceiverStream;
@@ -47,6 +58,28 @@ pub struct PDRouter {
     pub prefill_client: Client,
     pub retry_config: RetryConfig,
     pub circuit_breaker_config: CircuitBreakerConfig,
+    // API key used when querying workers for their capabilities
+    api_key: Option<String>,
+    // Adapter-aware routing and dynamic LoRA loading (None when disabled)
+    lora_manager: Option<Arc<LoraManager>>,
+    // Static worker topology specs ("zone/rack/node") keyed by worker URL
+    worker_topology: HashMap<String, String>,
+    // Joint prefill/decode pair selection (None when topology-aware pairing is disabled)
+    pair_policy: Option<Arc<TopologyAwarePairPolicy>>,
+    // Re-run requests on another pair (or the fallback pool) when decode fails
+    decode_failover: DecodeFailoverConfig,
+    fallback_pool: Arc<FallbackPool>,
+    // Prefill time and inter-token latency samples for role flip recommendations
+    pd_capacity: Option<Arc<PdCapacityController>>,
+    // Connector coordination; its dispatch mode picks concurrent or sequential stages
+    kv_transfer: Arc<dyn KvTransferProtocol>,
+    // Places long prompts by prefill KV capacity (sequential dispatch only, None when disabled)
+    chunked_prefill: Option<Arc<ChunkedPrefill>>,
+    // Profile each stage on its worker, stopping after a timeout
+    enable_profiling: bool,
+    profile_timeout_secs: u64,
+    // Active profiling timeout tasks keyed by worker URL
+    profiling_tasks: Arc<tokio::sync::Mutex<HashMap<String, tokio::task::AbortHandle>>>,
     // Channel for sending prefill responses to background workers for draining
     prefill_drain_tx: mpsc::Sender<reqwest::Response>,
 }
@@ -54,18 +87,21 @@ pub struct PDRouter {
 // Request context for PD router operations
 #[derive(Clone)]
 struct PDRequestContext<'a> {
-    route: &'static str,
+    route: &'a str,
     batch_size: Option<usize>,
     is_stream: bool,
     return_logprob: bool,
     request_text: Option<String>,
+    request_headers: Option<RequestHeaders>,
     model_id: Option<&'a str>,
+    requirements: RequestRequirements,
 }
 
 impl PDRouter {
     // Private helper method to perform health check on a new server
     async fn wait_for_server_health(&self, url: &str) -> Result<(), PDRouterError> {
         crate::routers::http::router::Router::wait_for_healthy_workers(
+            &self.client,
             &[url.to_string()],
             self.worker_startup_timeout_secs,
             self.worker_startup_check_interval_secs,
@@ -180,6 +216,50 @@ impl PDRouter {
         }
     }
 
+    // Start profiling a stage's worker when profiling is enabled; a timeout
+    // task stops it if the stage never finishes
+    async fn begin_stage_profiling(&self, worker_url: &str) {
+        if !self.enable_profiling {
+            return;
+        }
+        self.start_profiling(worker_url).await;
+
+        let timeout_secs = self.profile_timeout_secs;
+        let worker_url_owned = worker_url.to_string();
+        let router = self.clone();
+        let task_handle = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(timeout_secs)).await;
+            info!(
+                "Profiling timeout reached for {}, stopping profiling",
+                worker_url_owned
+            );
+            router.stop_profiling(&worker_url_owned).await;
+            router
+                .profiling_tasks
+                .lock()
+                .await
+                .remove(&worker_url_owned);
+        });
+
+        let mut tasks = self.profiling_tasks.lock().await;
+        if let Some(old_handle) = tasks.insert(worker_url.to_string(), task_handle.abort_handle()) {
+            // Cancel any existing timeout task for this worker
+            old_handle.abort();
+        }
+    }
+
+    // Stop profiling a stage's worker and cancel its timeout task
+    async fn end_stage_profiling(&self, worker_url: &str) {
+        if !self.enable_profiling {
+            return;
+        }
+        if let Some(handle) = self.profiling_tasks.lock().await.remove(worker_url) {
+            handle.abort();
+            info!("Cancelled profiling timeout task for {}", worker_url);
+        }
+        self.stop_profiling(worker_url).await;
+    }
+
     // Helper for proxying requests to the first prefill worker
     async fn proxy_to_first_prefill_worker(
         &self,
@@ -260,6 +340,17 @@ impl PDRouter {
         &self,
         url: String,
         bootstrap_port: Option<u16>,
+    ) -> Result<String, PDRouterError> {
+        self.add_prefill_server_with_labels(url, bootstrap_port, HashMap::new())
+            .await
+    }
+
+    /// Add a prefill server carrying `labels` on top of its topology labels
+    pub async fn add_prefill_server_with_labels(
+        &self,
+        url: String,
+        bootstrap_port: Option<u16>,
+        labels: HashMap<String, String>,
     ) -> Result<String, PDRouterError> {
         // Wait for the new server to be healthy
         self.wait_for_server_health(&url).await?;
@@ -271,13 +362,13 @@ impl PDRouter {
 
         // Create Worker for the new prefill server with circuit breaker configuration
         // TODO: In IGW mode, fetch model_id from worker's /get_model_info endpoint
-        let worker = WorkerFactory::create_prefill_with_config(
-            url.clone(),
-            bootstrap_port,
-            self.circuit_breaker_config.clone(),
-        );
+        let mut worker_labels = Self::topology_labels(&self.worker_topology, &url);
+        worker_labels.extend(labels);
+        let worker = BasicWorker::new(url.clone(), WorkerType::Prefill { bootstrap_port })
+            .with_circuit_breaker_config(self.circuit_breaker_config.clone())
+            .with_labels(worker_labels);
 
-        let worker_arc: Arc<dyn Worker> = Arc::from(worker);
+        let worker_arc: Arc<dyn Worker> = Arc::new(worker);
 
         // Register the worker in the registry
         self.worker_registry.register(worker_arc.clone());
@@ -297,11 +388,28 @@ impl PDRouter {
             }
         }
 
+        capabilities::populate_capabilities(
+            &self.client,
+            std::slice::from_ref(&worker_arc),
+            &self.api_key,
+        )
+        .await;
+
         info!("Added prefill server: {}", url);
         Ok(format!("Successfully added prefill server: {}", url))
     }
 
     pub async fn add_decode_server(&self, url: String) -> Result<String, PDRouterError> {
+        self.add_decode_server_with_labels(url, HashMap::new())
+            .await
+    }
+
+    /// Add a decode server carrying `labels` on top of its topology labels
+    pub async fn add_decode_server_with_labels(
+        &self,
+        url: String,
+        labels: HashMap<String, String>,
+    ) -> Result<String, PDRouterError> {
         // Wait for the new server to be healthy
         self.wait_for_server_health(&url).await?;
 
@@ -312,12 +420,13 @@ impl PDRouter {
 
         // Create Worker for the new decode server with circuit breaker configuration
         // TODO: In IGW mode, fetch model_id from worker's /get_model_info endpoint
-        let worker = WorkerFactory::create_decode_with_config(
-            url.clone(),
-            self.circuit_breaker_config.clone(),
-        );
+        let mut worker_labels = Self::topology_labels(&self.worker_topology, &url);
+        worker_labels.extend(labels);
+        let worker = BasicWorker::new(url.clone(), WorkerType::Decode)
+            .with_circuit_breaker_config(self.circuit_breaker_config.clone())
+            .with_labels(worker_labels);
 
-        let worker_arc: Arc<dyn Worker> = Arc::from(worker);
+        let worker_arc: Arc<dyn Worker> = Arc::new(worker);
 
         // Register the worker in the registry
         self.worker_registry.register(worker_arc.clone());
@@ -337,6 +446,13 @@ impl PDRouter {
             }
         }
 
+        capabilities::populate_capabilities(
+            &self.client,
+            std::slice::from_ref(&worker_arc),
+            &self.api_key,
+        )
+        .await;
+
         info!("Added decode server: {}", url);
         Ok(format!("Successfully added decode server: {}", url))
     }
@@ -438,76 +554,57 @@ impl PDRouter {
             window_duration: Duration::from_secs(circuit_breaker_config.window_duration_secs),
         };
 
-        // Automatically expand to DP-aware format when intra_node_data_parallel_size > 1
-        // This creates multiple worker URLs with @rank suffixes (e.g., "http://host:8000@0", "@1", etc.)
-        // without querying the workers. The router will add X-data-parallel-rank headers to route to specific ranks.
-        let (expanded_prefill_urls, expanded_decode_urls) =
-            if ctx.router_config.intra_node_data_parallel_size > 1 {
-                info!(
-                "DP-aware mode enabled (intra_node_data_parallel_size={}), expanding worker URLs",
-                ctx.router_config.intra_node_data_parallel_size
-            );
-
-                // Extract base URLs from prefill_urls (url, port) tuples
-                let prefill_base_urls: Vec<String> =
-                    prefill_urls.iter().map(|(url, _)| url.clone()).collect();
-
-                // Expand prefill URLs with DP ranks (0..intra_node_data_parallel_size-1)
-                let expanded_prefill = super::dp_utils::get_dp_aware_workers(
-                    &prefill_base_urls,
-                    &ctx.router_config.api_key,
-                    ctx.router_config.intra_node_data_parallel_size,
-                )
-                .await
-                .map_err(|e| format!("Failed to expand prefill workers: {}", e))?;
-
-                // Expand decode URLs with DP ranks (0..intra_node_data_parallel_size-1)
-                let expanded_decode = super::dp_utils::get_dp_aware_workers(
-                    &decode_urls,
-                    &ctx.router_config.api_key,
-                    ctx.router_config.intra_node_data_parallel_size,
-                )
-                .await
-                .map_err(|e| format!("Failed to expand decode workers: {}", e))?;
-
-                info!(
-                    "Expanded {} prefill URLs to {} DP-aware URLs",
-                    prefill_base_urls.len(),
-                    expanded_prefill.len()
-                );
-                info!(
-                    "Expanded {} decode URLs to {} DP-aware URLs",
-                    decode_urls.len(),
-                    expanded_decode.len()
-                );
-
-                // Keep the bootstrap_port from the original URLs, apply to all expanded URLs
-                let prefill_with_ports: Vec<(String, Option<u16>)> = expanded_prefill
-                    .into_iter()
-                    .map(|url| {
-                        // Use the port from the first original URL (all DP replicas share the same port config)
-                        let port = prefill_urls.first().and_then(|(_, p)| *p);
-                        (url, port)
-                    })
-                    .collect();
+        // Expand to DP-aware format (e.g., "http://host:8000@0", "@1", etc.) using either the
+        // discovered per-worker DP size or intra_node_data_parallel_size.
+        // The router will add X-data-parallel-rank headers to route to specific ranks.
+        let prefill_count = prefill_urls.len();
+        let mut expanded_prefill_urls: Vec<(String, Option<u16>)> = Vec::new();
+        for (url, port) in prefill_urls {
+            let expanded = super::dp_utils::expand_worker_urls(
+                &ctx.client,
+                std::slice::from_ref(&url),
+                &ctx.router_config.api_key,
+                ctx.router_config.intra_node_data_parallel_size,
+                &ctx.router_config.dp_discovery,
+            )
+            .await
+            .map_err(|e| format!("Failed to expand prefill workers: {}", e))?;
+            // All DP ranks of a prefill server share its bootstrap port
+            expanded_prefill_urls.extend(expanded.into_iter().map(|url| (url, port)));
+        }
 
-                (prefill_with_ports, expanded_decode)
-            } else {
-                info!("DP-aware mode disabled, using original worker URLs");
-                (prefill_urls, decode_urls)
-            };
+        let expanded_decode_urls = super::dp_utils::expand_worker_urls(
+            &ctx.client,
+            &decode_urls,
+            &ctx.router_config.api_key,
+            ctx.router_config.intra_node_data_parallel_size,
+            &ctx.router_config.dp_discovery,
+        )
+        .await
+        .map_err(|e| format!("Failed to expand decode workers: {}", e))?;
+
+        info!(
+            "Expanded {} prefill URLs to {} workers and {} decode URLs to {} workers",
+            prefill_count,
+            expanded_prefill_urls.len(),
+            decode_urls.len(),
+            expanded_decode_urls.len()
+        );
 
         let mut prefill_workers_urls = vec![];
         let mut decode_workers_urls = vec![];
         // Register prefill workers in the registry
+        let worker_topology = &ctx.router_config.pd_pairing.worker_topology;
         for (url, port) in expanded_prefill_urls {
             prefill_workers_urls.push(url.clone());
+            let labels = Self::topology_labels(worker_topology, &url);
             let worker = BasicWorker::new(
                 url,
                 WorkerType::Prefill {
                     bootstrap_port: port,
                 },
             )
+            .with_labels(labels)
             .with_circuit_breaker_config(core_cb_config.clone())
             .with_health_config(HealthConfig {
                 timeout_secs: ctx.router_config.health_check.timeout_secs,
@@ -522,7 +619,9 @@ impl PDRouter {
         // Register decode workers in the registry
         for url in expanded_decode_urls {
             decode_workers_urls.push(url.clone());
+            let labels = Self::topology_labels(worker_topology, &url);
             let worker = BasicWorker::new(url, WorkerType::Decode)
+                .with_labels(labels)
                 .with_circuit_breaker_config(core_cb_config.clone())
                 .with_health_config(HealthConfig {
                     timeout_secs: ctx.router_config.health_check.timeout_secs,
@@ -543,6 +642,7 @@ impl PDRouter {
         // At least one prefill and one decode are up
         if !prefill_workers_urls.is_empty() {
             crate::routers::http::router::Router::wait_for_healthy_workers(
+                &ctx.client,
                 &prefill_workers_urls,
                 ctx.router_config.worker_startup_timeout_secs,
                 ctx.router_config.worker_startup_check_interval_secs,
@@ -552,6 +652,7 @@ impl PDRouter {
 
         if !decode_workers_urls.is_empty() {
             crate::routers::http::router::Router::wait_for_healthy_workers(
+                &ctx.client,
                 &decode_workers_urls,
                 ctx.router_config.worker_startup_timeout_secs,
                 ctx.router_config.worker_startup_check_interval_secs,
@@ -559,6 +660,14 @@ impl PDRouter {
             .await?;
         }
 
+        // Discover context length, supported endpoints and LoRA adapters
+        capabilities::populate_capabilities(
+            &ctx.client,
+            &ctx.worker_registry.get_all(),
+            &ctx.router_config.api_key,
+        )
+        .await;
+
         // Initialize cache-aware policies with workers from registry
         // Note: We need to get workers by type and convert to Box<dyn Worker> for CacheAwarePolicy
         // This is a temporary workaround until CacheAwarePolicy is updated to work with Arc<dyn Worker>
@@ -599,7 +708,9 @@ impl PDRouter {
         // Individual routers no longer need to manage health checkers
 
         // Build a dedicated prefill client for fire-and-forget semantics
-        let prefill_client = reqwest::Client::builder()
+        let prefill_client = ctx
+            .upstream_tls
+            .client_builder()
             .pool_max_idle_per_host(0)
             .http1_only()
             .connect_timeout(Duration::from_millis(300))
@@ -676,6 +787,12 @@ impl PDRouter {
             info!("Prefill drain coordinator shutting down");
         });
 
+        let kv_transfer = pd_transfer::protocol_for(
+            ctx.router_config.kv_transfer_protocol,
+            ctx.router_config.mode.is_vllm_pd_mode(),
+            None,
+        );
+
         Ok(PDRouter {
             worker_registry: Arc::clone(&ctx.worker_registry),
             policy_registry: Arc::clone(&ctx.policy_registry),
@@ -690,9 +807,108 @@ impl PDRouter {
             prefill_drain_tx,
             retry_config: ctx.router_config.effective_retry_config(),
             circuit_breaker_config: core_cb_config,
+            api_key: ctx.router_config.api_key.clone(),
+            lora_manager: ctx.router_config.lora.enabled.then(|| {
+                Arc::new(LoraManager::new(
+                    ctx.client.clone(),
+                    ctx.router_config.api_key.clone(),
+                    ctx.router_config.lora.clone(),
+                    ctx.worker_registry.clone(),
+                ))
+            }),
+            worker_topology: ctx.router_config.pd_pairing.worker_topology.clone(),
+            pair_policy: ctx.router_config.pd_pairing.enabled.then(|| {
+                Arc::new(TopologyAwarePairPolicy::new(
+                    ctx.router_config.pd_pairing.load_weight,
+                    ctx.router_config.pd_pairing.transfer_cost.clone(),
+                ))
+            }),
+            decode_failover: ctx.router_config.decode_failover.clone(),
+            fallback_pool: Arc::new(FallbackPool::new(
+                ctx.router_config
+                    .decode_failover
+                    .fallback_worker_urls
+                    .clone(),
+            )),
+            pd_capacity: ctx.pd_capacity.clone(),
+            chunked_prefill: Self::chunked_prefill(ctx, kv_transfer.as_ref()),
+            kv_transfer,
+            enable_profiling: ctx.router_config.enable_profiling,
+            profile_timeout_secs: ctx.router_config.profile_timeout_secs,
+            profiling_tasks: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+        })
+    }
+
+    /// Replace the KV transfer protocol, e.g. with one that resolves
+    /// addresses through service discovery
+    pub fn with_kv_transfer(mut self, kv_transfer: Arc<dyn KvTransferProtocol>) -> Self {
+        info!("Using KV transfer protocol {}", kv_transfer.name());
+        self.kv_transfer = kv_transfer;
+        self
+    }
+
+    // Context-parallel chunks merge the transfer parameters of every prefill
+    // response, so long-prompt placement only applies to sequential protocols
+    fn chunked_prefill(
+        ctx: &Arc<crate::server::AppContext>,
+        kv_transfer: &dyn KvTransferProtocol,
+    ) -> Option<Arc<ChunkedPrefill>> {
+        let config = &ctx.router_config.chunked_prefill;
+        if !config.enabled || kv_transfer.dispatch_mode() != DispatchMode::Sequential {
+            return None;
+        }
+        let chunked = Arc::new(ChunkedPrefill::new(config.clone(), ctx.tokenizer.clone()));
+        chunked.start(ctx.client.clone(), Arc::clone(&ctx.worker_registry));
+        Some(chunked)
+    }
+
+    // Topology labels for a worker, looked up by its URL or its DP base URL
+    fn topology_labels(
+        worker_topology: &HashMap<String, String>,
+        url: &str,
+    ) -> HashMap<String, String> {
+        worker_topology
+            .get(url)
+            .or_else(|| worker_topology.get(&super::dp_utils::parse_worker_url(url).0))
+            .and_then(|spec| match WorkerTopology::parse(spec) {
+                Ok(topology) => Some(topology.to_labels()),
+                Err(e) => {
+                    warn!("Ignoring topology for {}: {}", url, e);
+                    None
+                }
+            })
+            .unwrap_or_default()
+    }
+
+    // Lowercased request headers for header-aware policies (consistent hashing)
+    pub(crate) fn request_headers(headers: Option<&HeaderMap>) -> Option<RequestHeaders> {
+        headers.map(|h| {
+            h.iter()
+                .filter_map(|(name, value)| {
+                    value
+                        .to_str()
+                        .ok()
+                        .map(|v| (name.as_str().to_lowercase(), v.to_string()))
+                })
+                .collect()
         })
     }
 
+    // Build the worker requirements of a request, including its LoRA adapter
+    pub(crate) fn request_requirements<T: GenerationRequest>(
+        &self,
+        route: &str,
+        body: &T,
+    ) -> Result<RequestRequirements, String> {
+        let requirements =
+            RequestRequirements::for_route(route, Some(&body.extract_text_for_routing()));
+        if self.lora_manager.is_none() {
+            return Ok(requirements);
+        }
+        let adapter = LoraManager::requested_adapter(body.get_lora_path())?;
+        Ok(requirements.with_lora_adapter(adapter))
+    }
+
     // Helper to handle server selection errors
     fn handle_server_selection_error(error: String) -> Response {
         error!("Failed to select PD pair error={}", error);
@@ -766,72 +982,51 @@ impl PDRouter {
         }
     }
 
-    // Helper to inject bootstrap fields into an existing JSON request value
-    fn inject_bootstrap_into_value(
-        mut original: Value,
-        prefill_worker: &dyn Worker,
-        batch_size: Option<usize>,
-    ) -> Result<Value, String> {
-        let bootstrap_port = match prefill_worker.worker_type() {
-            crate::core::WorkerType::Prefill { bootstrap_port } => bootstrap_port,
-            _ => None,
-        };
-        let hostname = super::pd_types::get_hostname(prefill_worker.url());
-
-        let obj = original
-            .as_object_mut()
-            .ok_or_else(|| "Request must be a JSON object".to_string())?;
-
-        if let Some(n) = batch_size {
-            let mut hosts = Vec::with_capacity(n);
-            let mut ports = Vec::with_capacity(n);
-            let mut rooms = Vec::with_capacity(n);
-            for _ in 0..n {
-                hosts.push(hostname.clone());
-                ports.push(bootstrap_port);
-                rooms.push(super::pd_types::generate_room_id());
-            }
-            obj.insert(
-                "bootstrap_host".to_string(),
-                Value::Array(hosts.into_iter().map(serde_json::Value::from).collect()),
-            );
-            obj.insert(
-                "bootstrap_port".to_string(),
-                Value::Array(
-                    ports
-                        .into_iter()
-                        .map(|p| match p {
-                            Some(v) => serde_json::Value::from(v),
-                            None => Value::Null,
-                        })
-                        .collect(),
-                ),
-            );
-            obj.insert(
-                "bootstrap_room".to_string(),
-                Value::Array(rooms.into_iter().map(serde_json::Value::from).collect()),
-            );
+    /// Dispatch a JSON request on any route through the PD engine
+    ///
+    /// Used for vLLM PD routes, where the request is forwarded as-is and
+    /// policies see the whole serialized request.
+    pub(crate) async fn route_json(
+        &self,
+        headers: Option<&HeaderMap>,
+        route: &str,
+        request: Value,
+        requirements: RequestRequirements,
+        model_id: Option<&str>,
+    ) -> Response {
+        let is_stream = request
+            .get("stream")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+        // Prompt logprobs (and echoed prompts) come from the prefill stage
+        let return_logprob = request
+            .get("logprobs")
+            .is_some_and(|v| !v.is_null() && v.as_bool() != Some(false))
+            || request
+                .get("echo")
+                .and_then(Value::as_bool)
+                .unwrap_or(false);
+        let request_text = if self.policies_need_request_text() {
+            serde_json::to_string(&request).ok()
         } else {
-            obj.insert(
-                "bootstrap_host".to_string(),
-                serde_json::Value::from(hostname),
-            );
-            obj.insert(
-                "bootstrap_port".to_string(),
-                match bootstrap_port {
-                    Some(v) => serde_json::Value::from(v),
-                    None => Value::Null,
-                },
-            );
-            obj.insert(
-                "bootstrap_room".to_string(),
-                serde_json::Value::from(super::pd_types::generate_room_id()),
-            );
-        }
-        Ok(original)
+            None
+        };
+
+        let context = PDRequestContext {
+            route,
+            batch_size: None,
+            is_stream,
+            return_logprob,
+            request_text,
+            request_headers: Self::request_headers(headers),
+            model_id,
+            requirements,
+        };
+        self.execute_dual_dispatch(headers, &request, context).await
     }
 
-    // Execute the dual dispatch to prefill and decode servers with retries and bootstrap injection
+    // Execute the dual dispatch to prefill and decode servers with retries,
+    // circuit breakers, decode failover and the configured KV transfer protocol
     async fn execute_dual_dispatch<T: Serialize + Clone>(
         &self,
         headers: Option<&HeaderMap>,
@@ -841,8 +1036,16 @@ impl PDRouter {
         let start_time = Instant::now();
 
         let route = context.route;
-        RetryExecutor::execute_response_with_retry(
-            &self.retry_config,
+        let lora_adapter = context.requirements.lora_adapter.clone();
+        let failover = &self.decode_failover;
+        // Workers that failed this request, avoided when picking the next pair
+        let failed_prefills = &Mutex::new(HashSet::new());
+        let failed_decodes = &Mutex::new(HashSet::new());
+        let attempts = &AtomicU32::new(0);
+        let max_retries = self.retry_config.max_retries.max(1);
+
+        let response = RetryExecutor::execute_response_with_retry(
+            &self.dispatch_retry_config(),
             // Operation per attempt
             {
                 let original_request = original_request.clone();
@@ -850,9 +1053,20 @@ impl PDRouter {
                     let original_request = original_request.clone();
                     let context = context.clone();
                     async move {
+                        attempts.store(attempt + 1, Ordering::Relaxed);
+                        let excluded_prefills = failed_prefills.lock().unwrap().clone();
+                        let excluded_decodes = failed_decodes.lock().unwrap().clone();
+
                         // Select workers fresh for each attempt
                         let (prefill, decode) = match self
-                            .select_pd_pair(context.request_text.as_deref(), context.model_id)
+                            .select_pd_pair_excluding(
+                                context.request_text.as_deref(),
+                                context.request_headers.as_ref(),
+                                context.model_id,
+                                &context.requirements,
+                                &excluded_prefills,
+                                &excluded_decodes,
+                            )
                             .await
                         {
                             Ok(pair) => pair,
@@ -874,41 +1088,129 @@ impl PDRouter {
                             Ok(v) => v,
                             Err(e) => return Self::handle_serialization_error(e),
                         };
+                        if let Some(adapter) = context.requirements.lora_adapter.as_deref() {
+                            LoraManager::target_adapter(&mut json_request, adapter);
+                        }
+
+                        // Long prompts are placed by prefill KV capacity
+                        let (prefill, chunks) =
+                            match self.plan_prefill(&json_request, &context.requirements) {
+                                Some(PrefillPlan::Single(worker)) => (worker, Vec::new()),
+                                Some(PrefillPlan::ContextParallel(chunks)) => {
+                                    (chunks[0].worker.clone(), chunks)
+                                }
+                                None => (prefill, Vec::new()),
+                            };
 
-                        // Inject bootstrap based on current prefill worker
-                        json_request = match Self::inject_bootstrap_into_value(
-                            json_request,
-                            prefill.as_ref(),
-                            context.batch_size,
+                        // Every stage needs the requested LoRA adapter
+                        let mut lora_leases = Vec::new();
+                        if let (Some(manager), Some(adapter)) = (
+                            &self.lora_manager,
+                            context.requirements.lora_adapter.as_deref(),
                         ) {
-                            Ok(v) => v,
-                            Err(e) => return Self::handle_serialization_error(e),
+                            let chunk_workers = chunks.iter().skip(1).map(|chunk| &chunk.worker);
+                            for worker in [&prefill, &decode].into_iter().chain(chunk_workers) {
+                                match manager.ensure_loaded(worker, adapter).await {
+                                    Ok(lease) => lora_leases.push(lease),
+                                    Err(e) => {
+                                        RouterMetrics::record_pd_error("lora_load_failed");
+                                        return (StatusCode::SERVICE_UNAVAILABLE, e)
+                                            .into_response();
+                                    }
+                                }
+                            }
+                        }
+
+                        // Connector coordination fields for the current pair
+                        let pair = TransferPair {
+                            prefill: prefill.as_ref(),
+                            decode: decode.as_ref(),
+                            batch_size: context.batch_size,
                         };
+                        if let Err(e) = self.kv_transfer.prepare_request(&mut json_request, &pair) {
+                            return Self::handle_serialization_error(e);
+                        }
 
                         // Execute the actual dual dispatch
-                        let response = self
-                            .execute_dual_dispatch_internal(
-                                headers,
-                                json_request,
-                                context,
-                                prefill.as_ref(),
-                                decode.as_ref(),
-                                start_time,
-                            )
-                            .await;
+                        let mut response = match self.kv_transfer.dispatch_mode() {
+                            DispatchMode::Concurrent => {
+                                self.execute_dual_dispatch_internal(
+                                    headers,
+                                    json_request,
+                                    context,
+                                    &prefill,
+                                    decode.as_ref(),
+                                    start_time,
+                                )
+                                .await
+                            }
+                            DispatchMode::Sequential => {
+                                self.execute_sequential_dispatch_internal(
+                                    headers,
+                                    json_request,
+                                    &context,
+                                    &prefill,
+                                    &decode,
+                                    &chunks,
+                                    start_time,
+                                )
+                                .await
+                            }
+                        };
 
-                        // Record outcomes for circuit breakers
-                        let _status = response.status();
-                        let not_error = _status.is_success() || _status.is_client_error();
-                        prefill.record_outcome(not_error);
-                        decode.record_outcome(not_error);
+                        // Prefill stages record their own outcomes; the final
+                        // status is the decode worker's unless prefill failed,
+                        // in which case the decode worker was never reached
+                        let status = response.status();
+                        match pd_failover::prefill_failure(&response) {
+                            Some(failed_url) => {
+                                failed_prefills
+                                    .lock()
+                                    .unwrap()
+                                    .insert(failed_url.to_string());
+                            }
+                            None => {
+                                decode.record_outcome(
+                                    status.is_success() || status.is_client_error(),
+                                );
+                            }
+                        }
+
+                        pd_failover::mark_served_by(&mut response, prefill.url(), decode.url());
+                        if failover.enabled {
+                            if pd_failover::is_decode_failure(&response) {
+                                RouterMetrics::record_pd_decode_failover(decode.url());
+                                failed_decodes
+                                    .lock()
+                                    .unwrap()
+                                    .insert(decode.url().to_string());
+                            }
+                            pd_failover::annotate_response(
+                                &mut response,
+                                attempt + 1,
+                                prefill.url(),
+                                decode.url(),
+                            );
+                        }
 
+                        for lease in lora_leases {
+                            response = lease.hold_until_body_end(response);
+                        }
                         response
                     }
                 }
             },
             // Should retry predicate
-            |res, _attempt| is_retryable_status(res.status()),
+            |res, attempt| {
+                if !failover.enabled {
+                    return is_retryable_status(res.status());
+                }
+                if pd_failover::is_decode_failure(res) {
+                    attempt + 1 < failover.max_attempts
+                } else {
+                    is_retryable_status(res.status()) && attempt + 1 < max_retries
+                }
+            },
             // On backoff hook
             |delay, attempt| {
                 RouterMetrics::record_retry(route);
@@ -917,7 +1219,94 @@ impl PDRouter {
             // On exhausted hook
             || RouterMetrics::record_retries_exhausted(route),
         )
-        .await
+        .await;
+
+        if !failover.enabled || failed_decodes.lock().unwrap().is_empty() {
+            return response;
+        }
+
+        let attempts = attempts.load(Ordering::Relaxed);
+        if !pd_failover::is_decode_failure(&response) {
+            RouterMetrics::record_pd_failover_result(route, "recovered", attempts);
+            return response;
+        }
+        if self.fallback_pool.is_empty() {
+            RouterMetrics::record_pd_failover_result(route, "failed", attempts);
+            return response;
+        }
+
+        // Every pair failed at decode: serve the request from the regular pool
+        warn!(
+            "Decode failed on {} PD pair(s) for {}, falling back to regular workers",
+            attempts, route
+        );
+        let mut body = match serde_json::to_value(original_request) {
+            Ok(v) => FallbackPool::prepare_request(v),
+            Err(e) => return Self::handle_serialization_error(e),
+        };
+        if let Some(adapter) = lora_adapter.as_deref() {
+            LoraManager::target_adapter(&mut body, adapter);
+        }
+        let mut response = self
+            .fallback_pool
+            .dispatch(|url| {
+                self.build_post_with_headers(&self.client, url, route, &body, headers, false)
+            })
+            .await;
+        response
+            .headers_mut()
+            .insert(pd_failover::ATTEMPTS_HEADER, HeaderValue::from(attempts));
+        RouterMetrics::record_pd_failover_result(route, "fallback", attempts);
+        response
+    }
+
+    // Retry budget of a dispatch; decode failover may try more decode workers
+    // than the generic retry budget
+    pub(crate) fn dispatch_retry_config(&self) -> RetryConfig {
+        let mut retry_config = self.retry_config.clone();
+        if self.decode_failover.enabled {
+            retry_config.max_retries = retry_config
+                .max_retries
+                .max(1)
+                .max(self.decode_failover.max_attempts);
+        }
+        retry_config
+    }
+
+    // Mark server-side decode failures as recoverable when decode failover is enabled
+    fn mark_recoverable(&self, response: Response) -> Response {
+        if self.decode_failover.enabled && response.status().is_server_error() {
+            pd_failover::mark_decode_failure(response)
+        } else {
+            response
+        }
+    }
+
+    // Decode body stream; with failover enabled, wait for the first chunk so a
+    // decode worker that dies before its first token can still be replaced.
+    // In concurrent dispatch the decode worker streams once the KV cache has
+    // arrived, so the delay to the first chunk doubles as the prefill time for
+    // the capacity controller; sequential dispatch times the prefill stage.
+    async fn decode_stream(
+        &self,
+        res: reqwest::Response,
+        timer: &PdTimer,
+    ) -> Result<pd_failover::ByteStream, String> {
+        let first_chunk_is_prefill = self.kv_transfer.dispatch_mode() == DispatchMode::Concurrent;
+        let stream: pd_failover::ByteStream = match &self.pd_capacity {
+            Some(controller) => controller.observe_stream(
+                res.bytes_stream(),
+                timer.started(),
+                first_chunk_is_prefill,
+            ),
+            None => Box::pin(res.bytes_stream()),
+        };
+        let stream = timer.observe_stream(stream);
+        if self.decode_failover.enabled {
+            pd_failover::wait_for_first_chunk(stream).await
+        } else {
+            Ok(stream)
+        }
     }
 
     async fn handle_decode_error_response(
@@ -977,10 +1366,11 @@ impl PDRouter {
         headers: Option<&HeaderMap>,
         json_request: Value,
         context: PDRequestContext<'_>,
-        prefill: &dyn Worker,
+        prefill_worker: &Arc<dyn Worker>,
         decode: &dyn Worker,
         start_time: Instant,
     ) -> Response {
+        let prefill = prefill_worker.as_ref();
         // For non-streaming: use guard for automatic load management
         // For streaming: load will be managed in create_streaming_response
         let _guard = if !context.is_stream {
@@ -989,6 +1379,8 @@ impl PDRouter {
             None
         };
 
+        let timer = PdTimer::start(prefill.url(), decode.url());
+
         // Build decode request with shared client
         let decode_request = self.build_post_with_headers(
             &self.client,
@@ -1017,8 +1409,12 @@ impl PDRouter {
                 false,
             );
             // When we need logprobs, wait for both responses
-            let (prefill_result, decode_result) =
-                tokio::join!(prefill_request.send(), decode_request.send());
+            let prefill_send = async {
+                let result = prefill_request.send().await;
+                timer.prefill_done();
+                result
+            };
+            let (prefill_result, decode_result) = tokio::join!(prefill_send, decode_request.send());
             debug!("Received responses from both servers");
 
             // Update metrics
@@ -1044,22 +1440,26 @@ impl PDRouter {
                             status
                         );
 
-                        return self
+                        let response = self
                             .handle_decode_error_response(res, &context, prefill, decode)
                             .await;
+                        return self.mark_recoverable(response);
                     }
 
                     // Process prefill response for logprobs
-                    let prefill_body = match self
+                    let prefill_stage = self
                         .process_prefill_response(
                             prefill_result,
                             prefill.url(),
                             context.return_logprob,
                         )
-                        .await
-                    {
+                        .await;
+                    record_prefill_outcome(prefill, &prefill_stage);
+                    let prefill_body = match prefill_stage {
                         Ok((_, body)) => body,
-                        Err(error_response) => return error_response,
+                        Err(error_response) => {
+                            return pd_failover::mark_prefill_failure(error_response, prefill.url())
+                        }
                     };
 
                     if context.is_stream {
@@ -1073,9 +1473,19 @@ impl PDRouter {
 
                         let response_headers =
                             header_utils::preserve_response_headers(res.headers());
+                        let stream = match self.decode_stream(res, &timer).await {
+                            Ok(stream) => stream,
+                            Err(e) => {
+                                error!(decode_url = %decode.url(), error = %e, "Decode stream failed");
+                                RouterMetrics::record_pd_decode_error(decode.url());
+                                return self.mark_recoverable(
+                                    (StatusCode::BAD_GATEWAY, e).into_response(),
+                                );
+                            }
+                        };
 
                         self.create_streaming_response(
-                            res.bytes_stream(),
+                            stream,
                             status,
                             prefill_logprobs,
                             context.return_logprob,
@@ -1086,13 +1496,16 @@ impl PDRouter {
                         )
                     } else {
                         // Non-streaming response with logprobs
-                        self.process_non_streaming_response(
-                            res,
-                            status,
-                            context.return_logprob,
-                            prefill_body,
-                        )
-                        .await
+                        let mut response = self
+                            .process_non_streaming_response(
+                                res,
+                                status,
+                                context.return_logprob,
+                                prefill_body,
+                            )
+                            .await;
+                        timer.annotate(&mut response);
+                        response
                     }
                 }
                 Err(e) => {
@@ -1102,11 +1515,13 @@ impl PDRouter {
                         "Decode request failed"
                     );
                     RouterMetrics::record_pd_decode_error(decode.url());
-                    (
-                        StatusCode::BAD_GATEWAY,
-                        format!("Decode server error: {}", e),
+                    self.mark_recoverable(
+                        (
+                            StatusCode::BAD_GATEWAY,
+                            format!("Decode server error: {}", e),
+                        )
+                            .into_response(),
                     )
-                        .into_response()
                 }
             }
         } else {
@@ -1129,8 +1544,16 @@ impl PDRouter {
             // This ensures HTTP compliance without blocking
             let drain_tx = self.prefill_drain_tx.clone();
             let prefill_url = prefill.url().to_string();
+            let prefill_timer = timer.clone();
+            let prefill_worker = Arc::clone(prefill_worker);
             tokio::spawn(async move {
-                if let Ok(response) = prefill_future.await {
+                let prefill_result = prefill_future.await;
+                let status = prefill_result.as_ref().map(|response| response.status());
+                prefill_worker.record_outcome(
+                    status.is_ok_and(|status| status.is_success() || status.is_client_error()),
+                );
+                if let Ok(response) = prefill_result {
+                    prefill_timer.prefill_done();
                     // Try to send to drain worker
                     // If channel is full (under extreme load), drain inline as fallback
                     match drain_tx.try_send(response) {
@@ -1190,60 +1613,455 @@ impl PDRouter {
                             status
                         );
 
-                        self.handle_decode_error_response(res, &context, prefill, decode)
-                            .await
-                    } else if context.is_stream {
-                        // Streaming response without logprobs - direct passthrough
-                        let decode_url = decode.url().to_string();
-                        let response_headers =
-                            header_utils::preserve_response_headers(res.headers());
+                        let response = self
+                            .handle_decode_error_response(res, &context, prefill, decode)
+                            .await;
+                        self.mark_recoverable(response)
+                    } else if context.is_stream {
+                        // Streaming response without logprobs - direct passthrough
+                        let decode_url = decode.url().to_string();
+                        let response_headers =
+                            header_utils::preserve_response_headers(res.headers());
+                        let stream = match self.decode_stream(res, &timer).await {
+                            Ok(stream) => stream,
+                            Err(e) => {
+                                error!(decode_url = %decode.url(), error = %e, "Decode stream failed");
+                                RouterMetrics::record_pd_decode_error(decode.url());
+                                return self.mark_recoverable(
+                                    (StatusCode::BAD_GATEWAY, e).into_response(),
+                                );
+                            }
+                        };
+
+                        self.create_streaming_response(
+                            stream,
+                            status,
+                            None,
+                            false,
+                            Some(decode_url),
+                            Some(response_headers),
+                            prefill,
+                            decode,
+                        )
+                    } else {
+                        // Non-streaming response without logprobs - direct passthrough like fast version
+                        let response_headers =
+                            header_utils::preserve_response_headers(res.headers());
+
+                        match res.bytes().await {
+                            Ok(decode_body) => {
+                                let mut response =
+                                    Response::new(axum::body::Body::from(decode_body));
+                                *response.status_mut() = status;
+                                *response.headers_mut() = response_headers;
+                                timer.annotate(&mut response);
+                                response
+                            }
+                            Err(e) => {
+                                error!("Failed to read decode response: {}", e);
+                                self.mark_recoverable(
+                                    (StatusCode::INTERNAL_SERVER_ERROR, "Failed to read response")
+                                        .into_response(),
+                                )
+                            }
+                        }
+                    }
+                }
+                Err(e) => {
+                    error!(
+                        decode_url = %decode.url(),
+                        error = %e,
+                        "Decode request failed"
+                    );
+                    RouterMetrics::record_pd_decode_error(decode.url());
+                    self.mark_recoverable(
+                        (
+                            StatusCode::BAD_GATEWAY,
+                            format!("Decode server error: {}", e),
+                        )
+                            .into_response(),
+                    )
+                }
+            }
+        }
+    }
+
+    // Sequential dispatch: prefill runs first and decode gets the connector
+    // fields from its response. Prefill load is held for the prefill stage
+    // only, decode load until the decode body has been sent.
+    #[allow(clippy::too_many_arguments)]
+    async fn execute_sequential_dispatch_internal(
+        &self,
+        headers: Option<&HeaderMap>,
+        json_request: Value,
+        context: &PDRequestContext<'_>,
+        prefill: &Arc<dyn Worker>,
+        decode: &Arc<dyn Worker>,
+        chunks: &[PrefillChunk],
+        start_time: Instant,
+    ) -> Response {
+        let pair = TransferPair {
+            prefill: prefill.as_ref(),
+            decode: decode.as_ref(),
+            batch_size: context.batch_size,
+        };
+        let request_id = self.kv_transfer.request_id(&pair);
+        debug!(
+            "Generated request ID {} for KV transfer protocol {}",
+            request_id,
+            self.kv_transfer.name()
+        );
+
+        // Stage 1: prefill with max_tokens=1 and the connector's fields
+        let mut prefill_request =
+            Self::prepare_prefill_request(json_request.clone(), context.route);
+        self.kv_transfer
+            .prepare_prefill(&mut prefill_request, &pair);
+
+        let timer = PdTimer::start(prefill.url(), decode.url());
+        let prefill_result = if chunks.len() > 1 {
+            self.run_context_parallel_prefill(
+                &prefill_request,
+                chunks,
+                decode.as_ref(),
+                &request_id,
+                context.route,
+                headers,
+            )
+            .await
+        } else {
+            self.run_prefill_stage(
+                &prefill_request,
+                prefill.as_ref(),
+                &request_id,
+                context.route,
+                headers,
+            )
+            .await
+        };
+        let prefill_response = match prefill_result {
+            Ok(response) => response,
+            Err(response) => {
+                RouterMetrics::record_pd_request(context.route);
+                RouterMetrics::record_pd_request_duration(context.route, start_time.elapsed());
+                return response;
+            }
+        };
+        timer.prefill_done();
+        if let Some(controller) = &self.pd_capacity {
+            controller.record_prefill_time(timer.started().elapsed());
+        }
+
+        // Stage 2: decode with the connector's fields from the prefill response
+        let mut decode_request = json_request;
+        self.kv_transfer
+            .prepare_decode(&mut decode_request, Some(&prefill_response));
+        debug!(
+            "Sending decode request to {} with request_id: {}",
+            decode.url(),
+            request_id
+        );
+
+        let decode_load = WorkerLoadLease::new(decode.clone());
+        self.begin_stage_profiling(decode.url()).await;
+        timer.decode_started();
+        let decode_result = self
+            .build_stage_request(
+                decode.url(),
+                context.route,
+                &decode_request,
+                headers,
+                &request_id,
+            )
+            .send()
+            .await;
+        self.end_stage_profiling(decode.url()).await;
+
+        RouterMetrics::record_pd_request_duration(context.route, start_time.elapsed());
+        RouterMetrics::record_pd_request(context.route);
+        RouterMetrics::record_pd_prefill_request(prefill.url());
+        RouterMetrics::record_pd_decode_request(decode.url());
+
+        let res = match decode_result {
+            Ok(res) => res,
+            Err(e) => {
+                let message = error_chain(&e);
+                error!(decode_url = %decode.url(), error = %message, "Decode request failed");
+                RouterMetrics::record_pd_decode_error(decode.url());
+                return self.mark_recoverable(
+                    (
+                        StatusCode::BAD_GATEWAY,
+                        format!("Decode server error: {}", message),
+                    )
+                        .into_response(),
+                );
+            }
+        };
+        let status = StatusCode::from_u16(res.status().as_u16())
+            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        if !status.is_success() {
+            RouterMetrics::record_pd_decode_error(decode.url());
+            error!(
+                "Decode server returned error status decode_url={} status={}",
+                decode.url(),
+                status
+            );
+            let response = self
+                .handle_decode_error_response(res, context, prefill.as_ref(), decode.as_ref())
+                .await;
+            return self.mark_recoverable(response);
+        }
+
+        let mut response_headers = header_utils::preserve_response_headers(res.headers());
+        if context.is_stream {
+            let stream = match self.decode_stream(res, &timer).await {
+                Ok(stream) => stream,
+                Err(e) => {
+                    error!(decode_url = %decode.url(), error = %e, "Decode stream failed");
+                    RouterMetrics::record_pd_decode_error(decode.url());
+                    return self.mark_recoverable((StatusCode::BAD_GATEWAY, e).into_response());
+                }
+            };
+            let mut response = Response::new(Body::from_stream(stream));
+            *response.status_mut() = status;
+            *response.headers_mut() = response_headers;
+            return decode_load.hold_until_body_end(response);
+        }
+
+        let mut decode_body = match res.bytes().await {
+            Ok(body) => body,
+            Err(e) => {
+                error!("Failed to read decode response: {}", e);
+                return self.mark_recoverable(
+                    (StatusCode::INTERNAL_SERVER_ERROR, "Failed to read response").into_response(),
+                );
+            }
+        };
+        // Prompt logprobs come from the prefill stage
+        if context.return_logprob {
+            match serde_json::from_slice::<Value>(&decode_body) {
+                Ok(mut decode_json) => {
+                    Self::merge_logprobs_in_json(&prefill_response, &mut decode_json);
+                    if let Ok(merged) = serde_json::to_vec(&decode_json) {
+                        decode_body = merged.into();
+                        response_headers.remove(CONTENT_LENGTH);
+                    }
+                }
+                Err(e) => warn!("Failed to parse decode response for logprob merging: {}", e),
+            }
+        }
+        let mut response = Response::new(Body::from(decode_body));
+        *response.status_mut() = status;
+        *response.headers_mut() = response_headers;
+        timer.annotate(&mut response);
+        response
+    }
+
+    // Send a prefill-stage request and return its parsed response, holding
+    // the worker's load for the stage
+    async fn run_prefill_stage(
+        &self,
+        prefill_request: &Value,
+        prefill: &dyn Worker,
+        request_id: &str,
+        route: &str,
+        headers: Option<&HeaderMap>,
+    ) -> Result<Value, Response> {
+        let _load = WorkerLoadGuard::new(prefill);
+        debug!(
+            "Sending prefill request to {} with request_id: {}",
+            prefill.url(),
+            request_id
+        );
+
+        self.begin_stage_profiling(prefill.url()).await;
+        let result = self
+            .build_stage_request(prefill.url(), route, prefill_request, headers, request_id)
+            .send()
+            .await;
+        let result = self
+            .process_prefill_response(result, prefill.url(), true)
+            .await;
+        self.end_stage_profiling(prefill.url()).await;
+
+        let stage = match result {
+            Ok((_, body)) => serde_json::from_slice(&body.unwrap_or_default()).map_err(|e| {
+                RouterMetrics::record_pd_prefill_error(prefill.url());
+                (
+                    StatusCode::BAD_GATEWAY,
+                    format!(
+                        "Failed to parse prefill response from {} as JSON: {}",
+                        prefill.url(),
+                        e
+                    ),
+                )
+                    .into_response()
+            }),
+            Err(response) => Err(response),
+        };
+        record_prefill_outcome(prefill, &stage);
+        stage.map_err(|response| pd_failover::mark_prefill_failure(response, prefill.url()))
+    }
+
+    // Prefill each chunk of a context-parallel plan concurrently. Every worker
+    // receives the whole prompt plus its token range under
+    // `kv_transfer_params.context_parallel`; decode gets the first chunk's
+    // response with the parameters of all chunks merged in.
+    async fn run_context_parallel_prefill(
+        &self,
+        prefill_request: &Value,
+        chunks: &[PrefillChunk],
+        decode: &dyn Worker,
+        request_id: &str,
+        route: &str,
+        headers: Option<&HeaderMap>,
+    ) -> Result<Value, Response> {
+        let stages = chunks.iter().enumerate().map(|(rank, chunk)| {
+            let mut request = prefill_request.clone();
+            request["kv_transfer_params"]["context_parallel"] =
+                pd_chunked_prefill::chunk_params(rank, chunks);
+            let request_id = if rank == 0 {
+                request_id.to_string()
+            } else {
+                self.kv_transfer.request_id(&TransferPair {
+                    prefill: chunk.worker.as_ref(),
+                    decode,
+                    batch_size: None,
+                })
+            };
+            async move {
+                self.run_prefill_stage(&request, chunk.worker.as_ref(), &request_id, route, headers)
+                    .await
+            }
+        });
+        let results = futures_util::future::try_join_all(stages).await?;
+
+        let params: Vec<Option<Value>> = results
+            .iter()
+            .map(|response| response.get("kv_transfer_params").cloned())
+            .collect();
+        let mut response = results.into_iter().next().ok_or_else(|| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Context-parallel prefill has no chunks",
+            )
+                .into_response()
+        })?;
+        response["kv_transfer_params"] = pd_chunked_prefill::merge_chunk_params(&params, chunks);
+        debug!(
+            "Context-parallel prefill of {} chunks completed for {}",
+            chunks.len(),
+            request_id
+        );
+        Ok(response)
+    }
+
+    // Drop excluded workers unless that would leave none
+    pub(crate) fn exclude_failed(
+        workers: Vec<Arc<dyn Worker>>,
+        excluded: &HashSet<String>,
+    ) -> Vec<Arc<dyn Worker>> {
+        if excluded.is_empty() {
+            return workers;
+        }
+        let remaining: Vec<Arc<dyn Worker>> = workers
+            .iter()
+            .filter(|w| !excluded.contains(w.url()))
+            .cloned()
+            .collect();
+        if remaining.is_empty() {
+            workers
+        } else {
+            remaining
+        }
+    }
 
-                        self.create_streaming_response(
-                            res.bytes_stream(),
-                            status,
-                            None,
-                            false,
-                            Some(decode_url),
-                            Some(response_headers),
-                            prefill,
-                            decode,
-                        )
-                    } else {
-                        // Non-streaming response without logprobs - direct passthrough like fast version
-                        let response_headers =
-                            header_utils::preserve_response_headers(res.headers());
+    // Placement of a long prompt by prefill KV capacity, if enabled
+    fn plan_prefill(
+        &self,
+        request: &Value,
+        requirements: &RequestRequirements,
+    ) -> Option<PrefillPlan> {
+        let chunked = self.chunked_prefill.as_ref()?;
+        let prefill_workers: Vec<Arc<dyn Worker>> = self
+            .worker_registry
+            .get_prefill_workers()
+            .into_iter()
+            .filter(|w| w.can_handle(requirements))
+            .collect();
+        chunked.plan(request, &prefill_workers)
+    }
 
-                        match res.bytes().await {
-                            Ok(decode_body) => {
-                                let mut response =
-                                    Response::new(axum::body::Body::from(decode_body));
-                                *response.status_mut() = status;
-                                *response.headers_mut() = response_headers;
-                                response
-                            }
-                            Err(e) => {
-                                error!("Failed to read decode response: {}", e);
-                                (StatusCode::INTERNAL_SERVER_ERROR, "Failed to read response")
-                                    .into_response()
-                            }
-                        }
+    /// Modify request for prefill stage (set max_tokens=1)
+    /// - For inference/v1/generate: patch sampling_params.max_tokens and sampling_params.min_tokens
+    /// - For other endpoints (fallback): patch top-level max_tokens, max_completion_tokens, min_tokens
+    ///
+    /// stream=false and stream_options removal are always applied at top level.
+    fn prepare_prefill_request(mut request: Value, path: &str) -> Value {
+        if path.contains("inference/v1/generate") {
+            // Generate API: max_tokens and min_tokens are in sampling_params
+            if let Some(sampling_params) = request.get_mut("sampling_params") {
+                sampling_params["max_tokens"] = json!(1);
+                // Also adjust min_tokens to ensure min_tokens <= max_tokens
+                // This is required because vLLM validates that min_tokens <= max_tokens
+                if let Some(min_tokens) = sampling_params.get("min_tokens").and_then(|v| v.as_u64())
+                {
+                    if min_tokens > 1 {
+                        sampling_params["min_tokens"] = json!(1);
                     }
                 }
-                Err(e) => {
-                    error!(
-                        decode_url = %decode.url(),
-                        error = %e,
-                        "Decode request failed"
-                    );
-                    RouterMetrics::record_pd_decode_error(decode.url());
-                    (
-                        StatusCode::BAD_GATEWAY,
-                        format!("Decode server error: {}", e),
-                    )
-                        .into_response()
+            } else {
+                // Create sampling_params with prefill defaults when missing
+                request["sampling_params"] = json!({"max_tokens": 1, "min_tokens": 1});
+            }
+        } else {
+            // Fallback: OpenAI-style endpoints (chat/completions)
+            request["max_tokens"] = json!(1);
+            if request.get("max_completion_tokens").is_some() {
+                request["max_completion_tokens"] = json!(1);
+            }
+            // Also adjust min_tokens to ensure min_tokens <= max_tokens
+            // This is required because vLLM validates that min_tokens <= max_tokens
+            if let Some(min_tokens) = request.get("min_tokens").and_then(|v| v.as_u64()) {
+                if min_tokens > 1 {
+                    request["min_tokens"] = json!(1);
                 }
             }
         }
+        // Force non-streaming for prefill to get JSON response with kv_transfer_params
+        request["stream"] = json!(false);
+        // Remove stream_options since we're setting stream=false
+        if let Some(obj) = request.as_object_mut() {
+            obj.remove("stream_options");
+        }
+        request
+    }
+
+    // Stage request for sequential dispatch; both stages carry the
+    // connector's request id
+    pub(crate) fn build_stage_request(
+        &self,
+        url: &str,
+        route: &str,
+        json_request: &Value,
+        headers: Option<&HeaderMap>,
+        request_id: &str,
+    ) -> reqwest::RequestBuilder {
+        let (base_url, dp_rank) = super::dp_utils::parse_worker_url(url);
+        let request = self
+            .client
+            .post(api_path(&base_url, route))
+            .header(
+                "Authorization",
+                format!(
+                    "Bearer {}",
+                    std::env::var("OPENAI_API_KEY").unwrap_or_default()
+                ),
+            )
+            .header("X-Request-Id", request_id)
+            .json(json_request);
+        let request = super::dp_utils::add_dp_rank_header(request, dp_rank);
+        header_utils::propagate_trace_headers(request, headers)
     }
 
     // Check if either prefill or decode policy needs request text
@@ -1259,9 +2077,32 @@ impl PDRouter {
         &self,
         request_text: Option<&str>,
         model_id: Option<&str>,
+        requirements: &RequestRequirements,
+    ) -> Result<(Arc<dyn Worker>, Arc<dyn Worker>), String> {
+        self.select_pd_pair_excluding(
+            request_text,
+            None,
+            model_id,
+            requirements,
+            &HashSet::new(),
+            &HashSet::new(),
+        )
+        .await
+    }
+
+    // Select a pair, avoiding workers that already failed this request as long
+    // as other workers of the same type remain
+    async fn select_pd_pair_excluding(
+        &self,
+        request_text: Option<&str>,
+        request_headers: Option<&RequestHeaders>,
+        model_id: Option<&str>,
+        requirements: &RequestRequirements,
+        excluded_prefills: &HashSet<String>,
+        excluded_decodes: &HashSet<String>,
     ) -> Result<(Arc<dyn Worker>, Arc<dyn Worker>), String> {
         // Get workers from registry - filter by model if provided
-        let prefill_workers = if let Some(model) = model_id {
+        let prefill_workers: Vec<Arc<dyn Worker>> = if let Some(model) = model_id {
             // Get model-specific workers and filter for prefill type
             self.worker_registry
                 .get_by_model_fast(model)
@@ -1272,7 +2113,7 @@ impl PDRouter {
             self.worker_registry.get_prefill_workers()
         };
 
-        let decode_workers = if let Some(model) = model_id {
+        let decode_workers: Vec<Arc<dyn Worker>> = if let Some(model) = model_id {
             // Get model-specific workers and filter for decode type
             self.worker_registry
                 .get_by_model_fast(model)
@@ -1283,6 +2124,54 @@ impl PDRouter {
             self.worker_registry.get_decode_workers()
         };
 
+        // Drop workers that cannot serve this request (context length, endpoint)
+        let prefill_workers: Vec<Arc<dyn Worker>> = prefill_workers
+            .into_iter()
+            .filter(|w| w.can_handle(requirements))
+            .collect();
+        let decode_workers: Vec<Arc<dyn Worker>> = decode_workers
+            .into_iter()
+            .filter(|w| w.can_handle(requirements))
+            .collect();
+        let prefill_workers = Self::exclude_failed(prefill_workers, excluded_prefills);
+        let decode_workers = Self::exclude_failed(decode_workers, excluded_decodes);
+
+        // Prefer workers that already have the requested LoRA adapter loaded
+        let (prefill_workers, decode_workers) = match &requirements.lora_adapter {
+            Some(adapter) => (
+                LoraManager::prefer_loaded(&prefill_workers, adapter),
+                LoraManager::prefer_loaded(&decode_workers, adapter),
+            ),
+            None => (prefill_workers, decode_workers),
+        };
+
+        // Topology-aware pairing scores prefill and decode workers together
+        if let Some(pair_policy) = &self.pair_policy {
+            let prefill_available: Vec<Arc<dyn Worker>> = prefill_workers
+                .into_iter()
+                .filter(|w| w.is_available())
+                .collect();
+            let decode_available: Vec<Arc<dyn Worker>> = decode_workers
+                .into_iter()
+                .filter(|w| w.is_available())
+                .collect();
+            return pair_policy
+                .select_worker_pair_with_headers(
+                    &prefill_available,
+                    &decode_available,
+                    request_text,
+                    request_headers,
+                )
+                .map(|(p, d)| (prefill_available[p].clone(), decode_available[d].clone()))
+                .ok_or_else(|| {
+                    format!(
+                        "No available prefill/decode pair ({} prefill, {} decode available)",
+                        prefill_available.len(),
+                        decode_available.len()
+                    )
+                });
+        }
+
         // Select workers using helper function
         // Use separate policies for prefill and decode to avoid counter conflicts
         let prefill_policy = self.policy_registry.get_prefill_policy();
@@ -1292,6 +2181,7 @@ impl PDRouter {
             &prefill_workers,
             &*prefill_policy,
             request_text,
+            request_headers,
             "prefill",
         )?;
 
@@ -1299,6 +2189,7 @@ impl PDRouter {
             &decode_workers,
             &*decode_policy,
             request_text,
+            request_headers,
             "decode",
         )?;
 
@@ -1310,6 +2201,7 @@ impl PDRouter {
         workers: &[Arc<dyn Worker>],
         policy: &dyn LoadBalancingPolicy,
         request_text: Option<&str>,
+        request_headers: Option<&RequestHeaders>,
         worker_type: &str,
     ) -> Result<Arc<dyn Worker>, String> {
         // Check if we have any workers
@@ -1336,7 +2228,7 @@ impl PDRouter {
 
         // Let policy select from available workers (no conversion needed now!)
         let selected_idx = policy
-            .select_worker(&available_workers, request_text)
+            .select_worker_with_headers(&available_workers, request_text, request_headers)
             .ok_or_else(|| {
                 format!(
                     "Policy {} failed to select a {} worker",
@@ -1771,6 +2663,14 @@ async fn get_worker_load(client: &Client, worker_url: &str) -> Option<isize> {
     }
 }
 
+// Record a prefill stage result on the worker's circuit breaker
+fn record_prefill_outcome<T>(prefill: &dyn Worker, stage: &Result<T, Response>) {
+    prefill.record_outcome(match stage {
+        Ok(_) => true,
+        Err(response) => response.status().is_client_error(),
+    });
+}
+
 #[async_trait]
 impl WorkerManagement for PDRouter {
     async fn add_worker(&self, _worker_url: &str) -> Result<String, String> {
@@ -1844,7 +2744,10 @@ impl RouterTrait for PDRouter {
         // Note: This endpoint actually causes the model to generate tokens, so we only test one pair
 
         // Select a random worker pair using the policy
-        let (prefill, decode) = match self.select_pd_pair(None, None).await {
+        let (prefill, decode) = match self
+            .select_pd_pair(None, None, &RequestRequirements::default())
+            .await
+        {
             Ok(pair) => pair,
             Err(e) => {
                 return (
@@ -1978,6 +2881,11 @@ impl RouterTrait for PDRouter {
         // Calculate batch size
         let batch_size = Self::get_generate_batch_size(body);
 
+        let requirements = match self.request_requirements("/generate", body) {
+            Ok(requirements) => requirements,
+            Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+        };
+
         // Create context
         let context = PDRequestContext {
             route: "/generate",
@@ -1985,10 +2893,12 @@ impl RouterTrait for PDRouter {
             is_stream,
             return_logprob,
             request_text,
+            request_headers: Self::request_headers(headers),
             model_id,
+            requirements,
         };
 
-        // Execute with retry and bootstrap injection
+        // Execute with retries and the configured KV transfer protocol
         self.execute_dual_dispatch(headers, body, context).await
     }
 
@@ -2019,6 +2929,11 @@ impl RouterTrait for PDRouter {
         // Calculate batch size
         let batch_size = Self::get_chat_batch_size(body);
 
+        let requirements = match self.request_requirements("/v1/chat/completions", body) {
+            Ok(requirements) => requirements,
+            Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+        };
+
         // Create context
         let context = PDRequestContext {
             route: "/v1/chat/completions",
@@ -2026,10 +2941,12 @@ impl RouterTrait for PDRouter {
             is_stream,
             return_logprob,
             request_text,
+            request_headers: Self::request_headers(headers),
             model_id,
+            requirements,
         };
 
-        // Execute with retry and bootstrap injection
+        // Execute with retries and the configured KV transfer protocol
         self.execute_dual_dispatch(headers, body, context).await
     }
 
@@ -2053,6 +2970,11 @@ impl RouterTrait for PDRouter {
         // Calculate batch size
         let batch_size = Self::get_completion_batch_size(body);
 
+        let requirements = match self.request_requirements("/v1/completions", body) {
+            Ok(requirements) => requirements,
+            Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+        };
+
         // Create context
         let context = PDRequestContext {
             route: "/v1/completions",
@@ -2060,10 +2982,12 @@ impl RouterTrait for PDRouter {
             is_stream,
             return_logprob,
             request_text,
+            request_headers: Self::request_headers(headers),
             model_id,
+            requirements,
         };
 
-        // Execute with retry and bootstrap injection
+        // Execute with retries and the configured KV transfer protocol
         self.execute_dual_dispatch(headers, body, context).await
     }
 
@@ -2122,6 +3046,11 @@ impl RouterTrait for PDRouter {
             None
         };
 
+        let requirements = match self.request_requirements("/v1/rerank", body) {
+            Ok(requirements) => requirements,
+            Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+        };
+
         // Create context
         let context = PDRequestContext {
             route: "/v1/rerank",
@@ -2129,10 +3058,12 @@ impl RouterTrait for PDRouter {
             is_stream: false,
             return_logprob: false,
             request_text: req_text,
+            request_headers: Self::request_headers(headers),
             model_id,
+            requirements,
         };
 
-        // Execute with retry and bootstrap injection
+        // Execute with retries and the configured KV transfer protocol
         self.execute_dual_dispatch(headers, body, context).await
     }
 
@@ -2390,8 +3321,9 @@ impl RouterTrait for PDRouter {
 
 #[cfg(test)]
 mod tests {
+    use super::super::pd_transfer::{BootstrapProtocol, NixlProtocol};
     use super::*;
-    use crate::core::{BasicWorker, WorkerType};
+    use crate::core::{BasicWorker, PrefilledBy, ServedBy, WorkerType};
 
     fn create_test_pd_router() -> PDRouter {
         let worker_registry = Arc::new(WorkerRegistry::new());
@@ -2410,6 +3342,18 @@ mod tests {
             prefill_drain_tx: mpsc::channel(100).0,
             retry_config: RetryConfig::default(),
             circuit_breaker_config: CircuitBreakerConfig::default(),
+            api_key: None,
+            lora_manager: None,
+            worker_topology: HashMap::new(),
+            pair_policy: None,
+            decode_failover: DecodeFailoverConfig::default(),
+            fallback_pool: Arc::new(FallbackPool::default()),
+            pd_capacity: None,
+            kv_transfer: Arc::new(BootstrapProtocol),
+            chunked_prefill: None,
+            enable_profiling: false,
+            profile_timeout_secs: 0,
+            profiling_tasks: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
         }
     }
 
@@ -2572,7 +3516,9 @@ mod tests {
         router.worker_registry.register(Arc::from(healthy_worker));
         router.worker_registry.register(Arc::from(decode_worker));
 
-        let result = router.select_pd_pair(None, None).await;
+        let result = router
+            .select_pd_pair(None, None, &RequestRequirements::default())
+            .await;
 
         assert!(result.is_ok());
         let (prefill, _decode) = result.unwrap();
@@ -2586,7 +3532,9 @@ mod tests {
     async fn test_empty_worker_lists() {
         let router = create_test_pd_router();
 
-        let result = router.select_pd_pair(None, None).await;
+        let result = router
+            .select_pd_pair(None, None, &RequestRequirements::default())
+            .await;
 
         assert!(result.is_err());
         assert!(result.unwrap_err().contains("No prefill workers available"));
@@ -2795,4 +3743,400 @@ mod tests {
         let workers = router.worker_registry.get_prefill_workers();
         assert_eq!(workers.len(), 5);
     }
+
+    // ============= Decode Failover Tests =============
+
+    async fn spawn_completion_worker(status: StatusCode) -> String {
+        let app = axum::Router::new().route(
+            "/v1/completions",
+            axum::routing::post(move || async move { (status, Json(json!({"choices": []}))) }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}", addr)
+    }
+
+    fn create_failover_pd_router(fallback_urls: Vec<String>) -> PDRouter {
+        let mut router = create_test_pd_router();
+        router.retry_config.initial_backoff_ms = 1;
+        router.retry_config.max_backoff_ms = 1;
+        router.decode_failover = DecodeFailoverConfig {
+            enabled: true,
+            max_attempts: 2,
+            fallback_worker_urls: fallback_urls.clone(),
+        };
+        router.fallback_pool = Arc::new(FallbackPool::new(fallback_urls));
+        router
+    }
+
+    fn completion_request() -> CompletionRequest {
+        serde_json::from_value(json!({"model": "test", "prompt": "hello"})).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_decode_failover_to_another_pair() {
+        let prefill_url = spawn_completion_worker(StatusCode::OK).await;
+        let bad_decode = spawn_completion_worker(StatusCode::INTERNAL_SERVER_ERROR).await;
+        let good_decode = spawn_completion_worker(StatusCode::OK).await;
+
+        let router = create_failover_pd_router(vec![]);
+        router
+            .worker_registry
+            .register(Arc::from(create_test_worker(
+                prefill_url,
+                WorkerType::Prefill {
+                    bootstrap_port: None,
+                },
+                true,
+            )));
+        router
+            .worker_registry
+            .register(Arc::from(create_test_worker(
+                bad_decode,
+                WorkerType::Decode,
+                true,
+            )));
+        let good_worker: Arc<dyn Worker> = Arc::from(create_test_worker(
+            good_decode.clone(),
+            WorkerType::Decode,
+            true,
+        ));
+        router.worker_registry.register(good_worker.clone());
+
+        // Power-of-two picks the idle (failing) decode worker first
+        router
+            .policy_registry
+            .set_decode_policy(Arc::new(crate::policies::PowerOfTwoPolicy::new()));
+        for _ in 0..5 {
+            good_worker.increment_load();
+        }
+
+        let response = router
+            .route_completion(None, &completion_request(), None)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[pd_failover::ATTEMPTS_HEADER], "2");
+        assert_eq!(
+            response.headers()[pd_failover::DECODE_WORKER_HEADER],
+            good_decode.as_str()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_decode_errors_do_not_trip_prefill_circuit() {
+        let mut router = create_test_pd_router();
+        router.retry_config.initial_backoff_ms = 1;
+        router.retry_config.max_backoff_ms = 1;
+        let prefill = register_worker(
+            &router,
+            spawn_completion_worker(StatusCode::OK).await,
+            WorkerType::Prefill {
+                bootstrap_port: None,
+            },
+        );
+        let decode = register_worker(
+            &router,
+            spawn_completion_worker(StatusCode::INTERNAL_SERVER_ERROR).await,
+            WorkerType::Decode,
+        );
+
+        let response = router
+            .route_completion(None, &completion_request(), None)
+            .await;
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(!decode.is_available());
+        assert!(prefill.is_available());
+    }
+
+    #[tokio::test]
+    async fn test_decode_failover_falls_back_to_regular_pool() {
+        let prefill_url = spawn_completion_worker(StatusCode::OK).await;
+        let bad_decode = spawn_completion_worker(StatusCode::INTERNAL_SERVER_ERROR).await;
+        let fallback_url = spawn_completion_worker(StatusCode::OK).await;
+
+        let router = create_failover_pd_router(vec![fallback_url.clone()]);
+        router
+            .worker_registry
+            .register(Arc::from(create_test_worker(
+                prefill_url,
+                WorkerType::Prefill {
+                    bootstrap_port: None,
+                },
+                true,
+            )));
+        router
+            .worker_registry
+            .register(Arc::from(create_test_worker(
+                bad_decode,
+                WorkerType::Decode,
+                true,
+            )));
+
+        let response = router
+            .route_completion(None, &completion_request(), None)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[pd_failover::ATTEMPTS_HEADER], "2");
+        assert_eq!(
+            response.headers()[pd_failover::FALLBACK_WORKER_HEADER],
+            fallback_url.as_str()
+        );
+    }
+
+    // ============= Sequential Dispatch Tests =============
+
+    async fn spawn_worker(app: axum::Router) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}", addr)
+    }
+
+    // Prefill worker returning KV transfer parameters, or failing with `status`
+    async fn spawn_prefill_worker(status: StatusCode) -> String {
+        spawn_worker(axum::Router::new().route(
+            "/v1/completions",
+            axum::routing::post(move || async move {
+                (
+                    status,
+                    Json(
+                        json!({"choices": [], "kv_transfer_params": {"remote_block_ids": [1, 2]}}),
+                    ),
+                )
+            }),
+        ))
+        .await
+    }
+
+    // Decode worker echoing the KV transfer parameters it received
+    async fn spawn_echo_decode_worker() -> String {
+        spawn_worker(axum::Router::new().route(
+            "/v1/completions",
+            axum::routing::post(|Json(body): Json<Value>| async move {
+                Json(json!({"choices": [], "kv_transfer_params": body["kv_transfer_params"]}))
+            }),
+        ))
+        .await
+    }
+
+    fn create_sequential_pd_router() -> PDRouter {
+        let mut router = create_test_pd_router();
+        router.kv_transfer = Arc::new(NixlProtocol);
+        router.retry_config.initial_backoff_ms = 1;
+        router.retry_config.max_backoff_ms = 1;
+        router
+    }
+
+    fn register_worker(router: &PDRouter, url: String, worker_type: WorkerType) -> Arc<dyn Worker> {
+        let worker: Arc<dyn Worker> = Arc::from(create_test_worker(url, worker_type, true));
+        router.worker_registry.register(worker.clone());
+        worker
+    }
+
+    #[tokio::test]
+    async fn test_sequential_dispatch_hands_prefill_params_to_decode() {
+        let router = create_sequential_pd_router();
+        let prefill = register_worker(
+            &router,
+            spawn_prefill_worker(StatusCode::OK).await,
+            WorkerType::Prefill {
+                bootstrap_port: None,
+            },
+        );
+        let decode = register_worker(
+            &router,
+            spawn_echo_decode_worker().await,
+            WorkerType::Decode,
+        );
+
+        let response = router
+            .route_completion(None, &completion_request(), None)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        // The pair is recorded for the audit trail even without failover
+        assert_eq!(
+            response.extensions().get::<PrefilledBy>(),
+            Some(&PrefilledBy(prefill.url().to_string()))
+        );
+        assert_eq!(
+            response.extensions().get::<ServedBy>(),
+            Some(&ServedBy(decode.url().to_string()))
+        );
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body["kv_transfer_params"]["remote_block_ids"],
+            json!([1, 2])
+        );
+        assert_eq!(prefill.load(), 0);
+        assert_eq!(decode.load(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_sequential_prefill_failure_retries_on_another_worker() {
+        let router = create_sequential_pd_router();
+        let bad_prefill = register_worker(
+            &router,
+            spawn_prefill_worker(StatusCode::SERVICE_UNAVAILABLE).await,
+            WorkerType::Prefill {
+                bootstrap_port: None,
+            },
+        );
+        let good_prefill = register_worker(
+            &router,
+            spawn_prefill_worker(StatusCode::OK).await,
+            WorkerType::Prefill {
+                bootstrap_port: None,
+            },
+        );
+        register_worker(
+            &router,
+            spawn_echo_decode_worker().await,
+            WorkerType::Decode,
+        );
+
+        // Power-of-two picks the idle (failing) prefill worker first
+        router
+            .policy_registry
+            .set_prefill_policy(Arc::new(crate::policies::PowerOfTwoPolicy::new()));
+        for _ in 0..5 {
+            good_prefill.increment_load();
+        }
+        let response = router
+            .route_completion(None, &completion_request(), None)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        // The failed stage released its load before the retry
+        assert_eq!(bad_prefill.load(), 0);
+    }
+
+    // ============= Prefill Stage Request Tests =============
+
+    #[test]
+    fn test_prefill_chat_completion_sets_max_tokens_1() {
+        let request = json!({
+            "model": "test",
+            "messages": [{"role": "user", "content": "hi"}],
+            "max_tokens": 512,
+            "stream": true
+        });
+        let result = PDRouter::prepare_prefill_request(request, "/v1/chat/completions");
+        assert_eq!(result["max_tokens"], 1);
+        assert_eq!(result["stream"], false);
+    }
+
+    #[test]
+    fn test_prefill_chat_completion_sets_max_completion_tokens_1() {
+        let request = json!({
+            "model": "test",
+            "messages": [{"role": "user", "content": "hi"}],
+            "max_tokens": 512,
+            "max_completion_tokens": 256
+        });
+        let result = PDRouter::prepare_prefill_request(request, "/v1/chat/completions");
+        assert_eq!(result["max_tokens"], 1);
+        assert_eq!(result["max_completion_tokens"], 1);
+    }
+
+    #[test]
+    fn test_prefill_chat_completion_clamps_min_tokens() {
+        let request = json!({
+            "model": "test",
+            "max_tokens": 512,
+            "min_tokens": 100
+        });
+        let result = PDRouter::prepare_prefill_request(request, "/v1/completions");
+        assert_eq!(result["max_tokens"], 1);
+        assert_eq!(result["min_tokens"], 1);
+    }
+
+    #[test]
+    fn test_prefill_chat_completion_leaves_small_min_tokens() {
+        let request = json!({
+            "model": "test",
+            "max_tokens": 512,
+            "min_tokens": 0
+        });
+        let result = PDRouter::prepare_prefill_request(request, "/v1/completions");
+        assert_eq!(result["max_tokens"], 1);
+        // min_tokens <= 1, so it should be left as-is
+        assert_eq!(result["min_tokens"], 0);
+    }
+
+    #[test]
+    fn test_prefill_chat_completion_removes_stream_options() {
+        let request = json!({
+            "model": "test",
+            "max_tokens": 512,
+            "stream": true,
+            "stream_options": {"include_usage": true}
+        });
+        let result = PDRouter::prepare_prefill_request(request, "/v1/chat/completions");
+        assert_eq!(result["stream"], false);
+        assert!(result.get("stream_options").is_none());
+    }
+
+    #[test]
+    fn test_prefill_generate_patches_sampling_params() {
+        let request = json!({
+            "token_ids": [123, 456],
+            "sampling_params": {
+                "max_tokens": 512,
+                "temperature": 0.7
+            }
+        });
+        let result = PDRouter::prepare_prefill_request(request, "/inference/v1/generate");
+        // sampling_params.max_tokens should be capped
+        assert_eq!(result["sampling_params"]["max_tokens"], 1);
+        // temperature should be preserved
+        assert_eq!(result["sampling_params"]["temperature"], 0.7);
+        // top-level max_tokens should NOT be set
+        assert!(result.get("max_tokens").is_none());
+    }
+
+    #[test]
+    fn test_prefill_generate_clamps_sampling_params_min_tokens() {
+        let request = json!({
+            "token_ids": [123, 456],
+            "sampling_params": {
+                "max_tokens": 512,
+                "min_tokens": 50
+            }
+        });
+        let result = PDRouter::prepare_prefill_request(request, "/inference/v1/generate");
+        assert_eq!(result["sampling_params"]["max_tokens"], 1);
+        assert_eq!(result["sampling_params"]["min_tokens"], 1);
+    }
+
+    #[test]
+    fn test_prefill_generate_without_sampling_params() {
+        // If sampling_params is missing, should not panic
+        let request = json!({
+            "token_ids": [123, 456],
+        });
+        let result = PDRouter::prepare_prefill_request(request, "/inference/v1/generate");
+        // stream should still be forced to false
+        assert_eq!(result["stream"], false);
+        // top-level max_tokens should NOT be set (generate path)
+        assert!(result.get("max_tokens").is_none());
+        // create sampling_params and set min max
+        assert_eq!(result["sampling_params"]["max_tokens"], 1);
+        assert_eq!(result["sampling_params"]["min_tokens"], 1);
+    }
+
+    #[test]
+    fn test_prefill_generate_forces_stream_false() {
+        let request = json!({
+            "token_ids": [123, 456],
+            "sampling_params": {"max_tokens": 512},
+            "stream": true,
+            "stream_options": {"include_usage": true}
+        });
+        let result = PDRouter::prepare_prefill_request(request, "/inference/v1/generate");
+        assert_eq!(result["stream"], false);
+        assert!(result.get("stream_options").is_none());
+    }
 }
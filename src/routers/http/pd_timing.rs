@@ -0,0 +1,191 @@
+//! Per-stage timing for PD requests
+//!
+//! Records prefill duration, KV-transfer wait, time to first token and
+//! inter-token latency as histograms labeled by worker, and reports the stage
+//! durations of non-streaming responses in a `server-timing` header.
+
+use super::pd_failover::ByteStream;
+use crate::metrics::RouterMetrics;
+use axum::http::HeaderValue;
+use axum::response::Response;
+use futures_util::StreamExt;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+pub const SERVER_TIMING_HEADER: &str = "server-timing";
+
+#[derive(Debug)]
+struct Stages {
+    prefill_worker: String,
+    decode_worker: String,
+    start: Instant,
+    decode_started: OnceLock<Instant>,
+    prefill_done: OnceLock<Instant>,
+    first_token: OnceLock<Instant>,
+}
+
+/// Stage clock for one prefill/decode exchange
+///
+/// Cheap to clone so the prefill side can be marked from a background task.
+#[derive(Debug, Clone)]
+pub struct PdTimer {
+    stages: Arc<Stages>,
+}
+
+impl PdTimer {
+    /// Start timing a request dispatched to a prefill/decode pair
+    pub fn start(prefill_worker: &str, decode_worker: &str) -> Self {
+        Self {
+            stages: Arc::new(Stages {
+                prefill_worker: prefill_worker.to_string(),
+                decode_worker: decode_worker.to_string(),
+                start: Instant::now(),
+                decode_started: OnceLock::new(),
+                prefill_done: OnceLock::new(),
+                first_token: OnceLock::new(),
+            }),
+        }
+    }
+
+    /// When the request was dispatched
+    pub fn started(&self) -> Instant {
+        self.stages.start
+    }
+
+    /// Mark the end of prefill and record its duration
+    pub fn prefill_done(&self) {
+        let now = Instant::now();
+        if self.stages.prefill_done.set(now).is_ok() {
+            RouterMetrics::record_pd_prefill_duration(
+                &self.stages.prefill_worker,
+                now - self.stages.start,
+            );
+        }
+    }
+
+    /// Mark the decode request being sent (sequential two-stage flow)
+    ///
+    /// Without this mark the decode stage is timed from the start, as when
+    /// prefill and decode are dispatched concurrently.
+    pub fn decode_started(&self) {
+        let _ = self.stages.decode_started.set(Instant::now());
+    }
+
+    /// Mark the first decode chunk and record TTFT and KV-transfer wait
+    ///
+    /// The KV-transfer wait runs from the end of prefill to the first token,
+    /// so it also covers the first decode step.
+    pub fn first_token(&self) {
+        let now = Instant::now();
+        if self.stages.first_token.set(now).is_err() {
+            return;
+        }
+        let stages = &self.stages;
+        RouterMetrics::record_pd_ttft(
+            &stages.prefill_worker,
+            &stages.decode_worker,
+            now - stages.start,
+        );
+        if let Some(prefill_done) = stages.prefill_done.get() {
+            RouterMetrics::record_pd_kv_transfer_wait(
+                &stages.prefill_worker,
+                &stages.decode_worker,
+                now.saturating_duration_since(*prefill_done),
+            );
+        }
+    }
+
+    /// Record TTFT on the first chunk and inter-token latency on the rest
+    pub fn observe_stream(&self, stream: ByteStream) -> ByteStream {
+        let timer = self.clone();
+        let mut last_chunk: Option<Instant> = None;
+        Box::pin(stream.inspect(move |chunk| {
+            if chunk.is_err() {
+                return;
+            }
+            let now = Instant::now();
+            match last_chunk {
+                Some(previous) => RouterMetrics::record_pd_inter_token_latency(
+                    &timer.stages.decode_worker,
+                    now - previous,
+                ),
+                None => timer.first_token(),
+            }
+            last_chunk = Some(now);
+        }))
+    }
+
+    /// `server-timing` value with the stage durations so far, in milliseconds
+    pub fn server_timing(&self) -> String {
+        let stages = &self.stages;
+        let now = Instant::now();
+        let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+
+        let mut entries = Vec::new();
+        if let Some(prefill_done) = stages.prefill_done.get() {
+            entries.push(format!(
+                "prefill;dur={:.1}",
+                ms(*prefill_done - stages.start)
+            ));
+        }
+        if let (Some(prefill_done), Some(first_token)) =
+            (stages.prefill_done.get(), stages.first_token.get())
+        {
+            entries.push(format!(
+                "kv_transfer;dur={:.1}",
+                ms(first_token.saturating_duration_since(*prefill_done))
+            ));
+        }
+        let decode_start = stages.decode_started.get().unwrap_or(&stages.start);
+        entries.push(format!("decode;dur={:.1}", ms(now - *decode_start)));
+        entries.push(format!("total;dur={:.1}", ms(now - stages.start)));
+        entries.join(", ")
+    }
+
+    /// Add the `server-timing` header to a response
+    pub fn annotate(&self, response: &mut Response) {
+        if let Ok(value) = HeaderValue::from_str(&self.server_timing()) {
+            response.headers_mut().insert(SERVER_TIMING_HEADER, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+    use axum::response::IntoResponse;
+    use bytes::Bytes;
+
+    #[test]
+    fn test_server_timing_stages() {
+        let timer = PdTimer::start("http://p1:8000", "http://d1:8000");
+        let value = timer.server_timing();
+        assert!(value.starts_with("decode;dur="));
+        assert!(value.contains("total;dur="));
+
+        timer.prefill_done();
+        timer.decode_started();
+        let mut response = StatusCode::OK.into_response();
+        timer.annotate(&mut response);
+        let value = response.headers()[SERVER_TIMING_HEADER].to_str().unwrap();
+        assert!(value.starts_with("prefill;dur="));
+        assert!(!value.contains("kv_transfer"));
+        assert_eq!(value.split(", ").count(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_observe_stream_marks_first_token() {
+        let timer = PdTimer::start("http://p1:8000", "http://d1:8000");
+        timer.prefill_done();
+        let chunks: Vec<Result<Bytes, reqwest::Error>> =
+            vec![Ok(Bytes::from("a")), Ok(Bytes::from("b"))];
+        let stream = timer.observe_stream(Box::pin(futures_util::stream::iter(chunks)));
+        assert!(timer.stages.first_token.get().is_none());
+
+        let collected: Vec<Bytes> = stream.map(|c| c.unwrap()).collect().await;
+        assert_eq!(collected.len(), 2);
+        assert!(timer.stages.first_token.get().is_some());
+        assert!(timer.server_timing().contains("kv_transfer;dur="));
+    }
+}
@@ -0,0 +1,347 @@
+//! KV transfer protocols for the PD routers
+//!
+//! Each KV connector expects the router to coordinate prefill and decode
+//! differently: SGLang-style connectors share a bootstrap room sent with both
+//! stages at once, NIXL hands `kv_transfer_params` from the prefill response
+//! to the decode request, and P2P NCCL encodes both ZMQ endpoints in the
+//! request id. The PD dispatch engine calls into a [`KvTransferProtocol`] for
+//! the connector-specific parts and handles retries, circuit breakers, metrics
+//! and logprob merging the same way for every connector, in both PD modes.
+
+use super::pd_types::{generate_room_id, get_hostname};
+use super::vllm_service_discovery::{ServiceRegistry, ServiceType, ZMQ_ADDRESS_LABEL};
+use crate::config::KvTransferProtocolKind;
+use crate::core::{Worker, WorkerType};
+use serde_json::{json, Value};
+use std::fmt;
+use std::sync::Arc;
+use tracing::debug;
+use uuid::Uuid;
+
+/// How the two stages of a request are dispatched
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DispatchMode {
+    /// Prefill and decode are sent together and coordinate on their own
+    Concurrent,
+    /// Decode is sent once prefill has returned its transfer parameters
+    Sequential,
+}
+
+/// Prefill/decode pair a request is dispatched to
+#[derive(Debug, Clone, Copy)]
+pub struct TransferPair<'a> {
+    pub prefill: &'a dyn Worker,
+    pub decode: &'a dyn Worker,
+    /// Batch size of a batched request, for per-item coordination fields
+    pub batch_size: Option<usize>,
+}
+
+/// Connector-specific coordination between the prefill and decode stages
+pub trait KvTransferProtocol: Send + Sync + fmt::Debug {
+    /// Protocol name used in logs
+    fn name(&self) -> &'static str;
+
+    fn dispatch_mode(&self) -> DispatchMode;
+
+    /// Request id sent to both stages as `X-Request-Id`
+    fn request_id(&self, _pair: &TransferPair<'_>) -> String {
+        Uuid::new_v4().to_string()
+    }
+
+    /// Add fields both stages must share, before the request is split
+    fn prepare_request(
+        &self,
+        _request: &mut Value,
+        _pair: &TransferPair<'_>,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Add connector fields to the prefill-stage request
+    fn prepare_prefill(&self, _request: &mut Value, _pair: &TransferPair<'_>) {}
+
+    /// Add connector fields to the decode-stage request
+    ///
+    /// `prefill_response` is the parsed prefill response in sequential mode.
+    fn prepare_decode(&self, _request: &mut Value, _prefill_response: Option<&Value>) {}
+}
+
+/// Bootstrap host/port/room injected into both stages (SGLang-style connectors)
+#[derive(Debug, Default)]
+pub struct BootstrapProtocol;
+
+impl KvTransferProtocol for BootstrapProtocol {
+    fn name(&self) -> &'static str {
+        "bootstrap"
+    }
+
+    fn dispatch_mode(&self) -> DispatchMode {
+        DispatchMode::Concurrent
+    }
+
+    fn prepare_request(&self, request: &mut Value, pair: &TransferPair<'_>) -> Result<(), String> {
+        let bootstrap_port = match pair.prefill.worker_type() {
+            WorkerType::Prefill { bootstrap_port } => bootstrap_port,
+            _ => None,
+        };
+        let hostname = get_hostname(pair.prefill.url());
+
+        let obj = request
+            .as_object_mut()
+            .ok_or_else(|| "Request must be a JSON object".to_string())?;
+
+        if let Some(n) = pair.batch_size {
+            obj.insert("bootstrap_host".to_string(), json!(vec![hostname; n]));
+            obj.insert("bootstrap_port".to_string(), json!(vec![bootstrap_port; n]));
+            let rooms: Vec<u64> = (0..n).map(|_| generate_room_id()).collect();
+            obj.insert("bootstrap_room".to_string(), json!(rooms));
+        } else {
+            obj.insert("bootstrap_host".to_string(), json!(hostname));
+            obj.insert("bootstrap_port".to_string(), json!(bootstrap_port));
+            obj.insert("bootstrap_room".to_string(), json!(generate_room_id()));
+        }
+        Ok(())
+    }
+}
+
+/// `kv_transfer_params` handed from the prefill response to decode (NixlConnector)
+#[derive(Debug, Default)]
+pub struct NixlProtocol;
+
+impl KvTransferProtocol for NixlProtocol {
+    fn name(&self) -> &'static str {
+        "nixl"
+    }
+
+    fn dispatch_mode(&self) -> DispatchMode {
+        DispatchMode::Sequential
+    }
+
+    fn prepare_prefill(&self, request: &mut Value, _pair: &TransferPair<'_>) {
+        // Tells the prefill instance to keep the KV cache for a remote decode
+        request["kv_transfer_params"] = json!({
+            "do_remote_decode": true,
+            "do_remote_prefill": false,
+            "remote_engine_id": Value::Null,
+            "remote_block_ids": Value::Null,
+            "remote_host": Value::Null,
+            "remote_port": Value::Null
+        });
+    }
+
+    fn prepare_decode(&self, request: &mut Value, prefill_response: Option<&Value>) {
+        match prefill_response.and_then(|response| response.get("kv_transfer_params")) {
+            Some(params) => request["kv_transfer_params"] = params.clone(),
+            None => debug!("No kv_transfer_params in prefill response"),
+        }
+    }
+}
+
+/// Both ZMQ endpoints encoded in the request id (P2pNcclConnector)
+#[derive(Debug, Default)]
+pub struct P2pNcclProtocol {
+    // Resolves worker HTTP addresses to their registered ZMQ addresses
+    registry: Option<Arc<ServiceRegistry>>,
+}
+
+impl P2pNcclProtocol {
+    pub fn new(registry: Option<Arc<ServiceRegistry>>) -> Self {
+        Self { registry }
+    }
+
+    /// ZMQ address of a worker, or its HTTP host:port if none is registered
+    ///
+    /// Discovered workers carry their address as a label; the registry is
+    /// consulted for workers registered before they were.
+    pub fn zmq_address(&self, worker: &dyn Worker, service_type: ServiceType) -> String {
+        if let Some(address) = worker.metadata().labels.get(ZMQ_ADDRESS_LABEL) {
+            return address.clone();
+        }
+        let http_address = worker
+            .url()
+            .trim_start_matches("http://")
+            .trim_start_matches("https://");
+        self.registry
+            .as_ref()
+            .and_then(|registry| registry.get_zmq_address(http_address, service_type))
+            .unwrap_or_else(|| http_address.to_string())
+    }
+}
+
+impl KvTransferProtocol for P2pNcclProtocol {
+    fn name(&self) -> &'static str {
+        "p2p_nccl"
+    }
+
+    fn dispatch_mode(&self) -> DispatchMode {
+        DispatchMode::Sequential
+    }
+
+    fn request_id(&self, pair: &TransferPair<'_>) -> String {
+        format!(
+            "___prefill_addr_{}___decode_addr_{}_{}",
+            self.zmq_address(pair.prefill, ServiceType::Prefill),
+            self.zmq_address(pair.decode, ServiceType::Decode),
+            Uuid::new_v4().simple()
+        )
+    }
+}
+
+/// NIXL transfer parameters with a P2P NCCL request id, so either vLLM
+/// connector works without configuration
+#[derive(Debug, Default)]
+pub struct VllmProtocol {
+    nixl: NixlProtocol,
+    p2p: P2pNcclProtocol,
+}
+
+impl VllmProtocol {
+    pub fn new(registry: Option<Arc<ServiceRegistry>>) -> Self {
+        Self {
+            nixl: NixlProtocol,
+            p2p: P2pNcclProtocol::new(registry),
+        }
+    }
+}
+
+impl KvTransferProtocol for VllmProtocol {
+    fn name(&self) -> &'static str {
+        "nixl+p2p_nccl"
+    }
+
+    fn dispatch_mode(&self) -> DispatchMode {
+        DispatchMode::Sequential
+    }
+
+    fn request_id(&self, pair: &TransferPair<'_>) -> String {
+        self.p2p.request_id(pair)
+    }
+
+    fn prepare_prefill(&self, request: &mut Value, pair: &TransferPair<'_>) {
+        self.nixl.prepare_prefill(request, pair);
+    }
+
+    fn prepare_decode(&self, request: &mut Value, prefill_response: Option<&Value>) {
+        self.nixl.prepare_decode(request, prefill_response);
+    }
+}
+
+/// Protocol for a configured kind; `Auto` picks the routing mode's default
+pub fn protocol_for(
+    kind: KvTransferProtocolKind,
+    vllm_mode: bool,
+    registry: Option<Arc<ServiceRegistry>>,
+) -> Arc<dyn KvTransferProtocol> {
+    match kind {
+        KvTransferProtocolKind::Auto if vllm_mode => Arc::new(VllmProtocol::new(registry)),
+        KvTransferProtocolKind::Auto | KvTransferProtocolKind::Bootstrap => {
+            Arc::new(BootstrapProtocol)
+        }
+        KvTransferProtocolKind::Nixl => Arc::new(NixlProtocol),
+        KvTransferProtocolKind::P2pNccl => Arc::new(P2pNcclProtocol::new(registry)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::BasicWorker;
+    use std::collections::HashMap;
+
+    fn workers() -> (BasicWorker, BasicWorker) {
+        (
+            BasicWorker::new(
+                "http://prefill:8000".to_string(),
+                WorkerType::Prefill {
+                    bootstrap_port: Some(9000),
+                },
+            ),
+            BasicWorker::new("http://decode:8000".to_string(), WorkerType::Decode),
+        )
+    }
+
+    #[test]
+    fn test_bootstrap_injection() {
+        let (prefill, decode) = workers();
+        let protocol = BootstrapProtocol;
+        let mut pair = TransferPair {
+            prefill: &prefill,
+            decode: &decode,
+            batch_size: None,
+        };
+
+        let mut request = json!({"prompt": "hi"});
+        protocol.prepare_request(&mut request, &pair).unwrap();
+        assert_eq!(request["bootstrap_host"], "prefill");
+        assert_eq!(request["bootstrap_port"], 9000);
+        assert!(request["bootstrap_room"].is_u64());
+
+        pair.batch_size = Some(2);
+        let mut batch = json!({"prompt": ["a", "b"]});
+        protocol.prepare_request(&mut batch, &pair).unwrap();
+        assert_eq!(batch["bootstrap_host"], json!(["prefill", "prefill"]));
+        assert_eq!(batch["bootstrap_room"].as_array().unwrap().len(), 2);
+
+        assert!(protocol.prepare_request(&mut json!([1]), &pair).is_err());
+    }
+
+    #[test]
+    fn test_nixl_hands_params_to_decode() {
+        let (prefill, decode) = workers();
+        let pair = TransferPair {
+            prefill: &prefill,
+            decode: &decode,
+            batch_size: None,
+        };
+        let protocol = NixlProtocol;
+
+        let mut prefill_request = json!({"prompt": "hi"});
+        protocol.prepare_prefill(&mut prefill_request, &pair);
+        assert_eq!(
+            prefill_request["kv_transfer_params"]["do_remote_decode"],
+            true
+        );
+
+        let prefill_response = json!({"kv_transfer_params": {"remote_engine_id": "e1"}});
+        let mut decode_request = json!({"prompt": "hi"});
+        protocol.prepare_decode(&mut decode_request, Some(&prefill_response));
+        assert_eq!(
+            decode_request["kv_transfer_params"]["remote_engine_id"],
+            "e1"
+        );
+
+        let mut untouched = json!({"prompt": "hi"});
+        protocol.prepare_decode(&mut untouched, Some(&json!({})));
+        assert!(untouched.get("kv_transfer_params").is_none());
+    }
+
+    #[test]
+    fn test_p2p_nccl_request_id() {
+        let (prefill, decode) = workers();
+        let pair = TransferPair {
+            prefill: &prefill,
+            decode: &decode,
+            batch_size: None,
+        };
+        let id = P2pNcclProtocol::default().request_id(&pair);
+        assert!(id.starts_with("___prefill_addr_prefill:8000___decode_addr_decode:8000_"));
+
+        // Discovered workers carry their ZMQ address as a label
+        let discovered =
+            BasicWorker::new("http://decode:8000".to_string(), WorkerType::Decode).with_labels(
+                HashMap::from([(ZMQ_ADDRESS_LABEL.to_string(), "decode:5555".to_string())]),
+            );
+        let labelled = TransferPair {
+            decode: &discovered,
+            ..pair
+        };
+        let id = P2pNcclProtocol::default().request_id(&labelled);
+        assert!(id.contains("___decode_addr_decode:5555_"));
+
+        let vllm = protocol_for(KvTransferProtocolKind::Auto, true, None);
+        assert_eq!(vllm.dispatch_mode(), DispatchMode::Sequential);
+        assert!(vllm.request_id(&pair).starts_with("___prefill_addr_"));
+        let pd = protocol_for(KvTransferProtocolKind::Auto, false, None);
+        assert_eq!(pd.dispatch_mode(), DispatchMode::Concurrent);
+    }
+}
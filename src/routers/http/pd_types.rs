@@ -19,6 +19,12 @@ pub enum PDRouterError {
     #[error("Network error: {message}")]
     NetworkError { message: String },
 
+    #[error("Prefill worker {url} failed: {message}")]
+    PrefillFailed { url: String, message: String },
+
+    #[error("Decode worker {url} failed: {message}")]
+    DecodeFailed { url: String, message: String },
+
     #[error("Timeout waiting for worker: {url}")]
     Timeout { url: String },
 }
@@ -1,7 +1,7 @@
-use crate::config::types::RetryConfig;
+use crate::config::types::{DpDiscoveryConfig, RetryConfig};
 use crate::core::{
-    is_retryable_status, BasicWorker, CircuitBreakerConfig, HealthConfig, RetryExecutor, Worker,
-    WorkerRegistry, WorkerType,
+    capabilities, is_retryable_status, BasicWorker, CircuitBreakerConfig, HealthConfig,
+    LoraManager, RequestRequirements, RetryExecutor, ServedBy, Worker, WorkerRegistry, WorkerType,
 };
 use crate::metrics::RouterMetrics;
 use crate::policies::{LoadBalancingPolicy, PolicyRegistry};
@@ -39,9 +39,11 @@ pub struct Router {
     worker_startup_timeout_secs: u64,
     worker_startup_check_interval_secs: u64,
     intra_node_data_parallel_size: usize,
+    dp_discovery: DpDiscoveryConfig,
     api_key: Option<String>,
     retry_config: RetryConfig,
     circuit_breaker_config: CircuitBreakerConfig,
+    lora_manager: Option<Arc<LoraManager>>,
     _worker_loads: Arc<tokio::sync::watch::Receiver<HashMap<String, isize>>>,
     _load_monitor_handle: Option<Arc<tokio::task::JoinHandle<()>>>,
 }
@@ -59,6 +61,7 @@ impl Router {
         // Wait for workers to be healthy (skip if empty - for service discovery mode)
         if !worker_urls.is_empty() {
             Self::wait_for_healthy_workers(
+                &ctx.client,
                 &worker_urls,
                 ctx.router_config.worker_startup_timeout_secs,
                 ctx.router_config.worker_startup_check_interval_secs,
@@ -66,19 +69,17 @@ impl Router {
             .await?;
         }
 
-        // Automatically expand to DP-aware workers when intra_node_data_parallel_size > 1
-        let worker_urls = if ctx.router_config.intra_node_data_parallel_size > 1 {
-            // worker address now in the format of "http://host:port@dp_rank"
-            dp_utils::get_dp_aware_workers(
-                &worker_urls,
-                &ctx.router_config.api_key,
-                ctx.router_config.intra_node_data_parallel_size,
-            )
-            .await
-            .map_err(|e| format!("Failed to get dp-aware workers: {}", e))?
-        } else {
-            worker_urls
-        };
+        // Expand to DP-aware workers ("http://host:port@dp_rank") using either the
+        // discovered per-worker DP size or intra_node_data_parallel_size
+        let worker_urls = dp_utils::expand_worker_urls(
+            &ctx.client,
+            &worker_urls,
+            &ctx.router_config.api_key,
+            ctx.router_config.intra_node_data_parallel_size,
+            &ctx.router_config.dp_discovery,
+        )
+        .await
+        .map_err(|e| format!("Failed to get dp-aware workers: {}", e))?;
 
         // Convert config CircuitBreakerConfig to core CircuitBreakerConfig
         let circuit_breaker_config = ctx.router_config.effective_circuit_breaker_config();
@@ -91,6 +92,7 @@ impl Router {
 
         // Register workers in the registry
         // In IGW mode, we need to fetch model info from workers
+        let mut registered_workers: Vec<Arc<dyn Worker>> = Vec::with_capacity(worker_urls.len());
         for url in &worker_urls {
             // TODO: In IGW mode, fetch model_id from worker's /get_model_info endpoint
             // For now, create worker without model_id
@@ -106,6 +108,7 @@ impl Router {
 
             let worker_arc = Arc::new(worker);
             ctx.worker_registry.register(worker_arc.clone());
+            registered_workers.push(worker_arc.clone());
 
             // Notify PolicyRegistry about the new worker
             let model_id = worker_arc.model_id();
@@ -124,6 +127,14 @@ impl Router {
             }
         }
 
+        // Discover context length, supported endpoints and LoRA adapters
+        capabilities::populate_capabilities(
+            &ctx.client,
+            &registered_workers,
+            &ctx.router_config.api_key,
+        )
+        .await;
+
         // Setup load monitoring for PowerOfTwo policy
         let (tx, rx) = tokio::sync::watch::channel(HashMap::new());
         let worker_loads = Arc::new(rx);
@@ -159,9 +170,18 @@ impl Router {
                 .router_config
                 .worker_startup_check_interval_secs,
             intra_node_data_parallel_size: ctx.router_config.intra_node_data_parallel_size,
+            dp_discovery: ctx.router_config.dp_discovery.clone(),
             api_key: ctx.router_config.api_key.clone(),
             retry_config: ctx.router_config.effective_retry_config(),
             circuit_breaker_config: core_cb_config,
+            lora_manager: ctx.router_config.lora.enabled.then(|| {
+                Arc::new(LoraManager::new(
+                    ctx.client.clone(),
+                    ctx.router_config.api_key.clone(),
+                    ctx.router_config.lora.clone(),
+                    ctx.worker_registry.clone(),
+                ))
+            }),
             _worker_loads: worker_loads,
             _load_monitor_handle: load_monitor_handle,
         })
@@ -182,6 +202,7 @@ impl Router {
     }
 
     pub async fn wait_for_healthy_workers(
+        client: &Client,
         worker_urls: &[String],
         worker_startup_timeout_secs: u64,
         worker_startup_check_interval_secs: u64,
@@ -194,6 +215,7 @@ impl Router {
 
         // Perform health check asynchronously
         Self::wait_for_healthy_workers_async(
+            client,
             worker_urls,
             worker_startup_timeout_secs,
             worker_startup_check_interval_secs,
@@ -202,6 +224,7 @@ impl Router {
     }
 
     async fn wait_for_healthy_workers_async(
+        client: &Client,
         worker_urls: &[String],
         worker_startup_timeout_secs: u64,
         worker_startup_check_interval_secs: u64,
@@ -239,10 +262,6 @@ impl Router {
         );
 
         let start_time = std::time::Instant::now();
-        let client = reqwest::Client::builder()
-            .timeout(Duration::from_secs(2))
-            .build()
-            .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
 
         loop {
             if start_time.elapsed() > Duration::from_secs(worker_startup_timeout_secs) {
@@ -264,7 +283,12 @@ impl Router {
 
                 let check_health = tokio::spawn(async move {
                     let health_url = format!("{}/health", url_clone);
-                    match client_clone.get(&health_url).send().await {
+                    match client_clone
+                        .get(&health_url)
+                        .timeout(Duration::from_secs(2))
+                        .send()
+                        .await
+                    {
                         Ok(res) => {
                             if res.status().is_success() {
                                 None
@@ -346,22 +370,8 @@ impl Router {
     }
 
     pub async fn send_health_check(&self, worker_url: &str) -> Response {
-        let health_url = if self.intra_node_data_parallel_size > 1 {
-            // Need to extract the URL from "http://host:port@dp_rank"
-            match dp_utils::extract_dp_rank(worker_url) {
-                Ok((worker_url_prefix, _dp_rank)) => worker_url_prefix,
-                Err(e) => {
-                    error!("Failed to extract dp_rank for health check: {}", e);
-                    return (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        format!("Failed to extract dp_rank: {}", e),
-                    )
-                        .into_response();
-                }
-            }
-        } else {
-            worker_url
-        };
+        // Strip the "@dp_rank" suffix of DP-aware workers
+        let (health_url, _) = dp_utils::parse_worker_url(worker_url);
 
         let request_builder = self.client.get(format!("{}/health", health_url));
 
@@ -474,6 +484,7 @@ impl Router {
         model_id: Option<&str>,
         text: Option<&str>,
         headers: Option<&HeaderMap>,
+        requirements: &RequestRequirements,
     ) -> Option<Arc<dyn Worker>> {
         // Get workers for the specified model (O(1) lookup if model_id is provided)
         let workers = match model_id {
@@ -483,13 +494,19 @@ impl Router {
 
         let available: Vec<Arc<dyn Worker>> = workers
             .iter()
-            .filter(|w| w.is_available())
+            .filter(|w| w.is_available() && w.can_handle(requirements))
             .cloned()
             .collect();
         if available.is_empty() {
             return None;
         }
 
+        // Prefer workers that already have the requested LoRA adapter loaded
+        let available = match &requirements.lora_adapter {
+            Some(adapter) => LoraManager::prefer_loaded(&available, adapter),
+            None => available,
+        };
+
         // Get the appropriate policy for this model
         let policy = match model_id {
             Some(model) => self.policy_registry.get_policy_or_default(model),
@@ -514,11 +531,54 @@ impl Router {
         let is_stream = typed_req.is_stream();
         let text = typed_req.extract_text_for_routing();
 
+        // Reject requests no worker can serve (e.g. prompt longer than every worker's
+        // context length) instead of retrying them
+        let mut requirements = RequestRequirements::for_route(route, Some(&text));
+        let mut lora_body = None;
+        if self.lora_manager.is_some() {
+            match LoraManager::requested_adapter(typed_req.get_lora_path()) {
+                Ok(Some(adapter)) => {
+                    let mut body = match serde_json::to_value(typed_req) {
+                        Ok(body) => body,
+                        Err(e) => {
+                            return (
+                                StatusCode::BAD_REQUEST,
+                                format!("Failed to serialize request: {}", e),
+                            )
+                                .into_response()
+                        }
+                    };
+                    LoraManager::target_adapter(&mut body, &adapter);
+                    lora_body = Some(body);
+                    requirements = requirements.with_lora_adapter(Some(adapter));
+                }
+                Ok(None) => {}
+                Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+            }
+        }
+        let workers = match model_id {
+            Some(model) => self.worker_registry.get_by_model_fast(model),
+            None => self.worker_registry.get_all(),
+        };
+        if !workers.is_empty() && !workers.iter().any(|w| w.can_handle(&requirements)) {
+            RouterMetrics::record_request_error(route, "no_capable_workers");
+            return (
+                StatusCode::BAD_REQUEST,
+                format!("No worker can handle this request ({})", requirements),
+            )
+                .into_response();
+        }
+
         let response = RetryExecutor::execute_response_with_retry(
             &self.retry_config,
             // operation per attempt
             |_: u32| async {
-                let worker = match self.select_worker_for_model(model_id, Some(&text), headers) {
+                let worker = match self.select_worker_for_model(
+                    model_id,
+                    Some(&text),
+                    headers,
+                    &requirements,
+                ) {
                     Some(w) => w,
                     None => {
                         RouterMetrics::record_request_error(route, "no_available_workers");
@@ -530,6 +590,20 @@ impl Router {
                     }
                 };
 
+                // Load the requested LoRA adapter if the worker does not have it yet
+                let lora_lease = match (&self.lora_manager, requirements.lora_adapter.as_deref()) {
+                    (Some(manager), Some(adapter)) => {
+                        match manager.ensure_loaded(&worker, adapter).await {
+                            Ok(lease) => Some(lease),
+                            Err(e) => {
+                                RouterMetrics::record_request_error(route, "lora_load_failed");
+                                return (StatusCode::SERVICE_UNAVAILABLE, e).into_response();
+                            }
+                        }
+                    }
+                    _ => None,
+                };
+
                 // Optional load tracking for cache-aware policy
                 // Get the policy for this model to check if it's cache-aware
                 let policy = match model_id {
@@ -552,21 +626,41 @@ impl Router {
                     None
                 };
 
-                let response = self
-                    .send_typed_request(
-                        headers,
-                        typed_req,
-                        route,
-                        worker.url(),
-                        is_stream,
-                        load_incremented,
-                    )
-                    .await;
+                let mut response = match &lora_body {
+                    Some(body) => {
+                        self.send_typed_request(
+                            headers,
+                            body,
+                            route,
+                            worker.url(),
+                            is_stream,
+                            load_incremented,
+                        )
+                        .await
+                    }
+                    None => {
+                        self.send_typed_request(
+                            headers,
+                            typed_req,
+                            route,
+                            worker.url(),
+                            is_stream,
+                            load_incremented,
+                        )
+                        .await
+                    }
+                };
 
                 // Client errors (4xx) are not worker failures - only server errors (5xx)
                 // should count against the circuit breaker. This matches pd_router.rs behavior.
                 let status = response.status();
                 worker.record_outcome(status.is_success() || status.is_client_error());
+                response
+                    .extensions_mut()
+                    .insert(ServedBy(worker.url().to_string()));
+                if let Some(lease) = lora_lease {
+                    response = lease.hold_until_body_end(response);
+                }
 
                 // For retryable failures, we need to decrement load since send_typed_request
                 // won't have done it (it only decrements on success or non-retryable failures)
@@ -605,14 +699,9 @@ impl Router {
         response
     }
 
-    // Helper: return base worker URL (strips DP suffix when enabled)
+    // Helper: return base worker URL (strips DP suffix if present)
     fn worker_base_url(&self, worker_url: &str) -> String {
-        if self.intra_node_data_parallel_size > 1 {
-            if let Ok((prefix, _)) = dp_utils::extract_dp_rank(worker_url) {
-                return prefix.to_string();
-            }
-        }
-        worker_url.to_string()
+        dp_utils::parse_worker_url(worker_url).0
     }
 
     // Generic simple routing for GET/POST without JSON body
@@ -723,47 +812,12 @@ impl Router {
         is_stream: bool,
         load_incremented: bool, // Whether load was incremented for this request
     ) -> Response {
-        let (mut request_builder, extracted_dp_rank) = if self.intra_node_data_parallel_size > 1 {
-            let (worker_url_prefix, dp_rank) = match dp_utils::extract_dp_rank(worker_url) {
-                Ok(tup) => tup,
-                Err(e) => {
-                    error!("Failed to extract dp_rank: {}", e);
-                    return (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        format!("Failed to extract dp_rank: {}", e),
-                    )
-                        .into_response();
-                }
-            };
-
-            // Parse the request body
-            let json_val = match serde_json::to_value(typed_req) {
-                Ok(j) => j,
-                Err(e) => {
-                    return (
-                        StatusCode::BAD_REQUEST,
-                        format!("Convert into serde_json::Value failed: {}", e),
-                    )
-                        .into_response();
-                }
-            };
-
-            // Use the original json_val without modification
-
-            (
-                self.client
-                    .post(format!("{}{}", worker_url_prefix, route))
-                    .json(&json_val),
-                Some(dp_rank),
-            )
-        } else {
-            (
-                self.client
-                    .post(format!("{}{}", worker_url, route))
-                    .json(typed_req),
-                None,
-            ) // Use json() directly with typed request
-        };
+        // DP-aware workers ("http://host:port@dp_rank") are reached through their base URL
+        let (worker_url_prefix, extracted_dp_rank) = dp_utils::parse_worker_url(worker_url);
+        let mut request_builder = self
+            .client
+            .post(format!("{}{}", worker_url_prefix, route))
+            .json(typed_req);
 
         // Copy all headers from original request if provided
         if let Some(headers) = headers {
@@ -937,11 +991,18 @@ impl Router {
     }
 
     pub async fn add_worker(&self, worker_url: &str) -> Result<String, String> {
+        self.add_worker_with_labels(worker_url, HashMap::new())
+            .await
+    }
+
+    /// Add a worker once healthy, attaching `labels` to every DP-aware worker
+    pub async fn add_worker_with_labels(
+        &self,
+        worker_url: &str,
+        labels: HashMap<String, String>,
+    ) -> Result<String, String> {
         let start_time = std::time::Instant::now();
-        let client = reqwest::Client::builder()
-            .timeout(Duration::from_secs(self.worker_startup_timeout_secs))
-            .build()
-            .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+        let timeout = Duration::from_secs(self.worker_startup_timeout_secs);
 
         loop {
             if start_time.elapsed() > Duration::from_secs(self.worker_startup_timeout_secs) {
@@ -955,21 +1016,28 @@ impl Router {
                 ));
             }
 
-            match client.get(format!("{}/health", worker_url)).send().await {
+            match self
+                .client
+                .get(format!("{}/health", worker_url))
+                .timeout(timeout)
+                .send()
+                .await
+            {
                 Ok(res) => {
                     if res.status().is_success() {
-                        if self.intra_node_data_parallel_size > 1 {
-                            // Expand worker URL into multiple DP-aware URLs based on configured intra_node_data_parallel_size
-                            // (e.g., "http://host:8000" → "http://host:8000@0", "@1", etc.)
-                            // without querying the worker
-                            let url_vec = vec![String::from(worker_url)];
-                            let dp_url_vec = dp_utils::get_dp_aware_workers(
-                                &url_vec,
-                                &self.api_key,
-                                self.intra_node_data_parallel_size,
-                            )
-                            .await
-                            .map_err(|e| format!("Failed to get dp-aware workers: {}", e))?;
+                        // Expand worker URL into multiple DP-aware URLs based on the discovered
+                        // or configured DP size (e.g., "http://host:8000" → "http://host:8000@0", "@1", etc.)
+                        let url_vec = vec![String::from(worker_url)];
+                        let dp_url_vec = dp_utils::expand_worker_urls(
+                            &self.client,
+                            &url_vec,
+                            &self.api_key,
+                            self.intra_node_data_parallel_size,
+                            &self.dp_discovery,
+                        )
+                        .await
+                        .map_err(|e| format!("Failed to get dp-aware workers: {}", e))?;
+                        if dp_url_vec != url_vec {
                             let mut worker_added: bool = false;
                             for dp_url in &dp_url_vec {
                                 if self.worker_registry.get_by_url(dp_url).is_some() {
@@ -982,7 +1050,8 @@ impl Router {
                                     BasicWorker::new(dp_url.to_string(), WorkerType::Regular)
                                         .with_circuit_breaker_config(
                                             self.circuit_breaker_config.clone(),
-                                        );
+                                        )
+                                        .with_labels(labels.clone());
 
                                 let worker_arc = Arc::new(new_worker);
                                 self.worker_registry.register(worker_arc.clone());
@@ -1019,7 +1088,8 @@ impl Router {
                                 BasicWorker::new(worker_url.to_string(), WorkerType::Regular)
                                     .with_circuit_breaker_config(
                                         self.circuit_breaker_config.clone(),
-                                    );
+                                    )
+                                    .with_labels(labels.clone());
 
                             let worker_arc = Arc::new(new_worker);
                             self.worker_registry.register(worker_arc.clone());
@@ -1042,6 +1112,13 @@ impl Router {
                             }
                         }
 
+                        let added: Vec<Arc<dyn Worker>> = dp_url_vec
+                            .iter()
+                            .filter_map(|url| self.worker_registry.get_by_url(url))
+                            .collect();
+                        capabilities::populate_capabilities(&self.client, &added, &self.api_key)
+                            .await;
+
                         RouterMetrics::set_active_workers(self.worker_registry.get_all().len());
 
                         return Ok(format!("Successfully added worker: {}", worker_url));
@@ -1083,7 +1160,7 @@ impl Router {
     }
 
     pub fn remove_worker(&self, worker_url: &str) {
-        if self.intra_node_data_parallel_size > 1 {
+        if self.worker_registry.get_by_url(worker_url).is_none() {
             // remove dp-aware workers in a prefix-matching fashion
             // without contacting the remote worker
             let mut removed_workers: Vec<String> = Vec::new();
@@ -1158,19 +1235,8 @@ impl Router {
     }
 
     async fn get_worker_load(&self, worker_url: &str) -> Option<isize> {
-        let worker_url = if self.intra_node_data_parallel_size > 1 {
-            // Need to extract the URL from "http://host:port@dp_rank"
-            let (worker_url_prefix, _dp_rank) = match dp_utils::extract_dp_rank(worker_url) {
-                Ok(tup) => tup,
-                Err(e) => {
-                    error!("Failed to extract dp_rank: {}", e);
-                    return None;
-                }
-            };
-            worker_url_prefix
-        } else {
-            worker_url
-        };
+        // Strip the "@dp_rank" suffix of DP-aware workers
+        let (worker_url, _) = dp_utils::parse_worker_url(worker_url);
 
         match self
             .client
@@ -1317,6 +1383,14 @@ impl WorkerManagement for Router {
         Router::add_worker(self, worker_url).await
     }
 
+    async fn add_worker_with_labels(
+        &self,
+        worker_url: &str,
+        labels: HashMap<String, String>,
+    ) -> Result<String, String> {
+        Router::add_worker_with_labels(self, worker_url, labels).await
+    }
+
     fn remove_worker(&self, worker_url: &str) {
         Router::remove_worker(self, worker_url)
     }
@@ -1477,23 +1551,8 @@ impl RouterTrait for Router {
         // Send requests to all workers concurrently without headers
         let mut tasks = Vec::new();
         for worker_url in &worker_urls {
-            let worker_url = if self.intra_node_data_parallel_size > 1 {
-                // Need to extract the URL from "http://host:port@dp_rank"
-                let (worker_url_prefix, _dp_rank) = match dp_utils::extract_dp_rank(worker_url) {
-                    Ok(tup) => tup,
-                    Err(e) => {
-                        error!("Failed to extract dp_rank: {}", e);
-                        return (
-                            StatusCode::INTERNAL_SERVER_ERROR,
-                            format!("Failed to extract dp_rank: {}", e),
-                        )
-                            .into_response();
-                    }
-                };
-                worker_url_prefix
-            } else {
-                worker_url
-            };
+            // Strip the "@dp_rank" suffix of DP-aware workers
+            let (worker_url, _) = dp_utils::parse_worker_url(worker_url);
             let request_builder = self.client.post(format!("{}/flush_cache", worker_url));
             tasks.push(request_builder.send());
         }
@@ -1664,7 +1723,12 @@ impl RouterTrait for Router {
                 }
 
                 match response_builder.body(body) {
-                    Ok(response) => response,
+                    Ok(mut response) => {
+                        response
+                            .extensions_mut()
+                            .insert(ServedBy(worker.url().to_string()));
+                        response
+                    }
                     Err(e) => (
                         StatusCode::INTERNAL_SERVER_ERROR,
                         format!("Failed to build response: {}", e),
@@ -1684,6 +1748,7 @@ impl RouterTrait for Router {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::{WorkerCapabilities, WorkerEndpoint};
     use std::collections::HashMap;
 
     fn create_test_regular_router() -> Router {
@@ -1706,10 +1771,12 @@ mod tests {
             worker_startup_timeout_secs: 5,
             worker_startup_check_interval_secs: 1,
             intra_node_data_parallel_size: 1,
+            dp_discovery: DpDiscoveryConfig::default(),
             api_key: None,
             client: Client::new(),
             retry_config: RetryConfig::default(),
             circuit_breaker_config: CircuitBreakerConfig::default(),
+            lora_manager: None,
             _worker_loads: Arc::new(rx),
             _load_monitor_handle: None,
         }
@@ -1739,7 +1806,7 @@ mod tests {
     #[tokio::test]
     async fn test_wait_for_healthy_workers_empty_list() {
         // Empty list will return error immediately
-        let result = Router::wait_for_healthy_workers(&[], 1, 1).await;
+        let result = Router::wait_for_healthy_workers(&Client::new(), &[], 1, 1).await;
         assert!(result.is_err());
         assert!(result.unwrap_err().contains("no workers provided"));
     }
@@ -1747,8 +1814,13 @@ mod tests {
     #[tokio::test]
     async fn test_wait_for_healthy_workers_invalid_urls() {
         // This test will timeout quickly since the URLs are invalid
-        let result =
-            Router::wait_for_healthy_workers(&["http://nonexistent:8080".to_string()], 1, 1).await;
+        let result = Router::wait_for_healthy_workers(
+            &Client::new(),
+            &["http://nonexistent:8080".to_string()],
+            1,
+            1,
+        )
+        .await;
         assert!(result.is_err());
         assert!(result.unwrap_err().contains("Timeout"));
     }
@@ -1778,10 +1850,12 @@ mod tests {
             worker_startup_timeout_secs: 5,
             worker_startup_check_interval_secs: 1,
             intra_node_data_parallel_size: 1,
+            dp_discovery: DpDiscoveryConfig::default(),
             api_key: None,
             client: Client::new(),
             retry_config: RetryConfig::default(),
             circuit_breaker_config: CircuitBreakerConfig::default(),
+            lora_manager: None,
             _worker_loads: Arc::new(rx),
             _load_monitor_handle: None,
         }
@@ -1834,7 +1908,12 @@ mod tests {
         let mut selected_urls: Vec<String> = Vec::new();
         for _ in 0..10 {
             let worker = router
-                .select_worker_for_model(None, Some(r#"{"prompt": "test"}"#), Some(&header_map))
+                .select_worker_for_model(
+                    None,
+                    Some(r#"{"prompt": "test"}"#),
+                    Some(&header_map),
+                    &RequestRequirements::default(),
+                )
                 .expect("Should select a worker");
             selected_urls.push(worker.url().to_string());
         }
@@ -1864,7 +1943,12 @@ mod tests {
         }
 
         let worker = router
-            .select_worker_for_model(None, Some(r#"{"prompt": "test"}"#), None)
+            .select_worker_for_model(
+                None,
+                Some(r#"{"prompt": "test"}"#),
+                None,
+                &RequestRequirements::default(),
+            )
             .expect("Should select the remaining healthy worker");
 
         assert_eq!(
@@ -1885,13 +1969,93 @@ mod tests {
             w.set_healthy(false);
         }
 
-        let result = router.select_worker_for_model(None, Some(r#"{"prompt": "test"}"#), None);
+        let result = router.select_worker_for_model(
+            None,
+            Some(r#"{"prompt": "test"}"#),
+            None,
+            &RequestRequirements::default(),
+        );
         assert!(
             result.is_none(),
             "Should return None when all workers are unavailable"
         );
     }
 
+    #[test]
+    fn test_select_worker_for_model_filters_by_capabilities() {
+        // A long prompt must skip short-context workers and embeddings must skip
+        // generation-only workers
+        let router = create_test_consistent_hash_router();
+        for w in router.worker_registry.get_all() {
+            let mut caps = WorkerCapabilities::default();
+            if w.url() == "http://worker1:8080" {
+                caps.max_model_len = Some(131072);
+                caps.endpoints.insert(WorkerEndpoint::Generation);
+            } else if w.url() == "http://worker2:8080" {
+                caps.max_model_len = Some(32768);
+                caps.endpoints.insert(WorkerEndpoint::Generation);
+            } else {
+                caps.endpoints.insert(WorkerEndpoint::Embeddings);
+            }
+            w.set_capabilities(caps);
+        }
+
+        let long_prompt = RequestRequirements {
+            endpoint: WorkerEndpoint::Generation,
+            prompt_tokens: Some(100_000),
+            ..Default::default()
+        };
+        for _ in 0..10 {
+            let worker = router
+                .select_worker_for_model(None, Some("long"), None, &long_prompt)
+                .expect("Should select the long-context worker");
+            assert_eq!(worker.url(), "http://worker1:8080");
+        }
+
+        let embeddings = RequestRequirements::for_route("/v1/embeddings", Some("hi"));
+        for _ in 0..10 {
+            let worker = router
+                .select_worker_for_model(None, Some("hi"), None, &embeddings)
+                .expect("Should select the embeddings worker");
+            assert_eq!(worker.url(), "http://worker3:8080");
+        }
+
+        let too_long = RequestRequirements {
+            endpoint: WorkerEndpoint::Generation,
+            prompt_tokens: Some(200_000),
+            ..Default::default()
+        };
+        assert!(router
+            .select_worker_for_model(None, Some("long"), None, &too_long)
+            .is_none());
+    }
+
+    #[test]
+    fn test_select_worker_for_model_prefers_loaded_lora_adapter() {
+        let router = create_test_consistent_hash_router();
+        let worker2 = router
+            .worker_registry
+            .get_by_url("http://worker2:8080")
+            .unwrap();
+        let mut caps = worker2.capabilities();
+        caps.lora_adapters.insert("sql-lora".to_string());
+        worker2.set_capabilities(caps);
+
+        let requirements = RequestRequirements::for_route("/v1/completions", Some("hi"))
+            .with_lora_adapter(Some("sql-lora".to_string()));
+        for i in 0..20 {
+            let mut header_map = HeaderMap::new();
+            header_map.insert(
+                "x-session-id",
+                HeaderValue::from_str(&format!("session-{}", i)).unwrap(),
+            );
+            let worker = router
+                .select_worker_for_model(None, Some("hi"), Some(&header_map), &requirements)
+                .expect("Should select a worker");
+            assert_eq!(worker.url(), "http://worker2:8080");
+        }
+    }
+
     #[test]
     fn test_consistent_hash_different_sessions_can_route_differently() {
         // Verify that different session IDs can route to different workers
@@ -1907,6 +2071,7 @@ mod tests {
                 None,
                 Some(r#"{"prompt": "test"}"#),
                 Some(&header_map),
+                &RequestRequirements::default(),
             ) {
                 worker_urls_seen.insert(worker.url().to_string());
             }
@@ -1968,7 +2133,7 @@ mod tests {
     #[tokio::test]
     async fn test_wait_for_healthy_workers_all_healthy() {
         let (url, _handle) = start_healthy_mock_server().await;
-        let result = Router::wait_for_healthy_workers(&[url], 5, 1).await;
+        let result = Router::wait_for_healthy_workers(&Client::new(), &[url], 5, 1).await;
         assert!(result.is_ok());
     }
 
@@ -1979,7 +2144,9 @@ mod tests {
         let (healthy_url, _handle) = start_healthy_mock_server().await;
         let unreachable_url = "http://127.0.0.1:1".to_string(); // port 1 is unreachable
 
-        let result = Router::wait_for_healthy_workers(&[healthy_url, unreachable_url], 5, 1).await;
+        let result =
+            Router::wait_for_healthy_workers(&Client::new(), &[healthy_url, unreachable_url], 5, 1)
+                .await;
         assert!(result.is_ok());
     }
 
@@ -2032,7 +2199,7 @@ mod tests {
             .map(|rank| format!("{}@{}", base_url, rank))
             .collect();
 
-        let result = Router::wait_for_healthy_workers(&dp_urls, 5, 1).await;
+        let result = Router::wait_for_healthy_workers(&Client::new(), &dp_urls, 5, 1).await;
         assert!(result.is_ok());
     }
 
@@ -2058,9 +2225,13 @@ mod tests {
         // ── Step 1: wait_for_healthy_workers (mirrors PDRouter::new startup) ──
         // This succeeds because the healthy worker responds immediately,
         // even though the delayed worker is still returning 503.
-        let result =
-            Router::wait_for_healthy_workers(&[healthy_url.clone(), delayed_url.clone()], 10, 1)
-                .await;
+        let result = Router::wait_for_healthy_workers(
+            &Client::new(),
+            &[healthy_url.clone(), delayed_url.clone()],
+            10,
+            1,
+        )
+        .await;
         assert!(
             result.is_ok(),
             "Startup should succeed with at least one healthy worker"
@@ -2102,7 +2273,7 @@ mod tests {
         );
 
         // ── Step 3: start background health checker (mirrors PDRouter::new) ──
-        let health_checker = registry.start_health_checker(1);
+        let health_checker = registry.start_health_checker(1, reqwest::Client::new());
 
         // ── Step 4: wait for delayed worker to recover ──
         // The mock server switches to 200 at t≈2s. With a 1s check interval
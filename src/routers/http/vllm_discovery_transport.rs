@@ -0,0 +1,346 @@
+//! Transports vLLM instances use to register with the service registry
+//!
+//! A transport binds the discovery address and feeds every registration or
+//! heartbeat it receives into a [`RegistrationSink`]. Expiry is handled by the
+//! registry, so a transport only has to deliver messages.
+//!
+//! ZMQ registrations use either libzmq (`zmq-native` feature) or a pure-Rust
+//! implementation (`zmq-pure` feature, preferred when both are enabled). The
+//! HTTP transport needs neither.
+
+use super::vllm_service_discovery::{RegistrationSink, ServiceRegistration};
+use crate::config::VllmDiscoveryTransport;
+use async_trait::async_trait;
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::post,
+    Json,
+};
+use std::fmt;
+use tokio::sync::broadcast;
+use tracing::{info, warn};
+
+/// Source of service registrations
+#[async_trait]
+pub trait DiscoveryTransport: Send + Sync + fmt::Debug {
+    /// Transport name used in logs
+    fn name(&self) -> &'static str;
+
+    /// Bind `address` and deliver registrations to `sink` until `shutdown` fires
+    ///
+    /// Returns once bound, so bind errors reach the caller.
+    async fn start(
+        &self,
+        address: &str,
+        sink: RegistrationSink,
+        shutdown: broadcast::Receiver<()>,
+    ) -> Result<(), String>;
+}
+
+/// Transport for a configured kind
+pub fn transport_for(kind: VllmDiscoveryTransport) -> Result<Box<dyn DiscoveryTransport>, String> {
+    match kind {
+        VllmDiscoveryTransport::Http => Ok(Box::new(HttpTransport)),
+        VllmDiscoveryTransport::Zmq => zmq_transport(),
+    }
+}
+
+#[cfg(any(feature = "zmq-native", feature = "zmq-pure"))]
+fn zmq_transport() -> Result<Box<dyn DiscoveryTransport>, String> {
+    Ok(Box::new(ZmqTransport))
+}
+
+#[cfg(not(any(feature = "zmq-native", feature = "zmq-pure")))]
+fn zmq_transport() -> Result<Box<dyn DiscoveryTransport>, String> {
+    Err("ZMQ discovery requires the zmq-native or zmq-pure feature".to_string())
+}
+
+/// MessagePack registrations on a ZMQ ROUTER socket
+#[cfg(any(feature = "zmq-native", feature = "zmq-pure"))]
+#[derive(Debug, Default)]
+pub struct ZmqTransport;
+
+#[cfg(feature = "zmq-pure")]
+#[async_trait]
+impl DiscoveryTransport for ZmqTransport {
+    fn name(&self) -> &'static str {
+        "zmq"
+    }
+
+    async fn start(
+        &self,
+        address: &str,
+        sink: RegistrationSink,
+        mut shutdown: broadcast::Receiver<()>,
+    ) -> Result<(), String> {
+        use zeromq::{Socket, SocketRecv};
+
+        let mut socket = zeromq::RouterSocket::new();
+        socket
+            .bind(&format!("tcp://{}", address))
+            .await
+            .map_err(|e| format!("Failed to bind ZMQ socket to {}: {}", address, e))?;
+        info!("ZMQ service discovery bound to tcp://{}", address);
+
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = shutdown.recv() => {
+                        info!("Service discovery shutting down");
+                        break;
+                    }
+                    message = socket.recv() => match message {
+                        Ok(message) => {
+                            // ROUTER sockets prefix the sender's identity
+                            if let (Some(identity), Some(data)) = (message.get(0), message.get(1)) {
+                                sink.register_msgpack(data, &String::from_utf8_lossy(identity));
+                            }
+                        }
+                        Err(e) => {
+                            warn!("ZMQ receive error: {}", e);
+                            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+                        }
+                    },
+                }
+            }
+        });
+        Ok(())
+    }
+}
+
+#[cfg(all(feature = "zmq-native", not(feature = "zmq-pure")))]
+#[async_trait]
+impl DiscoveryTransport for ZmqTransport {
+    fn name(&self) -> &'static str {
+        "zmq"
+    }
+
+    async fn start(
+        &self,
+        address: &str,
+        sink: RegistrationSink,
+        mut shutdown: broadcast::Receiver<()>,
+    ) -> Result<(), String> {
+        let context = zmq::Context::new();
+        let router_socket = context
+            .socket(zmq::ROUTER)
+            .map_err(|e| format!("Failed to create ZMQ socket: {}", e))?;
+        router_socket
+            .bind(&format!("tcp://{}", address))
+            .map_err(|e| format!("Failed to bind ZMQ socket to {}: {}", address, e))?;
+        info!("ZMQ service discovery bound to tcp://{}", address);
+
+        tokio::spawn(async move {
+            loop {
+                // Check for shutdown signal
+                if shutdown.try_recv().is_ok() {
+                    info!("Service discovery shutting down");
+                    break;
+                }
+
+                match router_socket.recv_multipart(zmq::DONTWAIT) {
+                    Ok(message_parts) => {
+                        if message_parts.len() >= 2 {
+                            sink.register_msgpack(
+                                &message_parts[1],
+                                &String::from_utf8_lossy(&message_parts[0]),
+                            );
+                        }
+                    }
+                    Err(zmq::Error::EAGAIN) => {
+                        // No message available, continue
+                        tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+                    }
+                    Err(e) => {
+                        warn!("ZMQ receive error: {}", e);
+                        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+                    }
+                }
+            }
+        });
+        Ok(())
+    }
+}
+
+/// JSON registrations via `POST /register`
+#[derive(Debug, Default)]
+pub struct HttpTransport;
+
+#[async_trait]
+impl DiscoveryTransport for HttpTransport {
+    fn name(&self) -> &'static str {
+        "http"
+    }
+
+    async fn start(
+        &self,
+        address: &str,
+        sink: RegistrationSink,
+        mut shutdown: broadcast::Receiver<()>,
+    ) -> Result<(), String> {
+        let listener = tokio::net::TcpListener::bind(address)
+            .await
+            .map_err(|e| format!("Failed to bind discovery listener to {}: {}", address, e))?;
+        info!("HTTP service discovery listening on {}", address);
+
+        let app = axum::Router::new()
+            .route("/register", post(register))
+            .with_state(sink);
+        tokio::spawn(async move {
+            let shutdown = async move {
+                let _ = shutdown.recv().await;
+            };
+            if let Err(e) = axum::serve(listener, app)
+                .with_graceful_shutdown(shutdown)
+                .await
+            {
+                warn!("HTTP service discovery stopped: {}", e);
+            }
+        });
+        Ok(())
+    }
+}
+
+async fn register(
+    State(sink): State<RegistrationSink>,
+    Json(registration): Json<ServiceRegistration>,
+) -> Response {
+    match sink.register(&registration) {
+        Ok(()) => StatusCode::OK.into_response(),
+        Err(e) => (StatusCode::BAD_REQUEST, e).into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routers::http::vllm_service_discovery::{ServiceRegistry, ServiceType};
+    use std::time::Duration;
+
+    fn free_address() -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap().to_string()
+    }
+
+    async fn wait_for_instances(registry: &ServiceRegistry, expected: (usize, usize)) {
+        for _ in 0..100 {
+            if registry.get_instance_counts() == expected {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!(
+            "expected {:?} instances, found {:?}",
+            expected,
+            registry.get_instance_counts()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_http_register_heartbeats() {
+        let address = free_address();
+        let mut registry = ServiceRegistry::new();
+        registry
+            .start_listener(&HttpTransport, &address)
+            .await
+            .unwrap();
+
+        let client = reqwest::Client::new();
+        let url = format!("http://{}/register", address);
+        let response = client
+            .post(&url)
+            .json(&serde_json::json!({
+                "type": "P",
+                "http_address": "10.0.0.1:8000",
+                "zmq_address": "10.0.0.1:5555"
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // The ZMQ address is optional for HTTP registrations
+        let response = client
+            .post(&url)
+            .json(&serde_json::json!({"type": "D", "http_address": "10.0.0.2:8000"}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = client
+            .post(&url)
+            .json(&serde_json::json!({"type": "X", "http_address": "10.0.0.3:8000"}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        wait_for_instances(&registry, (1, 1)).await;
+        assert_eq!(
+            registry
+                .get_zmq_address("10.0.0.1:8000", ServiceType::Prefill)
+                .as_deref(),
+            Some("10.0.0.1:5555")
+        );
+        assert!(registry
+            .get_zmq_address("10.0.0.2:8000", ServiceType::Decode)
+            .is_none());
+    }
+
+    #[cfg(feature = "zmq-pure")]
+    #[tokio::test]
+    async fn test_zmq_registration_from_in_process_publisher() {
+        use zeromq::{Socket, SocketSend};
+
+        let address = free_address();
+        let mut registry = ServiceRegistry::new();
+        registry
+            .start_listener(&ZmqTransport, &address)
+            .await
+            .unwrap();
+
+        let registration = ServiceRegistration {
+            service_type: "D".to_string(),
+            http_address: "10.0.0.2:8000".to_string(),
+            zmq_address: "10.0.0.2:5555".to_string(),
+        };
+        let mut publisher = zeromq::DealerSocket::new();
+        publisher
+            .connect(&format!("tcp://{}", address))
+            .await
+            .unwrap();
+        publisher
+            .send(rmp_serde::to_vec_named(&registration).unwrap().into())
+            .await
+            .unwrap();
+
+        wait_for_instances(&registry, (0, 1)).await;
+    }
+
+    #[cfg(all(feature = "zmq-native", not(feature = "zmq-pure")))]
+    #[tokio::test]
+    async fn test_zmq_registration_from_in_process_publisher() {
+        let address = free_address();
+        let mut registry = ServiceRegistry::new();
+        registry
+            .start_listener(&ZmqTransport, &address)
+            .await
+            .unwrap();
+
+        let registration = ServiceRegistration {
+            service_type: "D".to_string(),
+            http_address: "10.0.0.2:8000".to_string(),
+            zmq_address: "10.0.0.2:5555".to_string(),
+        };
+        let publisher = zmq::Context::new().socket(zmq::DEALER).unwrap();
+        publisher.connect(&format!("tcp://{}", address)).unwrap();
+        publisher
+            .send(rmp_serde::to_vec_named(&registration).unwrap(), 0)
+            .unwrap();
+
+        wait_for_instances(&registry, (0, 1)).await;
+    }
+}
@@ -1,16 +1,22 @@
 // vLLM Service Discovery Implementation
 // This module implements service discovery for vLLM P2P NCCL coordination
 
+use super::vllm_discovery_transport::DiscoveryTransport;
+use crate::core::{BasicWorker, CircuitBreakerConfig, Worker, WorkerRegistry, WorkerType};
+use crate::policies::{CacheAwarePolicy, LoadBalancingPolicy, PolicyRegistry};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::sync::broadcast;
 use tracing::{debug, info, warn};
 
 /// Default ping timeout in seconds
 const DEFAULT_PING_SECONDS: u64 = 5;
 
+/// Worker label holding a discovered instance's ZMQ address
+pub const ZMQ_ADDRESS_LABEL: &str = "zmq_address";
+
 /// Service type for registration
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ServiceType {
@@ -33,6 +39,8 @@ pub struct ServiceRegistration {
     #[serde(rename = "type")]
     pub service_type: String, // "P" or "D"
     pub http_address: String,
+    /// Empty for instances that only register over HTTP (e.g. NIXL)
+    #[serde(default)]
     pub zmq_address: String,
 }
 
@@ -43,108 +51,110 @@ pub struct ServiceInstance {
     pub expires_at: u64, // Unix timestamp
 }
 
-/// Service registry maintaining prefill and decode instances
-#[derive(Debug)]
-pub struct ServiceRegistry {
-    prefill_instances: Arc<Mutex<HashMap<String, ServiceInstance>>>,
-    decode_instances: Arc<Mutex<HashMap<String, ServiceInstance>>>,
-    shutdown_tx: Option<broadcast::Sender<()>>,
+/// Worker URL for a registered HTTP address
+pub fn worker_url(http_address: &str) -> String {
+    if http_address.starts_with("http://") || http_address.starts_with("https://") {
+        http_address.to_string()
+    } else {
+        format!("http://{}", http_address)
+    }
 }
 
-impl Default for ServiceRegistry {
-    fn default() -> Self {
-        Self::new()
-    }
+/// Mirrors registrations into the worker registry as long-lived workers, so
+/// load, circuit breaker and cache-aware state carry across requests
+#[derive(Debug, Clone)]
+pub struct WorkerRegistrySync {
+    worker_registry: Arc<WorkerRegistry>,
+    policy_registry: Arc<PolicyRegistry>,
+    circuit_breaker_config: CircuitBreakerConfig,
 }
 
-impl ServiceRegistry {
-    /// Create a new service registry
-    pub fn new() -> Self {
+impl WorkerRegistrySync {
+    pub fn new(
+        worker_registry: Arc<WorkerRegistry>,
+        policy_registry: Arc<PolicyRegistry>,
+        circuit_breaker_config: CircuitBreakerConfig,
+    ) -> Self {
         Self {
-            prefill_instances: Arc::new(Mutex::new(HashMap::new())),
-            decode_instances: Arc::new(Mutex::new(HashMap::new())),
-            shutdown_tx: None,
+            worker_registry,
+            policy_registry,
+            circuit_breaker_config,
         }
     }
 
-    /// Start the ZMQ service discovery listener
-    pub async fn start_listener(&mut self, bind_address: &str) -> Result<(), String> {
-        info!(
-            "Starting vLLM service discovery listener on {}",
-            bind_address
-        );
-
-        let (shutdown_tx, mut shutdown_rx) = broadcast::channel(1);
-        self.shutdown_tx = Some(shutdown_tx);
-
-        let prefill_instances = Arc::clone(&self.prefill_instances);
-        let decode_instances = Arc::clone(&self.decode_instances);
-        let bind_addr = bind_address.to_string();
-
-        tokio::spawn(async move {
-            // Initialize ZMQ context and socket
-            let context = zmq::Context::new();
-            let router_socket = context.socket(zmq::ROUTER).unwrap();
-
-            if let Err(e) = router_socket.bind(&format!("tcp://{}", bind_addr)) {
-                warn!("Failed to bind ZMQ socket to {}: {}", bind_addr, e);
+    /// Add a worker for a new registration; heartbeats of a known instance are no-ops
+    fn add(&self, http_address: &str, zmq_address: &str, service_type: &ServiceType) {
+        let url = worker_url(http_address);
+        if let Some(existing) = self.worker_registry.get_by_url(&url) {
+            let known_zmq = existing.metadata().labels.get(ZMQ_ADDRESS_LABEL);
+            if known_zmq.map_or("", String::as_str) == zmq_address {
                 return;
             }
+            // Re-registered with a new ZMQ address, e.g. after a restart
+            self.remove(http_address, service_type);
+        }
 
-            info!("ZMQ service discovery bound to tcp://{}", bind_addr);
-
-            // Set non-blocking mode for graceful shutdown
-            router_socket.set_rcvtimeo(1000).unwrap(); // 1 second timeout
-
-            loop {
-                // Check for shutdown signal
-                if shutdown_rx.try_recv().is_ok() {
-                    info!("Service discovery shutting down");
-                    break;
-                }
-
-                // Try to receive a message
-                match router_socket.recv_multipart(zmq::DONTWAIT) {
-                    Ok(message_parts) => {
-                        if message_parts.len() >= 2 {
-                            let remote_address = message_parts[0].clone();
-                            let message_data = &message_parts[1];
-
-                            Self::handle_registration_message(
-                                message_data,
-                                &remote_address,
-                                &prefill_instances,
-                                &decode_instances,
-                            )
-                            .await;
-                        }
-                    }
-                    Err(zmq::Error::EAGAIN) => {
-                        // No message available, continue
-                        tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
-                    }
-                    Err(e) => {
-                        warn!("ZMQ receive error: {}", e);
-                        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-                    }
-                }
+        let worker_type = match service_type {
+            ServiceType::Prefill => WorkerType::Prefill {
+                bootstrap_port: None,
+            },
+            ServiceType::Decode => WorkerType::Decode,
+        };
+        let mut labels = HashMap::new();
+        if !zmq_address.is_empty() {
+            labels.insert(ZMQ_ADDRESS_LABEL.to_string(), zmq_address.to_string());
+        }
+        let worker = BasicWorker::new(url, worker_type)
+            .with_circuit_breaker_config(self.circuit_breaker_config.clone())
+            .with_labels(labels);
+        self.worker_registry.register(Arc::new(worker));
+
+        let (policy, workers) = self.role(service_type);
+        if policy.requires_initialization() {
+            policy.init_workers(&workers);
+        }
+    }
 
-                // Clean up expired instances periodically
-                Self::cleanup_expired_instances(&prefill_instances, &decode_instances).await;
-            }
-        });
+    /// Remove the worker of an expired registration
+    fn remove(&self, http_address: &str, service_type: &ServiceType) {
+        let url = worker_url(http_address);
+        if self.worker_registry.remove_by_url(&url).is_none() {
+            return;
+        }
+        let (policy, _) = self.role(service_type);
+        if let Some(cache_aware) = policy.as_any().downcast_ref::<CacheAwarePolicy>() {
+            cache_aware.remove_worker_by_url(&url);
+        }
+    }
 
-        Ok(())
+    fn role(
+        &self,
+        service_type: &ServiceType,
+    ) -> (Arc<dyn LoadBalancingPolicy>, Vec<Arc<dyn Worker>>) {
+        match service_type {
+            ServiceType::Prefill => (
+                self.policy_registry.get_prefill_policy(),
+                self.worker_registry.get_prefill_workers(),
+            ),
+            ServiceType::Decode => (
+                self.policy_registry.get_decode_policy(),
+                self.worker_registry.get_decode_workers(),
+            ),
+        }
     }
+}
 
-    /// Handle incoming service registration message
-    async fn handle_registration_message(
-        message_data: &[u8],
-        remote_address: &[u8],
-        prefill_instances: &Arc<Mutex<HashMap<String, ServiceInstance>>>,
-        decode_instances: &Arc<Mutex<HashMap<String, ServiceInstance>>>,
-    ) {
-        // Parse MessagePack data
+/// Handle through which discovery transports record registrations
+#[derive(Debug, Clone, Default)]
+pub struct RegistrationSink {
+    prefill_instances: Arc<Mutex<HashMap<String, ServiceInstance>>>,
+    decode_instances: Arc<Mutex<HashMap<String, ServiceInstance>>>,
+    worker_sync: Option<WorkerRegistrySync>,
+}
+
+impl RegistrationSink {
+    /// Record a MessagePack-encoded registration, as sent over ZMQ
+    pub fn register_msgpack(&self, message_data: &[u8], remote_address: &str) {
         let data: ServiceRegistration = match rmp_serde::from_slice(message_data) {
             Ok(data) => data,
             Err(e) => {
@@ -152,7 +162,18 @@ impl ServiceRegistry {
                 return;
             }
         };
+        if let Err(e) = self.register(&data) {
+            warn!("{} from {}", e, remote_address);
+        }
+    }
 
+    /// Record a registration or heartbeat, extending the instance's lease
+    pub fn register(&self, data: &ServiceRegistration) -> Result<(), String> {
+        let service_type = match data.service_type.as_str() {
+            "P" => ServiceType::Prefill,
+            "D" => ServiceType::Decode,
+            other => return Err(format!("Unknown service type '{}'", other)),
+        };
         let current_time = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap()
@@ -163,99 +184,119 @@ impl ServiceRegistry {
             expires_at: current_time + DEFAULT_PING_SECONDS,
         };
 
-        let remote_addr_str = String::from_utf8_lossy(remote_address);
-
-        match data.service_type.as_str() {
-            "P" => {
-                let mut prefill = prefill_instances.lock().unwrap();
-                let is_new = !prefill.contains_key(&data.http_address);
-                prefill.insert(data.http_address.clone(), instance);
-
-                if is_new {
-                    info!(
-                        "🔵Add Prefill [HTTP:{}, ZMQ:{}]",
-                        data.http_address, data.zmq_address
-                    );
-                } else {
-                    debug!(
-                        "🔄Update Prefill [HTTP:{}, ZMQ:{}]",
-                        data.http_address, data.zmq_address
-                    );
-                }
-            }
-            "D" => {
-                let mut decode = decode_instances.lock().unwrap();
-                let is_new = !decode.contains_key(&data.http_address);
-                decode.insert(data.http_address.clone(), instance);
+        let mut instances = self.instances(&service_type).lock().unwrap();
+        let is_new = !instances.contains_key(&data.http_address);
+        instances.insert(data.http_address.clone(), instance);
+        if let Some(sync) = &self.worker_sync {
+            sync.add(&data.http_address, &data.zmq_address, &service_type);
+        }
 
-                if is_new {
-                    info!(
-                        "🔵Add Decode [HTTP:{}, ZMQ:{}]",
-                        data.http_address, data.zmq_address
-                    );
-                } else {
-                    debug!(
-                        "🔄Update Decode [HTTP:{}, ZMQ:{}]",
-                        data.http_address, data.zmq_address
-                    );
-                }
-            }
-            _ => {
-                warn!(
-                    "Unknown service type '{}' from {}",
-                    data.service_type, remote_addr_str
-                );
-            }
+        if is_new {
+            info!(
+                "🔵Add {:?} [HTTP:{}, ZMQ:{}]",
+                service_type, data.http_address, data.zmq_address
+            );
+        } else {
+            debug!(
+                "🔄Update {:?} [HTTP:{}, ZMQ:{}]",
+                service_type, data.http_address, data.zmq_address
+            );
         }
+        Ok(())
     }
 
-    /// Clean up expired service instances
-    async fn cleanup_expired_instances(
-        prefill_instances: &Arc<Mutex<HashMap<String, ServiceInstance>>>,
-        decode_instances: &Arc<Mutex<HashMap<String, ServiceInstance>>>,
-    ) {
+    /// Remove instances whose heartbeats have stopped
+    pub fn cleanup_expired(&self) {
         let current_time = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap()
             .as_secs();
 
-        // Clean prefill instances
-        {
-            let mut prefill = prefill_instances.lock().unwrap();
-            let expired_keys: Vec<_> = prefill
+        for service_type in [ServiceType::Prefill, ServiceType::Decode] {
+            let mut instances = self.instances(&service_type).lock().unwrap();
+            let expired_keys: Vec<_> = instances
                 .iter()
                 .filter(|(_, instance)| instance.expires_at <= current_time)
                 .map(|(key, _)| key.clone())
                 .collect();
 
             for key in expired_keys {
-                if let Some(instance) = prefill.remove(&key) {
+                if let Some(instance) = instances.remove(&key) {
+                    if let Some(sync) = &self.worker_sync {
+                        sync.remove(&key, &service_type);
+                    }
                     info!(
-                        "🔴Remove Prefill [HTTP:{}, ZMQ:{}, expired]",
-                        key, instance.zmq_address
+                        "🔴Remove {:?} [HTTP:{}, ZMQ:{}, expired]",
+                        service_type, key, instance.zmq_address
                     );
                 }
             }
         }
+    }
 
-        // Clean decode instances
-        {
-            let mut decode = decode_instances.lock().unwrap();
-            let expired_keys: Vec<_> = decode
-                .iter()
-                .filter(|(_, instance)| instance.expires_at <= current_time)
-                .map(|(key, _)| key.clone())
-                .collect();
+    fn instances(&self, service_type: &ServiceType) -> &Mutex<HashMap<String, ServiceInstance>> {
+        match service_type {
+            ServiceType::Prefill => &self.prefill_instances,
+            ServiceType::Decode => &self.decode_instances,
+        }
+    }
+}
 
-            for key in expired_keys {
-                if let Some(instance) = decode.remove(&key) {
-                    info!(
-                        "🔴Remove Decode [HTTP:{}, ZMQ:{}, expired]",
-                        key, instance.zmq_address
-                    );
+/// Service registry maintaining prefill and decode instances
+#[derive(Debug, Default)]
+pub struct ServiceRegistry {
+    sink: RegistrationSink,
+    shutdown_tx: Option<broadcast::Sender<()>>,
+}
+
+impl ServiceRegistry {
+    /// Create a new service registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep long-lived workers for registered instances in a worker registry
+    pub fn with_worker_sync(mut self, worker_sync: WorkerRegistrySync) -> Self {
+        self.sink.worker_sync = Some(worker_sync);
+        self
+    }
+
+    /// Start accepting registrations through a discovery transport
+    pub async fn start_listener(
+        &mut self,
+        transport: &dyn DiscoveryTransport,
+        bind_address: &str,
+    ) -> Result<(), String> {
+        info!(
+            "Starting vLLM service discovery listener ({}) on {}",
+            transport.name(),
+            bind_address
+        );
+
+        let (shutdown_tx, mut shutdown_rx) = broadcast::channel(1);
+        transport
+            .start(bind_address, self.sink.clone(), shutdown_tx.subscribe())
+            .await?;
+        self.shutdown_tx = Some(shutdown_tx);
+
+        // Leases expire on their own schedule, whatever the transport
+        let sink = self.sink.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(1));
+            loop {
+                tokio::select! {
+                    _ = shutdown_rx.recv() => break,
+                    _ = interval.tick() => sink.cleanup_expired(),
                 }
             }
-        }
+        });
+
+        Ok(())
+    }
+
+    /// Handle for recording registrations directly
+    pub fn sink(&self) -> RegistrationSink {
+        self.sink.clone()
     }
 
     /// Register a service manually (fallback mode)
@@ -265,61 +306,36 @@ impl ServiceRegistry {
         zmq_address: String,
         service_type: ServiceType,
     ) {
-        let current_time = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-
-        let instance = ServiceInstance {
-            zmq_address: zmq_address.clone(),
-            expires_at: current_time + DEFAULT_PING_SECONDS,
+        let registration = ServiceRegistration {
+            service_type: service_type.to_string(),
+            http_address,
+            zmq_address,
         };
-
-        match service_type {
-            ServiceType::Prefill => {
-                let mut prefill = self.prefill_instances.lock().unwrap();
-                prefill.insert(http_address.clone(), instance);
-                info!(
-                    "🔵Manual register Prefill [HTTP:{}, ZMQ:{}]",
-                    http_address, zmq_address
-                );
-            }
-            ServiceType::Decode => {
-                let mut decode = self.decode_instances.lock().unwrap();
-                decode.insert(http_address.clone(), instance);
-                info!(
-                    "🔵Manual register Decode [HTTP:{}, ZMQ:{}]",
-                    http_address, zmq_address
-                );
-            }
-        }
+        // The service type is always known here
+        let _ = self.sink.register(&registration);
     }
 
     /// Get ZMQ address for a given HTTP address
     pub fn get_zmq_address(&self, http_address: &str, service_type: ServiceType) -> Option<String> {
-        let instances = match service_type {
-            ServiceType::Prefill => &self.prefill_instances,
-            ServiceType::Decode => &self.decode_instances,
-        };
-
-        let guard = instances.lock().unwrap();
+        let guard = self.sink.instances(&service_type).lock().unwrap();
         guard
             .get(http_address)
             .map(|instance| instance.zmq_address.clone())
+            .filter(|address| !address.is_empty())
     }
 
     /// Get all available prefill instances
     pub fn get_prefill_instances(&self) -> Vec<(String, String)> {
-        let guard = self.prefill_instances.lock().unwrap();
-        guard
-            .iter()
-            .map(|(http, instance)| (http.clone(), instance.zmq_address.clone()))
-            .collect()
+        Self::instance_list(&self.sink.prefill_instances)
     }
 
     /// Get all available decode instances
     pub fn get_decode_instances(&self) -> Vec<(String, String)> {
-        let guard = self.decode_instances.lock().unwrap();
+        Self::instance_list(&self.sink.decode_instances)
+    }
+
+    fn instance_list(instances: &Mutex<HashMap<String, ServiceInstance>>) -> Vec<(String, String)> {
+        let guard = instances.lock().unwrap();
         guard
             .iter()
             .map(|(http, instance)| (http.clone(), instance.zmq_address.clone()))
@@ -328,8 +344,8 @@ impl ServiceRegistry {
 
     /// Get instance count for debugging
     pub fn get_instance_counts(&self) -> (usize, usize) {
-        let prefill_count = self.prefill_instances.lock().unwrap().len();
-        let decode_count = self.decode_instances.lock().unwrap().len();
+        let prefill_count = self.sink.prefill_instances.lock().unwrap().len();
+        let decode_count = self.sink.decode_instances.lock().unwrap().len();
         (prefill_count, decode_count)
     }
 
@@ -346,3 +362,90 @@ impl Drop for ServiceRegistry {
         self.shutdown();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PolicyConfig;
+
+    fn registry_with_sync() -> (ServiceRegistry, Arc<WorkerRegistry>) {
+        let worker_registry = Arc::new(WorkerRegistry::new());
+        let sync = WorkerRegistrySync::new(
+            Arc::clone(&worker_registry),
+            Arc::new(PolicyRegistry::new(PolicyConfig::RoundRobin)),
+            CircuitBreakerConfig::default(),
+        );
+        (
+            ServiceRegistry::new().with_worker_sync(sync),
+            worker_registry,
+        )
+    }
+
+    #[test]
+    fn test_registration_adds_long_lived_worker() {
+        let (registry, workers) = registry_with_sync();
+        registry.register_service(
+            "10.0.0.1:8000".to_string(),
+            "10.0.0.1:5555".to_string(),
+            ServiceType::Prefill,
+        );
+
+        let worker = workers.get_by_url("http://10.0.0.1:8000").unwrap();
+        assert!(matches!(worker.worker_type(), WorkerType::Prefill { .. }));
+        assert_eq!(
+            worker.metadata().labels.get(ZMQ_ADDRESS_LABEL).unwrap(),
+            "10.0.0.1:5555"
+        );
+
+        // A heartbeat keeps the same worker and its state
+        worker.increment_load();
+        registry.register_service(
+            "10.0.0.1:8000".to_string(),
+            "10.0.0.1:5555".to_string(),
+            ServiceType::Prefill,
+        );
+        let same = workers.get_by_url("http://10.0.0.1:8000").unwrap();
+        assert!(Arc::ptr_eq(&worker, &same));
+        assert_eq!(same.load(), 1);
+
+        // A new ZMQ address replaces the worker
+        registry.register_service(
+            "10.0.0.1:8000".to_string(),
+            "10.0.0.1:6666".to_string(),
+            ServiceType::Prefill,
+        );
+        let replaced = workers.get_by_url("http://10.0.0.1:8000").unwrap();
+        assert_eq!(
+            replaced.metadata().labels.get(ZMQ_ADDRESS_LABEL).unwrap(),
+            "10.0.0.1:6666"
+        );
+        assert_eq!(workers.get_prefill_workers().len(), 1);
+    }
+
+    #[test]
+    fn test_expiration_removes_worker() {
+        let (registry, workers) = registry_with_sync();
+        registry.register_service(
+            "10.0.0.1:8000".to_string(),
+            "10.0.0.1:5555".to_string(),
+            ServiceType::Prefill,
+        );
+        registry.register_service(
+            "http://10.0.0.2:8000".to_string(),
+            "10.0.0.2:5555".to_string(),
+            ServiceType::Decode,
+        );
+        assert_eq!(workers.get_decode_workers().len(), 1);
+
+        // Expire only the decode instance
+        for instance in registry.sink.decode_instances.lock().unwrap().values_mut() {
+            instance.expires_at = 0;
+        }
+        registry.sink().cleanup_expired();
+
+        assert!(workers.get_decode_workers().is_empty());
+        assert!(workers.get_by_url("http://10.0.0.2:8000").is_none());
+        assert_eq!(workers.get_prefill_workers().len(), 1);
+        assert_eq!(registry.get_instance_counts(), (1, 0));
+    }
+}
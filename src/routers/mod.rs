@@ -7,6 +7,7 @@ use axum::{
     http::{HeaderMap, Method, StatusCode},
     response::{IntoResponse, Response},
 };
+use std::collections::HashMap;
 use std::fmt::Debug;
 
 use crate::protocols::spec::{
@@ -33,6 +34,17 @@ pub trait WorkerManagement: Send + Sync {
     /// Add a worker to the router
     async fn add_worker(&self, worker_url: &str) -> Result<String, String>;
 
+    /// Add a worker carrying labels such as `model_id`, `priority` and `cost`
+    ///
+    /// Routers that do not track worker labels ignore them.
+    async fn add_worker_with_labels(
+        &self,
+        worker_url: &str,
+        _labels: HashMap<String, String>,
+    ) -> Result<String, String> {
+        self.add_worker(worker_url).await
+    }
+
     /// Remove a worker from the router
     fn remove_worker(&self, worker_url: &str);
 
@@ -480,6 +480,31 @@ impl WorkerManagement for RouterManager {
         }
     }
 
+    /// Add a worker whose labels may carry its model ID, priority and cost
+    async fn add_worker_with_labels(
+        &self,
+        worker_url: &str,
+        labels: std::collections::HashMap<String, String>,
+    ) -> Result<String, String> {
+        let config = WorkerConfigRequest {
+            url: worker_url.to_string(),
+            // Skip the server info query when the model is already known
+            model_id: labels.get("model_id").cloned(),
+            worker_type: None,
+            priority: None,
+            cost: None,
+            labels,
+            bootstrap_port: None,
+            tokenizer_path: None,
+            chat_template: None,
+        };
+
+        match self.add_worker(config).await {
+            Ok(response) => Ok(response.message),
+            Err(e) => Err(e.error),
+        }
+    }
+
     /// Remove a worker from the registry
     fn remove_worker(&self, worker_url: &str) {
         let _ = self.remove_worker_from_registry(worker_url);
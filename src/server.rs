@@ -1,42 +1,57 @@
 use crate::{
-    config::{ConnectionMode, HistoryBackend, RateMonitorConfig, RouterConfig},
-    core::{WorkerRegistry, WorkerType, rate_monitor::RateMonitor},
+    config::{
+        ConnectionMode, DiscoveryBackendKind, HistoryBackend, RateMonitorConfig, RouterConfig,
+    },
+    core::{
+        bearer_token, rate_monitor::RateMonitor, ApiKeyScope, ApiKeyStore, AuditLog,
+        CallerIdentity, ContentPolicy, ContentPolicyConfig, FilterRequest, HttpRoleActuator,
+        JwtValidator, KeyedRateLimitConfig, KeyedRateLimiter, PdCapacityController, RoleActuator,
+        ServerTls, TlsListener, TokenRateLimiter, UpstreamTls, ValidationCache, WorkerRegistry,
+        WorkerType, CONTENT_POLICY_TAGS_HEADER,
+    },
     data_connector::{MemoryResponseStorage, NoOpResponseStorage, SharedResponseStorage},
     logging::{self, LoggingConfig},
     metrics::{self, PrometheusConfig},
-    middleware::{self, QueuedRequest, TokenBucket},
+    middleware::{self, RequestQueue, TokenBucket},
     policies::PolicyRegistry,
     protocols::{
+        model_params::{self, SamplingParamsMut, PARAM_ADJUSTMENTS_HEADER},
         spec::{
             ChatCompletionRequest, CompletionRequest, EmbeddingRequest, GenerateRequest,
-            RerankRequest, ResponsesRequest, V1RerankReqInput,
+            GenerationRequest, RerankRequest, ResponsesRequest, V1RerankReqInput,
         },
         worker_spec::{WorkerApiResponse, WorkerConfigRequest, WorkerErrorResponse},
     },
     routers::{
-        RouterFactory, RouterTrait, router_manager::{RouterId, RouterManager}
+        http::dp_utils,
+        router_manager::{RouterId, RouterManager},
+        RouterFactory, RouterTrait,
+    },
+    service_discovery::{
+        endpoint_slice::start_endpoint_slice_discovery, start_polling_discovery,
+        start_service_discovery, ServiceDiscoveryConfig,
     },
-    service_discovery::{ServiceDiscoveryConfig, start_service_discovery},
     tokenizer::{factory as tokenizer_factory, traits::Tokenizer},
 };
 use axum::{
     extract::{Path, Query, Request, State},
+    handler::Handler,
     http::StatusCode,
+    middleware::Next,
     response::{IntoResponse, Response},
     routing::{delete, get, post},
-    serve, Json, Router,
+    serve, Extension, Json, Router,
 };
 use reqwest::Client;
 use serde::Deserialize;
 use serde_json::json;
 use std::{
-    collections::HashMap,
     sync::atomic::{AtomicBool, Ordering},
     sync::Arc,
     time::Duration,
 };
-use tokio::{net::TcpListener, signal, spawn, sync::RwLock};
-use tracing::{error, info, warn, Level};
+use tokio::{net::TcpListener, signal, spawn};
+use tracing::{debug, error, info, warn, Level};
 
 #[derive(Clone)]
 pub struct AppContext {
@@ -48,8 +63,17 @@ pub struct AppContext {
     pub policy_registry: Arc<PolicyRegistry>,
     pub router_manager: Option<Arc<RouterManager>>,
     pub response_storage: SharedResponseStorage,
-    pub api_key_cache: Arc<RwLock<HashMap<String, bool>>>,
+    pub api_key_cache: Arc<ValidationCache>,
     pub api_key_validation_urls: Arc<Vec<String>>,
+    pub jwt_validator: Option<Arc<JwtValidator>>,
+    pub api_key_store: Option<Arc<ApiKeyStore>>,
+    pub pd_capacity: Option<Arc<PdCapacityController>>,
+    pub keyed_rate_limiter: Option<Arc<KeyedRateLimiter>>,
+    pub token_rate_limiter: Option<Arc<TokenRateLimiter>>,
+    pub content_policy: Option<Arc<ContentPolicy>>,
+    pub audit_log: Option<Arc<AuditLog>>,
+    /// TLS settings for worker connections; `client` should be built with them
+    pub upstream_tls: UpstreamTls,
 }
 
 impl AppContext {
@@ -63,6 +87,23 @@ impl AppContext {
         let rate_limit_tokens = rate_limit_tokens_per_second.unwrap_or(max_concurrent_requests);
         let rate_limiter = Arc::new(TokenBucket::new(max_concurrent_requests, rate_limit_tokens));
 
+        let keyed_rate_limit_config = router_config
+            .rate_limit_config
+            .as_deref()
+            .map(KeyedRateLimitConfig::from_file)
+            .transpose()?;
+        let limits_tokens = keyed_rate_limit_config
+            .as_ref()
+            .is_some_and(KeyedRateLimitConfig::limits_tokens);
+        let content_policy_config = router_config
+            .content_policy_config
+            .as_deref()
+            .map(ContentPolicyConfig::from_file)
+            .transpose()?;
+        let content_policy_counts_tokens = content_policy_config
+            .as_ref()
+            .is_some_and(ContentPolicyConfig::needs_tokenizer);
+
         // Initialize gRPC-specific components only when in gRPC mode
         let tokenizer = if router_config.connection_mode == ConnectionMode::Grpc {
             // Get tokenizer path (required for gRPC mode)
@@ -80,6 +121,23 @@ impl AppContext {
                 tokenizer_factory::create_tokenizer(&tokenizer_path)
                     .map_err(|e| format!("Failed to create tokenizer: {e}"))?,
             )
+        } else if router_config.conditional_disaggregation.enabled
+            || router_config.chunked_prefill.enabled
+            || limits_tokens
+            || content_policy_counts_tokens
+        {
+            // Optional here: prompt lengths fall back to a character estimate
+            match router_config
+                .tokenizer_path
+                .clone()
+                .or_else(|| router_config.model_path.clone())
+            {
+                Some(tokenizer_path) => Some(
+                    tokenizer_factory::create_tokenizer(&tokenizer_path)
+                        .map_err(|e| format!("Failed to create tokenizer: {e}"))?,
+                ),
+                None => None,
+            }
         } else {
             // HTTP mode doesn't need tokenizer
             None
@@ -90,6 +148,99 @@ impl AppContext {
 
         let router_manager = None;
 
+        // PD capacity controller only applies to prefill/decode deployments
+        let pd_capacity = if router_config.pd_capacity.enabled && router_config.mode.is_pd_mode() {
+            let cb = router_config.effective_circuit_breaker_config();
+            let actuator = router_config.pd_capacity.actuator_path.clone().map(|path| {
+                Arc::new(HttpRoleActuator::new(
+                    client.clone(),
+                    path,
+                    router_config.api_key.clone(),
+                )) as Arc<dyn RoleActuator>
+            });
+            Some(Arc::new(
+                PdCapacityController::new(
+                    router_config.pd_capacity.clone(),
+                    worker_registry.clone(),
+                    policy_registry.clone(),
+                    crate::core::CircuitBreakerConfig {
+                        failure_threshold: cb.failure_threshold,
+                        success_threshold: cb.success_threshold,
+                        timeout_duration: Duration::from_secs(cb.timeout_duration_secs),
+                        window_duration: Duration::from_secs(cb.window_duration_secs),
+                    },
+                    actuator,
+                )
+                // Bootstrap connectors send the prefill worker's port with every request
+                .with_bootstrap_protocol(
+                    router_config
+                        .kv_transfer_protocol
+                        .uses_bootstrap(router_config.mode.is_vllm_pd_mode()),
+                ),
+            ))
+        } else {
+            None
+        };
+
+        let (keyed_rate_limiter, token_rate_limiter) = match keyed_rate_limit_config {
+            Some(config) => {
+                let token_rate_limiter = if limits_tokens {
+                    Some(Arc::new(TokenRateLimiter::new(config.clone())?))
+                } else {
+                    None
+                };
+                (
+                    Some(Arc::new(KeyedRateLimiter::new(config)?)),
+                    token_rate_limiter,
+                )
+            }
+            None => (None, None),
+        };
+
+        // JWKS, classifier and audit endpoints are not workers: they get a plain
+        // client so the worker mTLS identity and CA pinning never reach them
+        let external_client = Client::builder()
+            .connect_timeout(Duration::from_secs(10))
+            .timeout(Duration::from_secs(router_config.request_timeout_secs))
+            .build()
+            .map_err(|e| format!("Failed to create HTTP client: {e}"))?;
+        let jwt_validator = router_config
+            .jwt
+            .clone()
+            .map(|config| JwtValidator::new(config, external_client.clone()).map(Arc::new))
+            .transpose()?;
+        let api_key_cache = Arc::new(ValidationCache::new(&router_config.api_key_cache));
+        let api_key_store = router_config
+            .api_keys_file
+            .as_deref()
+            .map(|path| ApiKeyStore::load(path).map(Arc::new))
+            .transpose()?;
+        if let (Some(store), Some(limiter)) = (&api_key_store, &keyed_rate_limiter) {
+            for api_key in store.entries() {
+                if let Some(class) = &api_key.rate_limit_class {
+                    if !limiter.config().classes.contains_key(class) {
+                        return Err(format!(
+                            "API key of '{}' references unknown rate limit class '{}'",
+                            api_key.owner, class
+                        ));
+                    }
+                }
+            }
+        }
+
+        let content_policy = content_policy_config
+            .map(|config| {
+                ContentPolicy::from_config(&config, tokenizer.clone(), external_client.clone())
+                    .map(Arc::new)
+            })
+            .transpose()?;
+
+        let audit_log = if router_config.audit.is_enabled() {
+            Some(AuditLog::start(&router_config.audit, external_client)?)
+        } else {
+            None
+        };
+
         // Initialize response storage based on configuration
         let response_storage: SharedResponseStorage = match router_config.history_backend {
             HistoryBackend::Memory => Arc::new(MemoryResponseStorage::new()),
@@ -105,8 +256,16 @@ impl AppContext {
             policy_registry,
             router_manager,
             response_storage,
-            api_key_cache: Arc::new(RwLock::new(HashMap::new())),
+            api_key_cache,
             api_key_validation_urls: Arc::new(api_key_validation_urls),
+            jwt_validator,
+            api_key_store,
+            pd_capacity,
+            keyed_rate_limiter,
+            token_rate_limiter,
+            content_policy,
+            audit_log,
+            upstream_tls: UpstreamTls::default(),
         })
     }
 }
@@ -115,7 +274,7 @@ impl AppContext {
 pub struct AppState {
     pub router: Arc<dyn RouterTrait>,
     pub context: Arc<AppContext>,
-    pub concurrency_queue_tx: Option<tokio::sync::mpsc::Sender<QueuedRequest>>,
+    pub request_queue: Option<Arc<RequestQueue>>,
     pub router_manager: Option<Arc<RouterManager>>,
     pub rate_monitor: Arc<RateMonitor>,
 }
@@ -127,14 +286,13 @@ async fn sink_handler() -> Response {
 
 /// Transparent proxy handler for unmatched routes
 /// Routes requests through the router's route_transparent method
-async fn transparent_proxy_handler(State(state): State<Arc<AppState>>, req: Request) -> Response {
+async fn transparent_proxy_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(caller): Extension<CallerIdentity>,
+    req: Request,
+) -> Response {
     let headers = req.headers().clone();
 
-    // Check authorization
-    if let Err(response) = authorize_request(&state, &headers).await {
-        return response;
-    }
-
     // Extract path and method
     let path = req.uri().path().to_string();
     let method = req.method().clone();
@@ -164,6 +322,11 @@ async fn transparent_proxy_handler(State(state): State<Arc<AppState>>, req: Requ
         }
     };
 
+    let model = body_json.get("model").and_then(|model| model.as_str());
+    if let Some(response) = model_forbidden(&caller, model) {
+        return response;
+    }
+
     // Route through transparent proxy
     state
         .router
@@ -172,66 +335,31 @@ async fn transparent_proxy_handler(State(state): State<Arc<AppState>>, req: Requ
 }
 
 // Health check endpoints
-async fn liveness(State(state): State<Arc<AppState>>, req: Request) -> Response {
-    let headers = req.headers().clone();
-    if let Err(response) = authorize_request(&state, &headers).await {
-        return response;
-    }
-
+async fn liveness(State(state): State<Arc<AppState>>) -> Response {
     state.router.liveness()
 }
 
-async fn readiness(State(state): State<Arc<AppState>>, req: Request) -> Response {
-    let headers = req.headers().clone();
-    if let Err(response) = authorize_request(&state, &headers).await {
-        return response;
-    }
-
+async fn readiness(State(state): State<Arc<AppState>>) -> Response {
     state.router.readiness()
 }
 
 async fn health(State(state): State<Arc<AppState>>, req: Request) -> Response {
-    let headers = req.headers().clone();
-    if let Err(response) = authorize_request(&state, &headers).await {
-        return response;
-    }
-
     state.router.health(req).await
 }
 
 async fn health_generate(State(state): State<Arc<AppState>>, req: Request) -> Response {
-    let headers = req.headers().clone();
-    if let Err(response) = authorize_request(&state, &headers).await {
-        return response;
-    }
-
     state.router.health_generate(req).await
 }
 
 async fn get_server_info(State(state): State<Arc<AppState>>, req: Request) -> Response {
-    let headers = req.headers().clone();
-    if let Err(response) = authorize_request(&state, &headers).await {
-        return response;
-    }
-
     state.router.get_server_info(req).await
 }
 
 async fn v1_models(State(state): State<Arc<AppState>>, req: Request) -> Response {
-    let headers = req.headers().clone();
-    if let Err(response) = authorize_request(&state, &headers).await {
-        return response;
-    }
-
     state.router.get_models(req).await
 }
 
 async fn get_model_info(State(state): State<Arc<AppState>>, req: Request) -> Response {
-    let headers = req.headers().clone();
-    if let Err(response) = authorize_request(&state, &headers).await {
-        return response;
-    }
-
     state.router.get_model_info(req).await
 }
 
@@ -240,9 +368,10 @@ async fn get_model_info(State(state): State<Arc<AppState>>, req: Request) -> Res
 async fn generate(
     State(state): State<Arc<AppState>>,
     headers: http::HeaderMap,
+    Extension(caller): Extension<CallerIdentity>,
     Json(body): Json<GenerateRequest>,
 ) -> Response {
-    if let Err(response) = authorize_request(&state, &headers).await {
+    if let Some(response) = model_forbidden(&caller, body.get_model()) {
         return response;
     }
 
@@ -254,37 +383,61 @@ async fn generate(
 
 async fn v1_chat_completions(
     State(state): State<Arc<AppState>>,
-    headers: http::HeaderMap,
-    Json(body): Json<ChatCompletionRequest>,
+    mut headers: http::HeaderMap,
+    Extension(caller): Extension<CallerIdentity>,
+    Json(mut body): Json<ChatCompletionRequest>,
 ) -> Response {
-    if let Err(response) = authorize_request(&state, &headers).await {
+    if let Some(response) = model_forbidden(&caller, body.get_model()) {
         return response;
     }
+    let tags =
+        match apply_content_policy(&state, &mut headers, FilterRequest::Chat(&mut body)).await {
+            Ok(tags) => tags,
+            Err(response) => return response,
+        };
+    let adjustments = apply_model_params(&state, &mut body);
 
-    state.router.route_chat(Some(&headers), &body, None).await
+    let response = state.router.route_chat(Some(&headers), &body, None).await;
+    let response = with_header(response, CONTENT_POLICY_TAGS_HEADER, tags);
+    with_header(response, PARAM_ADJUSTMENTS_HEADER, adjustments)
 }
 
 async fn v1_completions(
     State(state): State<Arc<AppState>>,
-    headers: http::HeaderMap,
-    Json(body): Json<CompletionRequest>,
+    mut headers: http::HeaderMap,
+    Extension(caller): Extension<CallerIdentity>,
+    Json(mut body): Json<CompletionRequest>,
 ) -> Response {
-    if let Err(response) = authorize_request(&state, &headers).await {
+    if let Some(response) = model_forbidden(&caller, body.get_model()) {
         return response;
     }
+    let tags = match apply_content_policy(
+        &state,
+        &mut headers,
+        FilterRequest::Completion(&mut body),
+    )
+    .await
+    {
+        Ok(tags) => tags,
+        Err(response) => return response,
+    };
+    let adjustments = apply_model_params(&state, &mut body);
 
-    state
+    let response = state
         .router
         .route_completion(Some(&headers), &body, None)
-        .await
+        .await;
+    let response = with_header(response, CONTENT_POLICY_TAGS_HEADER, tags);
+    with_header(response, PARAM_ADJUSTMENTS_HEADER, adjustments)
 }
 
 async fn rerank(
     State(state): State<Arc<AppState>>,
     headers: http::HeaderMap,
+    Extension(caller): Extension<CallerIdentity>,
     Json(body): Json<RerankRequest>,
 ) -> Response {
-    if let Err(response) = authorize_request(&state, &headers).await {
+    if let Some(response) = model_forbidden(&caller, body.get_model()) {
         return response;
     }
 
@@ -294,24 +447,24 @@ async fn rerank(
 async fn v1_rerank(
     State(state): State<Arc<AppState>>,
     headers: http::HeaderMap,
+    Extension(caller): Extension<CallerIdentity>,
     Json(body): Json<V1RerankReqInput>,
 ) -> Response {
-    if let Err(response) = authorize_request(&state, &headers).await {
+    let body: RerankRequest = body.into();
+    if let Some(response) = model_forbidden(&caller, body.get_model()) {
         return response;
     }
 
-    state
-        .router
-        .route_rerank(Some(&headers), &body.into(), None)
-        .await
+    state.router.route_rerank(Some(&headers), &body, None).await
 }
 
 async fn v1_responses(
     State(state): State<Arc<AppState>>,
     headers: http::HeaderMap,
+    Extension(caller): Extension<CallerIdentity>,
     Json(body): Json<ResponsesRequest>,
 ) -> Response {
-    if let Err(response) = authorize_request(&state, &headers).await {
+    if let Some(response) = model_forbidden(&caller, body.get_model()) {
         return response;
     }
 
@@ -324,9 +477,10 @@ async fn v1_responses(
 async fn v1_embeddings(
     State(state): State<Arc<AppState>>,
     headers: http::HeaderMap,
+    Extension(caller): Extension<CallerIdentity>,
     Json(body): Json<EmbeddingRequest>,
 ) -> Response {
-    if let Err(response) = authorize_request(&state, &headers).await {
+    if let Some(response) = model_forbidden(&caller, body.get_model()) {
         return response;
     }
 
@@ -341,10 +495,6 @@ async fn v1_responses_get(
     Path(response_id): Path<String>,
     headers: http::HeaderMap,
 ) -> Response {
-    if let Err(response) = authorize_request(&state, &headers).await {
-        return response;
-    }
-
     state
         .router
         .get_response(Some(&headers), &response_id)
@@ -356,10 +506,6 @@ async fn v1_responses_cancel(
     Path(response_id): Path<String>,
     headers: http::HeaderMap,
 ) -> Response {
-    if let Err(response) = authorize_request(&state, &headers).await {
-        return response;
-    }
-
     state
         .router
         .cancel_response(Some(&headers), &response_id)
@@ -371,10 +517,6 @@ async fn v1_responses_delete(
     Path(response_id): Path<String>,
     headers: http::HeaderMap,
 ) -> Response {
-    if let Err(response) = authorize_request(&state, &headers).await {
-        return response;
-    }
-
     // Python server does not support this yet
     state
         .router
@@ -387,10 +529,6 @@ async fn v1_responses_list_input_items(
     Path(response_id): Path<String>,
     headers: http::HeaderMap,
 ) -> Response {
-    if let Err(response) = authorize_request(&state, &headers).await {
-        return response;
-    }
-
     // Python server does not support this yet
     state
         .router
@@ -408,33 +546,56 @@ struct UrlQuery {
     url: String,
 }
 
+/// Authenticate the bearer token for `scope`, resolving who the caller is
 async fn authorize_request(
     state: &Arc<AppState>,
     headers: &http::HeaderMap,
-) -> Result<(), Response> {
+    scope: ApiKeyScope,
+) -> Result<CallerIdentity, Response> {
     let validation_urls = state.context.api_key_validation_urls.as_ref();
-    if validation_urls.is_empty() {
-        return Ok(());
+    let jwt_validator = state.context.jwt_validator.as_deref();
+    let api_key_store = state.context.api_key_store.as_deref();
+    if validation_urls.is_empty() && jwt_validator.is_none() && api_key_store.is_none() {
+        return Ok(CallerIdentity::unverified(headers));
     }
 
-    let auth_header = headers
-        .get(http::header::AUTHORIZATION)
-        .and_then(|value| value.to_str().ok())
-        .unwrap_or_default();
-
-    let token = auth_header
-        .strip_prefix("Bearer ")
-        .map(str::trim)
-        .filter(|value| !value.is_empty())
+    let token = bearer_token(headers)
         .ok_or_else(|| (StatusCode::UNAUTHORIZED, AUTH_FAILURE_MESSAGE).into_response())?;
 
-    if let Some(valid) = state.context.api_key_cache.read().await.get(token).copied() {
-        if valid {
-            return Ok(());
+    // Keys from the keys file carry their own scopes
+    if let Some(api_key) = api_key_store.and_then(|store| store.lookup(token)) {
+        if !api_key.has_scope(scope) {
+            return Err(scope_forbidden(scope));
         }
+        return Ok(CallerIdentity::from_api_key(api_key));
+    }
+
+    // JWTs are verified locally; opaque keys go to the validation URLs
+    if let Some(validator) = jwt_validator {
+        if JwtValidator::is_jwt(token) {
+            let verified = validator.validate(token).map_err(|e| {
+                debug!("Rejected bearer JWT: {}", e);
+                (StatusCode::UNAUTHORIZED, AUTH_FAILURE_MESSAGE).into_response()
+            })?;
+            if scope == ApiKeyScope::Admin && !verified.admin {
+                return Err(scope_forbidden(scope));
+            }
+            return Ok(CallerIdentity::from_jwt(token, verified));
+        }
+    }
+    if validation_urls.is_empty() {
         return Err((StatusCode::UNAUTHORIZED, AUTH_FAILURE_MESSAGE).into_response());
     }
 
+    // Validation URLs only vouch for the caller, never for admin access
+    if let Some(valid) = state.context.api_key_cache.get(token) {
+        return match (valid, scope) {
+            (false, _) => Err((StatusCode::UNAUTHORIZED, AUTH_FAILURE_MESSAGE).into_response()),
+            (true, ApiKeyScope::Admin) => Err(scope_forbidden(scope)),
+            (true, ApiKeyScope::Inference) => Ok(CallerIdentity::from_token(token)),
+        };
+    }
+
     let mut validated = false;
     for url in validation_urls {
         match state
@@ -458,40 +619,133 @@ async fn authorize_request(
         }
     }
 
-    state
-        .context
-        .api_key_cache
-        .write()
+    state.context.api_key_cache.insert(token, validated);
+
+    match (validated, scope) {
+        (false, _) => Err((StatusCode::UNAUTHORIZED, AUTH_FAILURE_MESSAGE).into_response()),
+        (true, ApiKeyScope::Admin) => Err(scope_forbidden(scope)),
+        (true, ApiKeyScope::Inference) => Ok(CallerIdentity::from_token(token)),
+    }
+}
+
+fn scope_forbidden(scope: ApiKeyScope) -> Response {
+    (
+        StatusCode::FORBIDDEN,
+        format!(
+            "API key is not allowed to access {} endpoints",
+            scope.as_str()
+        ),
+    )
+        .into_response()
+}
+
+/// Authenticate inference requests once, ahead of rate limits and handlers
+///
+/// The caller goes into the request extensions, and onto the response for
+/// the audit layer outside.
+async fn auth_middleware(
+    State(state): State<Arc<AppState>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let caller = match authorize_request(&state, request.headers(), ApiKeyScope::Inference).await {
+        Ok(caller) => caller,
+        Err(response) => return response,
+    };
+    request.extensions_mut().insert(caller.clone());
+    let mut response = next.run(request).await;
+    response.extensions_mut().insert(caller);
+    response
+}
+
+/// Run the content policy, forwarding any tags it attached to the workers
+async fn apply_content_policy(
+    state: &Arc<AppState>,
+    headers: &mut http::HeaderMap,
+    request: FilterRequest<'_>,
+) -> Result<Option<http::HeaderValue>, Response> {
+    let Some(policy) = &state.context.content_policy else {
+        return Ok(None);
+    };
+    let tags = policy
+        .apply(request)
         .await
-        .insert(token.to_string(), validated);
+        .map_err(IntoResponse::into_response)?;
+    if tags.is_empty() {
+        return Ok(None);
+    }
+    let value = http::HeaderValue::from_str(&tags.join(",")).map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Invalid content policy tag",
+        )
+            .into_response()
+    })?;
+    headers.insert(CONTENT_POLICY_TAGS_HEADER, value.clone());
+    Ok(Some(value))
+}
 
-    if validated {
-        Ok(())
-    } else {
-        Err((StatusCode::UNAUTHORIZED, AUTH_FAILURE_MESSAGE).into_response())
+/// Fill and clamp sampling parameters from the model's configured entry
+fn apply_model_params<R: SamplingParamsMut + GenerationRequest>(
+    state: &Arc<AppState>,
+    request: &mut R,
+) -> Option<http::HeaderValue> {
+    let params = model_params::lookup(
+        &state.context.router_config.model_params,
+        request.get_model(),
+    )?;
+    let adjustments = model_params::apply(params, request);
+    if adjustments.is_empty() {
+        return None;
+    }
+    let value = adjustments
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ");
+    debug!("Adjusted request parameters: {}", value);
+    http::HeaderValue::from_str(&value).ok()
+}
+
+/// Add a header to a routed response when there is something to report
+fn with_header(
+    mut response: Response,
+    name: &'static str,
+    value: Option<http::HeaderValue>,
+) -> Response {
+    if let Some(value) = value {
+        response.headers_mut().insert(name, value);
+    }
+    response
+}
+
+/// 403 response if the caller's key may not use `model`
+fn model_forbidden(caller: &CallerIdentity, model: Option<&str>) -> Option<Response> {
+    if caller
+        .api_key
+        .as_ref()
+        .is_none_or(|api_key| api_key.allows_model(model))
+    {
+        return None;
     }
+    let message = match model {
+        Some(model) => format!("API key is not allowed to use model '{}'", model),
+        None => "API key is restricted to specific models; specify a model".to_string(),
+    };
+    Some((StatusCode::FORBIDDEN, message).into_response())
 }
 
 async fn add_worker(
     State(state): State<Arc<AppState>>,
     Query(UrlQuery { url }): Query<UrlQuery>,
-    headers: http::HeaderMap,
 ) -> Response {
-    if let Err(response) = authorize_request(&state, &headers).await {
-        return response;
-    }
-
     match state.router.add_worker(&url).await {
         Ok(message) => (StatusCode::OK, message).into_response(),
         Err(error) => (StatusCode::BAD_REQUEST, error).into_response(),
     }
 }
 
-async fn list_workers(State(state): State<Arc<AppState>>, headers: http::HeaderMap) -> Response {
-    if let Err(response) = authorize_request(&state, &headers).await {
-        return response;
-    }
-
+async fn list_workers(State(state): State<Arc<AppState>>) -> Response {
     let worker_list = state.router.get_worker_urls();
     Json(serde_json::json!({ "urls": worker_list })).into_response()
 }
@@ -499,12 +753,7 @@ async fn list_workers(State(state): State<Arc<AppState>>, headers: http::HeaderM
 async fn remove_worker(
     State(state): State<Arc<AppState>>,
     Query(UrlQuery { url }): Query<UrlQuery>,
-    headers: http::HeaderMap,
 ) -> Response {
-    if let Err(response) = authorize_request(&state, &headers).await {
-        return response;
-    }
-
     state.router.remove_worker(&url);
     (
         StatusCode::OK,
@@ -513,20 +762,52 @@ async fn remove_worker(
         .into_response()
 }
 
-async fn flush_cache(State(state): State<Arc<AppState>>, headers: http::HeaderMap) -> Response {
-    if let Err(response) = authorize_request(&state, &headers).await {
-        return response;
-    }
-
+async fn flush_cache(State(state): State<Arc<AppState>>) -> Response {
     state.router.flush_cache().await
 }
 
-async fn get_loads(State(state): State<Arc<AppState>>, headers: http::HeaderMap) -> Response {
-    if let Err(response) = authorize_request(&state, &headers).await {
-        return response;
+async fn get_loads(State(state): State<Arc<AppState>>) -> Response {
+    state.router.get_worker_loads().await
+}
+
+fn pd_capacity_disabled() -> Response {
+    (
+        StatusCode::NOT_FOUND,
+        Json(json!({
+            "error": "PD capacity controller is not enabled"
+        })),
+    )
+        .into_response()
+}
+
+/// GET /pd/capacity - Current prefill/decode role flip recommendation
+async fn get_pd_capacity(State(state): State<Arc<AppState>>) -> Response {
+    match &state.context.pd_capacity {
+        Some(controller) => Json(controller.evaluate()).into_response(),
+        None => pd_capacity_disabled(),
     }
+}
 
-    state.router.get_worker_loads().await
+/// POST /pd/capacity/apply - Perform the recommended role flip
+async fn apply_pd_capacity(State(state): State<Arc<AppState>>) -> Response {
+    let Some(controller) = &state.context.pd_capacity else {
+        return pd_capacity_disabled();
+    };
+    match controller.apply_recommended().await {
+        Ok(recommendation) => Json(json!({
+            "applied": true,
+            "recommendation": recommendation
+        }))
+        .into_response(),
+        Err(error) => (
+            StatusCode::CONFLICT,
+            Json(json!({
+                "applied": false,
+                "error": error
+            })),
+        )
+            .into_response(),
+    }
 }
 
 // ---------- Worker management endpoints (RESTful) ----------
@@ -534,13 +815,8 @@ async fn get_loads(State(state): State<Arc<AppState>>, headers: http::HeaderMap)
 /// POST /workers - Add a new worker with full configuration
 async fn create_worker(
     State(state): State<Arc<AppState>>,
-    headers: http::HeaderMap,
     Json(config): Json<WorkerConfigRequest>,
 ) -> Response {
-    if let Err(response) = authorize_request(&state, &headers).await {
-        return response;
-    }
-
     // Check if we have a RouterManager (enable_igw=true)
     if let Some(router_manager) = &state.router_manager {
         // Call RouterManager's add_worker method directly with the full config
@@ -570,14 +846,7 @@ async fn create_worker(
     }
 }
 
-async fn list_workers_rest(
-    State(state): State<Arc<AppState>>,
-    headers: http::HeaderMap,
-) -> Response {
-    if let Err(response) = authorize_request(&state, &headers).await {
-        return response;
-    }
-
+async fn list_workers_rest(State(state): State<Arc<AppState>>) -> Response {
     if let Some(router_manager) = &state.router_manager {
         let response = router_manager.list_workers();
         Json(response).into_response()
@@ -606,6 +875,12 @@ async fn list_workers_rest(
                     worker_info["bootstrap_port"] = serde_json::json!(bootstrap_port);
                 }
 
+                // Labels carry metadata such as a discovered instance's ZMQ address
+                let labels = &worker.metadata().labels;
+                if !labels.is_empty() {
+                    worker_info["labels"] = serde_json::json!(labels);
+                }
+
                 worker_info
             }).collect::<Vec<_>>(),
             "total": workers.len(),
@@ -620,15 +895,7 @@ async fn list_workers_rest(
 }
 
 /// GET /workers/{url} - Get specific worker info
-async fn get_worker(
-    State(state): State<Arc<AppState>>,
-    Path(url): Path<String>,
-    headers: http::HeaderMap,
-) -> Response {
-    if let Err(response) = authorize_request(&state, &headers).await {
-        return response;
-    }
-
+async fn get_worker(State(state): State<Arc<AppState>>, Path(url): Path<String>) -> Response {
     if let Some(router_manager) = &state.router_manager {
         if let Some(worker) = router_manager.get_worker(&url) {
             Json(worker).into_response()
@@ -659,15 +926,7 @@ async fn get_worker(
 }
 
 /// DELETE /workers/{url} - Remove a worker
-async fn delete_worker(
-    State(state): State<Arc<AppState>>,
-    Path(url): Path<String>,
-    headers: http::HeaderMap,
-) -> Response {
-    if let Err(response) = authorize_request(&state, &headers).await {
-        return response;
-    }
-
+async fn delete_worker(State(state): State<Arc<AppState>>, Path(url): Path<String>) -> Response {
     if let Some(router_manager) = &state.router_manager {
         match router_manager.remove_worker_from_registry(&url) {
             Ok(response) => (StatusCode::OK, Json(response)).into_response(),
@@ -698,6 +957,68 @@ pub struct ServerConfig {
     pub request_id_headers: Option<Vec<String>>,
 }
 
+/// Authenticate requests to the worker management endpoints
+///
+/// A configured admin key replaces the inference auth chain; otherwise admin
+/// requests need a keys-file key with the admin scope or a JWT whose admin
+/// claim grants it. Keys accepted by the validation URLs are never admins.
+async fn admin_auth_middleware(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let caller = match &state.context.router_config.admin.api_key {
+        Some(admin_key) => {
+            let token = bearer_token(request.headers()).unwrap_or_default();
+            // Compare digests so the check does not leak the key's prefix
+            if ApiKeyStore::hash_key(token) != ApiKeyStore::hash_key(admin_key) {
+                return (StatusCode::UNAUTHORIZED, "Invalid admin API key").into_response();
+            }
+            CallerIdentity {
+                id: Some("admin".to_string()),
+                ..Default::default()
+            }
+        }
+        None => match authorize_request(&state, request.headers(), ApiKeyScope::Admin).await {
+            Ok(caller) => caller,
+            Err(response) => return response,
+        },
+    };
+    // The admin audit layer outside reads the actor from the response
+    let mut response = next.run(request).await;
+    response.extensions_mut().insert(caller);
+    response
+}
+
+/// Admin routes when they are served by the separate admin listener
+async fn admin_disabled(_request: Request, _next: Next) -> Response {
+    StatusCode::NOT_FOUND.into_response()
+}
+
+/// Worker management routes, behind admin authentication
+fn admin_routes(app_state: &Arc<AppState>) -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/add_worker", post(add_worker))
+        .route("/remove_worker", post(remove_worker))
+        .route("/list_workers", get(list_workers))
+        .route("/flush_cache", post(flush_cache))
+        .route("/get_loads", get(get_loads))
+        .route("/pd/capacity", get(get_pd_capacity))
+        .route("/pd/capacity/apply", post(apply_pd_capacity))
+        .route("/workers", post(create_worker))
+        .route("/workers", get(list_workers_rest))
+        .route("/workers/{url}", get(get_worker))
+        .route("/workers/{url}", delete(delete_worker))
+        .route_layer(axum::middleware::from_fn_with_state(
+            app_state.clone(),
+            admin_auth_middleware,
+        ))
+        .route_layer(axum::middleware::from_fn_with_state(
+            app_state.clone(),
+            middleware::admin_audit_middleware,
+        ))
+}
+
 /// Build the Axum application with all routes and middleware
 pub fn build_app(
     app_state: Arc<AppState>,
@@ -728,6 +1049,18 @@ pub fn build_app(
         .route_layer(axum::middleware::from_fn_with_state(
             app_state.clone(),
             middleware::concurrency_limit_middleware,
+        ))
+        .route_layer(axum::middleware::from_fn_with_state(
+            app_state.clone(),
+            middleware::keyed_rate_limit_middleware,
+        ))
+        .route_layer(axum::middleware::from_fn_with_state(
+            app_state.clone(),
+            auth_middleware,
+        ))
+        .route_layer(axum::middleware::from_fn_with_state(
+            app_state.clone(),
+            middleware::audit_middleware,
         ));
 
     let public_routes = Router::new()
@@ -737,28 +1070,25 @@ pub fn build_app(
         .route("/health_generate", get(health_generate))
         .route("/v1/models", get(v1_models))
         .route("/get_model_info", get(get_model_info))
-        .route("/get_server_info", get(get_server_info));
-
-    let admin_routes = Router::new()
-        .route("/add_worker", post(add_worker))
-        .route("/remove_worker", post(remove_worker))
-        .route("/list_workers", get(list_workers))
-        .route("/flush_cache", post(flush_cache))
-        .route("/get_loads", get(get_loads));
+        .route("/get_server_info", get(get_server_info))
+        .route_layer(axum::middleware::from_fn_with_state(
+            app_state.clone(),
+            auth_middleware,
+        ));
 
-    // Worker management routes
-    let worker_routes = Router::new()
-        .route("/workers", post(create_worker))
-        .route("/workers", get(list_workers_rest))
-        .route("/workers/{url}", get(get_worker))
-        .route("/workers/{url}", delete(delete_worker));
+    // With a separate admin listener the paths stay registered here so they
+    // answer 404 instead of reaching the transparent proxy
+    let admin_routes = if app_state.context.router_config.admin.is_separate() {
+        admin_routes(&app_state).route_layer(axum::middleware::from_fn(admin_disabled))
+    } else {
+        admin_routes(&app_state)
+    };
 
     // Build base app with all routes and middleware
     let base_app = Router::new()
         .merge(protected_routes)
         .merge(public_routes)
         .merge(admin_routes)
-        .merge(worker_routes)
         // Request body size limiting
         .layer(tower_http::limit::RequestBodyLimitLayer::new(
             max_payload_size,
@@ -769,14 +1099,33 @@ pub fn build_app(
 
     // Choose fallback based on transparent proxy mode
     if enable_transparent_proxy {
+        let auth = axum::middleware::from_fn_with_state(app_state.clone(), auth_middleware);
+        let audit =
+            axum::middleware::from_fn_with_state(app_state.clone(), middleware::audit_middleware);
         base_app
-            .fallback(transparent_proxy_handler)
+            .fallback(transparent_proxy_handler.layer(auth).layer(audit))
             .with_state(app_state)
     } else {
         base_app.fallback(sink_handler).with_state(app_state)
     }
 }
 
+/// Build the application served by the separate admin listener
+pub fn build_admin_app(
+    app_state: Arc<AppState>,
+    max_payload_size: usize,
+    request_id_headers: Vec<String>,
+) -> Router {
+    admin_routes(&app_state)
+        .layer(tower_http::limit::RequestBodyLimitLayer::new(
+            max_payload_size,
+        ))
+        .layer(middleware::create_logging_layer())
+        .layer(middleware::RequestIdLayer::new(request_id_headers))
+        .fallback(sink_handler)
+        .with_state(app_state)
+}
+
 pub async fn startup(config: ServerConfig) -> Result<(), Box<dyn std::error::Error>> {
     println!("DEBUG: Server startup function called");
 
@@ -825,7 +1174,9 @@ pub async fn startup(config: ServerConfig) -> Result<(), Box<dyn std::error::Err
     );
 
     println!("DEBUG: Creating HTTP client");
-    let client = Client::builder()
+    let upstream_tls = UpstreamTls::load(&config.router_config.upstream_tls)?;
+    let client = upstream_tls
+        .client_builder()
         .pool_idle_timeout(Some(Duration::from_secs(50)))
         .pool_max_idle_per_host(500)
         .timeout(Duration::from_secs(config.request_timeout_secs))
@@ -838,13 +1189,16 @@ pub async fn startup(config: ServerConfig) -> Result<(), Box<dyn std::error::Err
 
     // Create the application context with all dependencies
     println!("DEBUG: Creating AppContext");
-    let app_context = AppContext::new(
-        config.router_config.clone(),
-        client.clone(),
-        config.router_config.max_concurrent_requests,
-        config.router_config.rate_limit_tokens_per_second,
-        config.router_config.api_key_validation_urls.clone(),
-    )?;
+    let app_context = AppContext {
+        upstream_tls,
+        ..AppContext::new(
+            config.router_config.clone(),
+            client.clone(),
+            config.router_config.max_concurrent_requests,
+            config.router_config.rate_limit_tokens_per_second,
+            config.router_config.api_key_validation_urls.clone(),
+        )?
+    };
     println!("DEBUG: AppContext created");
 
     let app_context = Arc::new(app_context);
@@ -922,14 +1276,62 @@ pub async fn startup(config: ServerConfig) -> Result<(), Box<dyn std::error::Err
         };
 
     // Start health checker for all workers in the registry
-    let _health_checker = app_context
-        .worker_registry
-        .start_health_checker(config.router_config.health_check.check_interval_secs);
+    let _health_checker = app_context.worker_registry.start_health_checker(
+        config.router_config.health_check.check_interval_secs,
+        app_context.client.clone(),
+    );
     info!(
         "Started health checker for workers with {}s interval",
         config.router_config.health_check.check_interval_secs
     );
 
+    // Start DP size monitor to re-expand DP-aware workers whose DP size changes
+    let cb_config = config.router_config.effective_circuit_breaker_config();
+    let dp_size_monitor = dp_utils::start_dp_size_monitor(
+        app_context.worker_registry.clone(),
+        app_context.policy_registry.clone(),
+        app_context.client.clone(),
+        config.router_config.api_key.clone(),
+        config.router_config.dp_discovery.clone(),
+        crate::core::CircuitBreakerConfig {
+            failure_threshold: cb_config.failure_threshold,
+            success_threshold: cb_config.success_threshold,
+            timeout_duration: Duration::from_secs(cb_config.timeout_duration_secs),
+            window_duration: Duration::from_secs(cb_config.window_duration_secs),
+        },
+    );
+    if dp_size_monitor.is_some() {
+        info!(
+            "Started DP size monitor with {}s interval",
+            config.router_config.dp_discovery.refresh_interval_secs
+        );
+    }
+
+    // Start the PD capacity controller (evaluates, and optionally applies, role flips)
+    if let Some(controller) = &app_context.pd_capacity {
+        controller.clone().start();
+        info!(
+            "Started PD capacity controller with {}s interval (auto-apply: {})",
+            config.router_config.pd_capacity.evaluation_interval_secs,
+            config.router_config.pd_capacity.auto_apply
+        );
+    }
+
+    // Persist token quota usage
+    if let Some(token_rate_limiter) = &app_context.token_rate_limiter {
+        token_rate_limiter.clone().start();
+    }
+
+    // Pick up edits to the API keys file
+    if let Some(api_key_store) = &app_context.api_key_store {
+        api_key_store.clone().start(app_context.audit_log.clone());
+    }
+
+    // Keep the JWKS used for JWT validation fresh
+    if let Some(jwt_validator) = &app_context.jwt_validator {
+        jwt_validator.clone().start();
+    }
+
     let rate_monitor = Arc::new(RateMonitor::new(RateMonitorConfig {
         threshold: 10,
         window_secs: 60,
@@ -938,41 +1340,57 @@ pub async fn startup(config: ServerConfig) -> Result<(), Box<dyn std::error::Err
     }));
 
     let _rate_monitor_handle = app_context
-    .worker_registry
-    .start_rate_monitor(rate_monitor.clone());
+        .worker_registry
+        .start_rate_monitor(rate_monitor.clone());
 
     // Set up concurrency limiter with queue if configured
     let (limiter, processor) = middleware::ConcurrencyLimiter::new(
         app_context.rate_limiter.clone(),
         config.router_config.queue_size,
         Duration::from_secs(config.router_config.queue_timeout_secs),
-    );
+        &config.router_config.admission,
+    )?;
 
     // Start queue processor if enabled
     if let Some(processor) = processor {
         tokio::spawn(processor.run());
         info!(
-            "Started request queue with size: {}, timeout: {}s",
-            config.router_config.queue_size, config.router_config.queue_timeout_secs
+            "Started request queue with size: {}, timeout: {}s, priority classes: {}",
+            config.router_config.queue_size,
+            config.router_config.queue_timeout_secs,
+            config.router_config.admission.classes.len().max(1)
         );
     }
 
-
-
     // Create app state with router and context
     let app_state = Arc::new(AppState {
         router,
         context: app_context.clone(),
-        concurrency_queue_tx: limiter.queue_tx.clone(),
+        request_queue: limiter.queue.clone(),
         router_manager,
-        rate_monitor: rate_monitor,
+        rate_monitor,
     });
     let router_arc = Arc::clone(&app_state.router);
 
     // Start the service discovery if enabled
     if let Some(service_discovery_config) = config.service_discovery_config {
         if service_discovery_config.enabled {
-            match start_service_discovery(service_discovery_config, router_arc).await {
+            let started = match service_discovery_config.backend {
+                DiscoveryBackendKind::Kubernetes => {
+                    start_service_discovery(service_discovery_config, router_arc)
+                        .await
+                        .map_err(|e| e.to_string())
+                }
+                DiscoveryBackendKind::EndpointSlices => {
+                    start_endpoint_slice_discovery(service_discovery_config, router_arc)
+                        .await
+                        .map_err(|e| e.to_string())
+                }
+                DiscoveryBackendKind::File | DiscoveryBackendKind::Dns => {
+                    start_polling_discovery(service_discovery_config, router_arc)
+                }
+            };
+            match started {
                 Ok(handle) => {
                     info!("Service discovery started");
                     // Spawn a task to handle the service discovery thread
@@ -1008,6 +1426,41 @@ pub async fn startup(config: ServerConfig) -> Result<(), Box<dyn std::error::Err
     // Enable transparent proxy for all routing modes
     let enable_transparent_proxy = true;
 
+    // Both listeners terminate TLS with the same, hot-reloaded certificate
+    let server_tls = config
+        .router_config
+        .tls
+        .clone()
+        .map(|tls| ServerTls::load(tls).map(Arc::new))
+        .transpose()?;
+    if let Some(server_tls) = &server_tls {
+        server_tls.clone().start(app_context.audit_log.clone());
+    }
+
+    let admin_config = &config.router_config.admin;
+    if let Some(admin_port) = admin_config.port {
+        let admin_app = build_admin_app(
+            app_state.clone(),
+            config.max_payload_size,
+            request_id_headers.clone(),
+        );
+        let admin_addr = format!("{}:{}", admin_config.host, admin_port);
+        let admin_listener = TcpListener::bind(&admin_addr).await?;
+        info!("Starting admin server on {}", admin_addr);
+        let admin_tls = server_tls.clone();
+        spawn(async move {
+            if let Err(e) = serve_app(admin_listener, admin_app, admin_tls).await {
+                error!("Admin server failed: {e}");
+            }
+        });
+    } else if admin_config.api_key.is_none()
+        && app_context.api_key_validation_urls.is_empty()
+        && app_context.jwt_validator.is_none()
+        && app_context.api_key_store.is_none()
+    {
+        warn!("Admin endpoints are served on the public listener without authentication");
+    }
+
     let app = build_app(
         app_state,
         config.max_payload_size,
@@ -1018,15 +1471,45 @@ pub async fn startup(config: ServerConfig) -> Result<(), Box<dyn std::error::Err
 
     let addr = format!("{}:{}", config.host, config.port);
     let listener = TcpListener::bind(&addr).await?;
-    info!("Starting server on {}", addr);
-    serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
+    let scheme = if server_tls.is_some() {
+        "https"
+    } else {
+        "http"
+    };
+    info!("Starting server on {}://{}", scheme, addr);
+    serve_app(listener, app, server_tls)
         .await
         .map_err(|e| Box::new(e) as Box<dyn std::error::Error>)?;
 
+    if let Some(token_rate_limiter) = &app_context.token_rate_limiter {
+        if let Err(e) = token_rate_limiter.flush() {
+            error!("{e}");
+        }
+    }
+
     Ok(())
 }
 
+/// Serve `app` until shutdown, terminating TLS when configured
+async fn serve_app(
+    listener: TcpListener,
+    app: Router,
+    tls: Option<Arc<ServerTls>>,
+) -> std::io::Result<()> {
+    match tls {
+        Some(tls) => {
+            serve(TlsListener::new(listener, tls)?, app)
+                .with_graceful_shutdown(shutdown_signal())
+                .await
+        }
+        None => {
+            serve(listener, app)
+                .with_graceful_shutdown(shutdown_signal())
+                .await
+        }
+    }
+}
+
 // Graceful shutdown handler
 async fn shutdown_signal() {
     let ctrl_c = async {
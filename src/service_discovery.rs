@@ -1,5 +1,11 @@
+pub mod dns;
+pub mod endpoint_slice;
+pub mod file;
+
+use crate::config::{DiscoveryBackendKind, EndpointSliceConfig};
 use crate::routers::RouterTrait;
 
+use async_trait::async_trait;
 use futures::{StreamExt, TryStreamExt};
 use k8s_openapi::api::core::v1::Pod;
 use kube::{
@@ -9,6 +15,7 @@ use kube::{
     Client,
 };
 use std::collections::{HashMap, HashSet};
+use std::fmt;
 
 use rustls;
 use std::sync::{Arc, Mutex};
@@ -31,6 +38,12 @@ pub struct ServiceDiscoveryConfig {
     pub decode_selector: HashMap<String, String>,
     // Bootstrap port annotation specific to mooncake implementation
     pub bootstrap_port_annotation: String,
+    // File and DNS backends
+    pub backend: DiscoveryBackendKind,
+    pub file_path: Option<String>,
+    pub dns_names: Vec<String>,
+    // EndpointSlice backend
+    pub endpoint_slices: EndpointSliceConfig,
 }
 
 impl Default for ServiceDiscoveryConfig {
@@ -45,6 +58,10 @@ impl Default for ServiceDiscoveryConfig {
             prefill_selector: HashMap::new(),
             decode_selector: HashMap::new(),
             bootstrap_port_annotation: "vllm.ai/bootstrap-port".to_string(),
+            backend: DiscoveryBackendKind::Kubernetes,
+            file_path: None,
+            dns_names: Vec::new(),
+            endpoint_slices: EndpointSliceConfig::default(),
         }
     }
 }
@@ -380,52 +397,15 @@ async fn handle_pod_event(
                 pod_info.name, pod_info.pod_type, worker_url
             );
 
-            // Handle PD mode with specific pod types
-            let result = if pd_mode && pod_info.pod_type.is_some() {
-                // Import both PD router types
-                use crate::routers::http::pd_router::PDRouter;
-                use crate::routers::http::vllm_pd_router::VllmPDRouter;
-
-                // Try to downcast to PDRouter first, then VllmPDRouter
-                if let Some(pd_router) = router.as_any().downcast_ref::<PDRouter>() {
-                    match &pod_info.pod_type {
-                        Some(PodType::Prefill) => pd_router
-                            .add_prefill_server(worker_url.clone(), pod_info.bootstrap_port)
-                            .await
-                            .map_err(|e| e.to_string()),
-                        Some(PodType::Decode) => pd_router
-                            .add_decode_server(worker_url.clone())
-                            .await
-                            .map_err(|e| e.to_string()),
-                        Some(PodType::Regular) | None => {
-                            // Fall back to regular add_worker for regular pods
-                            router.add_worker(&worker_url).await
-                        }
-                    }
-                } else if let Some(vllm_pd_router) = router.as_any().downcast_ref::<VllmPDRouter>()
-                {
-                    // Support --vllm-pd-disaggregation mode with K8s service discovery
-                    match &pod_info.pod_type {
-                        Some(PodType::Prefill) => vllm_pd_router
-                            .add_prefill_server(worker_url.clone(), pod_info.bootstrap_port)
-                            .await
-                            .map_err(|e| e.to_string()),
-                        Some(PodType::Decode) => vllm_pd_router
-                            .add_decode_server(worker_url.clone())
-                            .await
-                            .map_err(|e| e.to_string()),
-                        Some(PodType::Regular) | None => {
-                            // Fall back to regular add_worker for regular pods
-                            router.add_worker(&worker_url).await
-                        }
-                    }
-                } else {
-                    Err("PD mode enabled but router is not a PDRouter or VllmPDRouter".to_string())
-                }
-            } else {
-                // Regular mode or no pod type specified
-                router.add_worker(&worker_url).await
-            };
+            let result = add_worker_with_role(
+                &router,
+                &worker_url,
+                pod_info.pod_type.as_ref(),
+                pod_info.bootstrap_port,
+                HashMap::new(),
+                pd_mode,
+            )
+            .await;
 
             match result {
                 Ok(_) => {
@@ -469,56 +449,7 @@ async fn handle_pod_deletion(
             pod_info.name, pod_info.pod_type, worker_url
         );
 
-        // Handle PD mode removal
-        if pd_mode && pod_info.pod_type.is_some() {
-            // Import both PD router types
-            use crate::routers::http::pd_router::PDRouter;
-            use crate::routers::http::vllm_pd_router::VllmPDRouter;
-
-            // Try to downcast to PDRouter first, then VllmPDRouter
-            if let Some(pd_router) = router.as_any().downcast_ref::<PDRouter>() {
-                match &pod_info.pod_type {
-                    Some(PodType::Prefill) => {
-                        if let Err(e) = pd_router.remove_prefill_server(&worker_url).await {
-                            error!("Failed to remove prefill server {}: {}", worker_url, e);
-                        }
-                    }
-                    Some(PodType::Decode) => {
-                        if let Err(e) = pd_router.remove_decode_server(&worker_url).await {
-                            error!("Failed to remove decode server {}: {}", worker_url, e);
-                        }
-                    }
-                    Some(PodType::Regular) | None => {
-                        // Fall back to regular remove_worker
-                        router.remove_worker(&worker_url);
-                    }
-                }
-            } else if let Some(vllm_pd_router) = router.as_any().downcast_ref::<VllmPDRouter>() {
-                // Support --vllm-pd-disaggregation mode with K8s service discovery
-                match &pod_info.pod_type {
-                    Some(PodType::Prefill) => {
-                        if let Err(e) = vllm_pd_router.remove_prefill_server(&worker_url).await {
-                            error!("Failed to remove vllm prefill server {}: {}", worker_url, e);
-                        }
-                    }
-                    Some(PodType::Decode) => {
-                        if let Err(e) = vllm_pd_router.remove_decode_server(&worker_url).await {
-                            error!("Failed to remove vllm decode server {}: {}", worker_url, e);
-                        }
-                    }
-                    Some(PodType::Regular) | None => {
-                        // Fall back to regular remove_worker
-                        router.remove_worker(&worker_url);
-                    }
-                }
-            } else {
-                // PD mode but not a PDRouter or VllmPDRouter, use generic removal
-                router.remove_worker(&worker_url);
-            }
-        } else {
-            // Regular mode removal
-            router.remove_worker(&worker_url);
-        }
+        remove_worker_with_role(&router, &worker_url, pod_info.pod_type.as_ref(), pd_mode).await;
     } else {
         // This case might occur if a pod is deleted before it was ever marked healthy and added.
         // Or if the event is duplicated. No action needed on the router if it wasn't tracked (and thus not added).
@@ -529,6 +460,298 @@ async fn handle_pod_deletion(
     }
 }
 
+/// Worker reported by a polling discovery backend
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DiscoveredWorker {
+    pub url: String,
+    pub role: PodType,
+    pub bootstrap_port: Option<u16>,
+}
+
+impl DiscoveredWorker {
+    pub fn new(url: String, role: PodType) -> Self {
+        Self {
+            url,
+            role,
+            bootstrap_port: None,
+        }
+    }
+}
+
+/// Source of workers polled for the full current set
+///
+/// Kubernetes discovery is watch-based and lives in
+/// [`start_service_discovery`]; sources without a watch API implement this
+/// trait and are driven by [`start_polling_discovery`].
+#[async_trait]
+pub trait DiscoveryBackend: Send + Sync + fmt::Debug {
+    /// Backend name used in logs
+    fn name(&self) -> &'static str;
+
+    /// Delay between polls
+    fn poll_interval(&self) -> Duration;
+
+    /// Current workers, or `None` when nothing changed since the last poll
+    async fn discover(&mut self) -> Result<Option<Vec<DiscoveredWorker>>, String>;
+}
+
+/// Polling backend for the configured kind
+pub fn polling_backend(
+    config: &ServiceDiscoveryConfig,
+) -> Result<Box<dyn DiscoveryBackend>, String> {
+    match config.backend {
+        DiscoveryBackendKind::File => {
+            let path = config
+                .file_path
+                .clone()
+                .ok_or("File discovery requires a worker file path")?;
+            Ok(Box::new(file::FileDiscovery::new(path)))
+        }
+        DiscoveryBackendKind::Dns => Ok(Box::new(dns::DnsDiscovery::new(
+            &config.dns_names,
+            config.port,
+            config.check_interval,
+        )?)),
+        DiscoveryBackendKind::Kubernetes | DiscoveryBackendKind::EndpointSlices => {
+            Err("Kubernetes discovery is watch-based, not polled".to_string())
+        }
+    }
+}
+
+/// Start a file or DNS discovery task that keeps the router's workers in
+/// sync with the backend
+pub fn start_polling_discovery(
+    config: ServiceDiscoveryConfig,
+    router: Arc<dyn RouterTrait>,
+) -> Result<task::JoinHandle<()>, String> {
+    if !config.enabled {
+        return Err("Service discovery is disabled".to_string());
+    }
+
+    let mut backend = polling_backend(&config)?;
+    info!(
+        "Starting {} service discovery | poll interval: {:?}",
+        backend.name(),
+        backend.poll_interval()
+    );
+
+    let handle = task::spawn(async move {
+        let mut tracked = HashSet::new();
+        // Last discovered set, kept while some of its workers failed to add
+        let mut pending: Option<Vec<DiscoveredWorker>> = None;
+        let mut interval = time::interval(backend.poll_interval());
+
+        loop {
+            interval.tick().await;
+            let discovered = match backend.discover().await {
+                Ok(Some(workers)) => workers,
+                Ok(None) => match pending.take() {
+                    Some(workers) => workers,
+                    None => continue,
+                },
+                Err(e) => {
+                    // Keep the current workers through transient failures
+                    warn!("{} discovery failed: {}", backend.name(), e);
+                    continue;
+                }
+            };
+
+            let complete =
+                sync_discovered_workers(&mut tracked, &discovered, &router, config.pd_mode).await;
+            if !complete {
+                pending = Some(discovered);
+            }
+        }
+    });
+
+    Ok(handle)
+}
+
+/// Add workers new to `discovered` and remove tracked workers missing from it
+///
+/// Returns false when some workers could not be added; they stay untracked
+/// so the next sync retries them.
+async fn sync_discovered_workers(
+    tracked: &mut HashSet<DiscoveredWorker>,
+    discovered: &[DiscoveredWorker],
+    router: &Arc<dyn RouterTrait>,
+    pd_mode: bool,
+) -> bool {
+    let current: HashSet<&DiscoveredWorker> = discovered.iter().collect();
+    let removed: Vec<DiscoveredWorker> = tracked
+        .iter()
+        .filter(|worker| !current.contains(worker))
+        .cloned()
+        .collect();
+
+    for worker in removed {
+        info!(
+            "Removing discovered worker: {} | type: {:?}",
+            worker.url, worker.role
+        );
+        remove_worker_with_role(router, &worker.url, Some(&worker.role), pd_mode).await;
+        tracked.remove(&worker);
+    }
+
+    let mut complete = true;
+    for worker in current {
+        if tracked.contains(worker) {
+            continue;
+        }
+        info!(
+            "Adding discovered worker: {} | type: {:?}",
+            worker.url, worker.role
+        );
+        match add_worker_with_role(
+            router,
+            &worker.url,
+            Some(&worker.role),
+            worker.bootstrap_port,
+            HashMap::new(),
+            pd_mode,
+        )
+        .await
+        {
+            Ok(()) => {
+                tracked.insert(worker.clone());
+            }
+            Err(e) => {
+                error!("Failed to add worker {} to router: {}", worker.url, e);
+                complete = false;
+            }
+        }
+    }
+    complete
+}
+
+/// Add a discovered worker with `labels`, sending PD roles to the PD
+/// router's prefill and decode pools
+pub(crate) async fn add_worker_with_role(
+    router: &Arc<dyn RouterTrait>,
+    worker_url: &str,
+    pod_type: Option<&PodType>,
+    bootstrap_port: Option<u16>,
+    labels: HashMap<String, String>,
+    pd_mode: bool,
+) -> Result<(), String> {
+    // Regular mode or no pod type specified
+    if !pd_mode || pod_type.is_none() {
+        return router
+            .add_worker_with_labels(worker_url, labels)
+            .await
+            .map(|_| ());
+    }
+
+    // Import both PD router types
+    use crate::routers::http::pd_router::PDRouter;
+    use crate::routers::http::vllm_pd_router::VllmPDRouter;
+
+    // Try to downcast to PDRouter first, then VllmPDRouter
+    if let Some(pd_router) = router.as_any().downcast_ref::<PDRouter>() {
+        match pod_type {
+            Some(PodType::Prefill) => pd_router
+                .add_prefill_server_with_labels(worker_url.to_string(), bootstrap_port, labels)
+                .await
+                .map(|_| ())
+                .map_err(|e| e.to_string()),
+            Some(PodType::Decode) => pd_router
+                .add_decode_server_with_labels(worker_url.to_string(), labels)
+                .await
+                .map(|_| ())
+                .map_err(|e| e.to_string()),
+            Some(PodType::Regular) | None => {
+                // Fall back to regular add_worker for regular pods
+                router
+                    .add_worker_with_labels(worker_url, labels)
+                    .await
+                    .map(|_| ())
+            }
+        }
+    } else if let Some(vllm_pd_router) = router.as_any().downcast_ref::<VllmPDRouter>() {
+        // Support --vllm-pd-disaggregation mode with service discovery
+        match pod_type {
+            Some(PodType::Prefill) => vllm_pd_router
+                .add_prefill_server_with_labels(worker_url.to_string(), bootstrap_port, labels)
+                .await
+                .map(|_| ())
+                .map_err(|e| e.to_string()),
+            Some(PodType::Decode) => vllm_pd_router
+                .add_decode_server_with_labels(worker_url.to_string(), labels)
+                .await
+                .map(|_| ())
+                .map_err(|e| e.to_string()),
+            Some(PodType::Regular) | None => {
+                // Fall back to regular add_worker for regular pods
+                router
+                    .add_worker_with_labels(worker_url, labels)
+                    .await
+                    .map(|_| ())
+            }
+        }
+    } else {
+        Err("PD mode enabled but router is not a PDRouter or VllmPDRouter".to_string())
+    }
+}
+
+/// Remove a discovered worker added by [`add_worker_with_role`]
+pub(crate) async fn remove_worker_with_role(
+    router: &Arc<dyn RouterTrait>,
+    worker_url: &str,
+    pod_type: Option<&PodType>,
+    pd_mode: bool,
+) {
+    // Regular mode removal
+    if !pd_mode || pod_type.is_none() {
+        router.remove_worker(worker_url);
+        return;
+    }
+
+    // Import both PD router types
+    use crate::routers::http::pd_router::PDRouter;
+    use crate::routers::http::vllm_pd_router::VllmPDRouter;
+
+    // Try to downcast to PDRouter first, then VllmPDRouter
+    if let Some(pd_router) = router.as_any().downcast_ref::<PDRouter>() {
+        match pod_type {
+            Some(PodType::Prefill) => {
+                if let Err(e) = pd_router.remove_prefill_server(worker_url).await {
+                    error!("Failed to remove prefill server {}: {}", worker_url, e);
+                }
+            }
+            Some(PodType::Decode) => {
+                if let Err(e) = pd_router.remove_decode_server(worker_url).await {
+                    error!("Failed to remove decode server {}: {}", worker_url, e);
+                }
+            }
+            Some(PodType::Regular) | None => {
+                // Fall back to regular remove_worker
+                router.remove_worker(worker_url);
+            }
+        }
+    } else if let Some(vllm_pd_router) = router.as_any().downcast_ref::<VllmPDRouter>() {
+        // Support --vllm-pd-disaggregation mode with service discovery
+        match pod_type {
+            Some(PodType::Prefill) => {
+                if let Err(e) = vllm_pd_router.remove_prefill_server(worker_url).await {
+                    error!("Failed to remove vllm prefill server {}: {}", worker_url, e);
+                }
+            }
+            Some(PodType::Decode) => {
+                if let Err(e) = vllm_pd_router.remove_decode_server(worker_url).await {
+                    error!("Failed to remove vllm decode server {}: {}", worker_url, e);
+                }
+            }
+            Some(PodType::Regular) | None => {
+                // Fall back to regular remove_worker
+                router.remove_worker(worker_url);
+            }
+        }
+    } else {
+        // PD mode but not a PDRouter or VllmPDRouter, use generic removal
+        router.remove_worker(worker_url);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -616,7 +839,13 @@ mod tests {
     }
 
     // Helper to create a Router instance for testing event handlers
-    async fn create_test_router() -> Arc<dyn RouterTrait> {
+    pub(super) async fn create_test_router() -> Arc<dyn RouterTrait> {
+        create_test_router_with_registry().await.0
+    }
+
+    /// Regular router and the registry its workers are added to
+    pub(super) async fn create_test_router_with_registry(
+    ) -> (Arc<dyn RouterTrait>, Arc<crate::core::WorkerRegistry>) {
         use crate::config::RouterConfig;
         use crate::middleware::TokenBucket;
         use crate::routers::http::router::Router;
@@ -640,12 +869,25 @@ mod tests {
             tokenizer: None,      // HTTP mode doesn't need tokenizer
             router_manager: None, // Test doesn't need router manager
             response_storage: Arc::new(crate::data_connector::MemoryResponseStorage::new()),
-            api_key_cache: Arc::new(tokio::sync::RwLock::new(HashMap::new())),
+            api_key_cache: Arc::new(crate::core::ValidationCache::new(
+                &router_config.api_key_cache,
+            )),
             api_key_validation_urls: Arc::new(Vec::new()),
+            jwt_validator: None,
+            api_key_store: None,
+            pd_capacity: None,
+            keyed_rate_limiter: None,
+            token_rate_limiter: None,
+            content_policy: None,
+            audit_log: None,
+            upstream_tls: crate::core::UpstreamTls::default(),
         });
 
         let router = Router::new(vec![], &app_context).await.unwrap();
-        Arc::new(router) as Arc<dyn RouterTrait>
+        (
+            Arc::new(router) as Arc<dyn RouterTrait>,
+            app_context.worker_registry.clone(),
+        )
     }
 
     // Helper to create a PD config for testing
@@ -668,6 +910,7 @@ mod tests {
             prefill_selector,
             decode_selector,
             bootstrap_port_annotation: "vllm.ai/bootstrap-port".to_string(),
+            ..Default::default()
         }
     }
 
@@ -1211,4 +1454,51 @@ mod tests {
         // Pod should be removed from tracking
         assert!(!tracked_pods.lock().unwrap().contains(&pod_info));
     }
+
+    #[tokio::test]
+    async fn test_sync_discovered_workers_adds_and_removes() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let healthy_url = format!("http://{}", listener.local_addr().unwrap());
+        let app = axum::Router::new().route("/health", axum::routing::get(|| async { "ok" }));
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let router = create_test_router().await;
+        let mut tracked = HashSet::new();
+        let healthy = DiscoveredWorker::new(healthy_url.clone(), PodType::Regular);
+        let unreachable = DiscoveredWorker::new("http://127.0.0.1:1".into(), PodType::Regular);
+
+        // Unreachable workers stay untracked so the next sync retries them
+        let complete = sync_discovered_workers(
+            &mut tracked,
+            &[healthy.clone(), unreachable.clone()],
+            &router,
+            false,
+        )
+        .await;
+        assert!(!complete);
+        assert!(tracked.contains(&healthy));
+        assert!(!tracked.contains(&unreachable));
+        assert_eq!(router.get_worker_urls(), vec![healthy_url.clone()]);
+
+        // Workers missing from the next set are removed
+        assert!(sync_discovered_workers(&mut tracked, &[], &router, false).await);
+        assert!(tracked.is_empty());
+        assert!(router.get_worker_urls().is_empty());
+    }
+
+    #[test]
+    fn test_polling_backend_selection() {
+        let mut config = ServiceDiscoveryConfig {
+            enabled: true,
+            backend: DiscoveryBackendKind::File,
+            ..Default::default()
+        };
+        assert!(polling_backend(&config).is_err());
+
+        config.file_path = Some("/tmp/workers.yaml".into());
+        assert_eq!(polling_backend(&config).unwrap().name(), "file");
+
+        config.backend = DiscoveryBackendKind::Kubernetes;
+        assert!(polling_backend(&config).is_err());
+    }
 }
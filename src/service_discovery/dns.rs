@@ -0,0 +1,334 @@
+//! Worker discovery by polling DNS records
+//!
+//! Each configured name is one of:
+//!
+//! - an SRV name such as `_prefill._tcp.vllm.internal`; every record becomes
+//!   a worker at `http://<target>:<port>`, and a leading `_prefill` or
+//!   `_decode` label selects the PD role
+//! - a host name such as `vllm.internal` or `vllm.internal:8000`; every A and
+//!   AAAA record becomes a worker on the given port (or the discovery port)
+//!
+//! Either form may be prefixed with `prefill=`, `decode=` or `regular=` to
+//! set the role explicitly, and prefill names may end in `;bootstrap=<port>`
+//! to give their workers a bootstrap port, as in
+//! `prefill=_prefill._tcp.vllm.internal;bootstrap=8998`, which PD mode with
+//! the bootstrap protocol requires. A name with no records yields no workers, so
+//! scaling a service to zero removes its workers; other lookup failures keep
+//! the current workers until the next poll.
+
+use super::{DiscoveredWorker, DiscoveryBackend, PodType};
+use async_trait::async_trait;
+use hickory_resolver::config::{ResolverConfig, ResolverOpts};
+use hickory_resolver::error::{ResolveError, ResolveErrorKind};
+use hickory_resolver::TokioAsyncResolver;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tracing::warn;
+
+/// A name to resolve and the role of the workers behind it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DnsTarget {
+    Srv {
+        name: String,
+        role: PodType,
+        bootstrap_port: Option<u16>,
+    },
+    Host {
+        host: String,
+        port: u16,
+        role: PodType,
+        bootstrap_port: Option<u16>,
+    },
+}
+
+impl DnsTarget {
+    /// Parse a configured name, using `default_port` for hosts without one
+    pub fn parse(spec: &str, default_port: u16) -> Result<Self, String> {
+        let spec = spec.trim();
+        let (target, bootstrap_port) = match spec.split_once(';') {
+            Some((target, option)) => match option.trim().strip_prefix("bootstrap=") {
+                Some(port) => {
+                    let port = port
+                        .parse()
+                        .map_err(|_| format!("Invalid bootstrap port in '{}'", spec))?;
+                    (target.trim(), Some(port))
+                }
+                None => return Err(format!("Unknown option '{}' in '{}'", option, spec)),
+            },
+            None => (spec, None),
+        };
+        let (explicit_role, name) = match target.split_once('=') {
+            Some((role, name)) => {
+                let role = match role {
+                    "prefill" => PodType::Prefill,
+                    "decode" => PodType::Decode,
+                    "regular" => PodType::Regular,
+                    other => return Err(format!("Unknown role '{}' in '{}'", other, spec)),
+                };
+                (Some(role), name)
+            }
+            None => (None, target),
+        };
+        if name.is_empty() {
+            return Err(format!("Missing DNS name in '{}'", spec));
+        }
+
+        if name.starts_with('_') {
+            let role = explicit_role.unwrap_or_else(|| match name.split('.').next() {
+                Some("_prefill") => PodType::Prefill,
+                Some("_decode") => PodType::Decode,
+                _ => PodType::Regular,
+            });
+            Self::check_bootstrap_role(spec, &role, bootstrap_port)?;
+            return Ok(DnsTarget::Srv {
+                name: name.to_string(),
+                role,
+                bootstrap_port,
+            });
+        }
+
+        let (host, port) = match name.rsplit_once(':') {
+            Some((host, port)) => {
+                let port = port
+                    .parse()
+                    .map_err(|_| format!("Invalid port in '{}'", spec))?;
+                (host, port)
+            }
+            None => (name, default_port),
+        };
+        let role = explicit_role.unwrap_or(PodType::Regular);
+        Self::check_bootstrap_role(spec, &role, bootstrap_port)?;
+        Ok(DnsTarget::Host {
+            host: host.to_string(),
+            port,
+            role,
+            bootstrap_port,
+        })
+    }
+
+    /// Role of the workers behind this name
+    pub fn role(&self) -> &PodType {
+        match self {
+            DnsTarget::Srv { role, .. } | DnsTarget::Host { role, .. } => role,
+        }
+    }
+
+    /// Bootstrap port given to the workers behind this name
+    pub fn bootstrap_port(&self) -> Option<u16> {
+        match self {
+            DnsTarget::Srv { bootstrap_port, .. } | DnsTarget::Host { bootstrap_port, .. } => {
+                *bootstrap_port
+            }
+        }
+    }
+
+    fn check_bootstrap_role(
+        spec: &str,
+        role: &PodType,
+        bootstrap_port: Option<u16>,
+    ) -> Result<(), String> {
+        if bootstrap_port.is_some() && *role != PodType::Prefill {
+            return Err(format!(
+                "Bootstrap port in '{}' is only valid for prefill workers",
+                spec
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Backend polling DNS A/AAAA and SRV records
+#[derive(Debug)]
+pub struct DnsDiscovery {
+    targets: Vec<DnsTarget>,
+    interval: Duration,
+    resolver: TokioAsyncResolver,
+}
+
+impl DnsDiscovery {
+    pub fn new(names: &[String], default_port: u16, interval: Duration) -> Result<Self, String> {
+        let targets = names
+            .iter()
+            .map(|name| DnsTarget::parse(name, default_port))
+            .collect::<Result<Vec<_>, _>>()?;
+        if targets.is_empty() {
+            return Err("DNS discovery requires at least one name".to_string());
+        }
+
+        let resolver = TokioAsyncResolver::tokio_from_system_conf().unwrap_or_else(|e| {
+            warn!(
+                "Failed to read system DNS configuration, using defaults: {}",
+                e
+            );
+            TokioAsyncResolver::tokio(ResolverConfig::default(), ResolverOpts::default())
+        });
+        Ok(Self {
+            targets,
+            interval,
+            resolver,
+        })
+    }
+
+    async fn resolve(&self, target: &DnsTarget) -> Result<Vec<DiscoveredWorker>, ResolveError> {
+        match target {
+            DnsTarget::Srv {
+                name,
+                role,
+                bootstrap_port,
+            } => {
+                let records = self.resolver.srv_lookup(name.as_str()).await?;
+                Ok(records
+                    .iter()
+                    .map(|srv| {
+                        let target = srv.target().to_utf8();
+                        let url = format!("http://{}:{}", target.trim_end_matches('.'), srv.port());
+                        DiscoveredWorker {
+                            url,
+                            role: role.clone(),
+                            bootstrap_port: *bootstrap_port,
+                        }
+                    })
+                    .collect())
+            }
+            DnsTarget::Host {
+                host,
+                port,
+                role,
+                bootstrap_port,
+            } => {
+                let ips = self.resolver.lookup_ip(host.as_str()).await?;
+                Ok(ips
+                    .iter()
+                    .map(|ip| {
+                        let url = format!("http://{}", SocketAddr::new(ip, *port));
+                        DiscoveredWorker {
+                            url,
+                            role: role.clone(),
+                            bootstrap_port: *bootstrap_port,
+                        }
+                    })
+                    .collect())
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl DiscoveryBackend for DnsDiscovery {
+    fn name(&self) -> &'static str {
+        "dns"
+    }
+
+    fn poll_interval(&self) -> Duration {
+        self.interval
+    }
+
+    async fn discover(&mut self) -> Result<Option<Vec<DiscoveredWorker>>, String> {
+        let mut workers = Vec::new();
+        for target in &self.targets {
+            match self.resolve(target).await {
+                Ok(found) => workers.extend(found),
+                Err(e) if matches!(e.kind(), ResolveErrorKind::NoRecordsFound { .. }) => {}
+                Err(e) => return Err(format!("DNS lookup for {:?} failed: {}", target, e)),
+            }
+        }
+        Ok(Some(workers))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_srv_names() {
+        assert_eq!(
+            DnsTarget::parse("_prefill._tcp.vllm.internal", 8000).unwrap(),
+            DnsTarget::Srv {
+                name: "_prefill._tcp.vllm.internal".to_string(),
+                role: PodType::Prefill,
+                bootstrap_port: None,
+            }
+        );
+        assert_eq!(
+            DnsTarget::parse("_decode._tcp.vllm.internal", 8000).unwrap(),
+            DnsTarget::Srv {
+                name: "_decode._tcp.vllm.internal".to_string(),
+                role: PodType::Decode,
+                bootstrap_port: None,
+            }
+        );
+        assert_eq!(
+            DnsTarget::parse("decode=_http._tcp.vllm.internal", 8000).unwrap(),
+            DnsTarget::Srv {
+                name: "_http._tcp.vllm.internal".to_string(),
+                role: PodType::Decode,
+                bootstrap_port: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_host_names() {
+        assert_eq!(
+            DnsTarget::parse("vllm.internal", 8000).unwrap(),
+            DnsTarget::Host {
+                host: "vllm.internal".to_string(),
+                port: 8000,
+                role: PodType::Regular,
+                bootstrap_port: None,
+            }
+        );
+        assert_eq!(
+            DnsTarget::parse("prefill=prefill.vllm.internal:9000", 8000).unwrap(),
+            DnsTarget::Host {
+                host: "prefill.vllm.internal".to_string(),
+                port: 9000,
+                role: PodType::Prefill,
+                bootstrap_port: None,
+            }
+        );
+
+        assert!(DnsTarget::parse("vllm.internal:http", 8000).is_err());
+        assert!(DnsTarget::parse("leader=vllm.internal", 8000).is_err());
+        assert!(DnsTarget::parse("decode=", 8000).is_err());
+    }
+
+    #[test]
+    fn test_parse_bootstrap_port() {
+        assert_eq!(
+            DnsTarget::parse("prefill=_prefill._tcp.vllm.internal;bootstrap=8998", 8000).unwrap(),
+            DnsTarget::Srv {
+                name: "_prefill._tcp.vllm.internal".to_string(),
+                role: PodType::Prefill,
+                bootstrap_port: Some(8998),
+            }
+        );
+        assert_eq!(
+            DnsTarget::parse("prefill=prefill.vllm.internal:9000;bootstrap=8998", 8000)
+                .unwrap()
+                .bootstrap_port(),
+            Some(8998)
+        );
+
+        assert!(DnsTarget::parse("_prefill._tcp.vllm.internal;bootstrap=x", 8000).is_err());
+        assert!(DnsTarget::parse("_prefill._tcp.vllm.internal;zmq=5557", 8000).is_err());
+        assert!(DnsTarget::parse("_decode._tcp.vllm.internal;bootstrap=8998", 8000).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_dns_discovery_resolves_hosts_file_entries() {
+        let mut backend = DnsDiscovery::new(
+            &["localhost:8123".to_string()],
+            8000,
+            Duration::from_secs(1),
+        )
+        .unwrap();
+
+        let workers = backend.discover().await.unwrap().unwrap();
+        assert!(workers.contains(&DiscoveredWorker::new(
+            "http://127.0.0.1:8123".to_string(),
+            PodType::Regular
+        )));
+    }
+}
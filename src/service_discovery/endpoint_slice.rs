@@ -0,0 +1,741 @@
+//! Kubernetes discovery through the EndpointSlices of followed Services
+//!
+//! Instead of watching pods and assuming one port for every worker, this
+//! backend follows one Service per role and turns each routable endpoint of
+//! its EndpointSlices into a worker. Ports are looked up by name, so a
+//! prefill pod can expose its HTTP, bootstrap and ZMQ ports together. The
+//! model ID, priority and cost come from `vllm.ai/*` labels or annotations on
+//! the slice; annotations win because label values cannot hold model IDs
+//! with slashes.
+
+use super::{add_worker_with_role, remove_worker_with_role, PodType, ServiceDiscoveryConfig};
+use crate::protocols::worker_spec::WorkerConfigRequest;
+use crate::routers::http::vllm_service_discovery::ZMQ_ADDRESS_LABEL;
+use crate::routers::RouterTrait;
+use futures::StreamExt;
+use k8s_openapi::api::discovery::v1::{EndpointConditions, EndpointSlice};
+use kube::{
+    api::Api,
+    runtime::watcher::{self, watcher, Event},
+    runtime::WatchStreamExt,
+    Client,
+};
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use tokio::{task, time};
+use tracing::{debug, error, info, warn};
+
+/// Label the EndpointSlice controller sets to the owning Service's name
+pub const SERVICE_NAME_LABEL: &str = "kubernetes.io/service-name";
+/// Label or annotation with the model served by the endpoints
+pub const MODEL_ID_KEY: &str = "vllm.ai/model-id";
+/// Label or annotation with the worker priority (higher is preferred)
+pub const PRIORITY_KEY: &str = "vllm.ai/priority";
+/// Label or annotation with the worker cost factor
+pub const COST_KEY: &str = "vllm.ai/cost";
+
+/// A routable endpoint of a followed Service
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EndpointInfo {
+    pub address: String,
+    pub pod_type: PodType,
+    pub http_port: u16,
+    pub bootstrap_port: Option<u16>,
+    pub zmq_port: Option<u16>,
+    pub model_id: Option<String>,
+    pub priority: Option<u32>,
+    // Kept as validated text so the struct stays hashable
+    pub cost: Option<String>,
+}
+
+impl EndpointInfo {
+    /// Role of the slice's Service, or `None` when the Service is not followed
+    pub fn slice_role(slice: &EndpointSlice, config: &ServiceDiscoveryConfig) -> Option<PodType> {
+        let service = slice.metadata.labels.as_ref()?.get(SERVICE_NAME_LABEL)?;
+        let services = &config.endpoint_slices;
+        if config.pd_mode {
+            if services.prefill_service.as_ref() == Some(service) {
+                Some(PodType::Prefill)
+            } else if services.decode_service.as_ref() == Some(service) {
+                Some(PodType::Decode)
+            } else {
+                None
+            }
+        } else if services.service.as_ref() == Some(service) {
+            Some(PodType::Regular)
+        } else {
+            None
+        }
+    }
+
+    /// Returns true if an endpoint should receive new requests
+    ///
+    /// Terminating endpoints are drained even while they are still serving.
+    /// A missing `ready` condition falls back to `serving`, and both default
+    /// to true as the EndpointSlice API specifies.
+    pub fn is_routable(conditions: Option<&EndpointConditions>) -> bool {
+        let Some(conditions) = conditions else {
+            return true;
+        };
+        !conditions.terminating.unwrap_or(false)
+            && conditions.ready.or(conditions.serving).unwrap_or(true)
+    }
+
+    /// Routable endpoints of a slice, empty when its Service is not followed
+    pub fn from_slice(slice: &EndpointSlice, config: &ServiceDiscoveryConfig) -> Vec<Self> {
+        let Some(pod_type) = Self::slice_role(slice, config) else {
+            return Vec::new();
+        };
+
+        let ports = slice.ports.as_deref().unwrap_or_default();
+        let port_number = |port: Option<i32>| port.and_then(|port| u16::try_from(port).ok());
+        let named_port = |name: &str| {
+            ports
+                .iter()
+                .find(|port| port.name.as_deref() == Some(name))
+                .and_then(|port| port_number(port.port))
+        };
+        let names = &config.endpoint_slices;
+
+        // A Service with a single unnamed port serves HTTP on it
+        let http_port = named_port(&names.http_port_name)
+            .or_else(|| match ports {
+                [only] if only.name.as_deref().unwrap_or_default().is_empty() => {
+                    port_number(only.port)
+                }
+                _ => None,
+            })
+            .unwrap_or(config.port);
+        let bootstrap_port = match pod_type {
+            PodType::Prefill => named_port(&names.bootstrap_port_name),
+            _ => None,
+        };
+        let zmq_port = named_port(&names.zmq_port_name);
+
+        let model_id = metadata_value(slice, MODEL_ID_KEY);
+        let priority = metadata_value(slice, PRIORITY_KEY).and_then(|value| {
+            value
+                .parse::<u32>()
+                .inspect_err(|_| warn!("Ignoring invalid {} '{}'", PRIORITY_KEY, value))
+                .ok()
+        });
+        let cost = metadata_value(slice, COST_KEY).filter(|value| {
+            let valid = value.parse::<f32>().is_ok();
+            if !valid {
+                warn!("Ignoring invalid {} '{}'", COST_KEY, value);
+            }
+            valid
+        });
+
+        slice
+            .endpoints
+            .iter()
+            .filter(|endpoint| Self::is_routable(endpoint.conditions.as_ref()))
+            // All addresses of an endpoint reach the same pod; use the first
+            .filter_map(|endpoint| endpoint.addresses.first())
+            .map(|address| EndpointInfo {
+                address: address.clone(),
+                pod_type: pod_type.clone(),
+                http_port,
+                bootstrap_port,
+                zmq_port,
+                model_id: model_id.clone(),
+                priority,
+                cost: cost.clone(),
+            })
+            .collect()
+    }
+
+    fn host_port(&self, port: u16) -> String {
+        match self.address.parse::<IpAddr>() {
+            Ok(ip) => SocketAddr::new(ip, port).to_string(),
+            Err(_) => format!("{}:{}", self.address, port),
+        }
+    }
+
+    /// Generates a worker URL for this endpoint
+    pub fn worker_url(&self) -> String {
+        format!("http://{}", self.host_port(self.http_port))
+    }
+
+    /// Worker configuration carrying the endpoint's role, ports and metadata
+    pub fn worker_config(&self) -> WorkerConfigRequest {
+        let mut labels = HashMap::new();
+        if let Some(zmq_port) = self.zmq_port {
+            labels.insert(ZMQ_ADDRESS_LABEL.to_string(), self.host_port(zmq_port));
+        }
+        let worker_type = match self.pod_type {
+            PodType::Prefill => "prefill",
+            PodType::Decode => "decode",
+            PodType::Regular => "regular",
+        };
+
+        WorkerConfigRequest {
+            url: self.worker_url(),
+            model_id: self.model_id.clone(),
+            priority: self.priority,
+            cost: self.cost.as_deref().and_then(|cost| cost.parse().ok()),
+            worker_type: Some(worker_type.to_string()),
+            bootstrap_port: self.bootstrap_port,
+            tokenizer_path: None,
+            chat_template: None,
+            labels,
+        }
+    }
+}
+
+/// Label value, overridden by an annotation with the same key
+fn metadata_value(slice: &EndpointSlice, key: &str) -> Option<String> {
+    let metadata = &slice.metadata;
+    metadata
+        .annotations
+        .as_ref()
+        .and_then(|annotations| annotations.get(key))
+        .or_else(|| metadata.labels.as_ref().and_then(|labels| labels.get(key)))
+        .cloned()
+}
+
+/// Endpoints of every followed slice and the workers added for them
+#[derive(Debug, Default)]
+pub struct EndpointSliceTracker {
+    slices: HashMap<String, HashSet<EndpointInfo>>,
+    workers: HashSet<EndpointInfo>,
+    // Slices listed since the watcher (re)started, replacing `slices` when done
+    relisted: Option<HashMap<String, HashSet<EndpointInfo>>>,
+}
+
+impl EndpointSliceTracker {
+    fn slice_key(slice: &EndpointSlice) -> String {
+        format!(
+            "{}/{}",
+            slice.metadata.namespace.as_deref().unwrap_or_default(),
+            slice.metadata.name.as_deref().unwrap_or_default()
+        )
+    }
+
+    fn endpoints(slice: &EndpointSlice, config: &ServiceDiscoveryConfig) -> HashSet<EndpointInfo> {
+        EndpointInfo::from_slice(slice, config)
+            .into_iter()
+            .collect()
+    }
+
+    /// Endpoints currently backed by a worker
+    pub fn workers(&self) -> &HashSet<EndpointInfo> {
+        &self.workers
+    }
+
+    /// Apply a watcher event and sync the router with the result
+    pub async fn handle_event(
+        &mut self,
+        event: Event<EndpointSlice>,
+        config: &ServiceDiscoveryConfig,
+        router: &Arc<dyn RouterTrait>,
+    ) {
+        match event {
+            Event::Init => {
+                self.relisted = Some(HashMap::new());
+                return;
+            }
+            Event::InitApply(slice) => {
+                let endpoints = Self::endpoints(&slice, config);
+                self.relisted
+                    .get_or_insert_with(HashMap::new)
+                    .insert(Self::slice_key(&slice), endpoints);
+                return;
+            }
+            Event::InitDone => {
+                // Slices missing from the re-list were deleted while disconnected
+                if let Some(relisted) = self.relisted.take() {
+                    self.slices = relisted;
+                }
+            }
+            Event::Apply(slice) => {
+                let endpoints = Self::endpoints(&slice, config);
+                self.slices.insert(Self::slice_key(&slice), endpoints);
+            }
+            Event::Delete(slice) => {
+                self.slices.remove(&Self::slice_key(&slice));
+            }
+        }
+        self.reconcile(router, config.pd_mode).await;
+    }
+
+    /// Remove workers whose endpoints are gone and add new endpoints
+    ///
+    /// Endpoints that fail to add stay untracked and are retried on the next
+    /// reconcile.
+    pub async fn reconcile(&mut self, router: &Arc<dyn RouterTrait>, pd_mode: bool) {
+        let desired: HashSet<EndpointInfo> = self.slices.values().flatten().cloned().collect();
+
+        let removed: Vec<EndpointInfo> = self.workers.difference(&desired).cloned().collect();
+        for endpoint in removed {
+            let worker_url = endpoint.worker_url();
+            info!(
+                "Removing endpoint: {} | type: {:?}",
+                worker_url, endpoint.pod_type
+            );
+            remove_worker_with_role(router, &worker_url, Some(&endpoint.pod_type), pd_mode).await;
+            self.workers.remove(&endpoint);
+        }
+
+        for endpoint in desired {
+            if self.workers.contains(&endpoint) {
+                continue;
+            }
+            let worker_config = endpoint.worker_config();
+            info!(
+                "Adding endpoint: {} | type: {:?} | model: {:?}",
+                worker_config.url, endpoint.pod_type, worker_config.model_id
+            );
+            match add_worker_with_role(
+                router,
+                &worker_config.url,
+                Some(&endpoint.pod_type),
+                worker_config.bootstrap_port,
+                worker_config.worker_labels(),
+                pd_mode,
+            )
+            .await
+            {
+                Ok(()) => {
+                    debug!("Worker added: {}", worker_config.url);
+                    self.workers.insert(endpoint);
+                }
+                Err(e) => {
+                    error!(
+                        "Failed to add worker {} to router: {}",
+                        worker_config.url, e
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Services followed for the configured mode
+fn followed_services(config: &ServiceDiscoveryConfig) -> Vec<String> {
+    let services = &config.endpoint_slices;
+    let candidates = if config.pd_mode {
+        vec![&services.prefill_service, &services.decode_service]
+    } else {
+        vec![&services.service]
+    };
+    candidates.into_iter().flatten().cloned().collect()
+}
+
+pub async fn start_endpoint_slice_discovery(
+    config: ServiceDiscoveryConfig,
+    router: Arc<dyn RouterTrait>,
+) -> Result<task::JoinHandle<()>, kube::Error> {
+    let _ = rustls::crypto::ring::default_provider().install_default();
+
+    let client = Client::try_default().await?;
+    let services = followed_services(&config);
+    let label_selector = format!("{} in ({})", SERVICE_NAME_LABEL, services.join(","));
+    info!(
+        "Starting K8s EndpointSlice discovery | services: '{}'",
+        services.join(",")
+    );
+
+    let handle = task::spawn(async move {
+        let slices: Api<EndpointSlice> = if let Some(namespace) = &config.namespace {
+            Api::namespaced(client, namespace)
+        } else {
+            Api::all(client)
+        };
+        let mut tracker = EndpointSliceTracker::default();
+        let mut retry_interval = time::interval(config.check_interval);
+
+        loop {
+            let watcher_config = watcher::Config::default().labels(&label_selector);
+            let mut events = watcher(slices.clone(), watcher_config)
+                .default_backoff()
+                .boxed();
+
+            loop {
+                tokio::select! {
+                    event = events.next() => match event {
+                        Some(Ok(event)) => tracker.handle_event(event, &config, &router).await,
+                        Some(Err(e)) => warn!("Error in EndpointSlice watcher: {}", e),
+                        None => break,
+                    },
+                    // Retry endpoints that were not healthy yet
+                    _ = retry_interval.tick() => tracker.reconcile(&router, config.pd_mode).await,
+                }
+            }
+
+            warn!(
+                "EndpointSlice watcher exited, restarting in {} seconds",
+                config.check_interval.as_secs()
+            );
+            time::sleep(config.check_interval).await;
+        }
+    });
+
+    Ok(handle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{create_test_router, create_test_router_with_registry};
+    use super::*;
+    use k8s_openapi::api::discovery::v1::{Endpoint, EndpointPort};
+    use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
+    use std::collections::BTreeMap;
+
+    // Helper function to create an EndpointSlice for testing
+    fn create_endpoint_slice(
+        name: &str,
+        service: &str,
+        endpoints: Vec<(&str, Option<EndpointConditions>)>,
+        ports: Vec<(Option<&str>, i32)>,
+    ) -> EndpointSlice {
+        EndpointSlice {
+            metadata: ObjectMeta {
+                name: Some(name.to_string()),
+                namespace: Some("default".to_string()),
+                labels: Some(BTreeMap::from([(
+                    SERVICE_NAME_LABEL.to_string(),
+                    service.to_string(),
+                )])),
+                ..Default::default()
+            },
+            address_type: "IPv4".to_string(),
+            endpoints: endpoints
+                .into_iter()
+                .map(|(address, conditions)| Endpoint {
+                    addresses: vec![address.to_string()],
+                    conditions,
+                    ..Default::default()
+                })
+                .collect(),
+            ports: Some(
+                ports
+                    .into_iter()
+                    .map(|(name, port)| EndpointPort {
+                        name: name.map(str::to_string),
+                        port: Some(port),
+                        ..Default::default()
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    fn conditions(
+        ready: Option<bool>,
+        serving: Option<bool>,
+        terminating: Option<bool>,
+    ) -> Option<EndpointConditions> {
+        Some(EndpointConditions {
+            ready,
+            serving,
+            terminating,
+        })
+    }
+
+    fn create_regular_config() -> ServiceDiscoveryConfig {
+        let mut config = ServiceDiscoveryConfig {
+            enabled: true,
+            port: 8080,
+            ..Default::default()
+        };
+        config.endpoint_slices.service = Some("vllm".to_string());
+        config
+    }
+
+    fn create_pd_config() -> ServiceDiscoveryConfig {
+        let mut config = ServiceDiscoveryConfig {
+            enabled: true,
+            port: 8080,
+            pd_mode: true,
+            ..Default::default()
+        };
+        config.endpoint_slices.prefill_service = Some("vllm-prefill".to_string());
+        config.endpoint_slices.decode_service = Some("vllm-decode".to_string());
+        config
+    }
+
+    #[test]
+    fn test_endpoint_info_slice_role() {
+        let regular = create_regular_config();
+        let pd = create_pd_config();
+        let slice = |service| create_endpoint_slice("s", service, vec![], vec![]);
+
+        assert_eq!(
+            EndpointInfo::slice_role(&slice("vllm"), &regular),
+            Some(PodType::Regular)
+        );
+        assert_eq!(
+            EndpointInfo::slice_role(&slice("vllm-prefill"), &pd),
+            Some(PodType::Prefill)
+        );
+        assert_eq!(
+            EndpointInfo::slice_role(&slice("vllm-decode"), &pd),
+            Some(PodType::Decode)
+        );
+        assert_eq!(EndpointInfo::slice_role(&slice("vllm"), &pd), None);
+        assert_eq!(EndpointInfo::slice_role(&slice("other"), &regular), None);
+    }
+
+    #[test]
+    fn test_endpoint_info_is_routable() {
+        assert!(EndpointInfo::is_routable(None));
+        assert!(EndpointInfo::is_routable(
+            conditions(Some(true), Some(true), Some(false)).as_ref()
+        ));
+        assert!(EndpointInfo::is_routable(
+            conditions(None, None, None).as_ref()
+        ));
+        // Ready falls back to serving
+        assert!(EndpointInfo::is_routable(
+            conditions(None, Some(true), None).as_ref()
+        ));
+        assert!(!EndpointInfo::is_routable(
+            conditions(None, Some(false), None).as_ref()
+        ));
+        assert!(!EndpointInfo::is_routable(
+            conditions(Some(false), Some(true), None).as_ref()
+        ));
+        // Terminating endpoints drain even while serving
+        assert!(!EndpointInfo::is_routable(
+            conditions(Some(false), Some(true), Some(true)).as_ref()
+        ));
+    }
+
+    #[test]
+    fn test_endpoint_info_from_slice_filters_conditions() {
+        let slice = create_endpoint_slice(
+            "vllm-abc",
+            "vllm",
+            vec![
+                ("10.0.0.1", conditions(Some(true), Some(true), Some(false))),
+                ("10.0.0.2", conditions(Some(false), Some(false), None)),
+                ("10.0.0.3", conditions(Some(false), Some(true), Some(true))),
+                ("10.0.0.4", None),
+            ],
+            vec![(Some("http"), 8000)],
+        );
+
+        let urls: Vec<String> = EndpointInfo::from_slice(&slice, &create_regular_config())
+            .iter()
+            .map(EndpointInfo::worker_url)
+            .collect();
+        assert_eq!(urls, vec!["http://10.0.0.1:8000", "http://10.0.0.4:8000"]);
+    }
+
+    #[test]
+    fn test_endpoint_info_from_slice_named_ports() {
+        let slice = create_endpoint_slice(
+            "vllm-prefill-abc",
+            "vllm-prefill",
+            vec![("10.0.0.1", None)],
+            vec![
+                (Some("zmq"), 5555),
+                (Some("http"), 8000),
+                (Some("bootstrap"), 8998),
+            ],
+        );
+
+        let endpoints = EndpointInfo::from_slice(&slice, &create_pd_config());
+        assert_eq!(endpoints.len(), 1);
+        let endpoint = &endpoints[0];
+        assert_eq!(endpoint.pod_type, PodType::Prefill);
+        assert_eq!(endpoint.worker_url(), "http://10.0.0.1:8000");
+        assert_eq!(endpoint.bootstrap_port, Some(8998));
+        assert_eq!(endpoint.zmq_port, Some(5555));
+
+        // Bootstrap ports only apply to prefill endpoints
+        let slice = create_endpoint_slice(
+            "vllm-decode-abc",
+            "vllm-decode",
+            vec![("10.0.0.2", None)],
+            vec![(Some("http"), 8000), (Some("bootstrap"), 8998)],
+        );
+        let endpoints = EndpointInfo::from_slice(&slice, &create_pd_config());
+        assert_eq!(endpoints[0].bootstrap_port, None);
+    }
+
+    #[test]
+    fn test_endpoint_info_from_slice_port_fallbacks() {
+        let config = create_regular_config();
+
+        // A single unnamed port is the HTTP port
+        let slice =
+            create_endpoint_slice("a", "vllm", vec![("10.0.0.1", None)], vec![(None, 9000)]);
+        assert_eq!(EndpointInfo::from_slice(&slice, &config)[0].http_port, 9000);
+
+        // Otherwise the discovery port is used
+        let slice = create_endpoint_slice(
+            "b",
+            "vllm",
+            vec![("10.0.0.1", None)],
+            vec![(Some("metrics"), 9090), (Some("grpc"), 9000)],
+        );
+        assert_eq!(EndpointInfo::from_slice(&slice, &config)[0].http_port, 8080);
+    }
+
+    #[test]
+    fn test_endpoint_info_worker_url_ipv6() {
+        let mut slice = create_endpoint_slice(
+            "vllm-v6",
+            "vllm",
+            vec![("fd00::1", None)],
+            vec![(Some("http"), 8000), (Some("zmq"), 5555)],
+        );
+        slice.address_type = "IPv6".to_string();
+
+        let endpoint = &EndpointInfo::from_slice(&slice, &create_regular_config())[0];
+        assert_eq!(endpoint.worker_url(), "http://[fd00::1]:8000");
+        assert_eq!(
+            endpoint.worker_config().labels.get(ZMQ_ADDRESS_LABEL),
+            Some(&"[fd00::1]:5555".to_string())
+        );
+    }
+
+    #[test]
+    fn test_endpoint_info_metadata_labels_and_annotations() {
+        let mut slice = create_endpoint_slice(
+            "vllm-abc",
+            "vllm-prefill",
+            vec![("10.0.0.1", None)],
+            vec![(Some("http"), 8000), (Some("zmq"), 5555)],
+        );
+        let labels = slice.metadata.labels.as_mut().unwrap();
+        labels.insert(MODEL_ID_KEY.to_string(), "llama".to_string());
+        labels.insert(PRIORITY_KEY.to_string(), "80".to_string());
+        labels.insert(COST_KEY.to_string(), "cheap".to_string());
+        slice.metadata.annotations = Some(BTreeMap::from([(
+            MODEL_ID_KEY.to_string(),
+            "meta-llama/Llama-3.1-8B".to_string(),
+        )]));
+
+        let endpoint = &EndpointInfo::from_slice(&slice, &create_pd_config())[0];
+        let config = endpoint.worker_config();
+        assert_eq!(config.url, "http://10.0.0.1:8000");
+        assert_eq!(config.worker_type.as_deref(), Some("prefill"));
+        // Annotations override labels; invalid values are dropped
+        assert_eq!(config.model_id.as_deref(), Some("meta-llama/Llama-3.1-8B"));
+        assert_eq!(config.priority, Some(80));
+        assert_eq!(config.cost, None);
+
+        let labels = config.worker_labels();
+        assert_eq!(labels["model_id"], "meta-llama/Llama-3.1-8B");
+        assert_eq!(labels["priority"], "80");
+        assert_eq!(labels[ZMQ_ADDRESS_LABEL], "10.0.0.1:5555");
+        assert!(!labels.contains_key("cost"));
+    }
+
+    #[test]
+    fn test_followed_services() {
+        assert_eq!(followed_services(&create_regular_config()), vec!["vllm"]);
+        assert_eq!(
+            followed_services(&create_pd_config()),
+            vec!["vllm-prefill", "vllm-decode"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_handle_endpoint_slice_event_unhealthy_endpoint() {
+        let router = create_test_router().await;
+        let config = create_regular_config();
+        let mut tracker = EndpointSliceTracker::default();
+        let slice = create_endpoint_slice(
+            "vllm-abc",
+            "vllm",
+            vec![("127.0.0.1", None)],
+            vec![(Some("http"), 1)],
+        );
+
+        tracker
+            .handle_event(Event::Apply(slice), &config, &router)
+            .await;
+
+        // Endpoint should not be tracked since router.add_worker fails for a dead server
+        assert!(tracker.workers().is_empty());
+        assert_eq!(tracker.slices.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_handle_endpoint_slice_events_add_and_remove() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let app = axum::Router::new().route("/health", axum::routing::get(|| async { "ok" }));
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let (router, registry) = create_test_router_with_registry().await;
+        let config = create_regular_config();
+        let mut tracker = EndpointSliceTracker::default();
+        let mut slice = create_endpoint_slice(
+            "vllm-abc",
+            "vllm",
+            vec![("127.0.0.1", conditions(Some(true), Some(true), Some(false)))],
+            vec![(Some("http"), port as i32)],
+        );
+        slice.metadata.annotations = Some(BTreeMap::from([
+            (MODEL_ID_KEY.to_string(), "org/model".to_string()),
+            (PRIORITY_KEY.to_string(), "90".to_string()),
+        ]));
+        let worker_url = format!("http://127.0.0.1:{}", port);
+
+        tracker
+            .handle_event(Event::Apply(slice.clone()), &config, &router)
+            .await;
+        assert_eq!(tracker.workers().len(), 1);
+        let worker = registry.get_by_url(&worker_url).unwrap();
+        assert_eq!(worker.model_id(), "org/model");
+        assert_eq!(worker.priority(), 90);
+
+        // A terminating endpoint is removed while its slice still exists
+        slice.endpoints[0].conditions = conditions(Some(false), Some(true), Some(true));
+        tracker
+            .handle_event(Event::Apply(slice.clone()), &config, &router)
+            .await;
+        assert!(tracker.workers().is_empty());
+        assert!(registry.get_by_url(&worker_url).is_none());
+
+        // Re-listing restores the endpoint, then deleting the slice removes it
+        slice.endpoints[0].conditions = None;
+        tracker.handle_event(Event::Init, &config, &router).await;
+        tracker
+            .handle_event(Event::InitApply(slice.clone()), &config, &router)
+            .await;
+        assert!(tracker.workers().is_empty());
+        tracker
+            .handle_event(Event::InitDone, &config, &router)
+            .await;
+        assert_eq!(tracker.workers().len(), 1);
+
+        tracker
+            .handle_event(Event::Delete(slice), &config, &router)
+            .await;
+        assert!(tracker.workers().is_empty());
+        assert!(router.get_worker_urls().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_relist_drops_slices_deleted_while_disconnected() {
+        let router = create_test_router().await;
+        let config = create_regular_config();
+        let mut tracker = EndpointSliceTracker::default();
+        let slice = |name| create_endpoint_slice(name, "vllm", vec![], vec![]);
+
+        tracker
+            .handle_event(Event::Apply(slice("a")), &config, &router)
+            .await;
+        tracker
+            .handle_event(Event::Apply(slice("b")), &config, &router)
+            .await;
+        assert_eq!(tracker.slices.len(), 2);
+
+        tracker.handle_event(Event::Init, &config, &router).await;
+        tracker
+            .handle_event(Event::InitApply(slice("b")), &config, &router)
+            .await;
+        tracker
+            .handle_event(Event::InitDone, &config, &router)
+            .await;
+        assert_eq!(tracker.slices.keys().collect::<Vec<_>>(), vec!["default/b"]);
+    }
+}
@@ -0,0 +1,220 @@
+//! Worker discovery from a watched JSON or YAML file
+//!
+//! The file lists workers either at the top level or under `workers`:
+//!
+//! ```yaml
+//! workers:
+//!   - url: http://10.0.0.1:8000
+//!     role: prefill
+//!     bootstrap_port: 8998
+//!   - url: http://10.0.0.2:8000
+//!     role: decode
+//! ```
+//!
+//! `role` defaults to `regular`. Files ending in `.yaml` or `.yml` are parsed
+//! as YAML, anything else as JSON. The file is re-read every second and the
+//! worker set is only re-synced when its contents change, so replacing it
+//! atomically (or through a Kubernetes ConfigMap mount) is picked up.
+
+use super::{DiscoveredWorker, DiscoveryBackend, PodType};
+use crate::core::{parse_yaml_or_json, WatchedFiles};
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const FILE_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum WorkerFile {
+    Workers { workers: Vec<WorkerEntry> },
+    List(Vec<WorkerEntry>),
+}
+
+#[derive(Debug, Deserialize)]
+struct WorkerEntry {
+    url: String,
+    #[serde(default)]
+    role: WorkerRole,
+    #[serde(default)]
+    bootstrap_port: Option<u16>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum WorkerRole {
+    #[default]
+    Regular,
+    Prefill,
+    Decode,
+}
+
+impl From<WorkerRole> for PodType {
+    fn from(role: WorkerRole) -> Self {
+        match role {
+            WorkerRole::Regular => PodType::Regular,
+            WorkerRole::Prefill => PodType::Prefill,
+            WorkerRole::Decode => PodType::Decode,
+        }
+    }
+}
+
+/// Parse a worker file, as YAML when `path` ends in `.yaml`/`.yml` and JSON otherwise
+pub fn parse_worker_file(
+    path: impl AsRef<Path>,
+    contents: impl AsRef<[u8]>,
+) -> Result<Vec<DiscoveredWorker>, String> {
+    let file: WorkerFile = parse_yaml_or_json(path, contents)?;
+    let entries = match file {
+        WorkerFile::Workers { workers } => workers,
+        WorkerFile::List(workers) => workers,
+    };
+
+    entries
+        .into_iter()
+        .map(|entry| {
+            let url = entry.url.trim().trim_end_matches('/');
+            if url.is_empty() {
+                return Err("worker url must not be empty".to_string());
+            }
+            let url = if url.contains("://") {
+                url.to_string()
+            } else {
+                format!("http://{}", url)
+            };
+            Ok(DiscoveredWorker {
+                url,
+                role: entry.role.into(),
+                bootstrap_port: entry.bootstrap_port,
+            })
+        })
+        .collect()
+}
+
+/// Backend reading workers from a JSON or YAML file
+#[derive(Debug)]
+pub struct FileDiscovery {
+    path: PathBuf,
+    file: WatchedFiles,
+    last_error: Option<String>,
+}
+
+impl FileDiscovery {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        Self {
+            file: WatchedFiles::new([&path]),
+            path,
+            last_error: None,
+        }
+    }
+}
+
+#[async_trait]
+impl DiscoveryBackend for FileDiscovery {
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    fn poll_interval(&self) -> Duration {
+        FILE_POLL_INTERVAL
+    }
+
+    async fn discover(&mut self) -> Result<Option<Vec<DiscoveredWorker>>, String> {
+        let contents = match self.file.read_if_changed() {
+            Ok(Some(contents)) => contents,
+            Ok(None) => {
+                self.last_error = None;
+                return Ok(None);
+            }
+            Err(error) => {
+                // Report each distinct failure once rather than every poll
+                if self.last_error.as_ref() == Some(&error) {
+                    return Ok(None);
+                }
+                self.last_error = Some(error.clone());
+                return Err(error);
+            }
+        };
+        self.last_error = None;
+
+        parse_worker_file(&self.path, &contents[0])
+            .map(Some)
+            .map_err(|e| format!("Invalid worker file {}: {}", self.path.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_yaml_worker_file() {
+        let workers = parse_worker_file(
+            "workers.yaml",
+            r#"
+workers:
+  - url: http://10.0.0.1:8000/
+    role: prefill
+    bootstrap_port: 8998
+  - url: 10.0.0.2:8000
+    role: decode
+  - url: http://10.0.0.3:8000
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            workers,
+            vec![
+                DiscoveredWorker {
+                    url: "http://10.0.0.1:8000".to_string(),
+                    role: PodType::Prefill,
+                    bootstrap_port: Some(8998),
+                },
+                DiscoveredWorker::new("http://10.0.0.2:8000".to_string(), PodType::Decode),
+                DiscoveredWorker::new("http://10.0.0.3:8000".to_string(), PodType::Regular),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_json_worker_list() {
+        let workers = parse_worker_file(
+            "workers.json",
+            r#"[{"url": "http://a:8000"}, {"url": "http://b:8000", "role": "decode"}]"#,
+        )
+        .unwrap();
+        assert_eq!(workers.len(), 2);
+        assert_eq!(workers[1].role, PodType::Decode);
+
+        assert!(
+            parse_worker_file("workers.json", r#"[{"url": "http://a:8000", "role": "x"}]"#)
+                .is_err()
+        );
+        assert!(parse_worker_file("workers.json", r#"[{"url": " "}]"#).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_file_discovery_reports_changes_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("workers.json");
+        let mut backend = FileDiscovery::new(&path);
+
+        // A missing file is reported once
+        assert!(backend.discover().await.is_err());
+        assert_eq!(backend.discover().await.unwrap(), None);
+
+        std::fs::write(&path, r#"{"workers": [{"url": "http://a:8000"}]}"#).unwrap();
+        assert_eq!(backend.discover().await.unwrap().unwrap().len(), 1);
+        assert_eq!(backend.discover().await.unwrap(), None);
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(backend.discover().await.is_err());
+        assert_eq!(backend.discover().await.unwrap(), None);
+
+        std::fs::write(&path, r#"{"workers": []}"#).unwrap();
+        assert_eq!(backend.discover().await.unwrap(), Some(vec![]));
+    }
+}
@@ -1,19 +1,21 @@
 use axum::Router;
 use reqwest::Client;
 use std::sync::Arc;
+use std::time::Duration;
 use vllm_router_rs::{
-    config::RouterConfig,
+    config::{RateMonitorConfig, RouterConfig},
+    core::rate_monitor::RateMonitor,
+    middleware::ConcurrencyLimiter,
     routers::RouterTrait,
-    server::{build_app, AppContext, AppState},
+    server::{build_admin_app, build_app, AppContext, AppState},
 };
 
-/// Create a test Axum application using the actual server's build_app function
-#[allow(dead_code)]
-pub fn create_test_app(
+/// Create the AppState the server would build for `router_config`
+fn create_test_app_state(
     router: Arc<dyn RouterTrait>,
     client: Client,
     router_config: &RouterConfig,
-) -> Router {
+) -> Arc<AppState> {
     // Create AppContext
     let app_context = Arc::new(
         AppContext::new(
@@ -26,30 +28,72 @@ pub fn create_test_app(
         .expect("Failed to create AppContext in test"),
     );
 
+    // Set up the request queue the same way the server does
+    let (limiter, processor) = ConcurrencyLimiter::new(
+        app_context.rate_limiter.clone(),
+        router_config.queue_size,
+        Duration::from_secs(router_config.queue_timeout_secs),
+        &router_config.admission,
+    )
+    .expect("Failed to create request queue in test");
+    if let Some(processor) = processor {
+        tokio::spawn(processor.run());
+    }
+
     // Create AppState with the test router and context
-    let app_state = Arc::new(AppState {
+    Arc::new(AppState {
         router,
         context: app_context,
-        concurrency_queue_tx: None,
+        request_queue: limiter.queue,
         router_manager: None,
-    });
+        rate_monitor: Arc::new(RateMonitor::new(RateMonitorConfig {
+            threshold: 10,
+            window_secs: 60,
+            sustained_secs: 30,
+            vllm_base_args: vec![],
+        })),
+    })
+}
 
-    // Configure request ID headers (use defaults if not specified)
-    let request_id_headers = router_config.request_id_headers.clone().unwrap_or_else(|| {
+/// Request ID headers from the config, or the server defaults
+fn request_id_headers(router_config: &RouterConfig) -> Vec<String> {
+    router_config.request_id_headers.clone().unwrap_or_else(|| {
         vec![
             "x-request-id".to_string(),
             "x-correlation-id".to_string(),
             "x-trace-id".to_string(),
             "request-id".to_string(),
         ]
-    });
+    })
+}
 
+/// Create a test Axum application using the actual server's build_app function
+#[allow(dead_code)]
+pub fn create_test_app(
+    router: Arc<dyn RouterTrait>,
+    client: Client,
+    router_config: &RouterConfig,
+) -> Router {
     // Use the actual server's build_app function
     build_app(
-        app_state,
+        create_test_app_state(router, client, router_config),
         router_config.max_payload_size,
-        request_id_headers,
+        request_id_headers(router_config),
         router_config.cors_allowed_origins.clone(),
         true, // enable_transparent_proxy
     )
 }
+
+/// Create the separate admin listener's application
+#[allow(dead_code)]
+pub fn create_test_admin_app(
+    router: Arc<dyn RouterTrait>,
+    client: Client,
+    router_config: &RouterConfig,
+) -> Router {
+    build_admin_app(
+        create_test_app_state(router, client, router_config),
+        router_config.max_payload_size,
+        request_id_headers(router_config),
+    )
+}
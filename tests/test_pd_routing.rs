@@ -2,7 +2,11 @@
 mod test_pd_routing {
     use serde_json::json;
     use vllm_router_rs::config::{
-        CircuitBreakerConfig, ConnectionMode, PolicyConfig, RetryConfig, RouterConfig, RoutingMode,
+        AdminConfig, AdmissionConfig, ApiKeyCacheConfig, AuditConfig, ChunkedPrefillConfig,
+        CircuitBreakerConfig, ConditionalDisaggregationConfig, ConnectionMode,
+        DecodeFailoverConfig, DpDiscoveryConfig, KvTransferProtocolKind, LoraConfig,
+        PdCapacityConfig, PdPairingConfig, PolicyConfig, RetryConfig, RouterConfig, RoutingMode,
+        UpstreamTlsConfig, VllmDiscoveryTransport,
     };
     use vllm_router_rs::core::{WorkerFactory, WorkerType};
     use vllm_router_rs::routers::http::pd_types::get_hostname;
@@ -171,8 +175,28 @@ mod test_pd_routing {
                 worker_startup_timeout_secs: 10,
                 worker_startup_check_interval_secs: 1,
                 intra_node_data_parallel_size: 1,
+                dp_discovery: DpDiscoveryConfig::default(),
+                lora: LoraConfig::default(),
+                pd_pairing: PdPairingConfig::default(),
+                decode_failover: DecodeFailoverConfig::default(),
+                pd_capacity: PdCapacityConfig::default(),
+                conditional_disaggregation: ConditionalDisaggregationConfig::default(),
+                chunked_prefill: ChunkedPrefillConfig::default(),
+                kv_transfer_protocol: KvTransferProtocolKind::default(),
+                vllm_discovery_transport: VllmDiscoveryTransport::default(),
+                rate_limit_config: None,
+                content_policy_config: None,
+                model_params: std::collections::HashMap::new(),
+                admission: AdmissionConfig::default(),
                 api_key: None,
                 api_key_validation_urls: vec![],
+                api_keys_file: None,
+                jwt: None,
+                api_key_cache: ApiKeyCacheConfig::default(),
+                admin: AdminConfig::default(),
+                tls: None,
+                upstream_tls: UpstreamTlsConfig::default(),
+                audit: AuditConfig::default(),
                 discovery: None,
                 metrics: None,
                 log_dir: None,